@@ -0,0 +1,273 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use block_compression::{CompressionVariant, GpuBlockCompressor};
+use image::ImageFormat;
+use wgpu::{BufferDescriptor, BufferUsages, Device, Queue, Texture, TextureViewDescriptor};
+
+use crate::{
+    compress, download_blocks_data,
+    encoder::{compress_image_cpu, Encoder},
+    manifest::{load_manifest, match_rules},
+    read_image_and_create_texture, write_dds_file,
+};
+
+/// Upper limit of pixels, that are uploaded and compressed together in one compute pass.
+const MAX_PIXELS_PER_PASS: u64 = 64 * 1024 * 1024;
+
+struct Job {
+    input: PathBuf,
+    output: PathBuf,
+    variant: CompressionVariant,
+}
+
+/// Compresses all images of the inputs with the variants of the manifest and writes them into a
+/// mirrored tree inside the output directory.
+pub fn run(manifest: &Path, output_dir: &Path, inputs: &[String], force_cpu: bool) {
+    let rules = match load_manifest(manifest) {
+        Ok(rules) => rules,
+        Err(error) => {
+            println!("Error: {error}");
+            return;
+        }
+    };
+
+    let mut jobs = Vec::new();
+
+    for input in inputs {
+        let files = match resolve_input(input) {
+            Ok(files) => files,
+            Err(error) => {
+                println!("Error: {error}");
+                return;
+            }
+        };
+
+        for (root, file) in files {
+            // Images can be reached by more than one input.
+            if jobs.iter().any(|job: &Job| job.input == file) {
+                continue;
+            }
+
+            let relative_path = file.strip_prefix(&root).unwrap_or(&file).to_path_buf();
+
+            match match_rules(&rules, &relative_path) {
+                Some(variant) => jobs.push(Job {
+                    input: file,
+                    output: output_dir.join(&relative_path).with_extension("dds"),
+                    variant,
+                }),
+                None => println!("Skipping {}: no rule matches", file.display()),
+            }
+        }
+    }
+
+    if jobs.is_empty() {
+        println!("No images to compress");
+        return;
+    }
+
+    let start = Instant::now();
+
+    let encoder = Encoder::new(force_cpu);
+
+    // Fail before anything is written, if the encoder can't handle one of the variants.
+    for job in jobs.iter() {
+        if let Err(error) = encoder.check_variant(job.variant) {
+            println!("Error: {}: {error}", job.input.display());
+            return;
+        }
+    }
+
+    let job_count = jobs.len();
+
+    match encoder {
+        Encoder::Gpu { device, queue } => compress_gpu(&device, &queue, jobs),
+        Encoder::Cpu => {
+            for job in jobs {
+                match compress_image_cpu(&job.input, job.variant) {
+                    Ok((width, height, block_data)) => {
+                        write_job(&job, width, height, block_data, None)
+                    }
+                    Err(error) => println!("Skipping {error}"),
+                }
+            }
+        }
+    }
+
+    let duration = start.elapsed();
+    println!(
+        "Batch of {job_count} images took: {:.3} ms",
+        duration.as_secs_f64() * 1000.0
+    );
+}
+
+fn compress_gpu(device: &Arc<Device>, queue: &Arc<Queue>, jobs: Vec<Job>) {
+    // All pipelines are created once and shared by every image of the batch.
+    let mut compressor = GpuBlockCompressor::new(device.clone(), queue.clone());
+
+    let mut pending = Vec::new();
+    let mut pending_pixels = 0;
+
+    for job in jobs {
+        let texture = match read_image_and_create_texture(device, queue, &job.input, job.variant) {
+            Ok(texture) => texture,
+            Err(error) => {
+                println!("Skipping {error}");
+                continue;
+            }
+        };
+        let pixels = texture.width() as u64 * texture.height() as u64;
+
+        if !pending.is_empty() && pending_pixels + pixels > MAX_PIXELS_PER_PASS {
+            compress_pending(&mut compressor, device, queue, &mut pending);
+            pending_pixels = 0;
+        }
+
+        pending.push((job, texture));
+        pending_pixels += pixels;
+    }
+
+    compress_pending(&mut compressor, device, queue, &mut pending);
+}
+
+/// Compresses all pending images in one compute pass and writes the DDS files.
+fn compress_pending(
+    compressor: &mut GpuBlockCompressor,
+    device: &Device,
+    queue: &Queue,
+    pending: &mut Vec<(Job, Texture)>,
+) {
+    let blocks_buffers: Vec<_> = pending
+        .iter()
+        .map(|(job, texture)| {
+            let width = texture.width();
+            let height = texture.height();
+            let texture_view = texture.create_view(&TextureViewDescriptor::default());
+
+            let blocks_buffer = device.create_buffer(&BufferDescriptor {
+                label: Some("blocks buffer"),
+                size: job.variant.blocks_byte_size(width, height) as _,
+                usage: BufferUsages::COPY_SRC | BufferUsages::STORAGE,
+                mapped_at_creation: false,
+            });
+
+            compressor.add_compression_task(
+                job.variant,
+                &texture_view,
+                width,
+                height,
+                &blocks_buffer,
+                None,
+            );
+
+            blocks_buffer
+        })
+        .collect();
+
+    let durations = compress(compressor, device, queue);
+
+    for (index, ((job, texture), blocks_buffer)) in
+        pending.drain(..).zip(blocks_buffers).enumerate()
+    {
+        let block_data = download_blocks_data(device, queue, blocks_buffer);
+        let duration = durations.as_ref().map(|durations| durations[index]);
+        write_job(
+            &job,
+            texture.width(),
+            texture.height(),
+            block_data,
+            duration,
+        );
+    }
+}
+
+fn write_job(
+    job: &Job,
+    width: u32,
+    height: u32,
+    block_data: Vec<u8>,
+    gpu_duration: Option<Duration>,
+) {
+    let result = job
+        .output
+        .parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .map_err(|error| format!("can't create output directory: {error}"))
+        .and_then(|()| write_dds_file(&job.output, job.variant, width, height, block_data));
+
+    if let Err(error) = result {
+        println!("Skipping {}: {error}", job.input.display());
+        return;
+    }
+
+    match gpu_duration {
+        Some(duration) => println!(
+            "{} -> {} (GPU {:.3} ms)",
+            job.input.display(),
+            job.output.display(),
+            duration.as_secs_f64() * 1000.0
+        ),
+        None => println!("{} -> {}", job.input.display(), job.output.display()),
+    }
+}
+
+/// Resolves an input into image files, together with the root directory that the output tree
+/// mirrors. Inputs can be single files, directories or glob patterns.
+fn resolve_input(input: &str) -> Result<Vec<(PathBuf, PathBuf)>, String> {
+    let path = Path::new(input);
+
+    let (root, mut files) = if path.is_dir() {
+        let mut files = Vec::new();
+        collect_directory(path, &mut files)
+            .map_err(|error| format!("can't read directory '{input}': {error}"))?;
+        (path.to_path_buf(), files)
+    } else if path.is_file() {
+        let root = path.parent().unwrap_or(Path::new("")).to_path_buf();
+        (root, vec![path.to_path_buf()])
+    } else {
+        let paths =
+            glob::glob(input).map_err(|error| format!("invalid input '{input}': {error}"))?;
+        let files = paths
+            .filter_map(Result::ok)
+            .filter(|path| path.is_file() && is_image(path))
+            .collect();
+        (glob_root(input), files)
+    };
+
+    files.sort();
+
+    Ok(files.into_iter().map(|file| (root.clone(), file)).collect())
+}
+
+fn collect_directory(directory: &Path, files: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(directory)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_directory(&path, files)?;
+        } else if is_image(&path) {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+fn is_image(path: &Path) -> bool {
+    ImageFormat::from_path(path).is_ok_and(|format| format.reading_enabled())
+}
+
+/// Returns the leading components of a glob pattern, that don't contain any wildcards.
+fn glob_root(pattern: &str) -> PathBuf {
+    Path::new(pattern)
+        .components()
+        .take_while(|component| {
+            !component
+                .as_os_str()
+                .to_string_lossy()
+                .contains(['*', '?', '['])
+        })
+        .collect()
+}
@@ -0,0 +1,217 @@
+use std::{fs::File, path::Path};
+
+use block_compression::{
+    decode::{decompress_blocks_as_rgba32f, decompress_blocks_as_rgba8},
+    CompressionVariant, GpuBlockCompressor,
+};
+use ddsfile::Dds;
+use image::ImageReader;
+use wgpu::{BufferDescriptor, BufferUsages, TextureViewDescriptor};
+
+use crate::{
+    compress,
+    decompress::variant_from_dxgi_format,
+    download_blocks_data,
+    encoder::{compress_image_cpu, Encoder},
+    is_hdr_image,
+    metrics::{channel_metrics, write_heat_maps},
+    read_image_and_create_texture, srgb_to_linear,
+};
+
+/// Where the compressed blocks of a comparison come from.
+pub enum Source {
+    /// Compress the image with the given variant.
+    Compress(CompressionVariant),
+    /// Use the first mip level of the first layer of an existing DDS file.
+    Dds(std::path::PathBuf),
+}
+
+/// Compares an image with its compressed version and writes the error heat maps next to the
+/// image.
+pub fn run(image_path: &Path, source: Source, force_cpu: bool) {
+    if let Err(error) = compare(image_path, source, force_cpu) {
+        println!("Error: {error}");
+    }
+}
+
+fn compare(image_path: &Path, source: Source, force_cpu: bool) -> Result<(), String> {
+    let image = ImageReader::open(image_path)
+        .map_err(|error| format!("can't open '{}': {error}", image_path.display()))?
+        .decode()
+        .map_err(|error| format!("can't decode '{}': {error}", image_path.display()))?;
+    let width = image.width();
+    let height = image.height();
+
+    let (variant, blocks_data) = match source {
+        Source::Compress(variant) => (variant, compress_image(image_path, variant, force_cpu)?),
+        Source::Dds(dds_path) => read_dds_blocks(&dds_path, width, height)?,
+    };
+
+    let is_hdr = matches!(variant, CompressionVariant::BC6H(..));
+    let mut decoded = vec![0.0; width as usize * height as usize * 4];
+
+    // LDR data is compared as the stored, normalized values. BC6H is compared in linear space,
+    // because LDR images were converted into linear space before they were compressed.
+    let original: Vec<f32> = if is_hdr && is_hdr_image(&image) {
+        decompress_blocks_as_rgba32f(variant, width, height, &blocks_data, &mut decoded);
+
+        image.to_rgba32f().into_raw()
+    } else if is_hdr {
+        decompress_blocks_as_rgba32f(variant, width, height, &blocks_data, &mut decoded);
+
+        image
+            .to_rgba8()
+            .pixels()
+            .flat_map(|pixel| {
+                let [r, g, b, a] = pixel.0;
+                [
+                    srgb_to_linear(r) as f32,
+                    srgb_to_linear(g) as f32,
+                    srgb_to_linear(b) as f32,
+                    a as f32 / 255.0,
+                ]
+            })
+            .collect()
+    } else {
+        let mut decoded_rgba8 = vec![0; width as usize * height as usize * 4];
+        decompress_blocks_as_rgba8(variant, width, height, &blocks_data, &mut decoded_rgba8);
+
+        decoded
+            .iter_mut()
+            .zip(decoded_rgba8)
+            .for_each(|(decoded, value)| *decoded = value as f32 / 255.0);
+
+        image
+            .to_rgba8()
+            .iter()
+            .map(|&value| value as f32 / 255.0)
+            .collect()
+    };
+
+    // HDR values can exceed one, so the brightest color value is used as the peak of the PSNR.
+    let peak = original
+        .chunks_exact(4)
+        .flat_map(|pixel| &pixel[..3])
+        .fold(1.0, |peak: f32, &value| peak.max(value)) as f64;
+
+    let channels = stored_channels(variant);
+
+    println!("Channel       MSE      PSNR      SSIM");
+    for (channel, name) in ["red", "green", "blue", "alpha"]
+        .iter()
+        .enumerate()
+        .take(channels)
+    {
+        let metrics = channel_metrics(&original, &decoded, width, height, channel, peak);
+        println!(
+            "{name:<7} {:>9.6} {:>6.2} dB {:>9.6}",
+            metrics.mse, metrics.psnr, metrics.ssim
+        );
+    }
+
+    let stem = image_path
+        .file_stem()
+        .ok_or("image has no file name")?
+        .to_string_lossy();
+    let pixel_map_path = image_path.with_file_name(format!("{stem}_error_pixels.png"));
+    let block_map_path = image_path.with_file_name(format!("{stem}_error_blocks.png"));
+
+    let (max_pixel_error, max_block_error) = write_heat_maps(
+        &original,
+        &decoded,
+        width,
+        height,
+        channels,
+        variant.block_footprint(),
+        (&pixel_map_path, &block_map_path),
+    )?;
+
+    println!(
+        "Per-pixel heat map (max RMSE {max_pixel_error:.6}): {}",
+        pixel_map_path.display()
+    );
+    println!(
+        "Per-block heat map (max RMSE {max_block_error:.6}): {}",
+        block_map_path.display()
+    );
+
+    Ok(())
+}
+
+fn compress_image(
+    image_path: &Path,
+    variant: CompressionVariant,
+    force_cpu: bool,
+) -> Result<Vec<u8>, String> {
+    let encoder = Encoder::new(force_cpu);
+    encoder.check_variant(variant)?;
+
+    let Encoder::Gpu { device, queue } = encoder else {
+        let (_, _, block_data) = compress_image_cpu(image_path, variant)?;
+        return Ok(block_data);
+    };
+
+    let mut compressor = GpuBlockCompressor::new(device.clone(), queue.clone());
+
+    let texture = read_image_and_create_texture(&device, &queue, image_path, variant)?;
+    let texture_view = texture.create_view(&TextureViewDescriptor::default());
+    let width = texture.width();
+    let height = texture.height();
+
+    let blocks_buffer = device.create_buffer(&BufferDescriptor {
+        label: Some("blocks buffer"),
+        size: variant.blocks_byte_size(width, height) as _,
+        usage: BufferUsages::COPY_SRC | BufferUsages::STORAGE,
+        mapped_at_creation: false,
+    });
+
+    compressor.add_compression_task(variant, &texture_view, width, height, &blocks_buffer, None);
+
+    compress(&mut compressor, &device, &queue);
+
+    Ok(download_blocks_data(&device, &queue, blocks_buffer))
+}
+
+fn read_dds_blocks(
+    dds_path: &Path,
+    width: u32,
+    height: u32,
+) -> Result<(CompressionVariant, Vec<u8>), String> {
+    let file = File::open(dds_path)
+        .map_err(|error| format!("can't open '{}': {error}", dds_path.display()))?;
+    let dds =
+        Dds::read(file).map_err(|error| format!("can't read '{}': {error}", dds_path.display()))?;
+
+    let dxgi_format = dds
+        .get_dxgi_format()
+        .ok_or("DDS file has no DXGI format header")?;
+    let variant = variant_from_dxgi_format(dxgi_format)?;
+
+    if dds.get_width() != width || dds.get_height() != height {
+        return Err(format!(
+            "DDS file is {}x{}, but the image is {width}x{height}",
+            dds.get_width(),
+            dds.get_height()
+        ));
+    }
+
+    // The first mip level of the first layer is stored at the start of the data.
+    let size = variant.blocks_byte_size(width, height);
+    let blocks_data = dds
+        .data
+        .get(..size)
+        .ok_or("DDS file has not enough data")?
+        .to_vec();
+
+    Ok((variant, blocks_data))
+}
+
+/// Number of leading channels, that a variant stores.
+fn stored_channels(variant: CompressionVariant) -> usize {
+    match variant {
+        CompressionVariant::BC4 | CompressionVariant::EACR11 => 1,
+        CompressionVariant::BC5 | CompressionVariant::EACRG11 => 2,
+        CompressionVariant::BC6H(..) | CompressionVariant::ETC2RGB8 => 3,
+        _ => 4,
+    }
+}
@@ -0,0 +1,180 @@
+use std::{fs::File, path::Path, time::Instant};
+
+use block_compression::{
+    decode::{decompress_blocks_as_rgba32f, decompress_blocks_as_rgba8},
+    format::BlockFormat,
+    CompressionVariant,
+};
+use ddsfile::{Dds, DxgiFormat, MiscFlag};
+use image::{DynamicImage, ExtendedColorType, ImageFormat, Rgba32FImage};
+
+/// Output formats for LDR variants.
+const LDR_FORMATS: &[ImageFormat] = &[ImageFormat::Png, ImageFormat::Tga];
+
+/// Output formats for BC6H.
+const HDR_FORMATS: &[ImageFormat] = &[ImageFormat::OpenExr, ImageFormat::Hdr];
+
+/// Decodes every mip level and array layer of a DDS file into images inside the output
+/// directory. Without an explicit format LDR variants are written as PNG and BC6H as EXR.
+pub fn run(input: &Path, output_dir: Option<&Path>, format: Option<ImageFormat>) {
+    if let Err(error) = decompress_file(input, output_dir, format) {
+        println!("Error: {error}");
+    }
+}
+
+fn decompress_file(
+    input: &Path,
+    output_dir: Option<&Path>,
+    format: Option<ImageFormat>,
+) -> Result<(), String> {
+    let start = Instant::now();
+
+    let file =
+        File::open(input).map_err(|error| format!("can't open '{}': {error}", input.display()))?;
+    let dds =
+        Dds::read(file).map_err(|error| format!("can't read '{}': {error}", input.display()))?;
+
+    let dxgi_format = dds
+        .get_dxgi_format()
+        .ok_or("DDS file has no DXGI format header")?;
+    let variant = variant_from_dxgi_format(dxgi_format)?;
+    let is_hdr = matches!(variant, CompressionVariant::BC6H(..));
+
+    let (format, allowed_formats) = if is_hdr {
+        (format.unwrap_or(ImageFormat::OpenExr), HDR_FORMATS)
+    } else {
+        (format.unwrap_or(ImageFormat::Png), LDR_FORMATS)
+    };
+
+    if !allowed_formats.contains(&format) {
+        return Err(format!(
+            "{dxgi_format:?} can't be written as {}",
+            format.extensions_str()[0]
+        ));
+    }
+
+    let width = dds.get_width();
+    let height = dds.get_height();
+    let mip_levels = dds.get_num_mipmap_levels().max(1);
+
+    // The DX10 header stores the number of cubes, not the number of faces.
+    let is_cubemap = dds
+        .header10
+        .as_ref()
+        .is_some_and(|header10| header10.misc_flag.contains(MiscFlag::TEXTURECUBE));
+    let layers = dds.get_num_array_layers().max(1) * if is_cubemap { 6 } else { 1 };
+
+    let layer_size: usize = (0..mip_levels)
+        .map(|level| {
+            let (mip_width, mip_height) = mip_size(width, height, level);
+            variant.blocks_byte_size(mip_width, mip_height)
+        })
+        .sum();
+
+    if dds.data.len() < layer_size * layers as usize {
+        return Err(format!(
+            "DDS file has {} bytes of data, but {layers} layers with {mip_levels} mip levels need {}",
+            dds.data.len(),
+            layer_size * layers as usize
+        ));
+    }
+
+    let output_dir = output_dir
+        .or_else(|| input.parent())
+        .unwrap_or(Path::new(""));
+    std::fs::create_dir_all(output_dir)
+        .map_err(|error| format!("can't create '{}': {error}", output_dir.display()))?;
+
+    let stem = input
+        .file_stem()
+        .ok_or("input has no file name")?
+        .to_string_lossy();
+
+    // Data is stored layer by layer, each layer containing its whole mip chain.
+    let mut offset = 0;
+
+    for layer in 0..layers {
+        for level in 0..mip_levels {
+            let (mip_width, mip_height) = mip_size(width, height, level);
+            let size = variant.blocks_byte_size(mip_width, mip_height);
+            let blocks_data = &dds.data[offset..offset + size];
+            offset += size;
+
+            let mut name = stem.to_string();
+            if layers > 1 {
+                name.push_str(&format!("_layer{layer}"));
+            }
+            if mip_levels > 1 {
+                name.push_str(&format!("_mip{level}"));
+            }
+
+            let output = output_dir
+                .join(name)
+                .with_extension(format.extensions_str()[0]);
+
+            write_image(&output, variant, mip_width, mip_height, blocks_data, format)?;
+
+            println!("{} -> {}", input.display(), output.display());
+        }
+    }
+
+    let duration = start.elapsed();
+    println!(
+        "Decompression took: {:.3} ms",
+        duration.as_secs_f64() * 1000.0
+    );
+
+    Ok(())
+}
+
+fn write_image(
+    output: &Path,
+    variant: CompressionVariant,
+    width: u32,
+    height: u32,
+    blocks_data: &[u8],
+    format: ImageFormat,
+) -> Result<(), String> {
+    let result = if matches!(variant, CompressionVariant::BC6H(..)) {
+        let mut rgba_data = vec![0.0; width as usize * height as usize * 4];
+        decompress_blocks_as_rgba32f(variant, width, height, blocks_data, &mut rgba_data);
+
+        let image = Rgba32FImage::from_raw(width, height, rgba_data)
+            .expect("decoded data has the size of the image");
+
+        match format {
+            // Radiance HDR has no alpha channel.
+            ImageFormat::Hdr => DynamicImage::ImageRgba32F(image)
+                .to_rgb32f()
+                .save_with_format(output, format),
+            _ => image.save_with_format(output, format),
+        }
+    } else {
+        let mut rgba_data = vec![0; width as usize * height as usize * 4];
+        decompress_blocks_as_rgba8(variant, width, height, blocks_data, &mut rgba_data);
+
+        image::save_buffer_with_format(
+            output,
+            &rgba_data,
+            width,
+            height,
+            ExtendedColorType::Rgba8,
+            format,
+        )
+    };
+
+    result.map_err(|error| format!("can't write '{}': {error}", output.display()))
+}
+
+/// Maps the DXGI formats of block compressed textures back to their compression variant.
+///
+/// The settings of BC6H and BC7 only matter for compression, so the slowest presets are used.
+pub fn variant_from_dxgi_format(format: DxgiFormat) -> Result<CompressionVariant, String> {
+    BlockFormat::from_dxgi_format(format as u32)
+        .and_then(BlockFormat::variant)
+        .ok_or_else(|| format!("unsupported DXGI format {format:?}"))
+}
+
+fn mip_size(width: u32, height: u32, level: u32) -> (u32, u32) {
+    ((width >> level).max(1), (height >> level).max(1))
+}
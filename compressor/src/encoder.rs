@@ -0,0 +1,69 @@
+use std::{path::Path, sync::Arc};
+
+use block_compression::{encode::compress_rgba8, CompressionVariant};
+use wgpu::{Device, Queue};
+
+use crate::{create_resources, read_image};
+
+/// The path, that images are compressed with.
+pub enum Encoder {
+    Gpu {
+        device: Arc<Device>,
+        queue: Arc<Queue>,
+    },
+    /// Compresses with `encode::compress_rgba8`, which supports BC1 to BC5, ETC2, EAC and ASTC.
+    Cpu,
+}
+
+impl Encoder {
+    /// Uses the GPU, unless the CPU is forced or no GPU device can be created. Prints which path is
+    /// used.
+    pub fn new(force_cpu: bool) -> Self {
+        if force_cpu {
+            println!("Using CPU encoder");
+            return Self::Cpu;
+        }
+
+        match create_resources() {
+            Some((device, queue)) => Self::Gpu { device, queue },
+            None => {
+                println!("No usable GPU found, falling back to the CPU encoder");
+                Self::Cpu
+            }
+        }
+    }
+
+    /// Returns an error, if the encoder can't compress the variant.
+    pub fn check_variant(&self, variant: CompressionVariant) -> Result<(), String> {
+        match (self, variant) {
+            (Self::Cpu, CompressionVariant::BC6H(..) | CompressionVariant::BC7(..)) => Err(
+                "BC6H and BC7 need a GPU, the CPU encoder supports BC1 to BC5, ETC2, EAC and ASTC"
+                    .to_string(),
+            ),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Reads an image and compresses it on the CPU. Returns the width, height and block data.
+pub fn compress_image_cpu(
+    path: &Path,
+    variant: CompressionVariant,
+) -> Result<(u32, u32, Vec<u8>), String> {
+    let rgba_image = read_image(path, variant)?.to_rgba8();
+
+    let width = rgba_image.width();
+    let height = rgba_image.height();
+    let mut block_data = vec![0; variant.blocks_byte_size(width, height)];
+
+    compress_rgba8(
+        variant,
+        &rgba_image,
+        &mut block_data,
+        width,
+        height,
+        width * 4,
+    );
+
+    Ok((width, height, block_data))
+}
@@ -1,11 +1,17 @@
-use std::{fs::File, path::PathBuf, sync::Arc, time::Instant};
+use std::{
+    fs::File,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use block_compression::{
-    half::f16, BC6HSettings, BC7Settings, CompressionVariant, GpuBlockCompressor,
+    analysis::ImageAnalysis, format::BlockFormat, half::f16, CompressionVariant, GpuBlockCompressor,
 };
 use bytemuck::cast_slice;
 use ddsfile::{AlphaMode, D3D10ResourceDimension, Dds, DxgiFormat, NewDxgiParams};
-use image::ImageReader;
+use image::{DynamicImage, ImageFormat, ImageReader};
+use num_traits::FromPrimitive;
 use pollster::block_on;
 use wgpu::{
     util::{DeviceExt, TextureDataOrder},
@@ -17,18 +23,131 @@ use wgpu::{
     TextureFormat, TextureUsages, TextureViewDescriptor,
 };
 
+use crate::{
+    encoder::{compress_image_cpu, Encoder},
+    variant::parse_variant,
+};
+
+mod batch;
+mod compare;
+mod decompress;
+mod encoder;
+mod manifest;
+mod metrics;
+mod variant;
+
+enum Command {
+    Compress {
+        variant: CompressionVariant,
+        file_name: String,
+        force_cpu: bool,
+    },
+    Batch {
+        manifest: PathBuf,
+        output: PathBuf,
+        inputs: Vec<String>,
+        force_cpu: bool,
+    },
+    Decompress {
+        input: PathBuf,
+        output: Option<PathBuf>,
+        format: Option<ImageFormat>,
+    },
+    Compare {
+        image: PathBuf,
+        source: compare::Source,
+        force_cpu: bool,
+    },
+}
+
 fn main() {
-    let (variant, file_name) = match parse_args() {
-        Some(args) => args,
-        None => return,
+    match parse_args() {
+        Some(Command::Compress {
+            variant,
+            file_name,
+            force_cpu,
+        }) => compress_file(variant, &file_name, force_cpu),
+        Some(Command::Batch {
+            manifest,
+            output,
+            inputs,
+            force_cpu,
+        }) => batch::run(&manifest, &output, &inputs, force_cpu),
+        Some(Command::Decompress {
+            input,
+            output,
+            format,
+        }) => decompress::run(&input, output.as_deref(), format),
+        Some(Command::Compare {
+            image,
+            source,
+            force_cpu,
+        }) => compare::run(&image, source, force_cpu),
+        None => {}
+    }
+}
+
+fn compress_file(variant: CompressionVariant, file_name: &str, force_cpu: bool) {
+    let encoder = Encoder::new(force_cpu);
+
+    if let Err(error) = encoder.check_variant(variant) {
+        println!("Error: {error}");
+        return;
+    }
+
+    let result = match encoder {
+        Encoder::Gpu { device, queue } => compress_file_gpu(&device, &queue, variant, file_name),
+        Encoder::Cpu => {
+            let start = Instant::now();
+
+            let result = compress_image_cpu(Path::new(file_name), variant);
+
+            let duration = start.elapsed();
+            println!(
+                "Image read and CPU compression took: {:.3} ms",
+                duration.as_secs_f64() * 1000.0
+            );
+
+            result
+        }
     };
 
-    let (device, queue) = create_resources();
+    let (width, height, block_data) = match result {
+        Ok(result) => result,
+        Err(error) => {
+            println!("Error: {error}");
+            return;
+        }
+    };
+
+    let start = Instant::now();
+
+    let mut dds_name = PathBuf::from(file_name);
+    dds_name.set_extension("dds");
+
+    if let Err(error) = write_dds_file(&dds_name, variant, width, height, block_data) {
+        println!("Error: {error}");
+        return;
+    }
+
+    let duration = start.elapsed();
+    println!(
+        "DDS output to disk took: {:.3} ms",
+        duration.as_secs_f64() * 1000.0
+    );
+}
+
+fn compress_file_gpu(
+    device: &Arc<Device>,
+    queue: &Arc<Queue>,
+    variant: CompressionVariant,
+    file_name: &str,
+) -> Result<(u32, u32, Vec<u8>), String> {
     let mut compressor: GpuBlockCompressor = GpuBlockCompressor::new(device.clone(), queue.clone());
 
     let start = Instant::now();
 
-    let texture = read_image_and_create_texture(&device, &queue, &file_name, variant);
+    let texture = read_image_and_create_texture(device, queue, Path::new(file_name), variant)?;
     let texture_view = texture.create_view(&TextureViewDescriptor::default());
     let width = texture.width();
     let height = texture.height();
@@ -48,11 +167,11 @@ fn main() {
 
     compressor.add_compression_task(variant, &texture_view, width, height, &blocks_buffer, None);
 
-    compress(&mut compressor, &device, &queue);
+    compress(&mut compressor, device, queue);
 
     let start = Instant::now();
 
-    let block_data = download_blocks_data(&device, &queue, blocks_buffer);
+    let block_data = download_blocks_data(device, queue, blocks_buffer);
 
     let duration = start.elapsed();
     println!(
@@ -60,18 +179,11 @@ fn main() {
         duration.as_secs_f64() * 1000.0
     );
 
-    let start = Instant::now();
-
-    write_dds_file(&file_name, variant, width, height, block_data);
-
-    let duration = start.elapsed();
-    println!(
-        "DDS output to disk took: {:.3} ms",
-        duration.as_secs_f64() * 1000.0
-    );
+    Ok((width, height, block_data))
 }
 
-fn create_resources() -> (Arc<Device>, Arc<Queue>) {
+/// Returns `None`, if no GPU adapter exists or the device can't be created.
+fn create_resources() -> Option<(Arc<Device>, Arc<Queue>)> {
     let instance = Instance::new(&InstanceDescriptor {
         backends: Backends::from_env().unwrap_or_default(),
         flags: InstanceFlags::from_build_config().with_env(),
@@ -90,52 +202,143 @@ fn create_resources() -> (Arc<Device>, Arc<Queue>) {
         power_preference: PowerPreference::HighPerformance,
         compatible_surface: None,
         force_fallback_adapter: false,
-    }))
-    .expect("Failed to find an appropriate adapter");
+    }))?;
 
-    let (device, queue) = block_on(adapter.request_device(
+    let (device, queue) = match block_on(adapter.request_device(
         &DeviceDescriptor {
             label: Some("main device"),
-            required_features: Features::TIMESTAMP_QUERY,
+            // Timestamps are optional. They time the whole pass and, inside passes, each task
+            // of a batch.
+            required_features: adapter.features()
+                & (Features::TIMESTAMP_QUERY | Features::TIMESTAMP_QUERY_INSIDE_PASSES),
             required_limits: Limits::downlevel_defaults(),
             memory_hints: MemoryHints::Performance,
         },
         None,
-    ))
-    .expect("Failed to create device");
+    )) {
+        Ok(resources) => resources,
+        Err(error) => {
+            println!("Failed to create device: {error}");
+            return None;
+        }
+    };
     device.on_uncaptured_error(Box::new(error_handler));
 
     let info = adapter.get_info();
-    println!("Using backend: {:?}", info.backend);
+    println!("Using GPU encoder with backend: {:?}", info.backend);
 
-    (Arc::new(device), Arc::new(queue))
+    Some((Arc::new(device), Arc::new(queue)))
 }
 
+/// Returns `true` for images with float channels, like OpenEXR and Radiance HDR files.
+fn is_hdr_image(image: &DynamicImage) -> bool {
+    matches!(
+        image,
+        DynamicImage::ImageRgb32F(..) | DynamicImage::ImageRgba32F(..)
+    )
+}
+
+/// Reads an image and refuses to compress HDR images with LDR variants and images, that the
+/// blocks of the variant don't cover exactly.
+fn read_image(path: &Path, variant: CompressionVariant) -> Result<DynamicImage, String> {
+    let image = ImageReader::open(path)
+        .map_err(|e| format!("{}: {e}", path.display()))?
+        .decode()
+        .map_err(|e| format!("{}: {e}", path.display()))?;
+
+    if is_hdr_image(&image) && !matches!(variant, CompressionVariant::BC6H(..)) {
+        return Err(format!(
+            "'{}' is an HDR image and would lose its range with an 8-bit variant, use bc6h instead",
+            path.display()
+        ));
+    }
+
+    // Only the ASTC encoders handle partial blocks.
+    let (block_width, block_height) = variant.block_footprint();
+    if !matches!(variant, CompressionVariant::ASTC(..))
+        && (image.width() % block_width != 0 || image.height() % block_height != 0)
+    {
+        return Err(format!(
+            "{}: {}x{} pixels aren't a multiple of the {block_width}x{block_height} blocks of {variant}",
+            path.display(),
+            image.width(),
+            image.height()
+        ));
+    }
+
+    Ok(image)
+}
+
+/// Selects the variant from the image content. Float images always use BC6H, so that they aren't
+/// downgraded to 8-bit.
+fn select_variant(path: &Path) -> Result<CompressionVariant, String> {
+    let image = ImageReader::open(path)
+        .map_err(|error| format!("can't open '{}': {error}", path.display()))?
+        .decode()
+        .map_err(|error| format!("can't decode '{}': {error}", path.display()))?;
+
+    let width = image.width();
+    let height = image.height();
+
+    let analysis = if is_hdr_image(&image) {
+        let analysis = ImageAnalysis::from_rgba32f(&image.to_rgba32f(), width, height, width * 4);
+        ImageAnalysis {
+            hdr: true,
+            ..analysis
+        }
+    } else {
+        ImageAnalysis::from_rgba8(&image.to_rgba8(), width, height, width * 4)
+    };
+
+    let variant = analysis.recommended_variant();
+
+    println!(
+        "Auto selected {} (alpha: {:?}, grayscale: {}, two channel: {}, HDR: {})",
+        variant, analysis.alpha, analysis.grayscale, analysis.two_channel, analysis.hdr
+    );
+
+    Ok(variant)
+}
+
+/// LDR images are converted from sRGB into linear space for BC6H. HDR images are already linear
+/// and are uploaded with their full range, clamped to the range of BC6H.
 fn read_image_and_create_texture(
     device: &Device,
     queue: &Queue,
-    file_name: &str,
+    path: &Path,
     variant: CompressionVariant,
-) -> Texture {
-    let image = ImageReader::open(file_name)
-        .expect("can't open input image")
-        .decode()
-        .expect("can't decode image");
-
-    let rgba_image = image.to_rgba8();
-    let width = rgba_image.width();
-    let height = rgba_image.height();
-
-    if matches!(variant, CompressionVariant::BC6H(..)) {
-        let rgba_f16_data: Vec<u8> = rgba_image
-            .iter()
-            .flat_map(|color| f16::from_f64(srgb_to_linear(*color)).to_le_bytes())
-            .collect();
+) -> Result<Texture, String> {
+    let image = read_image(path, variant)?;
+    let width = image.width();
+    let height = image.height();
+
+    let texture = if matches!(variant, CompressionVariant::BC6H(..)) {
+        let rgba_f16_data: Vec<u8> = if is_hdr_image(&image) {
+            let mut rgba_image = image.to_rgba32f();
+
+            // BC6H can't store alpha, so only the color channels are clamped.
+            rgba_image.pixels_mut().for_each(|pixel| {
+                pixel.0[..3]
+                    .iter_mut()
+                    .for_each(|value| *value = value.clamp(0.0, f16::MAX.to_f32()));
+            });
+
+            rgba_image
+                .iter()
+                .flat_map(|value| f16::from_f32(*value).to_le_bytes())
+                .collect()
+        } else {
+            image
+                .to_rgba8()
+                .iter()
+                .flat_map(|color| f16::from_f64(srgb_to_linear(*color)).to_le_bytes())
+                .collect()
+        };
 
         device.create_texture_with_data(
             queue,
             &TextureDescriptor {
-                label: Some(file_name),
+                label: path.to_str(),
                 size: Extent3d {
                     width,
                     height,
@@ -155,7 +358,7 @@ fn read_image_and_create_texture(
         device.create_texture_with_data(
             queue,
             &TextureDescriptor {
-                label: Some(file_name),
+                label: path.to_str(),
                 size: Extent3d {
                     width,
                     height,
@@ -169,9 +372,11 @@ fn read_image_and_create_texture(
                 view_formats: &[],
             },
             TextureDataOrder::LayerMajor,
-            &rgba_image,
+            &image.to_rgba8(),
         )
-    }
+    };
+
+    Ok(texture)
 }
 
 #[inline]
@@ -184,67 +389,93 @@ pub fn srgb_to_linear(srgb: u8) -> f64 {
     }
 }
 
-fn compress(compressor: &mut GpuBlockCompressor, device: &Device, queue: &Queue) {
-    let timestamp_query_set = device.create_query_set(&wgpu::QuerySetDescriptor {
-        label: Some("timestamp query set"),
-        count: 2,
-        ty: QueryType::Timestamp,
-    });
+/// Compresses all tasks of the compressor and prints the GPU time of the whole pass, if the device
+/// supports timestamps. Returns the GPU time of every task, if the device supports timestamps
+/// inside passes.
+fn compress(
+    compressor: &mut GpuBlockCompressor,
+    device: &Device,
+    queue: &Queue,
+) -> Option<Vec<Duration>> {
+    let features = device.features();
+
+    let pass_timestamps = features.contains(Features::TIMESTAMP_QUERY).then(|| {
+        let query_set = device.create_query_set(&wgpu::QuerySetDescriptor {
+            label: Some("timestamp query set"),
+            count: 2,
+            ty: QueryType::Timestamp,
+        });
 
-    let timestamp_resolve_buffer = device.create_buffer(&BufferDescriptor {
-        label: Some("timestamp resolve buffer"),
-        size: 16,
-        usage: BufferUsages::COPY_DST | BufferUsages::COPY_SRC | BufferUsages::QUERY_RESOLVE,
-        mapped_at_creation: false,
-    });
+        let resolve_buffer = device.create_buffer(&BufferDescriptor {
+            label: Some("timestamp resolve buffer"),
+            size: 16,
+            usage: BufferUsages::COPY_DST | BufferUsages::COPY_SRC | BufferUsages::QUERY_RESOLVE,
+            mapped_at_creation: false,
+        });
 
-    let timestamp_readback_buffer = device.create_buffer(&wgpu::BufferDescriptor {
-        label: Some("timestamp read-back buffer"),
-        size: 16,
-        usage: BufferUsages::COPY_DST | BufferUsages::MAP_READ,
-        mapped_at_creation: false,
+        let readback_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("timestamp read-back buffer"),
+            size: 16,
+            usage: BufferUsages::COPY_DST | BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        (query_set, resolve_buffer, readback_buffer)
     });
 
     let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor {
         label: Some("command encoder"),
     });
 
-    {
+    let task_timestamps = {
         let mut pass = encoder.begin_compute_pass(&ComputePassDescriptor {
             label: Some("compute pass"),
-            timestamp_writes: Some(ComputePassTimestampWrites {
-                query_set: &timestamp_query_set,
-                beginning_of_pass_write_index: Some(0),
-                end_of_pass_write_index: Some(1),
+            timestamp_writes: pass_timestamps.as_ref().map(|(query_set, ..)| {
+                ComputePassTimestampWrites {
+                    query_set,
+                    beginning_of_pass_write_index: Some(0),
+                    end_of_pass_write_index: Some(1),
+                }
             }),
         });
 
-        compressor.compress(&mut pass);
-    }
+        if features.contains(Features::TIMESTAMP_QUERY | Features::TIMESTAMP_QUERY_INSIDE_PASSES) {
+            Some(compressor.compress_with_timestamps(&mut pass))
+        } else {
+            compressor.compress(&mut pass);
+            None
+        }
+    };
 
-    encoder.resolve_query_set(&timestamp_query_set, 0..2, &timestamp_resolve_buffer, 0);
+    if let Some((query_set, resolve_buffer, readback_buffer)) = &pass_timestamps {
+        encoder.resolve_query_set(query_set, 0..2, resolve_buffer, 0);
+        encoder.copy_buffer_to_buffer(resolve_buffer, 0, readback_buffer, 0, 16);
+    }
 
-    encoder.copy_buffer_to_buffer(
-        &timestamp_resolve_buffer,
-        0,
-        &timestamp_readback_buffer,
-        0,
-        16,
-    );
+    if let Some(task_timestamps) = &task_timestamps {
+        task_timestamps.resolve(&mut encoder);
+    }
 
     queue.submit([encoder.finish()]);
 
-    {
-        let buffer_slice = timestamp_readback_buffer.slice(..);
+    let (task_tx, task_rx) = std::sync::mpsc::channel();
+    if let Some(task_timestamps) = task_timestamps {
+        task_timestamps.map_durations(move |durations| task_tx.send(durations).unwrap());
+    }
 
-        let (tx, rx) = std::sync::mpsc::channel();
-        buffer_slice.map_async(MapMode::Read, move |v| tx.send(v).unwrap());
+    let (tx, rx) = std::sync::mpsc::channel();
+    if let Some((_, _, readback_buffer)) = &pass_timestamps {
+        readback_buffer
+            .slice(..)
+            .map_async(MapMode::Read, move |v| tx.send(v).unwrap());
+    }
 
-        device.poll(Maintain::Wait);
+    device.poll(Maintain::Wait);
 
+    if let Some((_, _, readback_buffer)) = &pass_timestamps {
         match rx.recv() {
             Ok(Ok(())) => {
-                let data = buffer_slice.get_mapped_range();
+                let data = readback_buffer.slice(..).get_mapped_range();
                 let timestamps: &[u64] = cast_slice(&data);
 
                 let period = queue.get_timestamp_period() as f64;
@@ -257,8 +488,14 @@ fn compress(compressor: &mut GpuBlockCompressor, device: &Device, queue: &Queue)
             _ => panic!("couldn't read from buffer"),
         }
 
-        timestamp_readback_buffer.unmap();
+        readback_buffer.unmap();
     }
+
+    // The poll above already waited for the GPU.
+    task_rx
+        .try_recv()
+        .ok()
+        .map(|durations| durations.expect("couldn't read task timestamps"))
 }
 
 fn download_blocks_data(device: &Device, queue: &Queue, block_buffer: Buffer) -> Vec<u8> {
@@ -303,17 +540,17 @@ fn download_blocks_data(device: &Device, queue: &Queue, block_buffer: Buffer) ->
 }
 
 fn write_dds_file(
-    file_name: &str,
+    output: &Path,
     variant: CompressionVariant,
     width: u32,
     height: u32,
     block_data: Vec<u8>,
-) {
+) -> Result<(), String> {
     let mut dds = Dds::new_dxgi(NewDxgiParams {
         height,
         width,
         depth: None,
-        format: dxgi_format(variant),
+        format: dxgi_format(variant)?,
         mipmap_levels: Some(1),
         array_layers: None,
         caps2: None,
@@ -321,69 +558,259 @@ fn write_dds_file(
         resource_dimension: D3D10ResourceDimension::Texture2D,
         alpha_mode: AlphaMode::Straight,
     })
-    .expect("failed to create DDS header");
+    .map_err(|error| format!("can't create DDS header: {error}"))?;
 
     dds.data = block_data;
 
-    let mut dds_name = PathBuf::from(file_name);
-    dds_name.set_extension("dds");
-
-    let mut file = File::create(dds_name).expect("failed to create output file");
-    dds.write(&mut file).expect("failed to write DDS file");
+    let mut file = File::create(output)
+        .map_err(|error| format!("can't create '{}': {error}", output.display()))?;
+    dds.write(&mut file)
+        .map_err(|error| format!("can't write '{}': {error}", output.display()))
 }
 
-fn dxgi_format(variant: CompressionVariant) -> DxgiFormat {
-    match variant {
-        CompressionVariant::BC1 => DxgiFormat::BC1_UNorm_sRGB,
-        CompressionVariant::BC2 => DxgiFormat::BC2_UNorm_sRGB,
-        CompressionVariant::BC3 => DxgiFormat::BC3_UNorm_sRGB,
-        CompressionVariant::BC4 => DxgiFormat::BC4_UNorm,
-        CompressionVariant::BC5 => DxgiFormat::BC5_UNorm,
-        CompressionVariant::BC6H(..) => DxgiFormat::BC6H_UF16,
-        CompressionVariant::BC7(..) => DxgiFormat::BC7_UNorm_sRGB,
-    }
+/// DDS files are written with sRGB colors, like the images they were compressed from. Only the
+/// BC variants have a DXGI format.
+fn dxgi_format(variant: CompressionVariant) -> Result<DxgiFormat, String> {
+    BlockFormat::from_variant(variant, true)
+        .dxgi_format()
+        .and_then(DxgiFormat::from_u32)
+        .ok_or_else(|| format!("{variant} can't be stored in a DDS file, only BC1 to BC7 can"))
 }
 
 fn print_help() {
-    println!("Usage: compressor <compression_variant> <input_file>");
+    println!("Usage: compressor [options] <compression_variant> <input_file>");
+    println!("       compressor [options] --auto <input_file>");
+    println!("       compressor batch <manifest> <output_dir> <input>...");
+    println!("       compressor decompress [--format <format>] <input_dds> [output_dir]");
+    println!("       compressor compare [options] <compression_variant> <input_file>");
+    println!("       compressor compare [options] --auto <input_file>");
+    println!("       compressor compare <input_dds> <input_file>");
     println!("\nCompression variants:");
     println!("  bc1  - BC1 compression (RGB)");
     println!("  bc2  - BC2 compression with sharp alpha (RGBA)");
     println!("  bc3  - BC3 compression with smooth alpha (RGBA)");
     println!("  bc4  - BC4 compression (R)");
     println!("  bc5  - BC5 compression (RG)");
-    println!("  bc6h - BC6H compression (RGB HDR)");
+    println!("  bc6h - BC6H compression (RGB HDR), the only variant for EXR and HDR images");
     println!("  bc7  - BC7 compression with smooth alpha (RGBA)");
+    println!("\nOptions:");
+    println!("  --auto                - Selects the variant and preset from the image content");
+    println!("  --cpu                 - Compresses on the CPU, which supports all variants except");
+    println!("                          BC6H and BC7 and is also used, if no GPU adapter exists");
+    println!("  --preset <preset>     - Quality preset of BC6H or BC7");
+    println!("  --set <field>=<value> - Overrides a settings field of the preset, lists are");
+    println!("                          written as [a,b,...] (can be used multiple times)");
+    println!("\nVariant specifications:");
+    println!("  Instead of --preset and --set, the variant can be given as");
+    println!(
+        "  variant[:preset][{{field=value,...}}], for example bc7:opaque_basic{{channels=4}}."
+    );
+    println!("\nBC7 presets (default alpha_slow):");
+    println!("  opaque_ultra_fast, opaque_very_fast, opaque_fast, opaque_basic, opaque_slow,");
+    println!("  alpha_ultra_fast, alpha_very_fast, alpha_fast, alpha_basic, alpha_slow");
+    println!("\nBC6H presets (default very_slow):");
+    println!("  very_fast, fast, basic, slow, very_slow");
+    println!("\nThe settings fields are the fields of BC7Settings and BC6HSettings.");
+    println!("\nBatch mode:");
+    println!(
+        "  Inputs can be image files, directories or glob patterns. Every image is compressed"
+    );
+    println!("  with the first rule of the TOML or JSON manifest, whose pattern matches the path");
+    println!("  relative to its input. The DDS files are written into a mirrored output tree.");
+    println!("\n  [[rules]]");
+    println!("  pattern = \"**/*_normal.png\"");
+    println!("  variant = \"bc5\"");
+    println!("\n  [[rules]]");
+    println!("  pattern = \"**/*.png\"");
+    println!("  variant = \"bc7\"");
+    println!("  preset = \"alpha_basic\"");
+    println!("\nAuto mode:");
+    println!("  EXR and HDR images use bc6h. Images with alpha use bc7 (alpha_basic), opaque");
+    println!("  grayscale images bc4, opaque images without blue bc5 and all other images bc7");
+    println!("  (opaque_basic).");
+    println!("\nDecompress mode:");
+    println!("  Decodes every mip level and array layer of a DDS file. LDR variants are written");
+    println!("  as png (default) or tga, BC6H as exr (default) or hdr.");
+    println!("\nCompare mode:");
+    println!("  Compresses the image, or reads the first mip level of a DDS file, decodes it and");
+    println!(
+        "  prints the MSE, PSNR and SSIM of every stored channel. The per-pixel and per-block"
+    );
+    println!("  error heat maps are written next to the image as <name>_error_pixels.png and");
+    println!("  <name>_error_blocks.png.");
 }
 
-fn parse_args() -> Option<(CompressionVariant, String)> {
-    let args: Vec<String> = std::env::args().collect();
+fn parse_args() -> Option<Command> {
+    let mut args: Vec<String> = std::env::args().collect();
 
-    if args.len() != 3 || args.contains(&"--help".to_string()) {
+    if args.contains(&"--help".to_string()) {
         print_help();
         return None;
     }
 
-    let variant = match args[1].to_lowercase().as_str() {
-        "bc1" => CompressionVariant::BC1,
-        "bc2" => CompressionVariant::BC2,
-        "bc3" => CompressionVariant::BC3,
-        "bc4" => CompressionVariant::BC4,
-        "bc5" => CompressionVariant::BC5,
-        "bc6h" => CompressionVariant::BC6H(BC6HSettings::very_slow()),
-        "bc7" => CompressionVariant::BC7(BC7Settings::alpha_slow()),
-        _ => {
-            println!("Error: Invalid compression variant");
+    let force_cpu = args.iter().any(|arg| arg == "--cpu");
+    args.retain(|arg| arg != "--cpu");
+
+    if args.get(1).is_some_and(|command| command == "batch") {
+        if args.len() < 5 {
+            print_help();
+            return None;
+        }
+
+        return Some(Command::Batch {
+            manifest: PathBuf::from(&args[2]),
+            output: PathBuf::from(&args[3]),
+            inputs: args[4..].to_vec(),
+            force_cpu,
+        });
+    }
+
+    if args.get(1).is_some_and(|command| command == "decompress") {
+        return parse_decompress_args(&args[2..]);
+    }
+
+    if args.get(1).is_some_and(|command| command == "compare") {
+        let compare_args = &args[2..];
+
+        if compare_args.len() == 2 && compare_args[0].to_lowercase().ends_with(".dds") {
+            return Some(Command::Compare {
+                image: PathBuf::from(&compare_args[1]),
+                source: compare::Source::Dds(PathBuf::from(&compare_args[0])),
+                force_cpu,
+            });
+        }
+
+        let (variant, file_name) = parse_compress_args(compare_args)?;
+
+        return Some(Command::Compare {
+            image: PathBuf::from(file_name),
+            source: compare::Source::Compress(variant),
+            force_cpu,
+        });
+    }
+
+    let (variant, file_name) = parse_compress_args(&args[1..])?;
+
+    // Fail before anything is compressed, if the blocks can't be written.
+    if let Err(error) = dxgi_format(variant) {
+        println!("Error: {error}");
+        return None;
+    }
+
+    Some(Command::Compress {
+        variant,
+        file_name,
+        force_cpu,
+    })
+}
+
+/// Parses the options and positional arguments of a compression.
+fn parse_compress_args(args: &[String]) -> Option<(CompressionVariant, String)> {
+    let mut preset = None;
+    let mut overrides = Vec::new();
+    let mut positional = Vec::new();
+    let mut auto = false;
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--auto" => auto = true,
+            "--preset" | "--set" => {
+                let Some(value) = iter.next() else {
+                    println!("Error: {arg} needs a value");
+                    print_help();
+                    return None;
+                };
+
+                if arg == "--preset" {
+                    preset = Some(value.clone());
+                } else {
+                    overrides.push(value.clone());
+                }
+            }
+            _ => positional.push(arg.clone()),
+        }
+    }
+
+    if auto {
+        if positional.len() != 1 || preset.is_some() || !overrides.is_empty() {
+            println!("Error: --auto only takes the input file and no --preset or --set");
+            print_help();
+            return None;
+        }
+
+        return match select_variant(Path::new(&positional[0])) {
+            Ok(variant) => Some((variant, positional[0].clone())),
+            Err(error) => {
+                println!("Error: {error}");
+                None
+            }
+        };
+    }
+
+    if positional.len() != 2 {
+        print_help();
+        return None;
+    }
+
+    let variant = match parse_variant(&positional[0], preset.as_deref(), &overrides) {
+        Ok(variant) => variant,
+        Err(error) => {
+            println!("Error: {error}");
             print_help();
             return None;
         }
     };
 
-    let file_name = args[2].clone();
+    let file_name = positional[1].clone();
 
     Some((variant, file_name))
 }
 
+fn parse_decompress_args(args: &[String]) -> Option<Command> {
+    let mut format = None;
+    let mut positional = Vec::new();
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--format" => {
+                let Some(value) = iter.next() else {
+                    println!("Error: {arg} needs a value");
+                    print_help();
+                    return None;
+                };
+
+                match ImageFormat::from_extension(value) {
+                    Some(
+                        image_format @ (ImageFormat::Png
+                        | ImageFormat::Tga
+                        | ImageFormat::OpenExr
+                        | ImageFormat::Hdr),
+                    ) => format = Some(image_format),
+                    _ => {
+                        println!("Error: invalid output format '{value}'");
+                        print_help();
+                        return None;
+                    }
+                }
+            }
+            _ => positional.push(PathBuf::from(arg)),
+        }
+    }
+
+    if positional.is_empty() || positional.len() > 2 {
+        print_help();
+        return None;
+    }
+
+    Some(Command::Decompress {
+        input: positional[0].clone(),
+        output: positional.get(1).cloned(),
+        format,
+    })
+}
+
 pub fn error_handler(error: Error) {
     let (message_type, message) = match error {
         Error::OutOfMemory { source } => ("OutOfMemory", source.to_string()),
@@ -0,0 +1,88 @@
+use std::path::Path;
+
+use block_compression::CompressionVariant;
+use glob::{MatchOptions, Pattern};
+use serde::Deserialize;
+
+use crate::{dxgi_format, variant::parse_variant};
+
+/// Batch manifest, that maps path patterns to compression variants.
+///
+/// The variant, preset and overrides of a rule are joined into one `CompressionVariant`
+/// specification, so `variant = "bc7:alpha_basic{channels=4}"` works too.
+///
+/// Example in TOML:
+///
+/// ```toml
+/// [[rules]]
+/// pattern = "**/*_normal.png"
+/// variant = "bc5"
+///
+/// [[rules]]
+/// pattern = "**/*.png"
+/// variant = "bc7"
+/// preset = "alpha_basic"
+/// overrides = ["refine_iterations_channel=4"]
+/// ```
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Manifest {
+    rules: Vec<Rule>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Rule {
+    pattern: String,
+    variant: String,
+    preset: Option<String>,
+    #[serde(default)]
+    overrides: Vec<String>,
+}
+
+/// A manifest rule with its pattern and variant already parsed.
+pub struct CompiledRule {
+    pattern: Pattern,
+    pub variant: CompressionVariant,
+}
+
+/// Reads a TOML or JSON manifest, depending on the file extension.
+pub fn load_manifest(path: &Path) -> Result<Vec<CompiledRule>, String> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|error| format!("can't read manifest '{}': {error}", path.display()))?;
+
+    let manifest: Manifest = match path.extension().and_then(|extension| extension.to_str()) {
+        Some("toml") => toml::from_str(&content).map_err(|error| error.to_string())?,
+        Some("json") => serde_json::from_str(&content).map_err(|error| error.to_string())?,
+        _ => return Err("manifest must be a .toml or .json file".to_string()),
+    };
+
+    manifest
+        .rules
+        .into_iter()
+        .map(|rule| {
+            let pattern = Pattern::new(&rule.pattern)
+                .map_err(|error| format!("invalid pattern '{}': {error}", rule.pattern))?;
+            let variant = parse_variant(&rule.variant, rule.preset.as_deref(), &rule.overrides)?;
+            dxgi_format(variant).map_err(|error| format!("rule '{}': {error}", rule.pattern))?;
+
+            Ok(CompiledRule { pattern, variant })
+        })
+        .collect()
+}
+
+/// Returns the variant of the first rule, whose pattern matches the relative path.
+///
+/// Wildcards don't match path separators, so `**` must be used to match files in subdirectories.
+pub fn match_rules(rules: &[CompiledRule], relative_path: &Path) -> Option<CompressionVariant> {
+    let options = MatchOptions {
+        case_sensitive: true,
+        require_literal_separator: true,
+        require_literal_leading_dot: false,
+    };
+
+    rules
+        .iter()
+        .find(|rule| rule.pattern.matches_path_with(relative_path, options))
+        .map(|rule| rule.variant)
+}
@@ -0,0 +1,206 @@
+use std::path::Path;
+
+use image::RgbImage;
+
+/// Quality metrics of a single channel.
+#[derive(Debug, Clone, Copy)]
+pub struct ChannelMetrics {
+    pub mse: f64,
+    /// Infinite, if both images are identical.
+    pub psnr: f64,
+    pub ssim: f64,
+}
+
+/// Size and stride of the SSIM windows in pixels.
+const SSIM_WINDOW: usize = 8;
+const SSIM_STRIDE: usize = 4;
+
+/// Calculates the metrics of one channel of interleaved RGBA data.
+///
+/// `peak` is the biggest possible value of the channel, which is `1.0` for normalized LDR data.
+pub fn channel_metrics(
+    original: &[f32],
+    decoded: &[f32],
+    width: u32,
+    height: u32,
+    channel: usize,
+    peak: f64,
+) -> ChannelMetrics {
+    assert_eq!(
+        original.len(),
+        decoded.len(),
+        "images must have the same size"
+    );
+    assert_eq!(
+        original.len(),
+        width as usize * height as usize * 4,
+        "image size doesn't match the dimensions"
+    );
+
+    let squared_error_sum: f64 = original
+        .iter()
+        .zip(decoded)
+        .skip(channel)
+        .step_by(4)
+        .map(|(&a, &b)| (a as f64 - b as f64).powi(2))
+        .sum();
+
+    let mse = squared_error_sum / (width as f64 * height as f64);
+    let psnr = 10.0 * (peak * peak / mse).log10();
+    let ssim = ssim(
+        original,
+        decoded,
+        width as usize,
+        height as usize,
+        channel,
+        peak,
+    );
+
+    ChannelMetrics { mse, psnr, ssim }
+}
+
+/// Mean structural similarity over overlapping windows.
+///
+/// Images smaller than a window are treated as a single window.
+fn ssim(
+    original: &[f32],
+    decoded: &[f32],
+    width: usize,
+    height: usize,
+    channel: usize,
+    peak: f64,
+) -> f64 {
+    let c1 = (0.01 * peak).powi(2);
+    let c2 = (0.03 * peak).powi(2);
+
+    let window_width = SSIM_WINDOW.min(width);
+    let window_height = SSIM_WINDOW.min(height);
+
+    let mut ssim_sum = 0.0;
+    let mut window_count = 0;
+
+    for y in (0..=height - window_height).step_by(SSIM_STRIDE) {
+        for x in (0..=width - window_width).step_by(SSIM_STRIDE) {
+            let mut sum_a = 0.0;
+            let mut sum_b = 0.0;
+            let mut sum_aa = 0.0;
+            let mut sum_bb = 0.0;
+            let mut sum_ab = 0.0;
+
+            for wy in y..y + window_height {
+                for wx in x..x + window_width {
+                    let index = (wy * width + wx) * 4 + channel;
+                    let a = original[index] as f64;
+                    let b = decoded[index] as f64;
+
+                    sum_a += a;
+                    sum_b += b;
+                    sum_aa += a * a;
+                    sum_bb += b * b;
+                    sum_ab += a * b;
+                }
+            }
+
+            let n = (window_width * window_height) as f64;
+            let mean_a = sum_a / n;
+            let mean_b = sum_b / n;
+            let variance_a = sum_aa / n - mean_a * mean_a;
+            let variance_b = sum_bb / n - mean_b * mean_b;
+            let covariance = sum_ab / n - mean_a * mean_b;
+
+            ssim_sum += ((2.0 * mean_a * mean_b + c1) * (2.0 * covariance + c2))
+                / ((mean_a * mean_a + mean_b * mean_b + c1) * (variance_a + variance_b + c2));
+            window_count += 1;
+        }
+    }
+
+    ssim_sum / window_count as f64
+}
+
+/// Writes false-color heat maps of the per-pixel and the per-block RMSE over the first
+/// `channels` channels. Both maps are normalized to their biggest error, which is returned.
+pub fn write_heat_maps(
+    original: &[f32],
+    decoded: &[f32],
+    width: u32,
+    height: u32,
+    channels: usize,
+    block_footprint: (u32, u32),
+    (pixel_map_path, block_map_path): (&Path, &Path),
+) -> Result<(f64, f64), String> {
+    let pixel_errors: Vec<f64> = original
+        .chunks_exact(4)
+        .zip(decoded.chunks_exact(4))
+        .map(|(a, b)| {
+            let squared_error: f64 = (0..channels)
+                .map(|channel| (a[channel] as f64 - b[channel] as f64).powi(2))
+                .sum();
+            (squared_error / channels as f64).sqrt()
+        })
+        .collect();
+
+    let (footprint_width, footprint_height) = block_footprint;
+    let blocks_x = width.div_ceil(footprint_width);
+    let blocks_y = height.div_ceil(footprint_height);
+    let mut block_squared_errors = vec![(0.0, 0u32); (blocks_x * blocks_y) as usize];
+
+    for y in 0..height {
+        for x in 0..width {
+            let block = ((y / footprint_height) * blocks_x + x / footprint_width) as usize;
+            block_squared_errors[block].0 += pixel_errors[(y * width + x) as usize].powi(2);
+            block_squared_errors[block].1 += 1;
+        }
+    }
+
+    let block_errors: Vec<f64> = block_squared_errors
+        .iter()
+        .map(|&(sum, count)| (sum / count as f64).sqrt())
+        .collect();
+
+    let max_pixel_error = pixel_errors.iter().copied().fold(0.0, f64::max);
+    let max_block_error = block_errors.iter().copied().fold(0.0, f64::max);
+
+    let pixel_map = RgbImage::from_fn(width, height, |x, y| {
+        heat_color(pixel_errors[(y * width + x) as usize], max_pixel_error)
+    });
+    let block_map = RgbImage::from_fn(width, height, |x, y| {
+        let block = ((y / footprint_height) * blocks_x + x / footprint_width) as usize;
+        heat_color(block_errors[block], max_block_error)
+    });
+
+    for (map, path) in [(pixel_map, pixel_map_path), (block_map, block_map_path)] {
+        map.save(path)
+            .map_err(|error| format!("can't write '{}': {error}", path.display()))?;
+    }
+
+    Ok((max_pixel_error, max_block_error))
+}
+
+/// Maps an error to a color ramp from black over blue, cyan, green and yellow to red.
+fn heat_color(error: f64, max_error: f64) -> image::Rgb<u8> {
+    const STOPS: [[f64; 3]; 6] = [
+        [0.0, 0.0, 0.0],
+        [0.0, 0.0, 1.0],
+        [0.0, 1.0, 1.0],
+        [0.0, 1.0, 0.0],
+        [1.0, 1.0, 0.0],
+        [1.0, 0.0, 0.0],
+    ];
+
+    let value = if max_error > 0.0 {
+        (error / max_error).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+
+    let position = value * (STOPS.len() - 1) as f64;
+    let index = (position as usize).min(STOPS.len() - 2);
+    let t = position - index as f64;
+
+    let color = std::array::from_fn(|channel| {
+        let value = STOPS[index][channel] * (1.0 - t) + STOPS[index + 1][channel] * t;
+        (value * 255.0).round() as u8
+    });
+
+    image::Rgb(color)
+}
@@ -0,0 +1,30 @@
+use block_compression::CompressionVariant;
+
+/// Parses a compression variant with an optional quality preset and settings overrides of the
+/// form `field=value`.
+///
+/// The arguments are joined into the specification `variant[:preset][{field=value,...}]`, so the
+/// command line and the manifests accept exactly the syntax of `CompressionVariant::from_str()`.
+/// The variant and preset names are case-insensitive.
+pub fn parse_variant(
+    variant: &str,
+    preset: Option<&str>,
+    overrides: &[String],
+) -> Result<CompressionVariant, String> {
+    let variant = variant.trim();
+    let (name, settings) = variant.split_at(variant.find('{').unwrap_or(variant.len()));
+    let mut spec = name.to_lowercase() + settings;
+
+    if let Some(preset) = preset {
+        spec.push(':');
+        spec.push_str(&preset.trim().to_lowercase());
+    }
+    if !overrides.is_empty() {
+        spec.push('{');
+        spec.push_str(&overrides.join(","));
+        spec.push('}');
+    }
+
+    spec.parse()
+        .map_err(|error| format!("invalid compression variant '{spec}': {error}"))
+}
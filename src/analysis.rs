@@ -0,0 +1,192 @@
+//! Image analysis to select a compression variant from the image content.
+//!
+//! ```
+//! use block_compression::analysis::ImageAnalysis;
+//!
+//! let rgba_data = vec![128u8; 64 * 64 * 4];
+//! let analysis = ImageAnalysis::from_rgba8(&rgba_data, 64, 64, 64 * 4);
+//! let variant = analysis.recommended_variant();
+//! ```
+
+#[cfg(feature = "bc6h")]
+use crate::BC6HSettings;
+#[cfg(feature = "bc7")]
+use crate::BC7Settings;
+use crate::CompressionVariant;
+
+/// How the alpha channel of an image is used.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum AlphaUsage {
+    /// All pixels are fully opaque.
+    Opaque,
+    /// All pixels are either fully opaque or fully transparent.
+    Binary,
+    /// Pixels have intermediate alpha values.
+    Smooth,
+}
+
+/// Properties of an image, that decide which compression variant fits it best.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct ImageAnalysis {
+    /// How the alpha channel is used.
+    pub alpha: AlphaUsage,
+    /// The red, green and blue channels are equal in every pixel.
+    pub grayscale: bool,
+    /// The blue channel is zero in every pixel, so only red and green carry data. This is the
+    /// usual layout of two channel normal maps.
+    pub two_channel: bool,
+    /// Color values are outside the range of `0.0` to `1.0`.
+    pub hdr: bool,
+}
+
+impl ImageAnalysis {
+    /// Analyses RGBA8 data with the given row stride in bytes.
+    ///
+    /// # Panics
+    /// - The `rgba_data` is too small for the given dimensions and stride
+    pub fn from_rgba8(rgba_data: &[u8], width: u32, height: u32, stride: u32) -> Self {
+        let mut analysis = Self::empty();
+
+        for y in 0..height as usize {
+            let row = &rgba_data[y * stride as usize..][..width as usize * 4];
+
+            for pixel in row.chunks_exact(4) {
+                analysis.add_pixel(
+                    [
+                        pixel[0] as f32 / 255.0,
+                        pixel[1] as f32 / 255.0,
+                        pixel[2] as f32 / 255.0,
+                    ],
+                    pixel[3] as f32 / 255.0,
+                );
+            }
+        }
+
+        analysis
+    }
+
+    /// Analyses RGBA32F data with the given row stride in `f32` elements.
+    ///
+    /// # Panics
+    /// - The `rgba_data` is too small for the given dimensions and stride
+    pub fn from_rgba32f(rgba_data: &[f32], width: u32, height: u32, stride: u32) -> Self {
+        let mut analysis = Self::empty();
+
+        for y in 0..height as usize {
+            let row = &rgba_data[y * stride as usize..][..width as usize * 4];
+
+            for pixel in row.chunks_exact(4) {
+                analysis.add_pixel([pixel[0], pixel[1], pixel[2]], pixel[3]);
+            }
+        }
+
+        analysis
+    }
+
+    /// The analysis of an image without pixels, which every pixel can only downgrade.
+    const fn empty() -> Self {
+        Self {
+            alpha: AlphaUsage::Opaque,
+            grayscale: true,
+            two_channel: true,
+            hdr: false,
+        }
+    }
+
+    fn add_pixel(&mut self, [red, green, blue]: [f32; 3], alpha: f32) {
+        if alpha > 0.0 && alpha < 1.0 {
+            self.alpha = AlphaUsage::Smooth;
+        } else if alpha == 0.0 && self.alpha == AlphaUsage::Opaque {
+            self.alpha = AlphaUsage::Binary;
+        }
+
+        self.grayscale &= red == green && green == blue;
+        self.two_channel &= blue == 0.0;
+        self.hdr |= [red, green, blue]
+            .iter()
+            .any(|value| !(0.0..=1.0).contains(value));
+    }
+
+    /// Returns the compression variant and settings preset, that fit the image best.
+    ///
+    /// 1. HDR images use BC6H with the `basic` preset.
+    /// 2. Images with binary or smooth alpha use BC7 with the `alpha_basic` preset, or BC3
+    ///    without the `bc7` feature.
+    /// 3. Opaque grayscale images use BC4.
+    /// 4. Opaque images without blue use BC5.
+    /// 5. All other opaque images use BC7 with the `opaque_basic` preset, or BC1 without the
+    ///    `bc7` feature.
+    ///
+    /// Without the `bc6h` feature HDR images are treated like LDR images.
+    pub fn recommended_variant(&self) -> CompressionVariant {
+        #[cfg(feature = "bc6h")]
+        if self.hdr {
+            return CompressionVariant::BC6H(BC6HSettings::basic());
+        }
+
+        match self.alpha {
+            #[cfg(feature = "bc7")]
+            AlphaUsage::Binary | AlphaUsage::Smooth => {
+                CompressionVariant::BC7(BC7Settings::alpha_basic())
+            }
+            #[cfg(not(feature = "bc7"))]
+            AlphaUsage::Binary | AlphaUsage::Smooth => CompressionVariant::BC3,
+            AlphaUsage::Opaque if self.grayscale => CompressionVariant::BC4,
+            AlphaUsage::Opaque if self.two_channel => CompressionVariant::BC5,
+            #[cfg(feature = "bc7")]
+            AlphaUsage::Opaque => CompressionVariant::BC7(BC7Settings::opaque_basic()),
+            #[cfg(not(feature = "bc7"))]
+            AlphaUsage::Opaque => CompressionVariant::BC1,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn analyse(pixels: &[[u8; 4]]) -> ImageAnalysis {
+        let rgba_data: Vec<u8> = pixels.iter().flatten().copied().collect();
+        ImageAnalysis::from_rgba8(&rgba_data, pixels.len() as u32, 1, pixels.len() as u32 * 4)
+    }
+
+    #[test]
+    fn detects_alpha_usage() {
+        assert_eq!(
+            analyse(&[[10, 20, 30, 255], [40, 50, 60, 255]]).alpha,
+            AlphaUsage::Opaque
+        );
+        assert_eq!(
+            analyse(&[[10, 20, 30, 0], [40, 50, 60, 255]]).alpha,
+            AlphaUsage::Binary
+        );
+        assert_eq!(
+            analyse(&[[10, 20, 30, 0], [40, 50, 60, 128]]).alpha,
+            AlphaUsage::Smooth
+        );
+    }
+
+    #[test]
+    fn recommends_single_and_two_channel_variants() {
+        let grayscale = analyse(&[[10, 10, 10, 255], [200, 200, 200, 255]]);
+        assert!(grayscale.grayscale);
+        assert_eq!(grayscale.recommended_variant(), CompressionVariant::BC4);
+
+        let two_channel = analyse(&[[10, 20, 0, 255], [200, 100, 0, 255]]);
+        assert!(two_channel.two_channel);
+        assert_eq!(two_channel.recommended_variant(), CompressionVariant::BC5);
+    }
+
+    #[cfg(feature = "bc6h")]
+    #[test]
+    fn detects_hdr() {
+        let rgba_data = [0.5, 4.0, 0.25, 1.0];
+        let analysis = ImageAnalysis::from_rgba32f(&rgba_data, 1, 1, 4);
+
+        assert!(analysis.hdr);
+        assert!(matches!(
+            analysis.recommended_variant(),
+            CompressionVariant::BC6H(..)
+        ));
+    }
+}
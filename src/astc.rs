@@ -0,0 +1,835 @@
+//! Shared definitions of the ASTC block format, used by the encoders and the decoder.
+//!
+//! Implemented after the "Khronos Data Format Specification v1.3", chapter "ASTC Compressed
+//! Texture Image Formats".
+//!
+//! The encoders of this crate only write single partition blocks with one weight plane and the
+//! direct LDR endpoint modes for RGB and RGBA. Every footprint uses a fixed weight grid.
+
+use bytemuck::{Pod, Zeroable};
+
+/// Block footprints of the ASTC LDR variant in pixels.
+///
+/// Bigger footprints use less memory, but have a lower quality, since every block is always
+/// stored in 128 bits.
+#[cfg_attr(docsrs, doc(cfg(feature = "astc")))]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum ASTCBlockSize {
+    /// 4x4 pixels (8.00 bits per pixel)
+    Block4x4,
+    /// 5x4 pixels (6.40 bits per pixel)
+    Block5x4,
+    /// 5x5 pixels (5.12 bits per pixel)
+    Block5x5,
+    /// 6x5 pixels (4.27 bits per pixel)
+    Block6x5,
+    /// 6x6 pixels (3.56 bits per pixel)
+    Block6x6,
+    /// 8x5 pixels (3.20 bits per pixel)
+    Block8x5,
+    /// 8x6 pixels (2.67 bits per pixel)
+    Block8x6,
+    /// 8x8 pixels (2.00 bits per pixel)
+    Block8x8,
+    /// 10x5 pixels (2.56 bits per pixel)
+    Block10x5,
+    /// 10x6 pixels (2.13 bits per pixel)
+    Block10x6,
+    /// 10x8 pixels (1.60 bits per pixel)
+    Block10x8,
+    /// 10x10 pixels (1.28 bits per pixel)
+    Block10x10,
+    /// 12x10 pixels (1.07 bits per pixel)
+    Block12x10,
+    /// 12x12 pixels (0.89 bits per pixel)
+    Block12x12,
+}
+
+impl ASTCBlockSize {
+    /// All block footprints, ordered like the formats of Vulkan.
+    pub(crate) const ALL: [Self; 14] = [
+        Self::Block4x4,
+        Self::Block5x4,
+        Self::Block5x5,
+        Self::Block6x5,
+        Self::Block6x6,
+        Self::Block8x5,
+        Self::Block8x6,
+        Self::Block8x8,
+        Self::Block10x5,
+        Self::Block10x6,
+        Self::Block10x8,
+        Self::Block10x10,
+        Self::Block12x10,
+        Self::Block12x12,
+    ];
+
+    /// Returns the width and height of a block in pixels.
+    pub const fn dimensions(self) -> (u32, u32) {
+        match self {
+            Self::Block4x4 => (4, 4),
+            Self::Block5x4 => (5, 4),
+            Self::Block5x5 => (5, 5),
+            Self::Block6x5 => (6, 5),
+            Self::Block6x6 => (6, 6),
+            Self::Block8x5 => (8, 5),
+            Self::Block8x6 => (8, 6),
+            Self::Block8x8 => (8, 8),
+            Self::Block10x5 => (10, 5),
+            Self::Block10x6 => (10, 6),
+            Self::Block10x8 => (10, 8),
+            Self::Block10x10 => (10, 10),
+            Self::Block12x10 => (12, 10),
+            Self::Block12x12 => (12, 12),
+        }
+    }
+
+    /// The weight grid and weight quantization level the encoders use for this footprint.
+    ///
+    /// Bigger footprints use a 5x5 grid, which is interpolated by the decoder. This leaves enough
+    /// bits for the color endpoints.
+    const fn weight_grid(self) -> (u32, u32, usize) {
+        match self {
+            Self::Block4x4 => (4, 4, QUANT_12),
+            Self::Block5x4 => (5, 4, QUANT_8),
+            _ => (5, 5, QUANT_6),
+        }
+    }
+
+    pub(crate) const fn encoder_parameters(self) -> EncoderParameters {
+        let (block_width, block_height) = self.dimensions();
+        let (grid_width, grid_height, weight_quant) = self.weight_grid();
+
+        let weight_bits = ise_bit_count(grid_width * grid_height, weight_quant);
+        let color_bits = 128 - 17 - weight_bits;
+
+        EncoderParameters {
+            block_width,
+            block_height,
+            grid_width,
+            grid_height,
+            weight_quant: weight_quant as u32,
+            block_mode: encode_block_mode(grid_width, grid_height, weight_quant),
+            color_quant_rgb: color_quant_level(color_bits, 6) as u32,
+            color_quant_rgba: color_quant_level(color_bits, 8) as u32,
+        }
+    }
+}
+
+/// Everything the encoders need to know about the layout of a block.
+#[derive(Copy, Clone, Debug, Zeroable, Pod)]
+#[repr(C)]
+pub(crate) struct EncoderParameters {
+    pub(crate) block_width: u32,
+    pub(crate) block_height: u32,
+    pub(crate) grid_width: u32,
+    pub(crate) grid_height: u32,
+    pub(crate) weight_quant: u32,
+    pub(crate) block_mode: u32,
+    pub(crate) color_quant_rgb: u32,
+    pub(crate) color_quant_rgba: u32,
+}
+
+/// The biggest footprint has 12x12 texels.
+pub(crate) const MAX_BLOCK_TEXELS: usize = 144;
+
+/// The ASTC error color, which is returned for blocks that can't be decoded.
+pub(crate) const ERROR_COLOR: [u8; 4] = [0xFF, 0x00, 0xFF, 0xFF];
+
+pub(crate) const QUANT_6: usize = 4;
+pub(crate) const QUANT_8: usize = 5;
+pub(crate) const QUANT_12: usize = 7;
+
+/// Quantization levels of the integer sequence encoding: number of levels, trits, quints and
+/// bits. Weights can use the first 12 levels, color endpoints all of them.
+pub(crate) const QUANT_LEVELS: [[u32; 4]; 21] = [
+    [2, 0, 0, 1],
+    [3, 1, 0, 0],
+    [4, 0, 0, 2],
+    [5, 0, 1, 0],
+    [6, 1, 0, 1],
+    [8, 0, 0, 3],
+    [10, 0, 1, 1],
+    [12, 1, 0, 2],
+    [16, 0, 0, 4],
+    [20, 0, 1, 2],
+    [24, 1, 0, 3],
+    [32, 0, 0, 5],
+    [40, 0, 1, 3],
+    [48, 1, 0, 4],
+    [64, 0, 0, 6],
+    [80, 0, 1, 4],
+    [96, 1, 0, 5],
+    [128, 0, 0, 7],
+    [160, 0, 1, 5],
+    [192, 1, 0, 6],
+    [256, 0, 0, 8],
+];
+
+/// Packed 8-bit values of five trits, indexed by `t0 + 3 * t1 + 9 * t2 + 27 * t3 + 81 * t4`.
+pub(crate) static TRIT_ENCODING: [u8; 243] = build_trit_encoding();
+
+/// Packed 7-bit values of three quints, indexed by `q0 + 5 * q1 + 25 * q2`.
+pub(crate) static QUINT_ENCODING: [u8; 125] = build_quint_encoding();
+
+/// Returns the number of bits of an integer sequence with `count` values.
+pub(crate) const fn ise_bit_count(count: u32, quant: usize) -> u32 {
+    let [_, trits, quints, bits] = QUANT_LEVELS[quant];
+    count * bits + (count * 8 * trits).div_ceil(5) + (count * 7 * quints).div_ceil(3)
+}
+
+/// Returns the highest quantization level, whose integer sequence of `count` values fits into
+/// `available_bits`.
+pub(crate) const fn color_quant_level(available_bits: u32, count: u32) -> usize {
+    let mut quant = QUANT_LEVELS.len() - 1;
+    while quant > 0 && ise_bit_count(count, quant) > available_bits {
+        quant -= 1;
+    }
+    quant
+}
+
+const fn bits(value: u32, high: u32, low: u32) -> u32 {
+    (value >> low) & ((1 << (high - low + 1)) - 1)
+}
+
+const fn decode_trits(packed: u32) -> [u32; 5] {
+    let (c, t3, t4) = if bits(packed, 4, 2) == 0b111 {
+        ((bits(packed, 7, 5) << 2) | bits(packed, 1, 0), 2, 2)
+    } else if bits(packed, 6, 5) == 0b11 {
+        (bits(packed, 4, 0), bits(packed, 7, 7), 2)
+    } else {
+        (bits(packed, 4, 0), bits(packed, 6, 5), bits(packed, 7, 7))
+    };
+
+    let (t0, t1, t2) = if bits(c, 1, 0) == 0b11 {
+        let t0 = (bits(c, 3, 3) << 1) | (bits(c, 2, 2) & !bits(c, 3, 3) & 1);
+        (t0, bits(c, 4, 4), 2)
+    } else if bits(c, 3, 2) == 0b11 {
+        (bits(c, 1, 0), 2, 2)
+    } else {
+        let t0 = (bits(c, 1, 1) << 1) | (bits(c, 0, 0) & !bits(c, 1, 1) & 1);
+        (t0, bits(c, 3, 2), bits(c, 4, 4))
+    };
+
+    [t0, t1, t2, t3, t4]
+}
+
+const fn decode_quints(packed: u32) -> [u32; 3] {
+    let q0 = bits(packed, 0, 0);
+
+    if bits(packed, 2, 1) == 0b11 && bits(packed, 6, 5) == 0 {
+        let q2 = (q0 << 2) | ((bits(packed, 4, 4) & !q0 & 1) << 1) | (bits(packed, 3, 3) & !q0 & 1);
+        return [4, 4, q2];
+    }
+
+    let (c, q2) = if bits(packed, 2, 1) == 0b11 {
+        let c = (bits(packed, 4, 3) << 3) | ((!bits(packed, 6, 5) & 0b11) << 1) | q0;
+        (c, 4)
+    } else {
+        (bits(packed, 4, 0), bits(packed, 6, 5))
+    };
+
+    if bits(c, 2, 0) == 0b101 {
+        [bits(c, 4, 3), 4, q2]
+    } else {
+        [bits(c, 2, 0), bits(c, 4, 3), q2]
+    }
+}
+
+/// Inverts the trit decoding. The smallest packed value of every combination is used, so that
+/// trailing zero trits don't set any high bits.
+const fn build_trit_encoding() -> [u8; 243] {
+    let mut table = [0; 243];
+    let mut found = [false; 243];
+
+    let mut packed = 0;
+    while packed < 256 {
+        let t = decode_trits(packed);
+        let index = (t[0] + 3 * t[1] + 9 * t[2] + 27 * t[3] + 81 * t[4]) as usize;
+        if !found[index] {
+            found[index] = true;
+            table[index] = packed as u8;
+        }
+        packed += 1;
+    }
+
+    table
+}
+
+/// Inverts the quint decoding, like [`build_trit_encoding`].
+const fn build_quint_encoding() -> [u8; 125] {
+    let mut table = [0; 125];
+    let mut found = [false; 125];
+
+    let mut packed = 0;
+    while packed < 128 {
+        let q = decode_quints(packed);
+        let index = (q[0] + 5 * q[1] + 25 * q[2]) as usize;
+        if !found[index] {
+            found[index] = true;
+            table[index] = packed as u8;
+        }
+        packed += 1;
+    }
+
+    table
+}
+
+#[inline(always)]
+const fn bit_mask(count: u32) -> u128 {
+    if count == 0 {
+        0
+    } else {
+        u128::MAX >> (128 - count)
+    }
+}
+
+/// Writes `count` bits of `value` at `offset` into `block`. Bits after the end of the block are
+/// dropped.
+#[inline(always)]
+pub(crate) fn write_bits(block: &mut u128, value: u32, count: u32, offset: u32) {
+    if offset < 128 {
+        *block |= ((value as u128) & bit_mask(count)) << offset;
+    }
+}
+
+/// Reads `count` bits at `offset` from `block`. Bits after the end of the block are read as zero.
+#[inline(always)]
+pub(crate) fn read_bits(block: u128, count: u32, offset: u32) -> u32 {
+    (block.checked_shr(offset).unwrap_or(0) & bit_mask(count)) as u32
+}
+
+/// Writes the values as integer sequence at `offset` into `block`. Returns the number of
+/// written bits.
+pub(crate) fn write_ise(block: &mut u128, values: &[u8], quant: usize, offset: u32) -> u32 {
+    let [_, trits, quints, bits] = QUANT_LEVELS[quant];
+    let total_bits = ise_bit_count(values.len() as u32, quant);
+
+    // Values are written into a scratch block, so that the unused bits of the last trit or quint
+    // block can be cut off.
+    let mut sequence = 0u128;
+    let mut position = 0;
+
+    let low_bits = |value: u8| value as u32 & ((1 << bits) - 1);
+
+    if trits == 1 {
+        for chunk in values.chunks(5) {
+            let mut t = [0; 5];
+            for (trit, &value) in t.iter_mut().zip(chunk) {
+                *trit = value as u32 >> bits;
+            }
+            let packed =
+                TRIT_ENCODING[(t[0] + 3 * t[1] + 9 * t[2] + 27 * t[3] + 81 * t[4]) as usize] as u32;
+
+            // The packed bits are interleaved with the low bits of the values.
+            const TRIT_BITS: [(u32, u32); 5] = [(0, 2), (2, 2), (4, 1), (5, 2), (7, 1)];
+            for (index, &(shift, count)) in TRIT_BITS.iter().enumerate() {
+                let value = chunk.get(index).copied().unwrap_or(0);
+                write_bits(&mut sequence, low_bits(value), bits, position);
+                position += bits;
+                write_bits(&mut sequence, packed >> shift, count, position);
+                position += count;
+            }
+        }
+    } else if quints == 1 {
+        for chunk in values.chunks(3) {
+            let mut q = [0; 3];
+            for (quint, &value) in q.iter_mut().zip(chunk) {
+                *quint = value as u32 >> bits;
+            }
+            let packed = QUINT_ENCODING[(q[0] + 5 * q[1] + 25 * q[2]) as usize] as u32;
+
+            const QUINT_BITS: [(u32, u32); 3] = [(0, 3), (3, 2), (5, 2)];
+            for (index, &(shift, count)) in QUINT_BITS.iter().enumerate() {
+                let value = chunk.get(index).copied().unwrap_or(0);
+                write_bits(&mut sequence, low_bits(value), bits, position);
+                position += bits;
+                write_bits(&mut sequence, packed >> shift, count, position);
+                position += count;
+            }
+        }
+    } else {
+        for &value in values {
+            write_bits(&mut sequence, value as u32, bits, position);
+            position += bits;
+        }
+    }
+
+    *block |= (sequence & bit_mask(total_bits)) << offset;
+
+    total_bits
+}
+
+/// Reads `values.len()` values of an integer sequence at `offset` from `block`.
+pub(crate) fn read_ise(block: u128, values: &mut [u8], quant: usize, offset: u32) {
+    let [_, trits, quints, bits] = QUANT_LEVELS[quant];
+    let total_bits = ise_bit_count(values.len() as u32, quant);
+
+    // Bits after the sequence are read as zero.
+    let sequence = (block >> offset) & bit_mask(total_bits);
+    let mut position = 0;
+
+    if trits == 1 {
+        for chunk in values.chunks_mut(5) {
+            let mut low = [0; 5];
+            let mut packed = 0;
+
+            const TRIT_BITS: [(u32, u32); 5] = [(0, 2), (2, 2), (4, 1), (5, 2), (7, 1)];
+            for (index, &(shift, count)) in TRIT_BITS.iter().enumerate() {
+                low[index] = read_bits(sequence, bits, position);
+                position += bits;
+                packed |= read_bits(sequence, count, position) << shift;
+                position += count;
+            }
+
+            let t = decode_trits(packed);
+            for (index, value) in chunk.iter_mut().enumerate() {
+                *value = ((t[index] << bits) | low[index]) as u8;
+            }
+        }
+    } else if quints == 1 {
+        for chunk in values.chunks_mut(3) {
+            let mut low = [0; 3];
+            let mut packed = 0;
+
+            const QUINT_BITS: [(u32, u32); 3] = [(0, 3), (3, 2), (5, 2)];
+            for (index, &(shift, count)) in QUINT_BITS.iter().enumerate() {
+                low[index] = read_bits(sequence, bits, position);
+                position += bits;
+                packed |= read_bits(sequence, count, position) << shift;
+                position += count;
+            }
+
+            let q = decode_quints(packed);
+            for (index, value) in chunk.iter_mut().enumerate() {
+                *value = ((q[index] << bits) | low[index]) as u8;
+            }
+        }
+    } else {
+        for value in values.iter_mut() {
+            *value = read_bits(sequence, bits, position) as u8;
+            position += bits;
+        }
+    }
+}
+
+/// Replicates the `from` lowest bits of `value` until `to` bits are filled.
+const fn replicate_bits(value: u32, from: u32, to: u32) -> u32 {
+    let mut result = 0;
+    let mut filled = 0;
+    while filled < to {
+        result = (result << from) | value;
+        filled += from;
+    }
+    result >> (filled - to)
+}
+
+/// Unquantizes a color endpoint value into the range of 0 to 255.
+pub(crate) fn unquantize_color(quant: usize, value: u8) -> u8 {
+    let [levels, trits, quints, bits] = QUANT_LEVELS[quant];
+    let value = value as u32;
+
+    if trits == 0 && quints == 0 {
+        return replicate_bits(value, bits, 8) as u8;
+    }
+    if bits == 0 {
+        return (value * 255 / (levels - 1)) as u8;
+    }
+
+    let d = value >> bits;
+    let a = if value & 1 == 1 { 0x1FF } else { 0 };
+    let bit = |index: u32| (value >> index) & 1;
+    let (b, c) = match (trits, bits) {
+        // 000000000
+        (1, 1) => (0, 204),
+        (0, 1) => (0, 113),
+        // b000b0bb0
+        (1, 2) => (bit(1) * 0x116, 93),
+        // b0000bb00
+        (0, 2) => (bit(1) * 0x10C, 54),
+        // cb000cbcb
+        (1, 3) => (bit(2) * 0x10A + bit(1) * 0x085, 44),
+        // cb0000cbc
+        (0, 3) => (bit(2) * 0x105 + bit(1) * 0x082, 26),
+        // dcb000dcb
+        (1, 4) => (bit(3) * 0x104 + bit(2) * 0x082 + bit(1) * 0x041, 22),
+        // dcb0000dc
+        (0, 4) => (bit(3) * 0x102 + bit(2) * 0x081 + bit(1) * 0x040, 13),
+        // edcb000ed
+        (1, 5) => (
+            bit(4) * 0x102 + bit(3) * 0x081 + bit(2) * 0x040 + bit(1) * 0x020,
+            11,
+        ),
+        // edcb0000e
+        (0, 5) => (
+            bit(4) * 0x101 + bit(3) * 0x080 + bit(2) * 0x040 + bit(1) * 0x020,
+            6,
+        ),
+        // fedcb000f
+        _ => (
+            bit(5) * 0x101 + bit(4) * 0x080 + bit(3) * 0x040 + bit(2) * 0x020 + bit(1) * 0x010,
+            5,
+        ),
+    };
+
+    let t = (d * c + b) ^ a;
+    ((a & 0x80) | (t >> 2)) as u8
+}
+
+/// Unquantizes a weight into the range of 0 to 64.
+pub(crate) fn unquantize_weight(quant: usize, value: u8) -> u8 {
+    let [levels, trits, quints, bits] = QUANT_LEVELS[quant];
+    let value = value as u32;
+
+    let t = if trits == 0 && quints == 0 {
+        replicate_bits(value, bits, 6)
+    } else if bits == 0 {
+        return (value * 64 / (levels - 1)) as u8;
+    } else {
+        let d = value >> bits;
+        let a = if value & 1 == 1 { 0x7F } else { 0 };
+        let bit = |index: u32| (value >> index) & 1;
+        let (b, c) = match (trits, bits) {
+            // 0000000
+            (1, 1) => (0, 50),
+            (0, 1) => (0, 28),
+            // b000b0b
+            (1, 2) => (bit(1) * 0x45, 23),
+            // b0000b0
+            (0, 2) => (bit(1) * 0x42, 13),
+            // cb000cb
+            _ => (bit(2) * 0x42 + bit(1) * 0x21, 11),
+        };
+
+        let t = (d * c + b) ^ a;
+        (a & 0x20) | (t >> 2)
+    };
+
+    if t > 32 {
+        (t + 1) as u8
+    } else {
+        t as u8
+    }
+}
+
+/// Builds the 11-bit block mode of a single plane block. Only grids that the encoders use are
+/// supported.
+const fn encode_block_mode(grid_width: u32, grid_height: u32, weight_quant: usize) -> u32 {
+    let high_precision = (weight_quant / 6) as u32;
+    let range = (weight_quant % 6) as u32 + 2;
+    let range_bits = (range >> 1) | ((range & 1) << 4) | (high_precision << 9);
+
+    match (grid_width, grid_height) {
+        (4..=7, 2..=5) => range_bits | ((grid_height - 2) << 5) | ((grid_width - 4) << 7),
+        (8..=11, 2..=5) => {
+            range_bits | (1 << 2) | ((grid_height - 2) << 5) | ((grid_width - 8) << 7)
+        }
+        (2..=5, 8..=11) => {
+            range_bits | (2 << 2) | ((grid_width - 2) << 5) | ((grid_height - 8) << 7)
+        }
+        _ => panic!("unsupported weight grid"),
+    }
+}
+
+/// The weight grid of a block.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub(crate) struct BlockMode {
+    pub(crate) grid_width: u32,
+    pub(crate) grid_height: u32,
+    pub(crate) weight_quant: usize,
+    pub(crate) dual_plane: bool,
+}
+
+/// Decodes the 11-bit block mode. Returns `None` for reserved modes and weight grids that are
+/// not allowed.
+pub(crate) fn decode_block_mode(block_mode: u32) -> Option<BlockMode> {
+    let mut range = (block_mode >> 4) & 1;
+    let mut high_precision = (block_mode >> 9) & 1;
+    let mut dual_plane = (block_mode >> 10) & 1;
+    let a = (block_mode >> 5) & 0x3;
+
+    let (grid_width, grid_height) = if block_mode & 0x3 != 0 {
+        range |= (block_mode & 0x3) << 1;
+        let b = (block_mode >> 7) & 0x3;
+
+        match (block_mode >> 2) & 0x3 {
+            0 => (b + 4, a + 2),
+            1 => (b + 8, a + 2),
+            2 => (a + 2, b + 8),
+            _ => {
+                let b = b & 1;
+                if block_mode & 0x100 != 0 {
+                    (b + 2, a + 2)
+                } else {
+                    (a + 2, b + 6)
+                }
+            }
+        }
+    } else {
+        range |= ((block_mode >> 2) & 0x3) << 1;
+        if (block_mode >> 2) & 0x3 == 0 {
+            return None;
+        }
+
+        let b = (block_mode >> 9) & 0x3;
+
+        match (block_mode >> 7) & 0x3 {
+            0 => (12, a + 2),
+            1 => (a + 2, 12),
+            2 => {
+                dual_plane = 0;
+                high_precision = 0;
+                (a + 6, b + 6)
+            }
+            _ => match a {
+                0 => (6, 10),
+                1 => (10, 6),
+                _ => return None,
+            },
+        }
+    };
+
+    let weight_quant = (range - 2 + 6 * high_precision) as usize;
+    let weight_count = grid_width * grid_height * (dual_plane + 1);
+    let weight_bits = ise_bit_count(weight_count, weight_quant);
+
+    if weight_count > 64 || !(24..=96).contains(&weight_bits) {
+        return None;
+    }
+
+    Some(BlockMode {
+        grid_width,
+        grid_height,
+        weight_quant,
+        dual_plane: dual_plane == 1,
+    })
+}
+
+/// Returns the four grid weights, that contribute to a texel and their factors, which sum up to
+/// 16.
+#[inline(always)]
+pub(crate) fn infill_contributions(
+    block_width: u32,
+    block_height: u32,
+    grid_width: u32,
+    grid_height: u32,
+    x: u32,
+    y: u32,
+) -> [(usize, u32); 4] {
+    let ds = (1024 + block_width / 2) / (block_width - 1);
+    let dt = (1024 + block_height / 2) / (block_height - 1);
+
+    let gs = (ds * x * (grid_width - 1) + 32) >> 6;
+    let gt = (dt * y * (grid_height - 1) + 32) >> 6;
+
+    let js = gs >> 4;
+    let fs = gs & 0xF;
+    let jt = gt >> 4;
+    let ft = gt & 0xF;
+
+    let w11 = (fs * ft + 8) >> 4;
+    let w10 = ft - w11;
+    let w01 = fs - w11;
+    let w00 = 16 + w11 - fs - ft;
+
+    // Contributions outside of the grid always have a factor of zero.
+    let v0 = (js + jt * grid_width) as usize;
+    let last = (grid_width * grid_height - 1) as usize;
+    let grid_width = grid_width as usize;
+
+    [
+        (v0, w00),
+        ((v0 + 1).min(last), w01),
+        ((v0 + grid_width).min(last), w10),
+        ((v0 + grid_width + 1).min(last), w11),
+    ]
+}
+
+/// Interpolates the unquantized grid weights into one weight per texel.
+pub(crate) fn infill_weights(
+    block_width: u32,
+    block_height: u32,
+    grid_width: u32,
+    grid_height: u32,
+    grid_weights: &[u8],
+    texel_weights: &mut [u8],
+) {
+    for y in 0..block_height {
+        for x in 0..block_width {
+            let contributions =
+                infill_contributions(block_width, block_height, grid_width, grid_height, x, y);
+
+            let mut sum = 8;
+            for (index, factor) in contributions {
+                sum += grid_weights[index] as u32 * factor;
+            }
+
+            texel_weights[(y * block_width + x) as usize] = (sum >> 4) as u8;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ise_roundtrip() {
+        for (quant, &[levels, ..]) in QUANT_LEVELS.iter().enumerate() {
+            for count in [6, 7, 8, 16, 25] {
+                if ise_bit_count(count, quant) + 5 > 128 {
+                    continue;
+                }
+
+                let values: Vec<u8> = (0..count).map(|i| ((i * 7 + 3) % levels) as u8).collect();
+
+                let mut block = 0;
+                let bits = write_ise(&mut block, &values, quant, 5);
+                assert_eq!(bits, ise_bit_count(count, quant));
+                assert_eq!(block >> (5 + bits), 0);
+
+                let mut decoded = vec![0; count as usize];
+                read_ise(block, &mut decoded, quant, 5);
+                assert_eq!(values, decoded, "quantization level {quant}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_unquantization_endpoints() {
+        for (quant, &[levels, ..]) in QUANT_LEVELS.iter().enumerate() {
+            let colors: Vec<u8> = (0..levels)
+                .map(|value| unquantize_color(quant, value as u8))
+                .collect();
+            assert!(colors.contains(&0) && colors.contains(&255));
+
+            if quant < 12 {
+                let weights: Vec<u8> = (0..levels)
+                    .map(|value| unquantize_weight(quant, value as u8))
+                    .collect();
+                assert!(weights.contains(&0) && weights.contains(&64));
+            }
+        }
+
+        // Known values of the 12 level color quantization.
+        let colors: Vec<u8> = (0..12)
+            .map(|value| unquantize_color(QUANT_12, value))
+            .collect();
+        assert_eq!(
+            colors,
+            [0, 255, 69, 186, 23, 232, 92, 163, 46, 209, 116, 139]
+        );
+    }
+
+    #[test]
+    fn test_block_mode_roundtrip() {
+        for (width, height) in [(4, 4), (5, 4), (5, 5), (6, 6), (8, 8), (10, 10), (12, 12)] {
+            let block_size = match (width, height) {
+                (4, 4) => ASTCBlockSize::Block4x4,
+                (5, 4) => ASTCBlockSize::Block5x4,
+                (5, 5) => ASTCBlockSize::Block5x5,
+                (6, 6) => ASTCBlockSize::Block6x6,
+                (8, 8) => ASTCBlockSize::Block8x8,
+                (10, 10) => ASTCBlockSize::Block10x10,
+                _ => ASTCBlockSize::Block12x12,
+            };
+            let parameters = block_size.encoder_parameters();
+
+            let mode = decode_block_mode(parameters.block_mode).unwrap();
+            assert_eq!(mode.grid_width, parameters.grid_width);
+            assert_eq!(mode.grid_height, parameters.grid_height);
+            assert_eq!(mode.weight_quant, parameters.weight_quant as usize);
+            assert!(!mode.dual_plane);
+        }
+    }
+
+    /// Blocks with the texels, that the ASTC decoder of Mesa (llvmpipe) produces for them: a
+    /// void-extent block, an RGB block with a 4x4 grid, an RGBA block with a 5x4 grid and an RGBA
+    /// block, whose 5x5 grid is interpolated to 6x6 texels.
+    ///
+    /// Mesa decodes like the `decode_unorm8` mode of `VK_EXT_astc_decode_mode` and truncates the
+    /// interpolated 16-bit colors, while this crate rounds them, so channels may differ by one.
+    const REFERENCE_BLOCKS: [(ASTCBlockSize, [u8; 16], &[u8]); 4] = [
+        (
+            ASTCBlockSize::Block4x4,
+            [
+                0xFC, 0xFD, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x34, 0x12, 0xFF, 0x80, 0xDC, 0xFE,
+                0x00, 0xC0,
+            ],
+            &[
+                18, 128, 254, 192, 18, 128, 254, 192, 18, 128, 254, 192, 18, 128, 254, 192, 18,
+                128, 254, 192, 18, 128, 254, 192, 18, 128, 254, 192, 18, 128, 254, 192, 18, 128,
+                254, 192, 18, 128, 254, 192, 18, 128, 254, 192, 18, 128, 254, 192, 18, 128, 254,
+                192, 18, 128, 254, 192, 18, 128, 254, 192, 18, 128, 254, 192,
+            ],
+        ),
+        (
+            ASTCBlockSize::Block4x4,
+            [
+                0x51, 0x02, 0x41, 0x08, 0x81, 0x0D, 0xA5, 0xF8, 0x00, 0xBA, 0x95, 0xAE, 0xF8, 0x53,
+                0x7A, 0x01,
+            ],
+            &[
+                32, 192, 82, 255, 49, 182, 89, 255, 68, 171, 97, 255, 88, 159, 106, 255, 39, 188,
+                85, 255, 58, 177, 93, 255, 88, 159, 106, 255, 105, 149, 113, 255, 49, 182, 89, 255,
+                76, 167, 100, 255, 96, 155, 109, 255, 115, 144, 117, 255, 58, 177, 93, 255, 88,
+                159, 106, 255, 105, 149, 113, 255, 132, 134, 124, 255,
+            ],
+        ),
+        (
+            ASTCBlockSize::Block5x4,
+            [
+                0xD3, 0x80, 0xE5, 0xFD, 0xF5, 0x53, 0xBD, 0xD0, 0xF1, 0x35, 0xFA, 0x62, 0xAE, 0x16,
+                0x1B, 0x13,
+            ],
+            &[
+                25, 194, 87, 249, 42, 186, 91, 244, 76, 168, 99, 232, 95, 159, 104, 225, 112, 150,
+                108, 219, 42, 186, 91, 244, 59, 177, 95, 238, 76, 168, 99, 232, 112, 150, 108, 219,
+                129, 142, 112, 213, 42, 186, 91, 244, 76, 168, 99, 232, 95, 159, 104, 225, 129,
+                142, 112, 213, 146, 133, 116, 207, 59, 177, 95, 238, 95, 159, 104, 225, 112, 150,
+                108, 219, 129, 142, 112, 213, 146, 133, 116, 207,
+            ],
+        ),
+        (
+            ASTCBlockSize::Block6x6,
+            [
+                0xE3, 0x80, 0xE5, 0xEC, 0xA8, 0xDA, 0x0F, 0x33, 0xED, 0xA3, 0x5D, 0xAE, 0xD3, 0x4D,
+                0x1B, 0x6D,
+            ],
+            &[
+                21, 196, 86, 250, 47, 180, 92, 239, 74, 164, 98, 228, 87, 155, 101, 223, 95, 151,
+                103, 219, 124, 133, 109, 207, 47, 180, 92, 239, 50, 178, 92, 238, 74, 164, 98, 228,
+                97, 149, 103, 218, 124, 133, 109, 207, 153, 115, 116, 195, 52, 177, 93, 237, 71,
+                165, 97, 229, 97, 149, 103, 218, 116, 138, 107, 210, 129, 129, 110, 205, 158, 111,
+                117, 193, 66, 169, 96, 232, 82, 159, 100, 225, 111, 141, 106, 213, 129, 129, 110,
+                205, 143, 121, 113, 199, 172, 103, 120, 187, 87, 155, 101, 223, 92, 152, 102, 220,
+                113, 139, 107, 211, 137, 124, 112, 201, 164, 108, 118, 190, 190, 92, 124, 179, 87,
+                155, 101, 223, 116, 138, 107, 210, 124, 133, 109, 207, 137, 124, 112, 201, 164,
+                108, 118, 190, 190, 92, 124, 179,
+            ],
+        ),
+    ];
+
+    #[test]
+    fn test_reference_blocks() {
+        for (block_size, block, expected) in REFERENCE_BLOCKS {
+            let (width, height) = block_size.dimensions();
+            let mut decompressed = vec![0; (width * height * 4) as usize];
+            crate::decode::decode_block_astc(
+                &block,
+                &mut decompressed,
+                width as usize * 4,
+                block_size,
+            );
+
+            for (index, (&value, &expected)) in decompressed.iter().zip(expected).enumerate() {
+                assert!(
+                    value.abs_diff(expected) <= 1,
+                    "{block_size:?} texel {} channel {}: {value} != {expected}",
+                    index / 4,
+                    index % 4
+                );
+            }
+        }
+    }
+}
@@ -1,19 +1,26 @@
-use std::{collections::HashMap, num::NonZeroU64, sync::Arc};
+use std::{collections::HashMap, num::NonZeroU64, sync::Arc, time::Duration};
 
 use bytemuck::{cast_slice, Pod, Zeroable};
 use wgpu::{
     self, include_wgsl, BindGroup, BindGroupDescriptor, BindGroupEntry, BindGroupLayout,
     BindGroupLayoutDescriptor, BindGroupLayoutEntry, BindingResource, BindingType, Buffer,
-    BufferBinding, BufferBindingType, BufferDescriptor, BufferUsages, ComputePass, ComputePipeline,
-    ComputePipelineDescriptor, Device, PipelineCompilationOptions, PipelineLayoutDescriptor, Queue,
-    ShaderModule, ShaderStages, TextureSampleType, TextureView, TextureViewDimension,
+    BufferAsyncError, BufferBinding, BufferBindingType, BufferDescriptor, BufferUsages,
+    CommandEncoder, ComputePass, ComputePipeline, ComputePipelineDescriptor, Device, Extent3d,
+    Features, MapMode, PipelineCompilationOptions, PipelineLayoutDescriptor, QuerySet,
+    QuerySetDescriptor, QueryType, Queue, ShaderModule, ShaderStages, TextureDescriptor,
+    TextureDimension, TextureFormat, TextureSampleType, TextureUsages, TextureView,
+    TextureViewDescriptor, TextureViewDimension, WasmNotSend, QUERY_SET_MAX_QUERIES, QUERY_SIZE,
 };
 
+#[cfg(feature = "astc")]
+use crate::astc::EncoderParameters;
+#[cfg(feature = "astc")]
+use crate::ASTCBlockSize;
 #[cfg(feature = "bc6h")]
 use crate::BC6HSettings;
 #[cfg(feature = "bc7")]
 use crate::BC7Settings;
-use crate::CompressionVariant;
+use crate::{AlphaOptions, CompressionVariant};
 
 #[derive(Copy, Clone, Zeroable, Pod)]
 #[repr(C)]
@@ -24,6 +31,53 @@ struct Uniforms {
     height: u32,
     /// Start of the blocks data in u32 elements.
     blocks_offset: u32,
+    /// Where the shader reads the source pixels from.
+    source_format: u32,
+    /// Start of the pixel data in the source buffer in u32 elements.
+    source_offset: u32,
+    /// Row stride of the pixel data in the source buffer in u32 elements.
+    source_stride: u32,
+    /// First pixel row of the source texture, for tasks that were split into strips.
+    source_row: u32,
+    /// Bit 0 premultiplies the color with the alpha, bit 1 weights the color error by the alpha.
+    alpha_flags: u32,
+    /// Non-zero selects the integer only encoder of BC1 to BC5.
+    strict: u32,
+}
+
+/// The source is read from the sampled texture at binding 0.
+const SOURCE_TEXTURE: u32 = 0;
+
+/// Pixel formats that can be used as storage buffer input of a compression task.
+///
+/// Pixels are stored in row-major order, with the channels of each pixel in RGBA order.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum SourceBufferFormat {
+    /// Four 8-bit unsigned normalized channels (32 bits per pixel).
+    Rgba8Unorm,
+    /// Four 16-bit float channels (64 bits per pixel).
+    Rgba16Float,
+    /// Four 32-bit float channels (128 bits per pixel).
+    Rgba32Float,
+}
+
+impl SourceBufferFormat {
+    /// Returns the size of a single pixel in bytes.
+    pub const fn bytes_per_pixel(self) -> u32 {
+        match self {
+            Self::Rgba8Unorm => 4,
+            Self::Rgba16Float => 8,
+            Self::Rgba32Float => 16,
+        }
+    }
+
+    const fn source_format(self) -> u32 {
+        match self {
+            Self::Rgba8Unorm => 1,
+            Self::Rgba16Float => 2,
+            Self::Rgba32Float => 3,
+        }
+    }
 }
 
 struct Task {
@@ -31,10 +85,80 @@ struct Task {
     width: u32,
     height: u32,
     uniform_offset: u32,
-    #[cfg(any(feature = "bc6h", feature = "bc7"))]
+    #[cfg(any(feature = "astc", feature = "bc6h", feature = "bc7"))]
     setting_offset: u32,
     buffer_offset: u32,
-    bind_group: BindGroup,
+    source_format: u32,
+    source_offset: u32,
+    source_stride: u32,
+    source_row: u32,
+    alpha_flags: u32,
+    strict: u32,
+    texture_view: TextureView,
+    source_buffer: BufferRange,
+    buffer: BufferRange,
+}
+
+/// A range of a buffer, that is bound when the task is dispatched.
+///
+/// The bind groups are created on dispatch, because the uniforms and settings buffers are
+/// recreated when more tasks are queued than they can hold.
+struct BufferRange {
+    buffer: Buffer,
+    offset: u64,
+    size: Option<NonZeroU64>,
+}
+
+impl BufferRange {
+    fn binding(&self) -> BufferBinding<'_> {
+        BufferBinding {
+            buffer: &self.buffer,
+            offset: self.offset,
+            size: self.size,
+        }
+    }
+}
+
+/// Where a task reads its source pixels from.
+#[derive(Copy, Clone)]
+enum Source<'a> {
+    Texture(&'a TextureView),
+    Buffer {
+        buffer: &'a Buffer,
+        format: SourceBufferFormat,
+        offset: u32,
+        row_stride: u32,
+    },
+}
+
+/// Binds `size` bytes of a buffer starting at `start`. Returns the range and the offset of
+/// `start` inside the range.
+///
+/// Buffers, that fit into a single binding, are bound entirely.
+fn binding_range(
+    buffer: &Buffer,
+    start: u64,
+    size: u64,
+    max_binding_size: u64,
+    alignment: u64,
+) -> (BufferRange, u32) {
+    if buffer.size() <= max_binding_size {
+        let range = BufferRange {
+            buffer: buffer.clone(),
+            offset: 0,
+            size: None,
+        };
+        return (range, start as u32);
+    }
+
+    let aligned_start = start - start % alignment;
+    let range = BufferRange {
+        buffer: buffer.clone(),
+        offset: aligned_start,
+        size: NonZeroU64::new(start - aligned_start + size),
+    };
+
+    (range, (start - aligned_start) as u32)
 }
 
 /// Compresses texture data with a block compression algorithm using WGPU compute shader.
@@ -46,8 +170,12 @@ pub struct GpuBlockCompressor {
     bc6h_settings_buffer: Buffer,
     #[cfg(feature = "bc7")]
     bc7_settings_buffer: Buffer,
+    #[cfg(feature = "astc")]
+    astc_settings_buffer: Buffer,
     bind_group_layouts: HashMap<CompressionVariant, BindGroupLayout>,
     pipelines: HashMap<CompressionVariant, ComputePipeline>,
+    dummy_texture_view: TextureView,
+    dummy_source_buffer: Buffer,
     device: Arc<Device>,
     queue: Arc<Queue>,
     uniforms_aligned_size: usize,
@@ -55,6 +183,9 @@ pub struct GpuBlockCompressor {
     bc6h_aligned_size: usize,
     #[cfg(feature = "bc7")]
     bc7_aligned_size: usize,
+    #[cfg(feature = "astc")]
+    astc_aligned_size: usize,
+    strict_mode: bool,
 }
 
 impl GpuBlockCompressor {
@@ -80,12 +211,23 @@ impl GpuBlockCompressor {
             size.div_ceil(alignment) * alignment
         };
 
+        #[cfg(feature = "astc")]
+        let astc_aligned_size = {
+            let alignment = limits.min_storage_buffer_offset_alignment as usize;
+            let size = size_of::<EncoderParameters>();
+            size.div_ceil(alignment) * alignment
+        };
+
         let shader_module_bc1_to_5 =
             device.create_shader_module(include_wgsl!("shader/BC1_to_5.wgsl"));
         #[cfg(feature = "bc6h")]
         let shader_module_bc6h = device.create_shader_module(include_wgsl!("shader/BC6H.wgsl"));
         #[cfg(feature = "bc7")]
         let shader_module_bc7 = device.create_shader_module(include_wgsl!("shader/BC7.wgsl"));
+        #[cfg(feature = "etc2")]
+        let shader_module_etc2 = device.create_shader_module(include_wgsl!("shader/ETC2.wgsl"));
+        #[cfg(feature = "astc")]
+        let shader_module_astc = device.create_shader_module(include_wgsl!("shader/ASTC.wgsl"));
 
         let uniforms_buffer = device.create_buffer(&BufferDescriptor {
             label: Some("uniforms"),
@@ -110,6 +252,40 @@ impl GpuBlockCompressor {
             mapped_at_creation: false,
         });
 
+        #[cfg(feature = "astc")]
+        let astc_settings_buffer = device.create_buffer(&BufferDescriptor {
+            label: Some("astc settings"),
+            size: (astc_aligned_size * 16) as _,
+            usage: BufferUsages::COPY_DST | BufferUsages::STORAGE,
+            mapped_at_creation: false,
+        });
+
+        // Every pipeline binds both a source texture and a source buffer. The shader only reads
+        // from one of them, so the unused binding is filled with one of these placeholders.
+        let dummy_texture_view = device
+            .create_texture(&TextureDescriptor {
+                label: Some("dummy source texture"),
+                size: Extent3d {
+                    width: 1,
+                    height: 1,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: TextureDimension::D2,
+                format: TextureFormat::Rgba8Unorm,
+                usage: TextureUsages::TEXTURE_BINDING,
+                view_formats: &[],
+            })
+            .create_view(&TextureViewDescriptor::default());
+
+        let dummy_source_buffer = device.create_buffer(&BufferDescriptor {
+            label: Some("dummy source buffer"),
+            size: 16,
+            usage: BufferUsages::STORAGE,
+            mapped_at_creation: false,
+        });
+
         let mut bind_group_layouts = HashMap::new();
         let mut pipelines = HashMap::new();
 
@@ -164,6 +340,54 @@ impl GpuBlockCompressor {
             &mut pipelines,
             CompressionVariant::BC7(BC7Settings::alpha_basic()),
         );
+        #[cfg(feature = "etc2")]
+        Self::create_pipeline(
+            &device,
+            &shader_module_etc2,
+            &mut bind_group_layouts,
+            &mut pipelines,
+            CompressionVariant::ETC2RGB8,
+        );
+        #[cfg(feature = "etc2")]
+        Self::create_pipeline(
+            &device,
+            &shader_module_etc2,
+            &mut bind_group_layouts,
+            &mut pipelines,
+            CompressionVariant::ETC2RGB8A1,
+        );
+        #[cfg(feature = "etc2")]
+        Self::create_pipeline(
+            &device,
+            &shader_module_etc2,
+            &mut bind_group_layouts,
+            &mut pipelines,
+            CompressionVariant::ETC2RGBA8,
+        );
+        #[cfg(feature = "etc2")]
+        Self::create_pipeline(
+            &device,
+            &shader_module_etc2,
+            &mut bind_group_layouts,
+            &mut pipelines,
+            CompressionVariant::EACR11,
+        );
+        #[cfg(feature = "etc2")]
+        Self::create_pipeline(
+            &device,
+            &shader_module_etc2,
+            &mut bind_group_layouts,
+            &mut pipelines,
+            CompressionVariant::EACRG11,
+        );
+        #[cfg(feature = "astc")]
+        Self::create_pipeline(
+            &device,
+            &shader_module_astc,
+            &mut bind_group_layouts,
+            &mut pipelines,
+            CompressionVariant::ASTC(ASTCBlockSize::Block4x4),
+        );
 
         Self {
             scratch_buffer: Vec::default(),
@@ -173,8 +397,12 @@ impl GpuBlockCompressor {
             bc6h_settings_buffer,
             #[cfg(feature = "bc7")]
             bc7_settings_buffer,
+            #[cfg(feature = "astc")]
+            astc_settings_buffer,
             bind_group_layouts,
             pipelines,
+            dummy_texture_view,
+            dummy_source_buffer,
             device,
             queue,
             uniforms_aligned_size,
@@ -182,9 +410,26 @@ impl GpuBlockCompressor {
             bc6h_aligned_size,
             #[cfg(feature = "bc7")]
             bc7_aligned_size,
+            #[cfg(feature = "astc")]
+            astc_aligned_size,
+            strict_mode: false,
         }
     }
 
+    /// Enables or disables the strict mode for all tasks, that are added afterwards.
+    ///
+    /// In strict mode BC1 to BC5 are compressed with an integer only encoder, that doesn't depend
+    /// on how the backend rounds floating point operations. The blocks are identical on every
+    /// backend and to the blocks of [`crate::encode::compress_rgba8_strict()`]. All other variants
+    /// ignore the strict mode.
+    ///
+    /// The source pixels are rounded to 8 bits per channel before they are compressed. The CPU
+    /// and GPU results only match for 8-bit sources, for example a `Rgba8Unorm` texture view or a
+    /// [`SourceBufferFormat::Rgba8Unorm`] buffer.
+    pub fn set_strict_mode(&mut self, strict: bool) {
+        self.strict_mode = strict;
+    }
+
     #[allow(unused_mut)]
     fn create_pipeline(
         device: &Device,
@@ -224,6 +469,16 @@ impl GpuBlockCompressor {
                 },
                 count: None,
             },
+            BindGroupLayoutEntry {
+                binding: 4,
+                visibility: ShaderStages::COMPUTE,
+                ty: BindingType::Buffer {
+                    ty: BufferBindingType::Storage { read_only: true },
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
         ];
 
         match variant {
@@ -253,6 +508,19 @@ impl GpuBlockCompressor {
                     count: None,
                 });
             }
+            #[cfg(feature = "astc")]
+            CompressionVariant::ASTC(..) => {
+                layout_entries.push(BindGroupLayoutEntry {
+                    binding: 3,
+                    visibility: ShaderStages::COMPUTE,
+                    ty: BindingType::Buffer {
+                        ty: BufferBindingType::Storage { read_only: true },
+                        has_dynamic_offset: true,
+                        min_binding_size: NonZeroU64::new(size_of::<EncoderParameters>() as _),
+                    },
+                    count: None,
+                });
+            }
             _ => {}
         }
 
@@ -301,6 +569,8 @@ impl GpuBlockCompressor {
     /// BC6H needs an `unorm` or `float` format. `Rgba16Float` is optimal for HDR textures.
     /// Colors should be in linear space and not in sRGBA space.
     ///
+    /// ETC2, EAC and ASTC also expect to work on an `unorm` format.
+    ///
     /// # Buffer Requirements
     /// The destination buffer must have sufficient capacity to store the compressed blocks at the
     /// specified offset. The required size can be calculated using
@@ -314,6 +584,14 @@ impl GpuBlockCompressor {
     /// assert!(buffer.size() >= total_size);
     /// ```
     ///
+    /// # Large Images
+    /// Destination buffers larger than `max_storage_buffer_binding_size` are bound in ranges, so
+    /// the task is split into horizontal strips automatically. The blocks are still written as
+    /// one contiguous layout. Images larger than `max_texture_dimension_2d` can't be stored in a
+    /// texture, but can be compressed from a storage buffer with
+    /// [`GpuBlockCompressor::add_buffer_compression_task()`], which splits large source buffers
+    /// the same way.
+    ///
     /// # Arguments
     /// * `variant` - The block compression format to use
     /// * `texture_view` - View into the source texture to compress
@@ -323,9 +601,10 @@ impl GpuBlockCompressor {
     /// * `offset` - Optional offset in bytes into the destination buffer
     ///
     /// # Panics
-    /// - If `width` or `height` is not a multiple of 4
+    /// - If `width` or `height` is not a multiple of 4 (except for ASTC, which supports any size)
     /// - If the destination `buffer` is not a storage buffer
     /// - If the destination `buffer` is too small to hold the compressed blocks at the specified offset
+    /// - If a single row of blocks doesn't fit into `max_storage_buffer_binding_size`
     #[allow(clippy::too_many_arguments)]
     pub fn add_compression_task(
         &mut self,
@@ -336,8 +615,344 @@ impl GpuBlockCompressor {
         buffer: &Buffer,
         offset: Option<u32>,
     ) {
-        assert_eq!(height % 4, 0);
-        assert_eq!(width % 4, 0);
+        self.add_compression_task_with_alpha(
+            variant,
+            texture_view,
+            width,
+            height,
+            buffer,
+            offset,
+            AlphaOptions::default(),
+        );
+    }
+
+    /// Adds a texture compression task to the queue like
+    /// [`GpuBlockCompressor::add_compression_task()`], with options for how the alpha channel
+    /// influences the color encoding.
+    ///
+    /// The [`AlphaOptions`] are used by BC2, BC3 and BC7 and ignored by all other variants.
+    ///
+    /// # Panics
+    /// - If `width` or `height` is not a multiple of 4 (except for ASTC, which supports any size)
+    /// - If the destination `buffer` is not a storage buffer
+    /// - If the destination `buffer` is too small to hold the compressed blocks at the specified offset
+    /// - If a single row of blocks doesn't fit into `max_storage_buffer_binding_size`
+    #[allow(clippy::too_many_arguments)]
+    pub fn add_compression_task_with_alpha(
+        &mut self,
+        variant: CompressionVariant,
+        texture_view: &TextureView,
+        width: u32,
+        height: u32,
+        buffer: &Buffer,
+        offset: Option<u32>,
+        alpha_options: AlphaOptions,
+    ) {
+        Self::validate_destination(variant, width, height, buffer, offset);
+
+        self.push_tasks(
+            variant,
+            Source::Texture(texture_view),
+            width,
+            height,
+            buffer,
+            offset.unwrap_or(0),
+            Self::alpha_flags(variant, alpha_options),
+        );
+    }
+
+    /// Adds a compression task to the queue, that reads the source pixels from a storage buffer.
+    ///
+    /// This works like [`GpuBlockCompressor::add_compression_task()`], but doesn't need an
+    /// intermediate texture. This is useful if the pixel data is already present in a GPU buffer,
+    /// for example after decoding an image with a compute shader.
+    ///
+    /// # Source Buffer Requirements
+    /// The source buffer must be a storage buffer and contain `height` rows of `width` pixels in
+    /// the given `source_format`. Each row starts `row_stride` bytes after the previous row. The
+    /// row stride allows padded rows, for example buffers that were filled by a texture to buffer
+    /// copy, which needs rows to be aligned to 256 bytes.
+    ///
+    /// Source and destination buffers larger than `max_storage_buffer_binding_size` are bound in
+    /// ranges and the task is split into horizontal strips automatically. This allows images
+    /// larger than `max_texture_dimension_2d`, as long as the buffers fit into `max_buffer_size`.
+    ///
+    /// The same color space rules as for textures apply: BC6H expects linear colors and all other
+    /// variants work on the raw channel values.
+    ///
+    /// # Arguments
+    /// * `variant` - The block compression format to use
+    /// * `source_buffer` - Storage buffer that contains the source pixels
+    /// * `source_format` - The pixel format of the source buffer
+    /// * `source_offset` - Optional offset in bytes into the source buffer
+    /// * `row_stride` - Number of bytes per row in the source buffer
+    /// * `width` - Width of the source image in pixels
+    /// * `height` - Height of the source image in pixels
+    /// * `buffer` - Destination storage buffer for the compressed data
+    /// * `offset` - Optional offset in bytes into the destination buffer
+    ///
+    /// # Panics
+    /// - If `width` or `height` is not a multiple of 4 (except for ASTC, which supports any size)
+    /// - If `source_offset` or `row_stride` is not a multiple of 4
+    /// - If `row_stride` is smaller than a tightly packed row of `width` pixels
+    /// - If the `source_buffer` is not a storage buffer or too small to hold the source pixels
+    /// - If the destination `buffer` is not a storage buffer
+    /// - If the destination `buffer` is too small to hold the compressed blocks at the specified offset
+    /// - If a single row of blocks doesn't fit into `max_storage_buffer_binding_size`
+    #[allow(clippy::too_many_arguments)]
+    pub fn add_buffer_compression_task(
+        &mut self,
+        variant: CompressionVariant,
+        source_buffer: &Buffer,
+        source_format: SourceBufferFormat,
+        source_offset: Option<u32>,
+        row_stride: u32,
+        width: u32,
+        height: u32,
+        buffer: &Buffer,
+        offset: Option<u32>,
+    ) {
+        self.add_buffer_compression_task_with_alpha(
+            variant,
+            source_buffer,
+            source_format,
+            source_offset,
+            row_stride,
+            width,
+            height,
+            buffer,
+            offset,
+            AlphaOptions::default(),
+        );
+    }
+
+    /// Adds a compression task to the queue, that reads the source pixels from a storage buffer,
+    /// like [`GpuBlockCompressor::add_buffer_compression_task()`], with options for how the alpha
+    /// channel influences the color encoding.
+    ///
+    /// The [`AlphaOptions`] are used by BC2, BC3 and BC7 and ignored by all other variants.
+    ///
+    /// # Panics
+    /// - If `width` or `height` is not a multiple of 4 (except for ASTC, which supports any size)
+    /// - If `source_offset` or `row_stride` is not a multiple of 4
+    /// - If `row_stride` is smaller than a tightly packed row of `width` pixels
+    /// - If the `source_buffer` is not a storage buffer or too small to hold the source pixels
+    /// - If the destination `buffer` is not a storage buffer
+    /// - If the destination `buffer` is too small to hold the compressed blocks at the specified offset
+    /// - If a single row of blocks doesn't fit into `max_storage_buffer_binding_size`
+    #[allow(clippy::too_many_arguments)]
+    pub fn add_buffer_compression_task_with_alpha(
+        &mut self,
+        variant: CompressionVariant,
+        source_buffer: &Buffer,
+        source_format: SourceBufferFormat,
+        source_offset: Option<u32>,
+        row_stride: u32,
+        width: u32,
+        height: u32,
+        buffer: &Buffer,
+        offset: Option<u32>,
+        alpha_options: AlphaOptions,
+    ) {
+        Self::validate_destination(variant, width, height, buffer, offset);
+
+        let source_offset = source_offset.unwrap_or(0);
+
+        assert!(
+            source_buffer.usage().contains(BufferUsages::STORAGE),
+            "source buffer needs to be a storage buffer"
+        );
+        assert_eq!(
+            source_offset % 4,
+            0,
+            "source offset needs to be a multiple of 4"
+        );
+        assert_eq!(row_stride % 4, 0, "row stride needs to be a multiple of 4");
+
+        let row_size = width as usize * source_format.bytes_per_pixel() as usize;
+        assert!(
+            row_stride as usize >= row_size,
+            "row stride ({}) is smaller than the size of a row ({})",
+            row_stride,
+            row_size
+        );
+
+        let required_size = (height as usize).saturating_sub(1) * row_stride as usize + row_size;
+        let total_size = source_offset as usize + required_size;
+        assert!(
+            source_buffer.size() as usize >= total_size,
+            "source buffer size ({}) is too small to hold the source pixels at offset {}. Required size: {}",
+            source_buffer.size(),
+            source_offset,
+            total_size
+        );
+
+        self.push_tasks(
+            variant,
+            Source::Buffer {
+                buffer: source_buffer,
+                format: source_format,
+                offset: source_offset,
+                row_stride,
+            },
+            width,
+            height,
+            buffer,
+            offset.unwrap_or(0),
+            Self::alpha_flags(variant, alpha_options),
+        );
+    }
+
+    /// Queues a task for every strip of block rows.
+    ///
+    /// Buffers, that fit into a single storage buffer binding, are bound entirely and the task is
+    /// not split. Larger buffers are bound in ranges, that fit into
+    /// `max_storage_buffer_binding_size`, so the task is split into horizontal strips, which
+    /// write into the same contiguous blocks layout.
+    #[allow(clippy::too_many_arguments)]
+    fn push_tasks(
+        &mut self,
+        variant: CompressionVariant,
+        source: Source,
+        width: u32,
+        height: u32,
+        buffer: &Buffer,
+        offset: u32,
+        alpha_flags: u32,
+    ) {
+        let limits = self.device.limits();
+        let max_binding_size = limits.max_storage_buffer_binding_size as u64;
+        let alignment = limits.min_storage_buffer_offset_alignment as u64;
+
+        let (footprint_width, footprint_height) = variant.block_footprint();
+        let block_rows = height.div_ceil(footprint_height);
+        let blocks_row_size =
+            width.div_ceil(footprint_width) as u64 * variant.block_byte_size() as u64;
+
+        // Binding ranges start at an aligned offset before the strip, so every range can be up
+        // to one alignment larger than the strip itself.
+        let mut strip_rows = block_rows;
+        if buffer.size() > max_binding_size {
+            let rows = max_binding_size.saturating_sub(alignment) / blocks_row_size;
+            strip_rows = strip_rows.min(rows as u32);
+        }
+        if let Source::Buffer {
+            buffer: source_buffer,
+            row_stride,
+            ..
+        } = source
+        {
+            if source_buffer.size() > max_binding_size {
+                let rows = max_binding_size.saturating_sub(alignment)
+                    / (row_stride as u64 * footprint_height as u64);
+                strip_rows = strip_rows.min(rows as u32);
+            }
+        }
+        assert!(
+            strip_rows > 0,
+            "a single row of blocks doesn't fit into max_storage_buffer_binding_size ({max_binding_size})"
+        );
+
+        for first_row in (0..block_rows).step_by(strip_rows as usize) {
+            let rows = strip_rows.min(block_rows - first_row);
+            let source_row = first_row * footprint_height;
+            let strip_height = (rows * footprint_height).min(height - source_row);
+
+            let (destination, buffer_offset) = binding_range(
+                buffer,
+                offset as u64 + first_row as u64 * blocks_row_size,
+                rows as u64 * blocks_row_size,
+                max_binding_size,
+                alignment,
+            );
+
+            let (
+                source_format,
+                source_offset,
+                source_stride,
+                source_row,
+                texture_view,
+                source_buffer,
+            ) = match source {
+                Source::Texture(texture_view) => (
+                    SOURCE_TEXTURE,
+                    0,
+                    0,
+                    source_row,
+                    texture_view.clone(),
+                    BufferRange {
+                        buffer: self.dummy_source_buffer.clone(),
+                        offset: 0,
+                        size: None,
+                    },
+                ),
+                Source::Buffer {
+                    buffer: source_buffer,
+                    format,
+                    offset: source_offset,
+                    row_stride,
+                } => {
+                    let row_size = width as u64 * format.bytes_per_pixel() as u64;
+                    let (source_range, source_offset) = binding_range(
+                        source_buffer,
+                        source_offset as u64 + source_row as u64 * row_stride as u64,
+                        (strip_height as u64 - 1) * row_stride as u64 + row_size,
+                        max_binding_size,
+                        alignment,
+                    );
+                    (
+                        format.source_format(),
+                        source_offset / 4,
+                        row_stride / 4,
+                        0,
+                        self.dummy_texture_view.clone(),
+                        source_range,
+                    )
+                }
+            };
+
+            self.task.push(Task {
+                variant,
+                width,
+                height: strip_height,
+                uniform_offset: 0,
+                #[cfg(any(feature = "astc", feature = "bc6h", feature = "bc7"))]
+                setting_offset: 0,
+                buffer_offset,
+                source_format,
+                source_offset,
+                source_stride,
+                source_row,
+                alpha_flags,
+                strict: (self.strict_mode && variant.supports_strict_mode()) as u32,
+                texture_view,
+                source_buffer,
+                buffer: destination,
+            });
+        }
+    }
+
+    /// Only the shaders of BC2, BC3 and BC7 read the alpha flags.
+    fn alpha_flags(variant: CompressionVariant, alpha_options: AlphaOptions) -> u32 {
+        match variant {
+            CompressionVariant::BC2 | CompressionVariant::BC3 => alpha_options.flags(),
+            #[cfg(feature = "bc7")]
+            CompressionVariant::BC7(..) => alpha_options.flags(),
+            _ => 0,
+        }
+    }
+
+    fn validate_destination(
+        variant: CompressionVariant,
+        width: u32,
+        height: u32,
+        buffer: &Buffer,
+        offset: Option<u32>,
+    ) {
+        if !variant.supports_any_size() {
+            assert_eq!(height % 4, 0);
+            assert_eq!(width % 4, 0);
+        }
         assert!(
             buffer.usage().contains(BufferUsages::STORAGE),
             "buffer needs to be a storage buffer"
@@ -353,18 +968,23 @@ impl GpuBlockCompressor {
             offset.unwrap_or(0),
             total_size
         );
+    }
 
+    fn create_bind_group(
+        &self,
+        variant: CompressionVariant,
+        texture_view: &TextureView,
+        source_buffer: BufferBinding<'_>,
+        buffer: BufferBinding<'_>,
+    ) -> BindGroup {
         let bind_group_layout = self
             .bind_group_layouts
             .get(&variant)
             .expect("Can't find bind group layout for variant");
 
-        let bind_group = match variant {
-            CompressionVariant::BC1
-            | CompressionVariant::BC2
-            | CompressionVariant::BC3
-            | CompressionVariant::BC4
-            | CompressionVariant::BC5 => self.device.create_bind_group(&BindGroupDescriptor {
+        match variant {
+            #[cfg(feature = "bc6h")]
+            CompressionVariant::BC6H(..) => self.device.create_bind_group(&BindGroupDescriptor {
                 label: Some("bind group"),
                 layout: bind_group_layout,
                 entries: &[
@@ -374,7 +994,7 @@ impl GpuBlockCompressor {
                     },
                     BindGroupEntry {
                         binding: 1,
-                        resource: buffer.as_entire_binding(),
+                        resource: BindingResource::Buffer(buffer.clone()),
                     },
                     BindGroupEntry {
                         binding: 2,
@@ -384,10 +1004,22 @@ impl GpuBlockCompressor {
                             size: Some(NonZeroU64::new(self.uniforms_aligned_size as u64).unwrap()),
                         }),
                     },
+                    BindGroupEntry {
+                        binding: 3,
+                        resource: BindingResource::Buffer(BufferBinding {
+                            buffer: &self.bc6h_settings_buffer,
+                            offset: 0,
+                            size: Some(NonZeroU64::new(self.bc6h_aligned_size as u64).unwrap()),
+                        }),
+                    },
+                    BindGroupEntry {
+                        binding: 4,
+                        resource: BindingResource::Buffer(source_buffer.clone()),
+                    },
                 ],
             }),
-            #[cfg(feature = "bc6h")]
-            CompressionVariant::BC6H(..) => self.device.create_bind_group(&BindGroupDescriptor {
+            #[cfg(feature = "bc7")]
+            CompressionVariant::BC7(..) => self.device.create_bind_group(&BindGroupDescriptor {
                 label: Some("bind group"),
                 layout: bind_group_layout,
                 entries: &[
@@ -397,7 +1029,7 @@ impl GpuBlockCompressor {
                     },
                     BindGroupEntry {
                         binding: 1,
-                        resource: buffer.as_entire_binding(),
+                        resource: BindingResource::Buffer(buffer.clone()),
                     },
                     BindGroupEntry {
                         binding: 2,
@@ -410,15 +1042,19 @@ impl GpuBlockCompressor {
                     BindGroupEntry {
                         binding: 3,
                         resource: BindingResource::Buffer(BufferBinding {
-                            buffer: &self.bc6h_settings_buffer,
+                            buffer: &self.bc7_settings_buffer,
                             offset: 0,
-                            size: Some(NonZeroU64::new(self.bc6h_aligned_size as u64).unwrap()),
+                            size: Some(NonZeroU64::new(self.bc7_aligned_size as u64).unwrap()),
                         }),
                     },
+                    BindGroupEntry {
+                        binding: 4,
+                        resource: BindingResource::Buffer(source_buffer.clone()),
+                    },
                 ],
             }),
-            #[cfg(feature = "bc7")]
-            CompressionVariant::BC7(..) => self.device.create_bind_group(&BindGroupDescriptor {
+            #[cfg(feature = "astc")]
+            CompressionVariant::ASTC(..) => self.device.create_bind_group(&BindGroupDescriptor {
                 label: Some("bind group"),
                 layout: bind_group_layout,
                 entries: &[
@@ -428,7 +1064,7 @@ impl GpuBlockCompressor {
                     },
                     BindGroupEntry {
                         binding: 1,
-                        resource: buffer.as_entire_binding(),
+                        resource: BindingResource::Buffer(buffer.clone()),
                     },
                     BindGroupEntry {
                         binding: 2,
@@ -441,25 +1077,45 @@ impl GpuBlockCompressor {
                     BindGroupEntry {
                         binding: 3,
                         resource: BindingResource::Buffer(BufferBinding {
-                            buffer: &self.bc7_settings_buffer,
+                            buffer: &self.astc_settings_buffer,
                             offset: 0,
-                            size: Some(NonZeroU64::new(self.bc7_aligned_size as u64).unwrap()),
+                            size: Some(NonZeroU64::new(self.astc_aligned_size as u64).unwrap()),
                         }),
                     },
+                    BindGroupEntry {
+                        binding: 4,
+                        resource: BindingResource::Buffer(source_buffer.clone()),
+                    },
                 ],
             }),
-        };
-
-        self.task.push(Task {
-            variant,
-            width,
-            height,
-            uniform_offset: 0,
-            #[cfg(any(feature = "bc6h", feature = "bc7"))]
-            setting_offset: 0,
-            buffer_offset: offset.unwrap_or(0),
-            bind_group,
-        });
+            // All other variants have no settings.
+            _ => self.device.create_bind_group(&BindGroupDescriptor {
+                label: Some("bind group"),
+                layout: bind_group_layout,
+                entries: &[
+                    BindGroupEntry {
+                        binding: 0,
+                        resource: BindingResource::TextureView(texture_view),
+                    },
+                    BindGroupEntry {
+                        binding: 1,
+                        resource: BindingResource::Buffer(buffer.clone()),
+                    },
+                    BindGroupEntry {
+                        binding: 2,
+                        resource: BindingResource::Buffer(BufferBinding {
+                            buffer: &self.uniforms_buffer,
+                            offset: 0,
+                            size: Some(NonZeroU64::new(self.uniforms_aligned_size as u64).unwrap()),
+                        }),
+                    },
+                    BindGroupEntry {
+                        binding: 4,
+                        resource: BindingResource::Buffer(source_buffer.clone()),
+                    },
+                ],
+            }),
+        }
     }
 
     fn update_buffer_sizes(&mut self) {
@@ -510,6 +1166,25 @@ impl GpuBlockCompressor {
                 });
             }
         }
+
+        #[cfg(feature = "astc")]
+        {
+            let astc_setting_count = self
+                .task
+                .iter()
+                .filter(|task| matches!(task.variant, CompressionVariant::ASTC(..)))
+                .count();
+
+            let total_astc_size = self.astc_aligned_size * astc_setting_count;
+            if total_astc_size > self.astc_settings_buffer.size() as usize {
+                self.astc_settings_buffer = self.device.create_buffer(&BufferDescriptor {
+                    label: Some("astc settings buffer"),
+                    size: total_astc_size as u64,
+                    usage: BufferUsages::COPY_DST | BufferUsages::STORAGE,
+                    mapped_at_creation: false,
+                });
+            }
+        }
     }
 
     fn upload(&mut self) {
@@ -522,6 +1197,12 @@ impl GpuBlockCompressor {
                 width: task.width,
                 height: task.height,
                 blocks_offset: task.buffer_offset / 4,
+                source_format: task.source_format,
+                source_offset: task.source_offset,
+                source_stride: task.source_stride,
+                source_row: task.source_row,
+                alpha_flags: task.alpha_flags,
+                strict: task.strict,
             };
 
             self.scratch_buffer
@@ -604,6 +1285,39 @@ impl GpuBlockCompressor {
                 }
             }
         }
+
+        #[cfg(feature = "astc")]
+        {
+            self.scratch_buffer.clear();
+            for (index, (parameters, task)) in self
+                .task
+                .iter_mut()
+                .filter_map(|task| {
+                    if let CompressionVariant::ASTC(block_size) = task.variant {
+                        Some((block_size.encoder_parameters(), task))
+                    } else {
+                        None
+                    }
+                })
+                .enumerate()
+            {
+                let offset = index * self.astc_aligned_size;
+                task.setting_offset = offset as u32;
+                self.scratch_buffer
+                    .resize(offset + self.astc_aligned_size, 0);
+                self.scratch_buffer[offset..offset + size_of::<EncoderParameters>()]
+                    .copy_from_slice(cast_slice(&[parameters]));
+            }
+            if !self.scratch_buffer.is_empty() {
+                if let Some(mut data) = self.queue.write_buffer_with(
+                    &self.astc_settings_buffer,
+                    0,
+                    NonZeroU64::new(self.scratch_buffer.len() as u64).unwrap(),
+                ) {
+                    data.copy_from_slice(&self.scratch_buffer);
+                }
+            }
+        }
     }
 
     /// Will upload all dispatch data and then dispatches all compression tasks to the GPU.
@@ -611,10 +1325,47 @@ impl GpuBlockCompressor {
     /// # Arguments
     /// * `pass` - The compute pass to record commands into
     pub fn compress(&mut self, pass: &mut ComputePass) {
+        self.dispatch(pass, None);
+    }
+
+    /// Dispatches all compression tasks like [`GpuBlockCompressor::compress()`] and writes a
+    /// timestamp before and after the dispatch of each task.
+    ///
+    /// The returned [`TaskTimestamps`] must be resolved into the command encoder of the pass,
+    /// after the pass has ended. The durations can be read once the command buffer was
+    /// submitted.
+    ///
+    /// # Arguments
+    /// * `pass` - The compute pass to record commands into
+    ///
+    /// # Panics
+    /// - If the device doesn't support [`Features::TIMESTAMP_QUERY`] and
+    ///   [`Features::TIMESTAMP_QUERY_INSIDE_PASSES`]
+    /// - If more than 2048 tasks are queued
+    pub fn compress_with_timestamps(&mut self, pass: &mut ComputePass) -> TaskTimestamps {
+        let timestamps = TaskTimestamps::new(&self.device, &self.queue, self.task.len() as u32);
+        self.dispatch(pass, Some(&timestamps.query_set));
+        timestamps
+    }
+
+    fn dispatch(&mut self, pass: &mut ComputePass, query_set: Option<&QuerySet>) {
         self.update_buffer_sizes();
         self.upload();
 
-        for task in self.task.drain(..) {
+        let bind_groups: Vec<BindGroup> = self
+            .task
+            .iter()
+            .map(|task| {
+                self.create_bind_group(
+                    task.variant,
+                    &task.texture_view,
+                    task.source_buffer.binding(),
+                    task.buffer.binding(),
+                )
+            })
+            .collect();
+
+        for ((index, task), bind_group) in self.task.drain(..).enumerate().zip(bind_groups) {
             let pipeline = self
                 .pipelines
                 .get(&task.variant)
@@ -627,7 +1378,7 @@ impl GpuBlockCompressor {
                 CompressionVariant::BC6H(..) => {
                     pass.set_bind_group(
                         0,
-                        &task.bind_group,
+                        &bind_group,
                         &[task.uniform_offset, task.setting_offset],
                     );
                 }
@@ -635,22 +1386,194 @@ impl GpuBlockCompressor {
                 CompressionVariant::BC7(..) => {
                     pass.set_bind_group(
                         0,
-                        &task.bind_group,
+                        &bind_group,
+                        &[task.uniform_offset, task.setting_offset],
+                    );
+                }
+                #[cfg(feature = "astc")]
+                CompressionVariant::ASTC(..) => {
+                    pass.set_bind_group(
+                        0,
+                        &bind_group,
                         &[task.uniform_offset, task.setting_offset],
                     );
                 }
                 _ => {
-                    pass.set_bind_group(0, &task.bind_group, &[task.uniform_offset]);
+                    pass.set_bind_group(0, &bind_group, &[task.uniform_offset]);
                 }
             }
 
-            let block_width = (task.width + 3) / 4;
-            let block_height = (task.height + 3) / 4;
+            // Every invocation compresses a single block and every workgroup covers 8x8 blocks.
+            let (footprint_width, footprint_height) = task.variant.block_footprint();
+            let block_width = task.width.div_ceil(footprint_width);
+            let block_height = task.height.div_ceil(footprint_height);
+
+            let workgroup_width = block_width.div_ceil(8);
+            let workgroup_height = block_height.div_ceil(8);
 
-            let workgroup_width = (block_width + 7) / 8;
-            let workgroup_height = (block_height + 7) / 8;
+            if let Some(query_set) = query_set {
+                pass.write_timestamp(query_set, index as u32 * 2);
+            }
 
             pass.dispatch_workgroups(workgroup_width, workgroup_height, 1);
+
+            if let Some(query_set) = query_set {
+                pass.write_timestamp(query_set, index as u32 * 2 + 1);
+            }
         }
     }
 }
+
+/// Timestamps written around the dispatch of each task by
+/// [`GpuBlockCompressor::compress_with_timestamps()`].
+///
+/// ```no_run
+/// # fn example(
+/// #     device: &wgpu::Device,
+/// #     queue: &wgpu::Queue,
+/// #     compressor: &mut block_compression::GpuBlockCompressor,
+/// # ) {
+/// let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+///
+/// let timestamps = {
+///     let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor::default());
+///     compressor.compress_with_timestamps(&mut pass)
+/// };
+/// timestamps.resolve(&mut encoder);
+///
+/// queue.submit([encoder.finish()]);
+///
+/// timestamps.map_durations(|durations| {
+///     for (index, duration) in durations.unwrap().iter().enumerate() {
+///         println!("Task {index} took {duration:?}");
+///     }
+/// });
+/// device.poll(wgpu::Maintain::Wait);
+/// # }
+/// ```
+pub struct TaskTimestamps {
+    query_set: QuerySet,
+    resolve_buffer: Buffer,
+    readback_buffer: Buffer,
+    task_count: u32,
+    period: f32,
+}
+
+impl TaskTimestamps {
+    fn new(device: &Device, queue: &Queue, task_count: u32) -> Self {
+        let required_features = Features::TIMESTAMP_QUERY | Features::TIMESTAMP_QUERY_INSIDE_PASSES;
+        assert!(
+            device.features().contains(required_features),
+            "device doesn't support timestamp queries inside passes"
+        );
+        assert!(
+            task_count * 2 <= QUERY_SET_MAX_QUERIES,
+            "can't write timestamps for more than {} tasks",
+            QUERY_SET_MAX_QUERIES / 2
+        );
+
+        // Query sets and buffers can't be empty.
+        let query_count = (task_count * 2).max(2);
+        let size = query_count as u64 * QUERY_SIZE as u64;
+
+        let query_set = device.create_query_set(&QuerySetDescriptor {
+            label: Some("task timestamps query set"),
+            count: query_count,
+            ty: QueryType::Timestamp,
+        });
+
+        let resolve_buffer = device.create_buffer(&BufferDescriptor {
+            label: Some("task timestamps resolve buffer"),
+            size,
+            usage: BufferUsages::COPY_SRC | BufferUsages::QUERY_RESOLVE,
+            mapped_at_creation: false,
+        });
+
+        let readback_buffer = device.create_buffer(&BufferDescriptor {
+            label: Some("task timestamps read-back buffer"),
+            size,
+            usage: BufferUsages::COPY_DST | BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        Self {
+            query_set,
+            resolve_buffer,
+            readback_buffer,
+            task_count,
+            period: queue.get_timestamp_period(),
+        }
+    }
+
+    /// Returns the number of timed tasks.
+    pub fn task_count(&self) -> u32 {
+        self.task_count
+    }
+
+    /// Resolves the timestamps and copies them into a buffer, that can be read from the CPU.
+    ///
+    /// Must be recorded into the command encoder of the compute pass after the pass has ended.
+    pub fn resolve(&self, encoder: &mut CommandEncoder) {
+        if self.task_count == 0 {
+            return;
+        }
+
+        encoder.resolve_query_set(
+            &self.query_set,
+            0..self.task_count * 2,
+            &self.resolve_buffer,
+            0,
+        );
+        encoder.copy_buffer_to_buffer(
+            &self.resolve_buffer,
+            0,
+            &self.readback_buffer,
+            0,
+            self.readback_buffer.size(),
+        );
+    }
+
+    /// Reads the GPU duration of each task in the order the tasks were added and passes them
+    /// to the callback.
+    ///
+    /// Must be called after the command buffer with the resolved timestamps was submitted. Like
+    /// [`wgpu::BufferSlice::map_async()`], the callback is called once the device was polled
+    /// after the GPU finished the work.
+    pub fn map_durations(
+        self,
+        callback: impl FnOnce(Result<Vec<Duration>, BufferAsyncError>) + WasmNotSend + 'static,
+    ) {
+        if self.task_count == 0 {
+            callback(Ok(Vec::new()));
+            return;
+        }
+
+        let readback_buffer = self.readback_buffer.clone();
+        let task_count = self.task_count as usize;
+        let period = self.period as f64;
+
+        self.readback_buffer
+            .slice(..)
+            .map_async(MapMode::Read, move |result| {
+                let durations = result.map(|()| {
+                    let durations = {
+                        let data = readback_buffer.slice(..).get_mapped_range();
+                        let timestamps: &[u64] = cast_slice(&data);
+
+                        timestamps[..task_count * 2]
+                            .chunks_exact(2)
+                            .map(|pair| {
+                                // Timestamps of some backends are not monotonic.
+                                let ticks = pair[1].saturating_sub(pair[0]);
+                                Duration::from_nanos((ticks as f64 * period) as u64)
+                            })
+                            .collect()
+                    };
+                    readback_buffer.unmap();
+                    durations
+                });
+
+                callback(durations);
+            });
+    }
+}
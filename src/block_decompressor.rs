@@ -0,0 +1,448 @@
+use std::{collections::HashMap, num::NonZeroU64, sync::Arc};
+
+use bytemuck::{cast_slice, Pod, Zeroable};
+use wgpu::{
+    self, include_wgsl, BindGroup, BindGroupDescriptor, BindGroupEntry, BindGroupLayout,
+    BindGroupLayoutDescriptor, BindGroupLayoutEntry, BindingResource, BindingType, Buffer,
+    BufferBinding, BufferBindingType, BufferDescriptor, BufferUsages, ComputePass, ComputePipeline,
+    ComputePipelineDescriptor, Device, PipelineCompilationOptions, PipelineLayoutDescriptor, Queue,
+    ShaderModule, ShaderStages, StorageTextureAccess, TextureFormat, TextureView,
+    TextureViewDimension,
+};
+
+#[cfg(feature = "bc6h")]
+use crate::BC6HSettings;
+#[cfg(feature = "bc7")]
+use crate::BC7Settings;
+use crate::{decode::FillValues, CompressionVariant};
+
+#[derive(Copy, Clone, Zeroable, Pod)]
+#[repr(C)]
+struct Uniforms {
+    /// The width of the image data.
+    width: u32,
+    /// The height of the image data.
+    height: u32,
+    /// Start of the blocks data in u32 elements.
+    blocks_offset: u32,
+    padding: u32,
+    /// Values of the channels, that the variant doesn't store.
+    fill: [f32; 4],
+}
+
+/// Storage texture formats that a decompression task can write into.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum OutputTextureFormat {
+    /// Four 8-bit unsigned normalized channels. BC6H values are written sRGB encoded.
+    Rgba8Unorm,
+    /// Four 16-bit float channels. LDR values are written in the range of `0.0` to `1.0`.
+    Rgba16Float,
+}
+
+impl OutputTextureFormat {
+    /// Returns the matching WGPU texture format.
+    pub const fn texture_format(self) -> TextureFormat {
+        match self {
+            Self::Rgba8Unorm => TextureFormat::Rgba8Unorm,
+            Self::Rgba16Float => TextureFormat::Rgba16Float,
+        }
+    }
+
+    const fn binding(self) -> u32 {
+        match self {
+            Self::Rgba8Unorm => 1,
+            Self::Rgba16Float => 3,
+        }
+    }
+
+    const fn name(self) -> &'static str {
+        match self {
+            Self::Rgba8Unorm => "rgba8",
+            Self::Rgba16Float => "rgba16f",
+        }
+    }
+}
+
+struct Task {
+    variant: CompressionVariant,
+    output_format: OutputTextureFormat,
+    width: u32,
+    height: u32,
+    uniform_offset: u32,
+    buffer_offset: u32,
+    fill: FillValues,
+    buffer: Buffer,
+    texture_view: TextureView,
+}
+
+/// Decompresses block compressed data into a storage texture using WGPU compute shader.
+///
+/// The shaders are a port of the CPU decoder in the [`crate::decode`] module and produce the same
+/// values. This is useful for adapters, that don't support sampling from BC textures, or to
+/// preview the result of a compression without a roundtrip to the CPU.
+pub struct GpuBlockDecompressor {
+    scratch_buffer: Vec<u8>,
+    task: Vec<Task>,
+    uniforms_buffer: Buffer,
+    bind_group_layouts: HashMap<OutputTextureFormat, BindGroupLayout>,
+    pipelines: HashMap<(CompressionVariant, OutputTextureFormat), ComputePipeline>,
+    device: Arc<Device>,
+    queue: Arc<Queue>,
+    uniforms_aligned_size: usize,
+}
+
+impl GpuBlockDecompressor {
+    /// Creates a new block decompressor instance.
+    pub fn new(device: Arc<Device>, queue: Arc<Queue>) -> Self {
+        let limits = device.limits();
+
+        let alignment = limits.min_uniform_buffer_offset_alignment as usize;
+        let size = size_of::<Uniforms>();
+        let uniforms_aligned_size = size.div_ceil(alignment) * alignment;
+
+        let shader_module_bc1_to_5 =
+            device.create_shader_module(include_wgsl!("shader/decode_BC1_to_5.wgsl"));
+        #[cfg(feature = "bc6h")]
+        let shader_module_bc6h =
+            device.create_shader_module(include_wgsl!("shader/decode_BC6H.wgsl"));
+        #[cfg(feature = "bc7")]
+        let shader_module_bc7 =
+            device.create_shader_module(include_wgsl!("shader/decode_BC7.wgsl"));
+
+        let uniforms_buffer = device.create_buffer(&BufferDescriptor {
+            label: Some("uniforms"),
+            size: (uniforms_aligned_size * 16) as _,
+            usage: BufferUsages::COPY_DST | BufferUsages::UNIFORM,
+            mapped_at_creation: false,
+        });
+
+        let mut bind_group_layouts = HashMap::new();
+        let mut pipelines = HashMap::new();
+
+        for output_format in [
+            OutputTextureFormat::Rgba8Unorm,
+            OutputTextureFormat::Rgba16Float,
+        ] {
+            let bind_group_layout = Self::create_bind_group_layout(&device, output_format);
+
+            for variant in [
+                CompressionVariant::BC1,
+                CompressionVariant::BC2,
+                CompressionVariant::BC3,
+                CompressionVariant::BC4,
+                CompressionVariant::BC5,
+            ] {
+                Self::create_pipeline(
+                    &device,
+                    &shader_module_bc1_to_5,
+                    &bind_group_layout,
+                    &mut pipelines,
+                    variant,
+                    output_format,
+                );
+            }
+            #[cfg(feature = "bc6h")]
+            Self::create_pipeline(
+                &device,
+                &shader_module_bc6h,
+                &bind_group_layout,
+                &mut pipelines,
+                CompressionVariant::BC6H(BC6HSettings::basic()),
+                output_format,
+            );
+            #[cfg(feature = "bc7")]
+            Self::create_pipeline(
+                &device,
+                &shader_module_bc7,
+                &bind_group_layout,
+                &mut pipelines,
+                CompressionVariant::BC7(BC7Settings::alpha_basic()),
+                output_format,
+            );
+
+            bind_group_layouts.insert(output_format, bind_group_layout);
+        }
+
+        Self {
+            scratch_buffer: Vec::default(),
+            task: Vec::default(),
+            uniforms_buffer,
+            bind_group_layouts,
+            pipelines,
+            device,
+            queue,
+            uniforms_aligned_size,
+        }
+    }
+
+    fn create_bind_group_layout(
+        device: &Device,
+        output_format: OutputTextureFormat,
+    ) -> BindGroupLayout {
+        device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+            label: Some(&format!(
+                "{} block decompression bind group layout",
+                output_format.name()
+            )),
+            entries: &[
+                BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: ShaderStages::COMPUTE,
+                    ty: BindingType::Buffer {
+                        ty: BufferBindingType::Storage { read_only: true },
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                BindGroupLayoutEntry {
+                    binding: output_format.binding(),
+                    visibility: ShaderStages::COMPUTE,
+                    ty: BindingType::StorageTexture {
+                        access: StorageTextureAccess::WriteOnly,
+                        format: output_format.texture_format(),
+                        view_dimension: TextureViewDimension::D2,
+                    },
+                    count: None,
+                },
+                BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: ShaderStages::COMPUTE,
+                    ty: BindingType::Buffer {
+                        ty: BufferBindingType::Uniform,
+                        has_dynamic_offset: true,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        })
+    }
+
+    fn create_pipeline(
+        device: &Device,
+        shader_module: &ShaderModule,
+        bind_group_layout: &BindGroupLayout,
+        pipelines: &mut HashMap<(CompressionVariant, OutputTextureFormat), ComputePipeline>,
+        variant: CompressionVariant,
+        output_format: OutputTextureFormat,
+    ) {
+        let name = variant.name();
+        let format_name = output_format.name();
+
+        let pipeline_layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
+            label: Some(&format!(
+                "{name} {format_name} block decompression pipeline layout"
+            )),
+            bind_group_layouts: &[bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let entry_point = format!("decompress_{name}_{format_name}");
+
+        let pipeline = device.create_compute_pipeline(&ComputePipelineDescriptor {
+            label: Some(&format!(
+                "{name} {format_name} block decompression pipeline"
+            )),
+            layout: Some(&pipeline_layout),
+            module: shader_module,
+            entry_point: Some(&entry_point),
+            compilation_options: PipelineCompilationOptions::default(),
+            cache: None,
+        });
+
+        pipelines.insert((variant, output_format), pipeline);
+    }
+
+    /// Adds a block decompression task to the queue.
+    ///
+    /// Like [`crate::GpuBlockCompressor::add_compression_task()`], it is possible to fill the mip
+    /// map levels of a texture with multiple calls to this function.
+    ///
+    /// # Buffer Requirements
+    /// The source buffer must be a storage buffer and contain the compressed blocks at the
+    /// specified offset. The size of the blocks can be calculated using
+    /// [`CompressionVariant::blocks_byte_size()`].
+    ///
+    /// # Texture View Requirements
+    /// The texture view must be a 2D view with the format of `output_format` and its texture must
+    /// have been created with the `STORAGE_BINDING` usage. Only pixels inside `width` and `height`
+    /// are written, so the image size doesn't need to be a multiple of 4.
+    ///
+    /// Channels, that the variant doesn't store, are written with the given `fill` values.
+    ///
+    /// # Arguments
+    /// * `variant` - The block compression format of the blocks
+    /// * `buffer` - Source storage buffer with the compressed blocks
+    /// * `offset` - Optional offset in bytes into the source buffer
+    /// * `width` - Width of the image in pixels
+    /// * `height` - Height of the image in pixels
+    /// * `texture_view` - View into the destination texture
+    /// * `output_format` - The format of the destination texture
+    /// * `fill` - The values of the channels, that the variant doesn't store
+    ///
+    /// # Panics
+    /// - If the `variant` is not a BC variant (ETC2 and EAC blocks can only be decoded on the CPU)
+    /// - If the source `buffer` is not a storage buffer
+    /// - If `offset` is not a multiple of 4
+    /// - If the source `buffer` is too small to hold the compressed blocks at the specified offset
+    #[allow(clippy::too_many_arguments)]
+    pub fn add_decompression_task(
+        &mut self,
+        variant: CompressionVariant,
+        buffer: &Buffer,
+        offset: Option<u32>,
+        width: u32,
+        height: u32,
+        texture_view: &TextureView,
+        output_format: OutputTextureFormat,
+        fill: FillValues,
+    ) {
+        let offset = offset.unwrap_or(0);
+
+        assert!(
+            self.pipelines.contains_key(&(variant, output_format)),
+            "GPU decompression is not supported for variant {variant:?}"
+        );
+        assert!(
+            buffer.usage().contains(BufferUsages::STORAGE),
+            "buffer needs to be a storage buffer"
+        );
+        assert_eq!(offset % 4, 0, "offset needs to be a multiple of 4");
+
+        let required_size = variant.blocks_byte_size(width, height);
+        let total_size = offset as usize + required_size;
+
+        assert!(
+            buffer.size() as usize >= total_size,
+            "buffer size ({}) is too small to hold compressed blocks at offset {}. Required size: {}",
+            buffer.size(),
+            offset,
+            total_size
+        );
+
+        self.task.push(Task {
+            variant,
+            output_format,
+            width,
+            height,
+            uniform_offset: 0,
+            buffer_offset: offset,
+            fill,
+            buffer: buffer.clone(),
+            texture_view: texture_view.clone(),
+        });
+    }
+
+    /// The bind groups are created on dispatch, because the uniforms buffer is recreated when
+    /// more tasks are queued than it can hold.
+    fn create_bind_group(&self, task: &Task) -> BindGroup {
+        let bind_group_layout = self
+            .bind_group_layouts
+            .get(&task.output_format)
+            .expect("Can't find bind group layout for output format");
+
+        self.device.create_bind_group(&BindGroupDescriptor {
+            label: Some("bind group"),
+            layout: bind_group_layout,
+            entries: &[
+                BindGroupEntry {
+                    binding: 0,
+                    resource: task.buffer.as_entire_binding(),
+                },
+                BindGroupEntry {
+                    binding: task.output_format.binding(),
+                    resource: BindingResource::TextureView(&task.texture_view),
+                },
+                BindGroupEntry {
+                    binding: 2,
+                    resource: BindingResource::Buffer(BufferBinding {
+                        buffer: &self.uniforms_buffer,
+                        offset: 0,
+                        size: Some(NonZeroU64::new(self.uniforms_aligned_size as u64).unwrap()),
+                    }),
+                },
+            ],
+        })
+    }
+
+    fn update_buffer_sizes(&mut self) {
+        let total_uniforms_size = self.uniforms_aligned_size * self.task.len();
+        if total_uniforms_size > self.uniforms_buffer.size() as usize {
+            self.uniforms_buffer = self.device.create_buffer(&BufferDescriptor {
+                label: Some("uniforms buffer"),
+                size: total_uniforms_size as u64,
+                usage: BufferUsages::COPY_DST | BufferUsages::UNIFORM,
+                mapped_at_creation: false,
+            });
+        }
+    }
+
+    fn upload(&mut self) {
+        self.scratch_buffer.clear();
+        for (index, task) in self.task.iter_mut().enumerate() {
+            let offset = index * self.uniforms_aligned_size;
+            task.uniform_offset = offset as u32;
+
+            let uniforms = Uniforms {
+                width: task.width,
+                height: task.height,
+                blocks_offset: task.buffer_offset / 4,
+                padding: 0,
+                fill: [
+                    task.fill.red,
+                    task.fill.green,
+                    task.fill.blue,
+                    task.fill.alpha,
+                ],
+            };
+
+            self.scratch_buffer
+                .resize(offset + self.uniforms_aligned_size, 0);
+            self.scratch_buffer[offset..offset + size_of::<Uniforms>()]
+                .copy_from_slice(cast_slice(&[uniforms]));
+        }
+        if !self.scratch_buffer.is_empty() {
+            if let Some(mut data) = self.queue.write_buffer_with(
+                &self.uniforms_buffer,
+                0,
+                NonZeroU64::new(self.scratch_buffer.len() as u64).unwrap(),
+            ) {
+                data.copy_from_slice(&self.scratch_buffer);
+            }
+        }
+    }
+
+    /// Will upload all dispatch data and then dispatches all decompression tasks to the GPU.
+    ///
+    /// # Arguments
+    /// * `pass` - The compute pass to record commands into
+    pub fn decompress(&mut self, pass: &mut ComputePass) {
+        self.update_buffer_sizes();
+        self.upload();
+
+        let bind_groups: Vec<BindGroup> = self
+            .task
+            .iter()
+            .map(|task| self.create_bind_group(task))
+            .collect();
+
+        for (task, bind_group) in self.task.drain(..).zip(bind_groups) {
+            let pipeline = self
+                .pipelines
+                .get(&(task.variant, task.output_format))
+                .expect("can't find pipeline for variant");
+
+            pass.set_pipeline(pipeline);
+            pass.set_bind_group(0, &bind_group, &[task.uniform_offset]);
+
+            let block_width = task.width.div_ceil(4);
+            let block_height = task.height.div_ceil(4);
+
+            let workgroup_width = block_width.div_ceil(8);
+            let workgroup_height = block_height.div_ceil(8);
+
+            pass.dispatch_workgroups(workgroup_width, workgroup_height, 1);
+        }
+    }
+}
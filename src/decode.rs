@@ -1,40 +1,242 @@
 //! CPU based decoding.
 
+#[cfg(feature = "astc")]
+mod astc;
 mod block;
+#[cfg(feature = "etc2")]
+mod etc;
+#[cfg(any(feature = "bc6h", feature = "bc7"))]
+mod inspect;
+#[cfg(feature = "bc6h")]
+mod preview;
+mod validate;
 
+#[cfg(feature = "astc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "astc")))]
+pub use self::astc::decode_block_astc;
 #[cfg(feature = "bc7")]
 #[cfg_attr(docsrs, doc(cfg(feature = "bc7")))]
 pub use self::block::decode_block_bc7;
-pub use self::block::{
-    decode_block_bc1, decode_block_bc2, decode_block_bc3, decode_block_bc4, decode_block_bc5,
-};
 #[cfg(feature = "bc6h")]
 #[cfg_attr(docsrs, doc(cfg(feature = "bc6h")))]
 pub use self::block::{decode_block_bc6h, decode_block_bc6h_float};
+#[cfg(feature = "etc2")]
+#[cfg_attr(docsrs, doc(cfg(feature = "etc2")))]
+pub use self::etc::{
+    decode_block_eac_r11, decode_block_eac_rg11, decode_block_etc2_rgb8, decode_block_etc2_rgb8a1,
+    decode_block_etc2_rgba8,
+};
+#[cfg(feature = "bc6h")]
+#[cfg_attr(docsrs, doc(cfg(feature = "bc6h")))]
+pub use self::inspect::{inspect_block_bc6h, BC6HBlockInfo, BC6HModeHistogram};
+#[cfg(feature = "bc7")]
+#[cfg_attr(docsrs, doc(cfg(feature = "bc7")))]
+pub use self::inspect::{inspect_block_bc7, BC7BlockInfo, BC7ModeHistogram};
+#[cfg(feature = "bc6h")]
+#[cfg_attr(docsrs, doc(cfg(feature = "bc6h")))]
+pub use self::preview::{decompress_bc6h_preview, PreviewOptions, ToneMapping};
+pub use self::{
+    block::{
+        decode_block_bc1, decode_block_bc2, decode_block_bc3, decode_block_bc4, decode_block_bc5,
+    },
+    validate::{
+        decompress_blocks_as_rgba8_validated, validate_blocks, InvalidBlock, InvalidBlockReason,
+    },
+};
+#[cfg(feature = "astc")]
+use crate::ASTCBlockSize;
 #[cfg(feature = "bc6h")]
 use crate::BC6HSettings;
 #[cfg(feature = "bc7")]
 use crate::BC7Settings;
 use crate::CompressionVariant;
 
-/// Trait to decode a BC variant into RGBA8 data.
-trait BlockRgba8Decoder {
-    fn decode_block_rgba8(compressed: &[u8], decompressed: &mut [u8], pitch: usize);
-    fn block_byte_size() -> u32;
+/// Values written into the channels, that a compression variant doesn't store.
+///
+/// For example BC4 only stores the red channel, so the green, blue and alpha channels of the
+/// output are filled with these values. The values are normalized and are converted into the
+/// output format (`1.0` becomes `255` for 8-bit outputs).
+///
+/// The default fills missing color channels with zero and missing alpha with one, so that the
+/// decoded images are opaque.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct FillValues {
+    /// Value of the red channel.
+    pub red: f32,
+    /// Value of the green channel.
+    pub green: f32,
+    /// Value of the blue channel.
+    pub blue: f32,
+    /// Value of the alpha channel.
+    pub alpha: f32,
 }
 
-/// Trait to decode a BC variant into RGBA16F data.
-#[cfg(feature = "bc6h")]
-trait BlockRgba16fDecoder {
-    fn decode_block_rgba16f(compressed: &[u8], decompressed: &mut [half::f16], pitch: usize);
-    fn block_byte_size() -> u32;
+impl Default for FillValues {
+    fn default() -> Self {
+        Self {
+            red: 0.0,
+            green: 0.0,
+            blue: 0.0,
+            alpha: 1.0,
+        }
+    }
+}
+
+impl FillValues {
+    fn to_output<O: OutputChannel>(self) -> [O; 4] {
+        [
+            O::from_fill(self.red),
+            O::from_fill(self.green),
+            O::from_fill(self.blue),
+            O::from_fill(self.alpha),
+        ]
+    }
+}
+
+/// Where a decoded image is written into a larger output image.
+///
+/// This allows decoding compressed tiles directly into an atlas, a sub-region of a larger canvas
+/// or a mapped staging buffer with padded rows.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct OutputRegion {
+    /// Horizontal offset of the decoded image inside the output image in pixels.
+    pub x: u32,
+    /// Vertical offset of the decoded image inside the output image in pixels.
+    pub y: u32,
+    /// Number of bytes between the starts of two rows of the output image.
+    pub row_pitch: usize,
+}
+
+impl OutputRegion {
+    /// A region at the origin of an output image with the given row pitch in bytes.
+    pub const fn with_row_pitch(row_pitch: usize) -> Self {
+        Self {
+            x: 0,
+            y: 0,
+            row_pitch,
+        }
+    }
+}
+
+/// A single channel of a decoded block.
+trait Texel: Copy + Default {
+    fn convert<O: OutputChannel>(self) -> O;
+}
+
+impl Texel for u8 {
+    #[inline(always)]
+    fn convert<O: OutputChannel>(self) -> O {
+        O::from_unorm8(self)
+    }
+}
+
+impl Texel for u16 {
+    #[inline(always)]
+    fn convert<O: OutputChannel>(self) -> O {
+        O::from_unorm16(self)
+    }
+}
+
+impl Texel for f32 {
+    #[inline(always)]
+    fn convert<O: OutputChannel>(self) -> O {
+        O::from_float(self)
+    }
+}
+
+/// A single channel of the decoded output image.
+trait OutputChannel: Copy {
+    fn from_unorm8(value: u8) -> Self;
+    fn from_unorm16(value: u16) -> Self;
+    fn from_float(value: f32) -> Self;
+    fn from_fill(value: f32) -> Self;
+}
+
+impl OutputChannel for u8 {
+    #[inline(always)]
+    fn from_unorm8(value: u8) -> Self {
+        value
+    }
+
+    #[inline(always)]
+    fn from_unorm16(value: u16) -> Self {
+        ((value as u32 * 255 + 32767) / 65535) as u8
+    }
+
+    /// HDR values are written as sRGB encoded values.
+    #[inline(always)]
+    fn from_float(value: f32) -> Self {
+        linear_to_srgb(value)
+    }
+
+    #[inline(always)]
+    fn from_fill(value: f32) -> Self {
+        (value.clamp(0.0, 1.0) * 255.0).round() as u8
+    }
 }
 
-/// Trait to decode a BC variant into RGBA32F data.
 #[cfg(feature = "bc6h")]
-trait BlockRgba32fDecoder {
-    fn decode_block_rgba32f(compressed: &[u8], decompressed: &mut [f32], pitch: usize);
-    fn block_byte_size() -> u32;
+impl OutputChannel for half::f16 {
+    #[inline(always)]
+    fn from_unorm8(value: u8) -> Self {
+        half::f16::from_f32(value as f32 / 255.0)
+    }
+
+    #[inline(always)]
+    fn from_unorm16(value: u16) -> Self {
+        half::f16::from_f32(value as f32 / 65535.0)
+    }
+
+    #[inline(always)]
+    fn from_float(value: f32) -> Self {
+        half::f16::from_f32(value)
+    }
+
+    #[inline(always)]
+    fn from_fill(value: f32) -> Self {
+        half::f16::from_f32(value)
+    }
+}
+
+impl OutputChannel for f32 {
+    #[inline(always)]
+    fn from_unorm8(value: u8) -> Self {
+        value as f32 / 255.0
+    }
+
+    #[inline(always)]
+    fn from_unorm16(value: u16) -> Self {
+        value as f32 / 65535.0
+    }
+
+    #[inline(always)]
+    fn from_float(value: f32) -> Self {
+        value
+    }
+
+    #[inline(always)]
+    fn from_fill(value: f32) -> Self {
+        value
+    }
+}
+
+/// Trait to decode a block into interleaved RGBA texels with a pitch of four texels per pixel
+/// of the block width.
+trait BlockDecoder {
+    /// The native precision of the decoded texels.
+    type Texel: Texel;
+
+    /// The number of channels the variant stores. Channels after these are filled.
+    const CHANNELS: usize;
+
+    fn decode_block(&self, compressed: &[u8], decompressed: &mut [Self::Texel]);
+
+    fn block_byte_size(&self) -> u32;
+
+    /// Width and height of a block in pixels.
+    fn footprint(&self) -> (usize, usize) {
+        (4, 4)
+    }
 }
 
 struct BC1Decoder;
@@ -46,278 +248,355 @@ struct BC5Decoder;
 struct BC6HDecoder;
 #[cfg(feature = "bc7")]
 struct BC7Decoder;
+#[cfg(feature = "etc2")]
+struct ETC2RGB8Decoder;
+#[cfg(feature = "etc2")]
+struct ETC2RGB8A1Decoder;
+#[cfg(feature = "etc2")]
+struct ETC2RGBA8Decoder;
+#[cfg(feature = "etc2")]
+struct EACR11Decoder;
+#[cfg(feature = "etc2")]
+struct EACRG11Decoder;
+#[cfg(feature = "astc")]
+struct ASTCDecoder(ASTCBlockSize);
+
+impl BlockDecoder for BC1Decoder {
+    type Texel = u8;
+    const CHANNELS: usize = 4;
 
-impl BlockRgba8Decoder for BC1Decoder {
     #[inline(always)]
-    fn decode_block_rgba8(compressed: &[u8], decompressed: &mut [u8], pitch: usize) {
-        decode_block_bc1(compressed, decompressed, pitch)
+    fn decode_block(&self, compressed: &[u8], decompressed: &mut [u8]) {
+        decode_block_bc1(compressed, decompressed, 16)
     }
 
-    fn block_byte_size() -> u32 {
+    fn block_byte_size(&self) -> u32 {
         CompressionVariant::BC1.block_byte_size()
     }
 }
 
-impl BlockRgba8Decoder for BC2Decoder {
+impl BlockDecoder for BC2Decoder {
+    type Texel = u8;
+    const CHANNELS: usize = 4;
+
     #[inline(always)]
-    fn decode_block_rgba8(compressed: &[u8], decompressed: &mut [u8], pitch: usize) {
-        decode_block_bc2(compressed, decompressed, pitch)
+    fn decode_block(&self, compressed: &[u8], decompressed: &mut [u8]) {
+        decode_block_bc2(compressed, decompressed, 16)
     }
 
-    fn block_byte_size() -> u32 {
+    fn block_byte_size(&self) -> u32 {
         CompressionVariant::BC2.block_byte_size()
     }
 }
 
-impl BlockRgba8Decoder for BC3Decoder {
+impl BlockDecoder for BC3Decoder {
+    type Texel = u8;
+    const CHANNELS: usize = 4;
+
     #[inline(always)]
-    fn decode_block_rgba8(compressed: &[u8], decompressed: &mut [u8], pitch: usize) {
-        decode_block_bc3(compressed, decompressed, pitch)
+    fn decode_block(&self, compressed: &[u8], decompressed: &mut [u8]) {
+        decode_block_bc3(compressed, decompressed, 16)
     }
 
-    fn block_byte_size() -> u32 {
+    fn block_byte_size(&self) -> u32 {
         CompressionVariant::BC3.block_byte_size()
     }
 }
 
-impl BlockRgba8Decoder for BC4Decoder {
+impl BlockDecoder for BC4Decoder {
+    type Texel = u8;
+    const CHANNELS: usize = 1;
+
     #[inline(always)]
-    fn decode_block_rgba8(compressed: &[u8], decompressed: &mut [u8], pitch: usize) {
-        const PITCH: usize = 4;
-        let mut buffer = [0u8; 16];
-        decode_block_bc4(compressed, &mut buffer, 4);
+    fn decode_block(&self, compressed: &[u8], decompressed: &mut [u8]) {
+        // Decoding with a pixel size of one and a pitch of 16 bytes writes the red values into
+        // the first quarter of each row, which we then spread out into RGBA texels.
+        decode_block_bc4(compressed, decompressed, 16);
 
-        // Convert R8 to RGBA8
         for y in 0..4 {
-            for x in 0..4 {
-                let out_pos = y * pitch + x * 4;
-                let in_pos = y * PITCH + x;
-
-                decompressed[out_pos] = buffer[in_pos];
-                decompressed[out_pos + 1] = 0;
-                decompressed[out_pos + 2] = 0;
-                decompressed[out_pos + 3] = 0;
+            for x in (0..4).rev() {
+                decompressed[y * 16 + x * 4] = decompressed[y * 16 + x];
             }
         }
     }
 
-    fn block_byte_size() -> u32 {
+    fn block_byte_size(&self) -> u32 {
         CompressionVariant::BC4.block_byte_size()
     }
 }
 
-impl BlockRgba8Decoder for BC5Decoder {
+impl BlockDecoder for BC5Decoder {
+    type Texel = u8;
+    const CHANNELS: usize = 2;
+
     #[inline(always)]
-    fn decode_block_rgba8(compressed: &[u8], decompressed: &mut [u8], pitch: usize) {
-        const PITCH: usize = 8;
-        let mut buffer = [0u8; 32];
-        decode_block_bc5(compressed, &mut buffer, PITCH);
+    fn decode_block(&self, compressed: &[u8], decompressed: &mut [u8]) {
+        // Same as BC4, but with two channels per texel.
+        decode_block_bc5(compressed, decompressed, 16);
 
-        // Convert RG8 to RGBA8
         for y in 0..4 {
-            for x in 0..4 {
-                let out_pos = y * pitch + x * 4;
-                let in_pos = y * PITCH + x * 2;
-
-                decompressed[out_pos] = buffer[in_pos];
-                decompressed[out_pos + 1] = buffer[in_pos + 1];
-                decompressed[out_pos + 2] = 0;
-                decompressed[out_pos + 3] = 0;
+            for x in (0..4).rev() {
+                decompressed[y * 16 + x * 4] = decompressed[y * 16 + x * 2];
+                decompressed[y * 16 + x * 4 + 1] = decompressed[y * 16 + x * 2 + 1];
             }
         }
     }
 
-    fn block_byte_size() -> u32 {
+    fn block_byte_size(&self) -> u32 {
         CompressionVariant::BC5.block_byte_size()
     }
 }
 
 #[cfg(feature = "bc6h")]
-fn linear_to_srgb(linear: f32) -> u8 {
-    let v = if linear <= 0.0031308 {
-        linear * 12.92
-    } else {
-        1.055 * linear.powf(1.0 / 2.4) - 0.055
-    };
-
-    (v.clamp(0.0, 1.0) * 255.0).round() as u8
-}
+impl BlockDecoder for BC6HDecoder {
+    type Texel = f32;
+    const CHANNELS: usize = 3;
 
-#[cfg(feature = "bc6h")]
-impl BlockRgba8Decoder for BC6HDecoder {
     #[inline(always)]
-    fn decode_block_rgba8(compressed: &[u8], decompressed: &mut [u8], pitch: usize) {
-        const PITCH: usize = 12;
-        let mut buffer = [0.0_f32; 48];
-        decode_block_bc6h_float(compressed, &mut buffer, PITCH, false);
+    fn decode_block(&self, compressed: &[u8], decompressed: &mut [f32]) {
+        // Decoding RGB with a pitch of 16 floats leaves space for the expansion to RGBA.
+        decode_block_bc6h_float(compressed, decompressed, 16, false);
 
-        // Convert RGB16F to RGBA8
+        // The channels are moved back to front, since the RGB and RGBA positions overlap.
         for y in 0..4 {
-            for x in 0..4 {
-                let out_pos = y * pitch + x * 4;
-                let in_pos = y * PITCH + x * 3;
-
-                decompressed[out_pos] = linear_to_srgb(buffer[in_pos]) as _;
-                decompressed[out_pos + 1] = linear_to_srgb(buffer[in_pos + 1]) as _;
-                decompressed[out_pos + 2] = linear_to_srgb(buffer[in_pos + 2]) as _;
-                decompressed[out_pos + 3] = 0;
+            for x in (0..4).rev() {
+                for channel in (0..3).rev() {
+                    decompressed[y * 16 + x * 4 + channel] = decompressed[y * 16 + x * 3 + channel];
+                }
             }
         }
     }
 
-    fn block_byte_size() -> u32 {
+    fn block_byte_size(&self) -> u32 {
         CompressionVariant::BC6H(BC6HSettings::basic()).block_byte_size()
     }
 }
 
 #[cfg(feature = "bc7")]
-impl BlockRgba8Decoder for BC7Decoder {
+impl BlockDecoder for BC7Decoder {
+    type Texel = u8;
+    const CHANNELS: usize = 4;
+
     #[inline(always)]
-    fn decode_block_rgba8(compressed: &[u8], decompressed: &mut [u8], pitch: usize) {
-        decode_block_bc7(compressed, decompressed, pitch)
+    fn decode_block(&self, compressed: &[u8], decompressed: &mut [u8]) {
+        decode_block_bc7(compressed, decompressed, 16)
     }
 
-    fn block_byte_size() -> u32 {
+    fn block_byte_size(&self) -> u32 {
         CompressionVariant::BC7(BC7Settings::alpha_basic()).block_byte_size()
     }
 }
 
-fn decompress_rgba8<D: BlockRgba8Decoder>(
-    width: u32,
-    height: u32,
-    blocks_data: &[u8],
-    rgba_data: &mut [u8],
-) {
-    let blocks_x = (width + 3) / 4;
-    let blocks_y = (height + 3) / 4;
-    let block_byte_size = D::block_byte_size() as usize;
-    let output_row_pitch = width as usize * 4; // Always RGBA
+#[cfg(feature = "etc2")]
+impl BlockDecoder for ETC2RGB8Decoder {
+    type Texel = u8;
+    const CHANNELS: usize = 3;
 
-    for by in 0..blocks_y {
-        for bx in 0..blocks_x {
-            let block_index = (by * blocks_x + bx) as usize;
-            let block_offset = block_index * block_byte_size;
+    #[inline(always)]
+    fn decode_block(&self, compressed: &[u8], decompressed: &mut [u8]) {
+        decode_block_etc2_rgb8(compressed, decompressed, 16)
+    }
 
-            if block_offset + block_byte_size > blocks_data.len() {
-                break;
-            }
+    fn block_byte_size(&self) -> u32 {
+        CompressionVariant::ETC2RGB8.block_byte_size()
+    }
+}
 
-            let output_offset = (by * 4 * output_row_pitch as u32 + bx * 16) as usize;
+#[cfg(feature = "etc2")]
+impl BlockDecoder for ETC2RGB8A1Decoder {
+    type Texel = u8;
+    const CHANNELS: usize = 4;
 
-            if output_offset < rgba_data.len() {
-                D::decode_block_rgba8(
-                    &blocks_data[block_offset..block_offset + block_byte_size],
-                    &mut rgba_data[output_offset..],
-                    output_row_pitch,
-                );
-            }
-        }
+    #[inline(always)]
+    fn decode_block(&self, compressed: &[u8], decompressed: &mut [u8]) {
+        decode_block_etc2_rgb8a1(compressed, decompressed, 16)
+    }
+
+    fn block_byte_size(&self) -> u32 {
+        CompressionVariant::ETC2RGB8A1.block_byte_size()
     }
 }
 
-#[cfg(feature = "bc6h")]
-impl BlockRgba16fDecoder for BC6HDecoder {
+#[cfg(feature = "etc2")]
+impl BlockDecoder for ETC2RGBA8Decoder {
+    type Texel = u8;
+    const CHANNELS: usize = 4;
+
     #[inline(always)]
-    fn decode_block_rgba16f(compressed: &[u8], decompressed: &mut [half::f16], pitch: usize) {
-        decode_block_bc6h(compressed, decompressed, pitch, false);
+    fn decode_block(&self, compressed: &[u8], decompressed: &mut [u8]) {
+        decode_block_etc2_rgba8(compressed, decompressed, 16)
     }
 
-    fn block_byte_size() -> u32 {
-        CompressionVariant::BC6H(BC6HSettings::basic()).block_byte_size()
+    fn block_byte_size(&self) -> u32 {
+        CompressionVariant::ETC2RGBA8.block_byte_size()
     }
 }
 
-#[cfg(feature = "bc6h")]
-fn decompress_rgba16f<D: BlockRgba16fDecoder>(
-    width: u32,
-    height: u32,
-    blocks_data: &[u8],
-    rgba_data: &mut [half::f16],
-) {
-    let blocks_x = (width + 3) / 4;
-    let blocks_y = (height + 3) / 4;
-    let block_byte_size = D::block_byte_size() as usize;
-    let output_row_pitch = width as usize * 4; // Always RGBA16f
+#[cfg(feature = "etc2")]
+impl BlockDecoder for EACR11Decoder {
+    type Texel = u16;
+    const CHANNELS: usize = 1;
 
-    for by in 0..blocks_y {
-        for bx in 0..blocks_x {
-            let block_index = (by * blocks_x + bx) as usize;
-            let block_offset = block_index * block_byte_size;
+    #[inline(always)]
+    fn decode_block(&self, compressed: &[u8], decompressed: &mut [u16]) {
+        // Same layout trick as BC4.
+        decode_block_eac_r11(compressed, decompressed, 16);
 
-            if block_offset + block_byte_size > blocks_data.len() {
-                break;
+        for y in 0..4 {
+            for x in (0..4).rev() {
+                decompressed[y * 16 + x * 4] = decompressed[y * 16 + x];
             }
+        }
+    }
 
-            let output_offset = (by * 4 * output_row_pitch as u32 + bx * 16) as usize;
+    fn block_byte_size(&self) -> u32 {
+        CompressionVariant::EACR11.block_byte_size()
+    }
+}
+
+#[cfg(feature = "etc2")]
+impl BlockDecoder for EACRG11Decoder {
+    type Texel = u16;
+    const CHANNELS: usize = 2;
 
-            if output_offset < rgba_data.len() {
-                D::decode_block_rgba16f(
-                    &blocks_data[block_offset..block_offset + block_byte_size],
-                    &mut rgba_data[output_offset..],
-                    output_row_pitch,
-                );
+    #[inline(always)]
+    fn decode_block(&self, compressed: &[u8], decompressed: &mut [u16]) {
+        // Same layout trick as BC5.
+        decode_block_eac_rg11(compressed, decompressed, 16);
+
+        for y in 0..4 {
+            for x in (0..4).rev() {
+                decompressed[y * 16 + x * 4] = decompressed[y * 16 + x * 2];
+                decompressed[y * 16 + x * 4 + 1] = decompressed[y * 16 + x * 2 + 1];
             }
         }
     }
+
+    fn block_byte_size(&self) -> u32 {
+        CompressionVariant::EACRG11.block_byte_size()
+    }
 }
 
-#[cfg(feature = "bc6h")]
-impl BlockRgba32fDecoder for BC6HDecoder {
+#[cfg(feature = "astc")]
+impl BlockDecoder for ASTCDecoder {
+    type Texel = u8;
+    const CHANNELS: usize = 4;
+
     #[inline(always)]
-    fn decode_block_rgba32f(compressed: &[u8], decompressed: &mut [f32], pitch: usize) {
-        decode_block_bc6h_float(compressed, decompressed, pitch, false);
+    fn decode_block(&self, compressed: &[u8], decompressed: &mut [u8]) {
+        let pitch = self.0.dimensions().0 as usize * 4;
+        decode_block_astc(compressed, decompressed, pitch, self.0)
     }
 
-    fn block_byte_size() -> u32 {
-        CompressionVariant::BC6H(BC6HSettings::basic()).block_byte_size()
+    fn block_byte_size(&self) -> u32 {
+        CompressionVariant::ASTC(self.0).block_byte_size()
+    }
+
+    fn footprint(&self) -> (usize, usize) {
+        let (width, height) = self.0.dimensions();
+        (width as usize, height as usize)
     }
 }
 
-#[cfg(feature = "bc6h")]
-fn decompress_rgba32f<D: BlockRgba32fDecoder>(
+fn linear_to_srgb(linear: f32) -> u8 {
+    let v = if linear <= 0.0031308 {
+        linear * 12.92
+    } else {
+        1.055 * linear.powf(1.0 / 2.4) - 0.055
+    };
+
+    (v.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+fn decompress<D: BlockDecoder, O: OutputChannel, const CHANNELS: usize>(
+    decoder: D,
     width: u32,
     height: u32,
     blocks_data: &[u8],
-    rgba_data: &mut [f32],
+    output_data: &mut [O],
+    region: OutputRegion,
+    fill: [O; 4],
 ) {
-    let blocks_x = (width + 3) / 4;
-    let blocks_y = (height + 3) / 4;
-    let block_byte_size = D::block_byte_size() as usize;
-    let output_row_pitch = width as usize * 4; // Always RGBA32f
+    let width = width as usize;
+    let height = height as usize;
+    let row_pitch = region.row_pitch / size_of::<O>();
+    let (region_x, region_y) = (region.x as usize, region.y as usize);
+    let (footprint_width, footprint_height) = decoder.footprint();
+    let blocks_x = width.div_ceil(footprint_width);
+    let blocks_y = height.div_ceil(footprint_height);
+    let block_byte_size = decoder.block_byte_size() as usize;
+    let pitch = footprint_width * 4;
+
+    // Big enough for the biggest ASTC footprint of 12x12 pixels.
+    let mut block = [D::Texel::default(); 12 * 12 * 4];
 
     for by in 0..blocks_y {
         for bx in 0..blocks_x {
-            let block_index = (by * blocks_x + bx) as usize;
-            let block_offset = block_index * block_byte_size;
+            let block_offset = (by * blocks_x + bx) * block_byte_size;
 
-            if block_offset + block_byte_size > blocks_data.len() {
-                break;
-            }
+            decoder.decode_block(
+                &blocks_data[block_offset..block_offset + block_byte_size],
+                &mut block,
+            );
+
+            let block_pixels_y = (height - by * footprint_height).min(footprint_height);
+            let block_pixels_x = (width - bx * footprint_width).min(footprint_width);
 
-            let output_offset = (by * 4 * output_row_pitch as u32 + bx * 16) as usize;
+            for y in 0..block_pixels_y {
+                for x in 0..block_pixels_x {
+                    let output_offset = (region_y + by * footprint_height + y) * row_pitch
+                        + (region_x + bx * footprint_width + x) * CHANNELS;
+                    let texel = &block[y * pitch + x * 4..][..4];
+                    let output = &mut output_data[output_offset..][..CHANNELS];
 
-            if output_offset < rgba_data.len() {
-                D::decode_block_rgba32f(
-                    &blocks_data[block_offset..block_offset + block_byte_size],
-                    &mut rgba_data[output_offset..],
-                    output_row_pitch,
-                );
+                    for (channel, value) in output.iter_mut().enumerate() {
+                        *value = if channel < D::CHANNELS {
+                            texel[channel].convert()
+                        } else {
+                            fill[channel]
+                        };
+                    }
+                }
             }
         }
     }
 }
 
-/// Helper function to easily decompress block data into RGBA8 data.
-///
-/// # Panics
-/// - The `blocks_data` has not the expected size (`variant.blocks_byte_size()`)
-/// - The `rgba_data` has not the expected size (`width * height * 4`)
-pub fn decompress_blocks_as_rgba8(
+fn decompress_blocks<O: OutputChannel, const CHANNELS: usize>(
     variant: CompressionVariant,
     width: u32,
     height: u32,
     blocks_data: &[u8],
-    rgba_data: &mut [u8],
+    output_data: &mut [O],
+    fill: FillValues,
+) {
+    let expected_output_size = width as usize * height as usize * CHANNELS;
+    assert_eq!(
+        output_data.len(),
+        expected_output_size,
+        "the output slice has not the expected size"
+    );
+
+    let region = OutputRegion::with_row_pitch(width as usize * CHANNELS * size_of::<O>());
+    decompress_blocks_into::<O, CHANNELS>(
+        variant,
+        width,
+        height,
+        blocks_data,
+        output_data,
+        region,
+        fill,
+    )
+}
+
+fn decompress_blocks_into<O: OutputChannel, const CHANNELS: usize>(
+    variant: CompressionVariant,
+    width: u32,
+    height: u32,
+    blocks_data: &[u8],
+    output_data: &mut [O],
+    region: OutputRegion,
+    fill: FillValues,
 ) {
     let expected_input_size = variant.blocks_byte_size(width, height);
     assert_eq!(
@@ -326,46 +605,275 @@ pub fn decompress_blocks_as_rgba8(
         "the input bitstream slice has not the expected size"
     );
 
-    let expected_output_size = width as usize * height as usize * 4;
+    let texel_size = CHANNELS * size_of::<O>();
     assert_eq!(
-        rgba_data.len(),
-        expected_output_size,
-        "the output slice has not the expected size"
+        region.row_pitch % size_of::<O>(),
+        0,
+        "the row pitch needs to be a multiple of the channel size"
+    );
+    let row_size = (region.x as usize + width as usize) * texel_size;
+    assert!(
+        region.row_pitch >= row_size,
+        "the row pitch ({}) is smaller than the rows of the region ({})",
+        region.row_pitch,
+        row_size
     );
+    if width > 0 && height > 0 {
+        let output_size = size_of_val(output_data);
+        let required_size = (region.y as usize + height as usize - 1) * region.row_pitch + row_size;
+        assert!(
+            output_size >= required_size,
+            "the output slice ({output_size} bytes) is too small for the region. Required size: {required_size}",
+        );
+    }
+
+    let fill = fill.to_output();
 
     match variant {
-        CompressionVariant::BC1 => {
-            decompress_rgba8::<BC1Decoder>(width, height, blocks_data, rgba_data)
-        }
-        CompressionVariant::BC2 => {
-            decompress_rgba8::<BC2Decoder>(width, height, blocks_data, rgba_data)
-        }
-        CompressionVariant::BC3 => {
-            decompress_rgba8::<BC3Decoder>(width, height, blocks_data, rgba_data)
-        }
-        CompressionVariant::BC4 => {
-            decompress_rgba8::<BC4Decoder>(width, height, blocks_data, rgba_data)
-        }
-        CompressionVariant::BC5 => {
-            decompress_rgba8::<BC5Decoder>(width, height, blocks_data, rgba_data)
-        }
+        CompressionVariant::BC1 => decompress::<BC1Decoder, O, CHANNELS>(
+            BC1Decoder,
+            width,
+            height,
+            blocks_data,
+            output_data,
+            region,
+            fill,
+        ),
+        CompressionVariant::BC2 => decompress::<BC2Decoder, O, CHANNELS>(
+            BC2Decoder,
+            width,
+            height,
+            blocks_data,
+            output_data,
+            region,
+            fill,
+        ),
+        CompressionVariant::BC3 => decompress::<BC3Decoder, O, CHANNELS>(
+            BC3Decoder,
+            width,
+            height,
+            blocks_data,
+            output_data,
+            region,
+            fill,
+        ),
+        CompressionVariant::BC4 => decompress::<BC4Decoder, O, CHANNELS>(
+            BC4Decoder,
+            width,
+            height,
+            blocks_data,
+            output_data,
+            region,
+            fill,
+        ),
+        CompressionVariant::BC5 => decompress::<BC5Decoder, O, CHANNELS>(
+            BC5Decoder,
+            width,
+            height,
+            blocks_data,
+            output_data,
+            region,
+            fill,
+        ),
         #[cfg(feature = "bc6h")]
-        CompressionVariant::BC6H(..) => {
-            decompress_rgba8::<BC6HDecoder>(width, height, blocks_data, rgba_data)
-        }
+        CompressionVariant::BC6H(..) => decompress::<BC6HDecoder, O, CHANNELS>(
+            BC6HDecoder,
+            width,
+            height,
+            blocks_data,
+            output_data,
+            region,
+            fill,
+        ),
         #[cfg(feature = "bc7")]
-        CompressionVariant::BC7(..) => {
-            decompress_rgba8::<BC7Decoder>(width, height, blocks_data, rgba_data)
-        }
+        CompressionVariant::BC7(..) => decompress::<BC7Decoder, O, CHANNELS>(
+            BC7Decoder,
+            width,
+            height,
+            blocks_data,
+            output_data,
+            region,
+            fill,
+        ),
+        #[cfg(feature = "etc2")]
+        CompressionVariant::ETC2RGB8 => decompress::<ETC2RGB8Decoder, O, CHANNELS>(
+            ETC2RGB8Decoder,
+            width,
+            height,
+            blocks_data,
+            output_data,
+            region,
+            fill,
+        ),
+        #[cfg(feature = "etc2")]
+        CompressionVariant::ETC2RGB8A1 => decompress::<ETC2RGB8A1Decoder, O, CHANNELS>(
+            ETC2RGB8A1Decoder,
+            width,
+            height,
+            blocks_data,
+            output_data,
+            region,
+            fill,
+        ),
+        #[cfg(feature = "etc2")]
+        CompressionVariant::ETC2RGBA8 => decompress::<ETC2RGBA8Decoder, O, CHANNELS>(
+            ETC2RGBA8Decoder,
+            width,
+            height,
+            blocks_data,
+            output_data,
+            region,
+            fill,
+        ),
+        #[cfg(feature = "etc2")]
+        CompressionVariant::EACR11 => decompress::<EACR11Decoder, O, CHANNELS>(
+            EACR11Decoder,
+            width,
+            height,
+            blocks_data,
+            output_data,
+            region,
+            fill,
+        ),
+        #[cfg(feature = "etc2")]
+        CompressionVariant::EACRG11 => decompress::<EACRG11Decoder, O, CHANNELS>(
+            EACRG11Decoder,
+            width,
+            height,
+            blocks_data,
+            output_data,
+            region,
+            fill,
+        ),
+        #[cfg(feature = "astc")]
+        CompressionVariant::ASTC(block_size) => decompress::<ASTCDecoder, O, CHANNELS>(
+            ASTCDecoder(block_size),
+            width,
+            height,
+            blocks_data,
+            output_data,
+            region,
+            fill,
+        ),
     }
 }
 
-/// Helper function to easily decompress block data into RGBA16F data. Only BCH6 is currently supported.
+/// Helper function to easily decompress block data into R8 data.
+///
+/// Only the red channel of the decoded image is written. BC6H values are converted into sRGB.
+///
+/// # Panics
+/// - The `blocks_data` has not the expected size (`variant.blocks_byte_size()`)
+/// - The `r_data` has not the expected size (`width * height`)
+pub fn decompress_blocks_as_r8(
+    variant: CompressionVariant,
+    width: u32,
+    height: u32,
+    blocks_data: &[u8],
+    r_data: &mut [u8],
+) {
+    decompress_blocks::<u8, 1>(
+        variant,
+        width,
+        height,
+        blocks_data,
+        r_data,
+        FillValues::default(),
+    )
+}
+
+/// Helper function to easily decompress block data into RG8 data.
+///
+/// A missing green channel (BC4) is filled with the default [`FillValues`].
+/// BC6H values are converted into sRGB.
+///
+/// # Panics
+/// - The `blocks_data` has not the expected size (`variant.blocks_byte_size()`)
+/// - The `rg_data` has not the expected size (`width * height * 2`)
+pub fn decompress_blocks_as_rg8(
+    variant: CompressionVariant,
+    width: u32,
+    height: u32,
+    blocks_data: &[u8],
+    rg_data: &mut [u8],
+) {
+    decompress_blocks_as_rg8_with_fill(
+        variant,
+        width,
+        height,
+        blocks_data,
+        rg_data,
+        FillValues::default(),
+    )
+}
+
+/// Helper function to easily decompress block data into RG8 data, with custom values for the
+/// missing channels.
+///
+/// # Panics
+/// - The `blocks_data` has not the expected size (`variant.blocks_byte_size()`)
+/// - The `rg_data` has not the expected size (`width * height * 2`)
+pub fn decompress_blocks_as_rg8_with_fill(
+    variant: CompressionVariant,
+    width: u32,
+    height: u32,
+    blocks_data: &[u8],
+    rg_data: &mut [u8],
+    fill: FillValues,
+) {
+    decompress_blocks::<u8, 2>(variant, width, height, blocks_data, rg_data, fill)
+}
+
+/// Helper function to easily decompress block data into RGBA8 data.
+///
+/// Missing channels (for example green, blue and alpha of BC4) are filled with the default
+/// [`FillValues`], which results in opaque images. BC6H values are converted into sRGB.
+///
+/// # Panics
+/// - The `blocks_data` has not the expected size (`variant.blocks_byte_size()`)
+/// - The `rgba_data` has not the expected size (`width * height * 4`)
+pub fn decompress_blocks_as_rgba8(
+    variant: CompressionVariant,
+    width: u32,
+    height: u32,
+    blocks_data: &[u8],
+    rgba_data: &mut [u8],
+) {
+    decompress_blocks_as_rgba8_with_fill(
+        variant,
+        width,
+        height,
+        blocks_data,
+        rgba_data,
+        FillValues::default(),
+    )
+}
+
+/// Helper function to easily decompress block data into RGBA8 data, with custom values for the
+/// missing channels.
+///
+/// # Panics
+/// - The `blocks_data` has not the expected size (`variant.blocks_byte_size()`)
+/// - The `rgba_data` has not the expected size (`width * height * 4`)
+pub fn decompress_blocks_as_rgba8_with_fill(
+    variant: CompressionVariant,
+    width: u32,
+    height: u32,
+    blocks_data: &[u8],
+    rgba_data: &mut [u8],
+    fill: FillValues,
+) {
+    decompress_blocks::<u8, 4>(variant, width, height, blocks_data, rgba_data, fill)
+}
+
+/// Helper function to easily decompress block data into RGBA16F data.
+///
+/// LDR variants are written as normalized values in the range of `0.0` to `1.0`. Missing channels
+/// are filled with the default [`FillValues`].
 ///
 /// # Panics
 /// - The `blocks_data` has not the expected size (`variant.blocks_byte_size()`)
 /// - The `rgba_data` has not the expected size (`width * height * 4`)
-/// - If `variant` is any other value than BC6H.
 #[cfg(feature = "bc6h")]
 #[cfg_attr(docsrs, doc(cfg(feature = "bc6h")))]
 pub fn decompress_blocks_as_rgba16f(
@@ -375,66 +883,365 @@ pub fn decompress_blocks_as_rgba16f(
     blocks_data: &[u8],
     rgba_data: &mut [half::f16],
 ) {
-    let expected_input_size = variant.blocks_byte_size(width, height);
-
-    assert_eq!(
-        blocks_data.len(),
-        expected_input_size,
-        "the input bitstream slice has not the expected size"
-    );
+    decompress_blocks_as_rgba16f_with_fill(
+        variant,
+        width,
+        height,
+        blocks_data,
+        rgba_data,
+        FillValues::default(),
+    )
+}
 
-    let expected_output_size = width as usize * height as usize * 4;
-    assert_eq!(
-        rgba_data.len(),
-        expected_output_size,
-        "the output slice has not the expected size"
-    );
+/// Helper function to easily decompress block data into RGBA16F data, with custom values for the
+/// missing channels.
+///
+/// # Panics
+/// - The `blocks_data` has not the expected size (`variant.blocks_byte_size()`)
+/// - The `rgba_data` has not the expected size (`width * height * 4`)
+#[cfg(feature = "bc6h")]
+#[cfg_attr(docsrs, doc(cfg(feature = "bc6h")))]
+pub fn decompress_blocks_as_rgba16f_with_fill(
+    variant: CompressionVariant,
+    width: u32,
+    height: u32,
+    blocks_data: &[u8],
+    rgba_data: &mut [half::f16],
+    fill: FillValues,
+) {
+    decompress_blocks::<half::f16, 4>(variant, width, height, blocks_data, rgba_data, fill)
+}
 
-    match variant {
-        CompressionVariant::BC6H(..) => {
-            decompress_rgba16f::<BC6HDecoder>(width, height, blocks_data, rgba_data)
-        }
-        _ => {
-            panic!("unsupported compression variant");
-        }
-    }
+/// Helper function to easily decompress block data into RGBA32F data.
+///
+/// LDR variants are written as normalized values in the range of `0.0` to `1.0`. Missing channels
+/// are filled with the default [`FillValues`].
+///
+/// # Panics
+/// - The `blocks_data` has not the expected size (`variant.blocks_byte_size()`)
+/// - The `rgba_data` has not the expected size (`width * height * 4`)
+pub fn decompress_blocks_as_rgba32f(
+    variant: CompressionVariant,
+    width: u32,
+    height: u32,
+    blocks_data: &[u8],
+    rgba_data: &mut [f32],
+) {
+    decompress_blocks_as_rgba32f_with_fill(
+        variant,
+        width,
+        height,
+        blocks_data,
+        rgba_data,
+        FillValues::default(),
+    )
 }
 
-/// Helper function to easily decompress block data into RGBA32F data. Only BCH6 is currently supported.
+/// Helper function to easily decompress block data into RGBA32F data, with custom values for the
+/// missing channels.
 ///
 /// # Panics
 /// - The `blocks_data` has not the expected size (`variant.blocks_byte_size()`)
 /// - The `rgba_data` has not the expected size (`width * height * 4`)
-/// - If `variant` is any other value than BC6H.
+pub fn decompress_blocks_as_rgba32f_with_fill(
+    variant: CompressionVariant,
+    width: u32,
+    height: u32,
+    blocks_data: &[u8],
+    rgba_data: &mut [f32],
+    fill: FillValues,
+) {
+    decompress_blocks::<f32, 4>(variant, width, height, blocks_data, rgba_data, fill)
+}
+
+/// Helper function to decompress block data into a region of a larger R8 image.
+///
+/// The decoded image is written at the offset of the `region` into `r_data`, whose rows are
+/// `region.row_pitch` bytes apart. Texels outside of the region are left untouched.
+///
+/// # Panics
+/// - The `blocks_data` has not the expected size (`variant.blocks_byte_size()`)
+/// - The `region.row_pitch` is no multiple of the channel size or shorter than `x + width` texels
+/// - The `r_data` is too small to contain the region
+pub fn decompress_blocks_as_r8_into(
+    variant: CompressionVariant,
+    width: u32,
+    height: u32,
+    blocks_data: &[u8],
+    r_data: &mut [u8],
+    region: OutputRegion,
+    fill: FillValues,
+) {
+    decompress_blocks_into::<u8, 1>(variant, width, height, blocks_data, r_data, region, fill)
+}
+
+/// Helper function to decompress block data into a region of a larger RG8 image.
+///
+/// The decoded image is written at the offset of the `region` into `rg_data`, whose rows are
+/// `region.row_pitch` bytes apart. Texels outside of the region are left untouched.
+///
+/// # Panics
+/// - The `blocks_data` has not the expected size (`variant.blocks_byte_size()`)
+/// - The `region.row_pitch` is no multiple of the channel size or shorter than `x + width` texels
+/// - The `rg_data` is too small to contain the region
+pub fn decompress_blocks_as_rg8_into(
+    variant: CompressionVariant,
+    width: u32,
+    height: u32,
+    blocks_data: &[u8],
+    rg_data: &mut [u8],
+    region: OutputRegion,
+    fill: FillValues,
+) {
+    decompress_blocks_into::<u8, 2>(variant, width, height, blocks_data, rg_data, region, fill)
+}
+
+/// Helper function to decompress block data into a region of a larger RGBA8 image.
+///
+/// The decoded image is written at the offset of the `region` into `rgba_data`, whose rows are
+/// `region.row_pitch` bytes apart. Texels outside of the region are left untouched.
+///
+/// # Panics
+/// - The `blocks_data` has not the expected size (`variant.blocks_byte_size()`)
+/// - The `region.row_pitch` is no multiple of the channel size or shorter than `x + width` texels
+/// - The `rgba_data` is too small to contain the region
+pub fn decompress_blocks_as_rgba8_into(
+    variant: CompressionVariant,
+    width: u32,
+    height: u32,
+    blocks_data: &[u8],
+    rgba_data: &mut [u8],
+    region: OutputRegion,
+    fill: FillValues,
+) {
+    decompress_blocks_into::<u8, 4>(variant, width, height, blocks_data, rgba_data, region, fill)
+}
+
+/// Helper function to decompress block data into a region of a larger RGBA16F image.
+///
+/// The decoded image is written at the offset of the `region` into `rgba_data`, whose rows are
+/// `region.row_pitch` bytes apart. Texels outside of the region are left untouched.
+///
+/// # Panics
+/// - The `blocks_data` has not the expected size (`variant.blocks_byte_size()`)
+/// - The `region.row_pitch` is no multiple of the channel size or shorter than `x + width` texels
+/// - The `rgba_data` is too small to contain the region
 #[cfg(feature = "bc6h")]
 #[cfg_attr(docsrs, doc(cfg(feature = "bc6h")))]
-pub fn decompress_blocks_as_rgba32f(
+pub fn decompress_blocks_as_rgba16f_into(
+    variant: CompressionVariant,
+    width: u32,
+    height: u32,
+    blocks_data: &[u8],
+    rgba_data: &mut [half::f16],
+    region: OutputRegion,
+    fill: FillValues,
+) {
+    decompress_blocks_into::<half::f16, 4>(
+        variant,
+        width,
+        height,
+        blocks_data,
+        rgba_data,
+        region,
+        fill,
+    )
+}
+
+/// Helper function to decompress block data into a region of a larger RGBA32F image.
+///
+/// The decoded image is written at the offset of the `region` into `rgba_data`, whose rows are
+/// `region.row_pitch` bytes apart. Texels outside of the region are left untouched.
+///
+/// # Panics
+/// - The `blocks_data` has not the expected size (`variant.blocks_byte_size()`)
+/// - The `region.row_pitch` is no multiple of the channel size or shorter than `x + width` texels
+/// - The `rgba_data` is too small to contain the region
+pub fn decompress_blocks_as_rgba32f_into(
     variant: CompressionVariant,
     width: u32,
     height: u32,
     blocks_data: &[u8],
     rgba_data: &mut [f32],
+    region: OutputRegion,
+    fill: FillValues,
 ) {
-    let expected_input_size = variant.blocks_byte_size(width, height);
-    assert_eq!(
-        blocks_data.len(),
-        expected_input_size,
-        "the input bitstream slice has not the expected size"
-    );
+    decompress_blocks_into::<f32, 4>(variant, width, height, blocks_data, rgba_data, region, fill)
+}
 
-    let expected_output_size = width as usize * height as usize * 4;
-    assert_eq!(
-        rgba_data.len(),
-        expected_output_size,
-        "the output slice has not the expected size"
-    );
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    match variant {
-        CompressionVariant::BC6H(..) => {
-            decompress_rgba32f::<BC6HDecoder>(width, height, blocks_data, rgba_data)
+    // BC4 block with both endpoints at 0x80, so every texel decodes to 0x80.
+    const BC4_BLOCK: [u8; 8] = [0x80, 0x80, 0, 0, 0, 0, 0, 0];
+
+    #[test]
+    fn test_bc4_rgba8_is_opaque() {
+        let mut rgba = [0u8; 64];
+        decompress_blocks_as_rgba8(CompressionVariant::BC4, 4, 4, &BC4_BLOCK, &mut rgba);
+
+        for texel in rgba.chunks_exact(4) {
+            assert_eq!(texel, [0x80, 0x00, 0x00, 0xFF]);
+        }
+    }
+
+    #[test]
+    fn test_bc4_rgba8_custom_fill() {
+        let fill = FillValues {
+            red: 0.0,
+            green: 1.0,
+            blue: 0.5,
+            alpha: 0.0,
+        };
+
+        let mut rgba = [0u8; 64];
+        decompress_blocks_as_rgba8_with_fill(
+            CompressionVariant::BC4,
+            4,
+            4,
+            &BC4_BLOCK,
+            &mut rgba,
+            fill,
+        );
+
+        for texel in rgba.chunks_exact(4) {
+            assert_eq!(texel, [0x80, 0xFF, 0x80, 0x00]);
+        }
+    }
+
+    #[test]
+    fn test_bc5_rg8() {
+        let blocks = [0x10, 0x10, 0, 0, 0, 0, 0, 0, 0x20, 0x20, 0, 0, 0, 0, 0, 0];
+
+        let mut rg = [0u8; 32];
+        decompress_blocks_as_rg8(CompressionVariant::BC5, 4, 4, &blocks, &mut rg);
+
+        for texel in rg.chunks_exact(2) {
+            assert_eq!(texel, [0x10, 0x20]);
         }
-        _ => {
-            panic!("unsupported compression variant");
+    }
+
+    #[test]
+    fn test_bc1_r8_partial_block() {
+        // Red BC1 block, decoded into an image that only covers a part of the block.
+        let blocks = [0x00, 0xF8, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00];
+
+        let mut r = [0u8; 6];
+        decompress_blocks_as_r8(CompressionVariant::BC1, 3, 2, &blocks, &mut r);
+
+        assert_eq!(r, [0xFF; 6]);
+    }
+
+    #[test]
+    fn test_bc4_rgba32f() {
+        let mut rgba = [0.0f32; 64];
+        decompress_blocks_as_rgba32f(CompressionVariant::BC4, 4, 4, &BC4_BLOCK, &mut rgba);
+
+        for texel in rgba.chunks_exact(4) {
+            assert_eq!(texel, [128.0 / 255.0, 0.0, 0.0, 1.0]);
         }
     }
+
+    #[cfg(feature = "bc6h")]
+    #[test]
+    fn test_bc6h_rgba32f_keeps_channels() {
+        // Mode 11 block with different endpoints per channel and varying indices.
+        let mut bits = 0b00011u128;
+        for (channel, (e0, e1)) in [(100u128, 900u128), (400, 200), (700, 50)]
+            .iter()
+            .enumerate()
+        {
+            bits |= e0 << (5 + channel * 10);
+            bits |= e1 << (35 + channel * 10);
+        }
+        bits |= 0x0123_4567_89AB_CDEF_u128 << 65;
+        let blocks = bits.to_le_bytes();
+
+        let mut expected = [0.0; 48];
+        decode_block_bc6h_float(&blocks, &mut expected, 12, false);
+
+        let mut rgba = [0.0f32; 64];
+        decompress_blocks_as_rgba32f(
+            CompressionVariant::BC6H(BC6HSettings::basic()),
+            4,
+            4,
+            &blocks,
+            &mut rgba,
+        );
+
+        for (texel, expected) in rgba.chunks_exact(4).zip(expected.chunks_exact(3)) {
+            assert_eq!(&texel[..3], expected);
+            assert_eq!(texel[3], 1.0);
+        }
+    }
+
+    #[test]
+    fn test_bc4_rgba8_into_region() {
+        // 8x6 canvas with rows padded to 40 bytes, the block is written at (3, 1).
+        let region = OutputRegion {
+            x: 3,
+            y: 1,
+            row_pitch: 40,
+        };
+        let mut canvas = [0x11u8; 6 * 40];
+        decompress_blocks_as_rgba8_into(
+            CompressionVariant::BC4,
+            4,
+            4,
+            &BC4_BLOCK,
+            &mut canvas,
+            region,
+            FillValues::default(),
+        );
+
+        for (y, row) in canvas.chunks_exact(40).enumerate() {
+            for (x, texel) in row.chunks_exact(4).enumerate() {
+                if (1..5).contains(&y) && (3..7).contains(&x) {
+                    assert_eq!(texel, [0x80, 0x00, 0x00, 0xFF], "texel ({x}, {y})");
+                } else {
+                    assert_eq!(texel, [0x11; 4], "texel ({x}, {y})");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_bc1_r8_into_partial_block() {
+        // The last row of the region doesn't need the padding of the row pitch.
+        let blocks = [0x00, 0xF8, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00];
+        let region = OutputRegion {
+            x: 1,
+            y: 0,
+            row_pitch: 8,
+        };
+
+        let mut r = [0u8; 12];
+        decompress_blocks_as_r8_into(
+            CompressionVariant::BC1,
+            3,
+            2,
+            &blocks,
+            &mut r,
+            region,
+            FillValues::default(),
+        );
+
+        assert_eq!(r, [0, 0xFF, 0xFF, 0xFF, 0, 0, 0, 0, 0, 0xFF, 0xFF, 0xFF]);
+    }
+
+    #[test]
+    #[should_panic(expected = "row pitch")]
+    fn test_rgba32f_into_rejects_short_row_pitch() {
+        let mut rgba = [0.0f32; 128];
+        decompress_blocks_as_rgba32f_into(
+            CompressionVariant::BC4,
+            4,
+            4,
+            &BC4_BLOCK,
+            &mut rgba,
+            OutputRegion::with_row_pitch(4 * 16 - 4),
+            FillValues::default(),
+        );
+    }
 }
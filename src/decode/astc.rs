@@ -0,0 +1,222 @@
+//! Decoder for the ASTC LDR block format.
+//!
+//! Implemented after the "Khronos Data Format Specification v1.3", chapter "ASTC Compressed
+//! Texture Image Formats".
+//!
+//! The decoder supports the subset of the format, that is needed to verify the encoders of this
+//! crate: void-extent blocks and single partition blocks with one weight plane and the direct
+//! luminance, luminance-alpha, RGB and RGBA endpoint modes. All other blocks decode to the ASTC
+//! error color magenta.
+
+use crate::{
+    astc::{
+        decode_block_mode, infill_weights, ise_bit_count, read_bits, read_ise, unquantize_color,
+        unquantize_weight, ERROR_COLOR, MAX_BLOCK_TEXELS, QUANT_6, QUANT_LEVELS,
+    },
+    ASTCBlockSize,
+};
+
+/// Decodes an ASTC LDR block with the footprint `block_size` by reading 16 bytes from `compressed_block` and writing the RGBA8 data into `decompressed_block` with `destination_pitch` many bytes per output row.
+///
+/// Blocks using features, that the decoder doesn't support (multiple partitions, dual weight
+/// planes, HDR or offset endpoint modes) are decoded with the ASTC error color magenta.
+#[inline(always)]
+pub fn decode_block_astc(
+    compressed_block: &[u8],
+    decompressed_block: &mut [u8],
+    destination_pitch: usize,
+    block_size: ASTCBlockSize,
+) {
+    let (block_width, block_height) = block_size.dimensions();
+    let block = u128::from_le_bytes(compressed_block[..16].try_into().unwrap());
+
+    let mut texels = [ERROR_COLOR; MAX_BLOCK_TEXELS];
+    decode_texels(block, block_width, block_height, &mut texels);
+
+    for y in 0..block_height as usize {
+        for x in 0..block_width as usize {
+            let offset = y * destination_pitch + x * 4;
+            decompressed_block[offset..offset + 4]
+                .copy_from_slice(&texels[y * block_width as usize + x]);
+        }
+    }
+}
+
+/// Decodes all texels of a block. Leaves the texels untouched, if the block can't be decoded.
+fn decode_texels(
+    block: u128,
+    block_width: u32,
+    block_height: u32,
+    texels: &mut [[u8; 4]; MAX_BLOCK_TEXELS],
+) {
+    let texel_count = (block_width * block_height) as usize;
+
+    if read_bits(block, 9, 0) == 0x1FC {
+        // Void-extent blocks with HDR colors are not supported.
+        if read_bits(block, 1, 9) == 1 {
+            return;
+        }
+
+        let mut color = [0; 4];
+        for (channel, value) in color.iter_mut().enumerate() {
+            *value = (read_bits(block, 16, 64 + channel as u32 * 16) >> 8) as u8;
+        }
+        texels[..texel_count].fill(color);
+        return;
+    }
+
+    let Some(mode) = decode_block_mode(read_bits(block, 11, 0)) else {
+        return;
+    };
+
+    let partition_count = read_bits(block, 2, 11) + 1;
+    if partition_count != 1
+        || mode.dual_plane
+        || mode.grid_width > block_width
+        || mode.grid_height > block_height
+    {
+        return;
+    }
+
+    let color_endpoint_mode = read_bits(block, 4, 13);
+    let value_count = match color_endpoint_mode {
+        0 => 2,
+        4 => 4,
+        8 => 6,
+        12 => 8,
+        _ => return,
+    };
+
+    let grid_count = mode.grid_width * mode.grid_height;
+    let weight_bits = ise_bit_count(grid_count, mode.weight_quant);
+    let color_bits = 128 - 17 - weight_bits;
+
+    // The color endpoints use the highest quantization level, that fits into the remaining bits.
+    let Some(color_quant) = (QUANT_6..QUANT_LEVELS.len())
+        .rev()
+        .find(|&quant| ise_bit_count(value_count, quant) <= color_bits)
+    else {
+        return;
+    };
+
+    let mut values = [0; 8];
+    read_ise(block, &mut values[..value_count as usize], color_quant, 17);
+    for value in values.iter_mut() {
+        *value = unquantize_color(color_quant, *value);
+    }
+
+    let [e0, e1] = decode_endpoints(color_endpoint_mode, &values);
+
+    // Weights are stored in reverse bit order, starting at the most significant bit.
+    let mut grid_weights = [0; 64];
+    read_ise(
+        block.reverse_bits(),
+        &mut grid_weights[..grid_count as usize],
+        mode.weight_quant,
+        0,
+    );
+    for weight in grid_weights.iter_mut() {
+        *weight = unquantize_weight(mode.weight_quant, *weight);
+    }
+
+    let mut texel_weights = [0; MAX_BLOCK_TEXELS];
+    infill_weights(
+        block_width,
+        block_height,
+        mode.grid_width,
+        mode.grid_height,
+        &grid_weights,
+        &mut texel_weights,
+    );
+
+    for (texel, &weight) in texels.iter_mut().zip(&texel_weights[..texel_count]) {
+        for (channel, value) in texel.iter_mut().enumerate() {
+            *value = interpolate(e0[channel], e1[channel], weight);
+        }
+    }
+}
+
+/// Decodes the two RGBA endpoints of the direct LDR endpoint modes.
+fn decode_endpoints(color_endpoint_mode: u32, v: &[u8; 8]) -> [[u8; 4]; 2] {
+    match color_endpoint_mode {
+        0 => [[v[0], v[0], v[0], 255], [v[1], v[1], v[1], 255]],
+        4 => [[v[0], v[0], v[0], v[2]], [v[1], v[1], v[1], v[3]]],
+        _ => {
+            let (a0, a1) = if color_endpoint_mode == 12 {
+                (v[6], v[7])
+            } else {
+                (255, 255)
+            };
+
+            let s0 = v[0] as u32 + v[2] as u32 + v[4] as u32;
+            let s1 = v[1] as u32 + v[3] as u32 + v[5] as u32;
+
+            if s1 >= s0 {
+                [[v[0], v[2], v[4], a0], [v[1], v[3], v[5], a1]]
+            } else {
+                [
+                    blue_contract([v[1], v[3], v[5], a1]),
+                    blue_contract([v[0], v[2], v[4], a0]),
+                ]
+            }
+        }
+    }
+}
+
+#[inline(always)]
+fn blue_contract([r, g, b, a]: [u8; 4]) -> [u8; 4] {
+    [
+        ((r as u32 + b as u32) >> 1) as u8,
+        ((g as u32 + b as u32) >> 1) as u8,
+        b,
+        a,
+    ]
+}
+
+/// Interpolates between the endpoints with a weight between 0 and 64 in 16-bit precision and
+/// converts the result back into 8-bit.
+#[inline(always)]
+fn interpolate(e0: u8, e1: u8, weight: u8) -> u8 {
+    let e0 = e0 as u32 * 257;
+    let e1 = e1 as u32 * 257;
+    let weight = weight as u32;
+
+    let color = (e0 * (64 - weight) + e1 * weight + 32) >> 6;
+    ((color * 255 + 32767) / 65535) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_void_extent() {
+        // Void-extent block with the color (255, 128, 0, 255) in UNORM16.
+        let mut block = 0x1FCu128 | (0xFFF << 12) | (0xFFF << 38);
+        block |= 0xFFFFu128 << 64;
+        block |= 0x8080u128 << 80;
+        block |= 0xFFFFu128 << 112;
+
+        let mut decompressed = [0; 6 * 6 * 4];
+        decode_block_astc(
+            &block.to_le_bytes(),
+            &mut decompressed,
+            6 * 4,
+            ASTCBlockSize::Block6x6,
+        );
+
+        for texel in decompressed.chunks_exact(4) {
+            assert_eq!(texel, [255, 128, 0, 255]);
+        }
+    }
+
+    #[test]
+    fn test_decode_reserved_block_mode() {
+        let mut decompressed = [0; 4 * 4 * 4];
+        decode_block_astc(&[0; 16], &mut decompressed, 16, ASTCBlockSize::Block4x4);
+
+        for texel in decompressed.chunks_exact(4) {
+            assert_eq!(texel, ERROR_COLOR);
+        }
+    }
+}
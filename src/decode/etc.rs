@@ -0,0 +1,492 @@
+//! Decoders for the ETC2 and EAC block formats.
+//!
+//! Implemented after the "Khronos Data Format Specification v1.3", chapter "ETC2 Compressed
+//! Texture Image Formats".
+//!
+//! ETC2 and EAC blocks are stored as big-endian 64-bit values and the pixel indices are ordered
+//! column by column, so pixel `x, y` uses index `x * 4 + y`.
+
+/// Intensity modifiers of the individual and differential mode.
+static ETC1_MODIFIER_TABLE: &[[i32; 2]; 8] = &[
+    [2, 8],
+    [5, 17],
+    [9, 29],
+    [13, 42],
+    [18, 60],
+    [24, 80],
+    [33, 106],
+    [47, 183],
+];
+
+/// Distances of the T and H mode.
+static DISTANCE_TABLE: &[i32; 8] = &[3, 6, 11, 16, 23, 32, 41, 64];
+
+/// Modifiers of the EAC alpha, R11 and RG11 formats.
+static EAC_MODIFIER_TABLE: &[[i32; 8]; 16] = &[
+    [-3, -6, -9, -15, 2, 5, 8, 14],
+    [-3, -7, -10, -13, 2, 6, 9, 12],
+    [-2, -5, -8, -13, 1, 4, 7, 12],
+    [-2, -4, -6, -13, 1, 3, 5, 12],
+    [-3, -6, -8, -12, 2, 5, 7, 11],
+    [-3, -7, -9, -11, 2, 6, 8, 10],
+    [-4, -7, -8, -11, 3, 6, 7, 10],
+    [-3, -5, -8, -11, 2, 4, 7, 10],
+    [-2, -6, -8, -10, 1, 5, 7, 9],
+    [-2, -5, -8, -10, 1, 4, 7, 9],
+    [-2, -4, -8, -10, 1, 3, 7, 9],
+    [-2, -5, -7, -10, 1, 4, 6, 9],
+    [-3, -4, -7, -10, 2, 3, 6, 9],
+    [-1, -2, -3, -10, 0, 1, 2, 9],
+    [-4, -6, -8, -9, 3, 5, 7, 8],
+    [-3, -5, -7, -9, 2, 4, 6, 8],
+];
+
+/// Decodes an ETC2 RGB8 block by reading 8 bytes from `compressed_block` and writing the RGBA8 data into `decompressed_block` with `destination_pitch` many bytes per output row.
+#[inline(always)]
+pub fn decode_block_etc2_rgb8(
+    compressed_block: &[u8],
+    decompressed_block: &mut [u8],
+    destination_pitch: usize,
+) {
+    decode_color_block::<false>(compressed_block, decompressed_block, destination_pitch);
+}
+
+/// Decodes an ETC2 RGB8A1 block with punch-through alpha by reading 8 bytes from `compressed_block` and writing the RGBA8 data into `decompressed_block` with `destination_pitch` many bytes per output row.
+#[inline(always)]
+pub fn decode_block_etc2_rgb8a1(
+    compressed_block: &[u8],
+    decompressed_block: &mut [u8],
+    destination_pitch: usize,
+) {
+    decode_color_block::<true>(compressed_block, decompressed_block, destination_pitch);
+}
+
+/// Decodes an ETC2 RGBA8 block by reading 16 bytes from `compressed_block` and writing the RGBA8 data into `decompressed_block` with `destination_pitch` many bytes per output row.
+#[inline(always)]
+pub fn decode_block_etc2_rgba8(
+    compressed_block: &[u8],
+    decompressed_block: &mut [u8],
+    destination_pitch: usize,
+) {
+    decode_color_block::<false>(
+        &compressed_block[8..],
+        decompressed_block,
+        destination_pitch,
+    );
+
+    let alpha = decode_eac_block(compressed_block, false);
+    for y in 0..4 {
+        for x in 0..4 {
+            decompressed_block[y * destination_pitch + x * 4 + 3] = alpha[x * 4 + y] as u8;
+        }
+    }
+}
+
+/// Decodes an EAC R11 block by reading 8 bytes from `compressed_block` and writing the R16 data into `decompressed_block` with `destination_pitch` many elements per output row.
+///
+/// The 11-bit values are extended to 16-bit by bit replication.
+#[inline(always)]
+pub fn decode_block_eac_r11(
+    compressed_block: &[u8],
+    decompressed_block: &mut [u16],
+    destination_pitch: usize,
+) {
+    decode_eac_r11_block::<1>(compressed_block, decompressed_block, destination_pitch);
+}
+
+/// Decodes an EAC RG11 block by reading 16 bytes from `compressed_block` and writing the RG16 data into `decompressed_block` with `destination_pitch` many elements per output row.
+///
+/// The 11-bit values are extended to 16-bit by bit replication.
+#[inline(always)]
+pub fn decode_block_eac_rg11(
+    compressed_block: &[u8],
+    decompressed_block: &mut [u16],
+    destination_pitch: usize,
+) {
+    decode_eac_r11_block::<2>(compressed_block, decompressed_block, destination_pitch);
+    decode_eac_r11_block::<2>(
+        &compressed_block[8..],
+        &mut decompressed_block[1..],
+        destination_pitch,
+    );
+}
+
+#[inline(always)]
+fn decode_eac_r11_block<const PIXEL_SIZE: usize>(
+    compressed_block: &[u8],
+    decompressed_block: &mut [u16],
+    destination_pitch: usize,
+) {
+    let values = decode_eac_block(compressed_block, true);
+    for y in 0..4 {
+        for x in 0..4 {
+            let value = values[x * 4 + y] as u16;
+            decompressed_block[y * destination_pitch + x * PIXEL_SIZE] =
+                (value << 5) | (value >> 6);
+        }
+    }
+}
+
+/// Decodes an EAC block into 8-bit alpha or 11-bit values in column-major order.
+#[inline(always)]
+fn decode_eac_block(compressed_block: &[u8], eleven_bit: bool) -> [i32; 16] {
+    let block = u64::from_be_bytes(compressed_block[0..8].try_into().unwrap());
+
+    let base = (block >> 56) as i32;
+    let multiplier = ((block >> 52) & 0xF) as i32;
+    let modifiers = &EAC_MODIFIER_TABLE[((block >> 48) & 0xF) as usize];
+
+    let mut values = [0; 16];
+
+    for (pixel, value) in values.iter_mut().enumerate() {
+        let index = ((block >> (45 - pixel * 3)) & 0x7) as usize;
+        let modifier = modifiers[index];
+
+        *value = if !eleven_bit {
+            (base + modifier * multiplier).clamp(0, 255)
+        } else if multiplier != 0 {
+            (base * 8 + 4 + modifier * multiplier * 8).clamp(0, 2047)
+        } else {
+            (base * 8 + 4 + modifier).clamp(0, 2047)
+        };
+    }
+
+    values
+}
+
+#[inline(always)]
+fn extend_4to8(value: u64) -> i32 {
+    let value = (value & 0xF) as i32;
+    (value << 4) | value
+}
+
+#[inline(always)]
+fn extend_5to8(value: i32) -> i32 {
+    (value << 3) | (value >> 2)
+}
+
+#[inline(always)]
+fn extend_6to8(value: u64) -> i32 {
+    let value = (value & 0x3F) as i32;
+    (value << 2) | (value >> 4)
+}
+
+#[inline(always)]
+fn extend_7to8(value: u64) -> i32 {
+    let value = (value & 0x7F) as i32;
+    (value << 1) | (value >> 6)
+}
+
+#[inline(always)]
+fn sign_extend_3(value: u64) -> i32 {
+    let value = (value & 0x7) as i32;
+    (value << 29) >> 29
+}
+
+#[inline(always)]
+fn pixel_index(block: u64, pixel: usize) -> usize {
+    let msb = (block >> (16 + pixel)) & 1;
+    let lsb = (block >> pixel) & 1;
+    ((msb << 1) | lsb) as usize
+}
+
+#[inline(always)]
+fn write_pixel(
+    decompressed_block: &mut [u8],
+    destination_pitch: usize,
+    pixel: usize,
+    color: [i32; 3],
+    alpha: u8,
+) {
+    let x = pixel / 4;
+    let y = pixel % 4;
+    let offset = y * destination_pitch + x * 4;
+    decompressed_block[offset] = color[0].clamp(0, 255) as u8;
+    decompressed_block[offset + 1] = color[1].clamp(0, 255) as u8;
+    decompressed_block[offset + 2] = color[2].clamp(0, 255) as u8;
+    decompressed_block[offset + 3] = alpha;
+}
+
+/// Decompresses an ETC2 color block. With `PUNCH_THROUGH` the differential bit is used as the
+/// opaque bit.
+#[inline(always)]
+fn decode_color_block<const PUNCH_THROUGH: bool>(
+    compressed_block: &[u8],
+    decompressed_block: &mut [u8],
+    destination_pitch: usize,
+) {
+    let block = u64::from_be_bytes(compressed_block[0..8].try_into().unwrap());
+
+    let differential = (block >> 33) & 1 == 1;
+    let opaque = !PUNCH_THROUGH || differential;
+
+    if !PUNCH_THROUGH && !differential {
+        // Individual mode: two 444 base colors
+        let base_colors = [
+            [
+                extend_4to8(block >> 60),
+                extend_4to8(block >> 52),
+                extend_4to8(block >> 44),
+            ],
+            [
+                extend_4to8(block >> 56),
+                extend_4to8(block >> 48),
+                extend_4to8(block >> 40),
+            ],
+        ];
+        decode_subblocks::<PUNCH_THROUGH>(
+            block,
+            base_colors,
+            opaque,
+            decompressed_block,
+            destination_pitch,
+        );
+        return;
+    }
+
+    let r = ((block >> 59) & 0x1F) as i32;
+    let g = ((block >> 51) & 0x1F) as i32;
+    let b = ((block >> 43) & 0x1F) as i32;
+    let r2 = r + sign_extend_3(block >> 56);
+    let g2 = g + sign_extend_3(block >> 48);
+    let b2 = b + sign_extend_3(block >> 40);
+
+    if !(0..=31).contains(&r2) {
+        // T mode
+        let r1 = (((block >> 59) & 0x3) << 2) | ((block >> 56) & 0x3);
+        let color0 = [
+            extend_4to8(r1),
+            extend_4to8(block >> 52),
+            extend_4to8(block >> 48),
+        ];
+        let color1 = [
+            extend_4to8(block >> 44),
+            extend_4to8(block >> 40),
+            extend_4to8(block >> 36),
+        ];
+        let distance =
+            DISTANCE_TABLE[((((block >> 34) & 0x3) << 1) | ((block >> 32) & 1)) as usize];
+
+        let paint_colors = [
+            color0,
+            color1.map(|c| c + distance),
+            color1,
+            color1.map(|c| c - distance),
+        ];
+        decode_paint_colors::<PUNCH_THROUGH>(
+            block,
+            paint_colors,
+            opaque,
+            decompressed_block,
+            destination_pitch,
+        );
+    } else if !(0..=31).contains(&g2) {
+        // H mode
+        let r1 = (block >> 59) & 0xF;
+        let g1 = (((block >> 56) & 0x7) << 1) | ((block >> 52) & 1);
+        let b1 = (((block >> 51) & 1) << 3) | ((block >> 47) & 0x7);
+        let r2 = (block >> 43) & 0xF;
+        let g2 = (block >> 39) & 0xF;
+        let b2 = (block >> 35) & 0xF;
+
+        let value0 = (r1 << 8) | (g1 << 4) | b1;
+        let value1 = (r2 << 8) | (g2 << 4) | b2;
+        let distance_index =
+            (((block >> 34) & 1) << 2) | (((block >> 32) & 1) << 1) | (value0 >= value1) as u64;
+        let distance = DISTANCE_TABLE[distance_index as usize];
+
+        let color0 = [extend_4to8(r1), extend_4to8(g1), extend_4to8(b1)];
+        let color1 = [extend_4to8(r2), extend_4to8(g2), extend_4to8(b2)];
+
+        let paint_colors = [
+            color0.map(|c| c + distance),
+            color0.map(|c| c - distance),
+            color1.map(|c| c + distance),
+            color1.map(|c| c - distance),
+        ];
+        decode_paint_colors::<PUNCH_THROUGH>(
+            block,
+            paint_colors,
+            opaque,
+            decompressed_block,
+            destination_pitch,
+        );
+    } else if !(0..=31).contains(&b2) {
+        // Planar mode
+        let origin = [
+            extend_6to8(block >> 57),
+            extend_7to8((((block >> 56) & 1) << 6) | ((block >> 49) & 0x3F)),
+            extend_6to8(
+                (((block >> 48) & 1) << 5)
+                    | (((block >> 43) & 0x3) << 3)
+                    | (((block >> 40) & 0x3) << 1)
+                    | ((block >> 39) & 1),
+            ),
+        ];
+        let horizontal = [
+            extend_6to8((((block >> 34) & 0x1F) << 1) | ((block >> 32) & 1)),
+            extend_7to8(block >> 25),
+            extend_6to8(block >> 19),
+        ];
+        let vertical = [
+            extend_6to8(block >> 13),
+            extend_7to8(block >> 6),
+            extend_6to8(block),
+        ];
+
+        for pixel in 0..16 {
+            let x = (pixel / 4) as i32;
+            let y = (pixel % 4) as i32;
+
+            let color = [0, 1, 2].map(|c| {
+                (x * (horizontal[c] - origin[c])
+                    + y * (vertical[c] - origin[c])
+                    + 4 * origin[c]
+                    + 2)
+                    >> 2
+            });
+
+            write_pixel(decompressed_block, destination_pitch, pixel, color, 0xFF);
+        }
+    } else {
+        // Differential mode: 555 base color and a 333 delta
+        let base_colors = [
+            [extend_5to8(r), extend_5to8(g), extend_5to8(b)],
+            [extend_5to8(r2), extend_5to8(g2), extend_5to8(b2)],
+        ];
+        decode_subblocks::<PUNCH_THROUGH>(
+            block,
+            base_colors,
+            opaque,
+            decompressed_block,
+            destination_pitch,
+        );
+    }
+}
+
+#[inline(always)]
+fn decode_subblocks<const PUNCH_THROUGH: bool>(
+    block: u64,
+    base_colors: [[i32; 3]; 2],
+    opaque: bool,
+    decompressed_block: &mut [u8],
+    destination_pitch: usize,
+) {
+    let flip = (block >> 32) & 1 == 1;
+    let tables = [(block >> 37) & 0x7, (block >> 34) & 0x7];
+
+    for pixel in 0..16 {
+        let x = pixel / 4;
+        let y = pixel % 4;
+        let subblock = if flip { y / 2 } else { x / 2 };
+
+        let [a, b] = ETC1_MODIFIER_TABLE[tables[subblock] as usize];
+        let index = pixel_index(block, pixel);
+
+        if PUNCH_THROUGH && !opaque {
+            // The small modifiers are replaced by zero and index 2 marks transparent pixels.
+            let modifier = match index {
+                0 => 0,
+                1 => b,
+                2 => {
+                    write_pixel(decompressed_block, destination_pitch, pixel, [0; 3], 0);
+                    continue;
+                }
+                _ => -b,
+            };
+            let color = base_colors[subblock].map(|c| c + modifier);
+            write_pixel(decompressed_block, destination_pitch, pixel, color, 0xFF);
+        } else {
+            let modifier = [a, b, -a, -b][index];
+            let color = base_colors[subblock].map(|c| c + modifier);
+            write_pixel(decompressed_block, destination_pitch, pixel, color, 0xFF);
+        }
+    }
+}
+
+#[inline(always)]
+fn decode_paint_colors<const PUNCH_THROUGH: bool>(
+    block: u64,
+    paint_colors: [[i32; 3]; 4],
+    opaque: bool,
+    decompressed_block: &mut [u8],
+    destination_pitch: usize,
+) {
+    for pixel in 0..16 {
+        let index = pixel_index(block, pixel);
+
+        if PUNCH_THROUGH && !opaque && index == 2 {
+            write_pixel(decompressed_block, destination_pitch, pixel, [0; 3], 0);
+        } else {
+            write_pixel(
+                decompressed_block,
+                destination_pitch,
+                pixel,
+                paint_colors[index],
+                0xFF,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_etc2_individual_solid() {
+        // Individual mode, both base colors 0x8 (0x88), table 0 and all indices 0 (+2).
+        let compressed_block = [0x88, 0x88, 0x88, 0x00, 0x00, 0x00, 0x00, 0x00];
+        let mut decoded = [0u8; 64];
+        decode_block_etc2_rgb8(&compressed_block, &mut decoded, 16);
+
+        for texel in decoded.chunks_exact(4) {
+            assert_eq!(texel, [0x8A, 0x8A, 0x8A, 0xFF]);
+        }
+    }
+
+    #[test]
+    fn test_etc2_differential_flip() {
+        // Differential mode with flip: the top half is black and the bottom half uses a delta
+        // of +3 on the red channel. Both use table 7 and all indices are 1 (+183).
+        let compressed_block = [0x03, 0x00, 0x00, 0xFF, 0x00, 0x00, 0xFF, 0xFF];
+        let mut decoded = [0u8; 64];
+        decode_block_etc2_rgb8(&compressed_block, &mut decoded, 16);
+
+        for y in 0..4 {
+            for x in 0..4 {
+                let texel = &decoded[y * 16 + x * 4..][..4];
+                if y < 2 {
+                    assert_eq!(texel, [183, 183, 183, 0xFF], "x: {x} y: {y}");
+                } else {
+                    assert_eq!(texel, [207, 183, 183, 0xFF], "x: {x} y: {y}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_etc2_rgb8a1_transparent() {
+        // Non-opaque differential block with all pixel indices 2 (transparent).
+        let compressed_block = [0x80, 0x00, 0x00, 0x00, 0xFF, 0xFF, 0x00, 0x00];
+        let mut decoded = [0xAAu8; 64];
+        decode_block_etc2_rgb8a1(&compressed_block, &mut decoded, 16);
+
+        assert!(decoded.iter().all(|&value| value == 0));
+    }
+
+    #[test]
+    fn test_eac_r11_extremes() {
+        // Base 255, multiplier 15, table 0: the first pixel uses index 7 (+14) and all others
+        // index 3 (-15).
+        let compressed_block = [0xFF, 0xF0, 0xED, 0xB6, 0xDB, 0x6D, 0xB6, 0xDB];
+        let mut decoded = [0u16; 16];
+        decode_block_eac_r11(&compressed_block, &mut decoded, 4);
+
+        assert_eq!(decoded[0], 0xFFFF);
+        let expected = (255 * 8 + 4 - 15 * 15 * 8) as u16;
+        assert!(decoded[1..]
+            .iter()
+            .all(|&value| value == (expected << 5) | (expected >> 6)));
+    }
+}
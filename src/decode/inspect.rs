@@ -0,0 +1,328 @@
+//! Inspection of the BC6H and BC7 block layout.
+//!
+//! The functions of this module don't decode the texels of a block, but return how a block was
+//! encoded: the mode, the partition, the endpoints and the indices. The histograms aggregate the
+//! mode usage over all blocks of an image and can be used to tune the encoder settings.
+
+#[cfg(feature = "bc6h")]
+use super::block::{bc6h_subset, finish_unquantize, parse_block_bc6h};
+#[cfg(feature = "bc7")]
+use super::block::{bc7_subset, parse_block_bc7};
+
+/// Layout of a single BC7 block.
+#[cfg(feature = "bc7")]
+#[cfg_attr(docsrs, doc(cfg(feature = "bc7")))]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct BC7BlockInfo {
+    /// The mode of the block (0 to 7).
+    pub mode: u8,
+    /// The partition of the modes 0, 1, 2, 3 and 7. Always 0 for the other modes.
+    pub partition: u8,
+    /// The number of subsets (1 to 3).
+    pub subset_count: u8,
+    /// The channel rotation of the modes 4 and 5. Values 1, 2 and 3 swap the alpha channel with the
+    /// red, green and blue channel.
+    pub rotation: u8,
+    /// The index selection bit of mode 4. If set, the primary indices are used for the alpha
+    /// channel and the secondary indices for the color channels.
+    pub index_selection: bool,
+    /// The fully decoded RGBA8 endpoints, two for each subset. Unused endpoints are zero.
+    pub endpoints: [[u8; 4]; 6],
+    /// The number of bits of the primary indices.
+    pub primary_index_bits: u8,
+    /// The number of bits of the secondary indices of the modes 4 and 5. Zero for the other modes.
+    pub secondary_index_bits: u8,
+    /// The primary indices of all texels in row-major order.
+    pub primary_indices: [u8; 16],
+    /// The secondary indices of all texels in row-major order. Zero if the mode has none.
+    pub secondary_indices: [u8; 16],
+}
+
+#[cfg(feature = "bc7")]
+#[cfg_attr(docsrs, doc(cfg(feature = "bc7")))]
+impl BC7BlockInfo {
+    /// Returns the subset of the texel at `x` and `y`.
+    pub fn subset(&self, x: usize, y: usize) -> usize {
+        assert!(x < 4 && y < 4, "texel coordinates must be inside the block");
+        bc7_subset(self.subset_count, self.partition, x, y)
+    }
+}
+
+/// Layout of a single BC6H block.
+#[cfg(feature = "bc6h")]
+#[cfg_attr(docsrs, doc(cfg(feature = "bc6h")))]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct BC6HBlockInfo {
+    /// The mode of the block (0 to 13). The modes are counted from 0, so the mode 1 of the
+    /// specification is reported as 0.
+    pub mode: u8,
+    /// The partition of the two subset modes (0 to 31). Always 0 for the other modes.
+    pub partition: u8,
+    /// The number of subsets (1 or 2).
+    pub subset_count: u8,
+    /// The fully decoded RGB endpoints, two for each subset. Unused endpoints are zero.
+    pub endpoints: [[half::f16; 3]; 4],
+    /// The number of bits of the indices.
+    pub index_bits: u8,
+    /// The indices of all texels in row-major order.
+    pub indices: [u8; 16],
+}
+
+#[cfg(feature = "bc6h")]
+#[cfg_attr(docsrs, doc(cfg(feature = "bc6h")))]
+impl BC6HBlockInfo {
+    /// Returns the subset of the texel at `x` and `y`.
+    pub fn subset(&self, x: usize, y: usize) -> usize {
+        assert!(x < 4 && y < 4, "texel coordinates must be inside the block");
+        bc6h_subset(self.mode, self.partition, x, y)
+    }
+}
+
+/// Reads the layout of a BC7 block from the first 16 bytes of `compressed_block`.
+///
+/// Returns `None` if the block uses the reserved mode.
+#[cfg(feature = "bc7")]
+#[cfg_attr(docsrs, doc(cfg(feature = "bc7")))]
+pub fn inspect_block_bc7(compressed_block: &[u8]) -> Option<BC7BlockInfo> {
+    parse_block_bc7(compressed_block)
+}
+
+/// Reads the layout of a BC6H block from the first 16 bytes of `compressed_block`.
+///
+/// Returns `None` if the block uses one of the reserved modes.
+#[cfg(feature = "bc6h")]
+#[cfg_attr(docsrs, doc(cfg(feature = "bc6h")))]
+pub fn inspect_block_bc6h(compressed_block: &[u8], is_signed: bool) -> Option<BC6HBlockInfo> {
+    let block = parse_block_bc6h(compressed_block, is_signed)?;
+
+    let mut endpoints = [[half::f16::ZERO; 3]; 4];
+    for (endpoint, unquantized) in endpoints.iter_mut().zip(block.endpoints.iter()) {
+        for (channel, &value) in endpoint.iter_mut().zip(unquantized.iter()) {
+            *channel = half::f16::from_bits(finish_unquantize(value, is_signed));
+        }
+    }
+
+    let (subset_count, index_bits) = if block.mode >= 10 { (1, 4) } else { (2, 3) };
+
+    Some(BC6HBlockInfo {
+        mode: block.mode,
+        partition: block.partition,
+        subset_count,
+        endpoints,
+        index_bits,
+        indices: block.indices,
+    })
+}
+
+/// Mode usage of a set of BC7 blocks.
+///
+/// The modes can be mapped to the mode selection of the [`crate::BC7Settings`]: the first entry
+/// enables the modes 0 and 2, the second entry the modes 1, 3 and 7, the third entry the modes 4
+/// and 5 and the last entry the mode 6.
+#[cfg(feature = "bc7")]
+#[cfg_attr(docsrs, doc(cfg(feature = "bc7")))]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BC7ModeHistogram {
+    /// The number of blocks per mode.
+    pub modes: [u64; 8],
+    /// The number of blocks per mode and partition.
+    pub partitions: [[u64; 64]; 8],
+    /// The number of blocks per rotation of the modes 4 and 5.
+    pub rotations: [u64; 4],
+    /// The number of blocks using the reserved mode.
+    pub reserved: u64,
+}
+
+#[cfg(feature = "bc7")]
+impl Default for BC7ModeHistogram {
+    fn default() -> Self {
+        Self {
+            modes: [0; 8],
+            partitions: [[0; 64]; 8],
+            rotations: [0; 4],
+            reserved: 0,
+        }
+    }
+}
+
+#[cfg(feature = "bc7")]
+#[cfg_attr(docsrs, doc(cfg(feature = "bc7")))]
+impl BC7ModeHistogram {
+    /// Creates the histogram of all BC7 blocks in `blocks`.
+    pub fn from_blocks(blocks: &[u8]) -> Self {
+        let mut histogram = Self::default();
+        histogram.add_blocks(blocks);
+        histogram
+    }
+
+    /// Adds all BC7 blocks in `blocks` to the histogram. Can be used to aggregate the usage over
+    /// all mip levels and layers of a texture.
+    pub fn add_blocks(&mut self, blocks: &[u8]) {
+        assert_eq!(
+            blocks.len() % 16,
+            0,
+            "blocks must be a multiple of 16 bytes"
+        );
+
+        for block in blocks.chunks_exact(16) {
+            match parse_block_bc7(block) {
+                Some(info) => {
+                    self.modes[info.mode as usize] += 1;
+                    self.partitions[info.mode as usize][info.partition as usize] += 1;
+                    if info.mode == 4 || info.mode == 5 {
+                        self.rotations[info.rotation as usize] += 1;
+                    }
+                }
+                None => self.reserved += 1,
+            }
+        }
+    }
+
+    /// Returns the total number of blocks, including the blocks with the reserved mode.
+    pub fn total(&self) -> u64 {
+        self.modes.iter().sum::<u64>() + self.reserved
+    }
+
+    /// Returns the fraction of the blocks that use the given mode.
+    pub fn mode_fraction(&self, mode: usize) -> f64 {
+        fraction(self.modes[mode], self.total())
+    }
+}
+
+/// Mode usage of a set of BC6H blocks.
+#[cfg(feature = "bc6h")]
+#[cfg_attr(docsrs, doc(cfg(feature = "bc6h")))]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct BC6HModeHistogram {
+    /// The number of blocks per mode. The modes are counted from 0, so the mode 1 of the
+    /// specification is found at index 0.
+    pub modes: [u64; 14],
+    /// The number of blocks per partition of the two subset modes.
+    pub partitions: [u64; 32],
+    /// The number of blocks using one of the reserved modes.
+    pub reserved: u64,
+}
+
+#[cfg(feature = "bc6h")]
+#[cfg_attr(docsrs, doc(cfg(feature = "bc6h")))]
+impl BC6HModeHistogram {
+    /// Creates the histogram of all BC6H blocks in `blocks`.
+    pub fn from_blocks(blocks: &[u8]) -> Self {
+        let mut histogram = Self::default();
+        histogram.add_blocks(blocks);
+        histogram
+    }
+
+    /// Adds all BC6H blocks in `blocks` to the histogram. Can be used to aggregate the usage over
+    /// all mip levels and layers of a texture.
+    pub fn add_blocks(&mut self, blocks: &[u8]) {
+        assert_eq!(
+            blocks.len() % 16,
+            0,
+            "blocks must be a multiple of 16 bytes"
+        );
+
+        for block in blocks.chunks_exact(16) {
+            // The mode and partition don't depend on the signedness.
+            match parse_block_bc6h(block, false) {
+                Some(info) => {
+                    self.modes[info.mode as usize] += 1;
+                    if info.mode < 10 {
+                        self.partitions[info.partition as usize] += 1;
+                    }
+                }
+                None => self.reserved += 1,
+            }
+        }
+    }
+
+    /// Returns the total number of blocks, including the blocks with a reserved mode.
+    pub fn total(&self) -> u64 {
+        self.modes.iter().sum::<u64>() + self.reserved
+    }
+
+    /// Returns the fraction of the blocks that use the given mode.
+    pub fn mode_fraction(&self, mode: usize) -> f64 {
+        fraction(self.modes[mode], self.total())
+    }
+}
+
+fn fraction(count: u64, total: u64) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        count as f64 / total as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "bc7")]
+    #[test]
+    fn test_inspect_bc7_partitioned_block() {
+        // Mode 1 with partition 5, all other bits zero.
+        let mut block = [0; 16];
+        block[0] = 0b0001_0110;
+
+        let info = inspect_block_bc7(&block).unwrap();
+        assert_eq!(info.mode, 1);
+        assert_eq!(info.partition, 5);
+        assert_eq!(info.subset_count, 2);
+        assert_eq!(info.primary_index_bits, 3);
+        assert_eq!(info.secondary_index_bits, 0);
+        assert_eq!(info.subset(0, 0), 0);
+        assert_eq!(info.subset(3, 3), 1);
+    }
+
+    #[cfg(feature = "bc7")]
+    #[test]
+    fn test_inspect_bc7_rotation() {
+        // Mode 4 with rotation 2 and the index selection bit set.
+        let mut block = [0; 16];
+        block[0] = 0b1101_0000;
+
+        let info = inspect_block_bc7(&block).unwrap();
+        assert_eq!(info.mode, 4);
+        assert_eq!(info.rotation, 2);
+        assert!(info.index_selection);
+        assert_eq!(info.primary_index_bits, 2);
+        assert_eq!(info.secondary_index_bits, 3);
+    }
+
+    #[cfg(feature = "bc7")]
+    #[test]
+    fn test_bc7_mode_histogram() {
+        let mut blocks = [0; 48];
+        blocks[0] = 0b0100_0000; // Mode 6
+        blocks[16] = 0b0001_0110; // Mode 1, partition 5
+
+        let histogram = BC7ModeHistogram::from_blocks(&blocks);
+        assert_eq!(histogram.total(), 3);
+        assert_eq!(histogram.reserved, 1);
+        assert_eq!(histogram.modes[6], 1);
+        assert_eq!(histogram.partitions[1][5], 1);
+        assert!((histogram.mode_fraction(1) - 1.0 / 3.0).abs() < 1e-9);
+    }
+
+    #[cfg(feature = "bc6h")]
+    #[test]
+    fn test_bc6h_mode_histogram() {
+        let mut blocks = [0; 48];
+        blocks[0] = 0b0_0011; // Mode 11
+        blocks[16] = 0b1_0011; // Reserved
+
+        let histogram = BC6HModeHistogram::from_blocks(&blocks);
+        assert_eq!(histogram.total(), 3);
+        assert_eq!(histogram.reserved, 1);
+        assert_eq!(histogram.modes[0], 1);
+        assert_eq!(histogram.modes[10], 1);
+        assert_eq!(histogram.partitions[0], 1);
+
+        let info = inspect_block_bc6h(&blocks[..16], false).unwrap();
+        assert_eq!(info.mode, 10);
+        assert_eq!(info.subset_count, 1);
+        assert_eq!(info.index_bits, 4);
+    }
+}
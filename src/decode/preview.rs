@@ -0,0 +1,245 @@
+//! Tone-mapped LDR previews of BC6H data.
+//!
+//! HDR values can't be shown on an LDR display directly. The preview scales the linear values by
+//! the exposure, compresses them into the displayable range with a tone-mapping operator and
+//! encodes the result as opaque sRGB texels, which is suitable for thumbnails of HDR textures.
+
+use super::{block::decode_block_bc6h_float, linear_to_srgb};
+use crate::{BC6HSettings, CompressionVariant};
+
+/// Operator that maps linear HDR colors into the displayable range.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub enum ToneMapping {
+    /// Clamps every channel to `1.0`. Bright areas burn out to white.
+    Clamp,
+    /// Maps every channel with `x / (1 + x)`. Keeps the hue, but looks flat.
+    Reinhard,
+    /// The filmic curve of the ACES reference rendering transform, fitted by Krzysztof Narkowicz.
+    #[default]
+    AcesFilmic,
+    /// The AgX view transform of Blender, which desaturates bright colors towards white.
+    AgX,
+}
+
+impl ToneMapping {
+    /// Maps a linear HDR color into a linear color with channels between `0.0` and `1.0`.
+    pub fn apply(self, color: [f32; 3]) -> [f32; 3] {
+        match self {
+            Self::Clamp => color.map(|value| value.clamp(0.0, 1.0)),
+            Self::Reinhard => color.map(|value| {
+                let value = value.max(0.0);
+                value / (1.0 + value)
+            }),
+            Self::AcesFilmic => color.map(|value| {
+                // The fit is brighter than the reference transform, which this scale accounts for.
+                let x = value.max(0.0) * 0.6;
+                ((x * (2.51 * x + 0.03)) / (x * (2.43 * x + 0.59) + 0.14)).clamp(0.0, 1.0)
+            }),
+            Self::AgX => agx(color),
+        }
+    }
+}
+
+/// Inset matrix of AgX, which maps the color into the working space of the curve.
+const AGX_INSET: [[f32; 3]; 3] = [
+    [0.842_479_06, 0.078_433_6, 0.079_223_745],
+    [0.042_328_24, 0.878_468_6, 0.079_166_13],
+    [0.042_375_655, 0.078_433_6, 0.879_143],
+];
+
+/// Outset matrix of AgX, the inverse of [`AGX_INSET`].
+const AGX_OUTSET: [[f32; 3]; 3] = [
+    [1.196_879, -0.098_020_88, -0.099_029_74],
+    [-0.052_896_85, 1.151_903_1, -0.098_961_18],
+    [-0.052_971_635, -0.098_043_45, 1.151_073_7],
+];
+
+/// The range of exposure values around middle gray, that the AgX curve covers.
+const AGX_MIN_EV: f32 = -12.473_931;
+const AGX_MAX_EV: f32 = 4.026_069;
+
+fn multiply(matrix: &[[f32; 3]; 3], color: [f32; 3]) -> [f32; 3] {
+    matrix.map(|row| row[0] * color[0] + row[1] * color[1] + row[2] * color[2])
+}
+
+/// The AgX transform with the default look, using the polynomial approximation of the contrast
+/// curve by Benjamin Wrensch.
+fn agx(color: [f32; 3]) -> [f32; 3] {
+    let color = multiply(&AGX_INSET, color).map(|value| {
+        let ev = value.max(1e-10).log2().clamp(AGX_MIN_EV, AGX_MAX_EV);
+        let x = (ev - AGX_MIN_EV) / (AGX_MAX_EV - AGX_MIN_EV);
+
+        let x2 = x * x;
+        let x4 = x2 * x2;
+        15.5 * x4 * x2 - 40.14 * x4 * x + 31.96 * x4 - 6.868 * x2 * x + 0.4298 * x2 + 0.1191 * x
+            - 0.00232
+    });
+
+    // The curve outputs display encoded values, which are converted back into linear values.
+    multiply(&AGX_OUTSET, color).map(|value| value.max(0.0).powf(2.2).min(1.0))
+}
+
+/// Settings of a tone-mapped preview.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct PreviewOptions {
+    /// Exposure in stops. The linear values are multiplied with `2^exposure` before the tone
+    /// mapping.
+    pub exposure: f32,
+    /// The tone-mapping operator.
+    pub tone_mapping: ToneMapping,
+}
+
+/// Decodes BC6H data into a tone-mapped RGBA8 preview.
+///
+/// The linear values are scaled by the exposure and mapped into the displayable range with the
+/// tone-mapping operator of the `options`. The output contains sRGB encoded colors and an opaque
+/// alpha channel.
+///
+/// # Panics
+/// - The `blocks_data` has not the expected size (`variant.blocks_byte_size()`)
+/// - The `rgba_data` has not the expected size (`width * height * 4`)
+///
+/// # Example
+/// ```
+/// use block_compression::{
+///     decode::{decompress_bc6h_preview, PreviewOptions, ToneMapping},
+///     BC6HSettings, CompressionVariant,
+/// };
+///
+/// let variant = CompressionVariant::BC6H(BC6HSettings::basic());
+/// let blocks_data = vec![0u8; variant.blocks_byte_size(64, 64)];
+/// let mut rgba_data = vec![0u8; 64 * 64 * 4];
+///
+/// let options = PreviewOptions {
+///     exposure: -1.0,
+///     tone_mapping: ToneMapping::AgX,
+/// };
+/// decompress_bc6h_preview(64, 64, &blocks_data, &mut rgba_data, options);
+/// ```
+pub fn decompress_bc6h_preview(
+    width: u32,
+    height: u32,
+    blocks_data: &[u8],
+    rgba_data: &mut [u8],
+    options: PreviewOptions,
+) {
+    let variant = CompressionVariant::BC6H(BC6HSettings::basic());
+    let expected_input_size = variant.blocks_byte_size(width, height);
+    assert_eq!(
+        blocks_data.len(),
+        expected_input_size,
+        "the input bitstream slice has not the expected size"
+    );
+
+    let width = width as usize;
+    let height = height as usize;
+    assert_eq!(
+        rgba_data.len(),
+        width * height * 4,
+        "the output slice has not the expected size"
+    );
+
+    let scale = options.exposure.exp2();
+    let blocks_x = width.div_ceil(4);
+    let block_byte_size = variant.block_byte_size() as usize;
+    let mut block = [0.0; 48];
+
+    for (index, compressed) in blocks_data.chunks_exact(block_byte_size).enumerate() {
+        let (bx, by) = (index % blocks_x, index / blocks_x);
+        decode_block_bc6h_float(compressed, &mut block, 12, false);
+
+        for y in 0..(height - by * 4).min(4) {
+            for x in 0..(width - bx * 4).min(4) {
+                let texel = &block[y * 12 + x * 3..][..3];
+                let color = options.tone_mapping.apply([
+                    texel[0] * scale,
+                    texel[1] * scale,
+                    texel[2] * scale,
+                ]);
+
+                let offset = ((by * 4 + y) * width + bx * 4 + x) * 4;
+                rgba_data[offset..offset + 4].copy_from_slice(&[
+                    linear_to_srgb(color[0]),
+                    linear_to_srgb(color[1]),
+                    linear_to_srgb(color[2]),
+                    255,
+                ]);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OPERATORS: [ToneMapping; 4] = [
+        ToneMapping::Clamp,
+        ToneMapping::Reinhard,
+        ToneMapping::AcesFilmic,
+        ToneMapping::AgX,
+    ];
+
+    /// A mode 11 block with both endpoints at the given 10-bit values, so every texel
+    /// has the same color.
+    fn constant_block(color: [u32; 3]) -> [u8; 16] {
+        let mut bits = 0b00011u128;
+        for (channel, &value) in color.iter().enumerate() {
+            bits |= (value as u128) << (5 + channel * 10);
+            bits |= (value as u128) << (35 + channel * 10);
+        }
+        bits.to_le_bytes()
+    }
+
+    #[test]
+    fn operators_are_monotonic_and_bounded() {
+        for operator in OPERATORS {
+            assert_eq!(operator.apply([0.0; 3]), [0.0; 3], "{operator:?}");
+
+            let mut previous = 0.0;
+            for step in 1..=64 {
+                let value = (step as f32 / 4.0 - 8.0).exp2();
+                let mapped = operator.apply([value; 3])[0];
+
+                assert!(mapped >= previous, "{operator:?} at {value}");
+                assert!(mapped <= 1.0, "{operator:?} at {value}");
+                previous = mapped;
+            }
+            assert!(previous > 0.9, "{operator:?}");
+        }
+
+        assert_eq!(ToneMapping::Reinhard.apply([1.0; 3]), [0.5; 3]);
+    }
+
+    #[test]
+    fn preview_is_opaque_and_applies_exposure() {
+        let blocks = constant_block([496, 400, 560]);
+
+        let mut texels = [0.0; 48];
+        decode_block_bc6h_float(&blocks, &mut texels, 12, false);
+        let color = [texels[0], texels[1], texels[2]];
+        assert!(color[0] > 0.5 && color[0] < 2.0, "{color:?}");
+
+        for operator in OPERATORS {
+            for exposure in [-2.0, 0.0, 1.5] {
+                let options = PreviewOptions {
+                    exposure,
+                    tone_mapping: operator,
+                };
+                let mut rgba = [0; 64];
+                decompress_bc6h_preview(4, 4, &blocks, &mut rgba, options);
+
+                let mapped = operator.apply(color.map(|value| value * exposure.exp2()));
+                let expected = [
+                    linear_to_srgb(mapped[0]),
+                    linear_to_srgb(mapped[1]),
+                    linear_to_srgb(mapped[2]),
+                    255,
+                ];
+                for texel in rgba.chunks_exact(4) {
+                    assert_eq!(texel, expected, "{operator:?} with exposure {exposure}");
+                }
+            }
+        }
+    }
+}
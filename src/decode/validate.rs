@@ -0,0 +1,252 @@
+//! Validation of compressed block data.
+//!
+//! Decoders of the block formats are required to decode every bit pattern, so reserved modes
+//! decode to a fixed color instead of failing. This module finds such blocks and data of the wrong
+//! size, which usually means that the data is corrupted or wasn't encoded in the expected format.
+
+#[cfg(feature = "bc6h")]
+use super::block::parse_block_bc6h;
+#[cfg(feature = "bc7")]
+use super::block::parse_block_bc7;
+#[cfg(feature = "astc")]
+use crate::astc::{decode_block_mode, read_bits};
+use crate::CompressionVariant;
+
+/// The reason why a block is invalid.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum InvalidBlockReason {
+    /// The block uses a mode, that the format reserves: the BC7 mode byte 0, one of the four
+    /// reserved BC6H modes or a reserved or illegal ASTC block mode.
+    ReservedMode,
+    /// The data ends inside or before the block.
+    Truncated,
+    /// The data continues after the last block of the image. The reported block is the first
+    /// block below the image.
+    TrailingData,
+}
+
+/// The first invalid block of the data.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct InvalidBlock {
+    /// The column of the block. Multiply with the block footprint to get the pixel position.
+    pub x: u32,
+    /// The row of the block. Multiply with the block footprint to get the pixel position.
+    pub y: u32,
+    /// Why the block is invalid.
+    pub reason: InvalidBlockReason,
+}
+
+impl std::fmt::Display for InvalidBlock {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (x, y) = (self.x, self.y);
+        match self.reason {
+            InvalidBlockReason::ReservedMode => write!(f, "block ({x}, {y}) uses a reserved mode"),
+            InvalidBlockReason::Truncated => write!(f, "the data ends inside block ({x}, {y})"),
+            InvalidBlockReason::TrailingData => {
+                write!(f, "the data continues after the last block at ({x}, {y})")
+            }
+        }
+    }
+}
+
+impl std::error::Error for InvalidBlock {}
+
+/// Checks, that the blocks of an image with the given dimensions are well-formed.
+///
+/// Returns the first invalid block in row-major order. Blocks of BC1 to BC5, ETC2 and EAC have no
+/// reserved encodings, so only the size of their data is checked.
+///
+/// # Example
+/// ```
+/// use block_compression::{
+///     decode::{validate_blocks, InvalidBlockReason},
+///     CompressionVariant,
+/// };
+///
+/// let blocks_data = vec![0u8; CompressionVariant::BC1.blocks_byte_size(64, 64) - 4];
+/// let error = validate_blocks(CompressionVariant::BC1, 64, 64, &blocks_data).unwrap_err();
+///
+/// assert_eq!((error.x, error.y), (15, 15));
+/// assert_eq!(error.reason, InvalidBlockReason::Truncated);
+/// ```
+pub fn validate_blocks(
+    variant: CompressionVariant,
+    width: u32,
+    height: u32,
+    blocks_data: &[u8],
+) -> Result<(), InvalidBlock> {
+    let (footprint_width, footprint_height) = variant.block_footprint();
+    let blocks_x = width.div_ceil(footprint_width);
+    let blocks_y = height.div_ceil(footprint_height);
+    let block_byte_size = variant.block_byte_size() as usize;
+    let block_count = blocks_x as usize * blocks_y as usize;
+
+    let invalid_block = |index: usize, reason| InvalidBlock {
+        x: (index % blocks_x as usize) as u32,
+        y: (index / blocks_x as usize) as u32,
+        reason,
+    };
+
+    for (index, block) in blocks_data
+        .chunks_exact(block_byte_size)
+        .take(block_count)
+        .enumerate()
+    {
+        if !is_valid_block(variant, block) {
+            return Err(invalid_block(index, InvalidBlockReason::ReservedMode));
+        }
+    }
+
+    let expected_size = block_count * block_byte_size;
+    if blocks_data.len() < expected_size {
+        let index = blocks_data.len() / block_byte_size;
+        return Err(invalid_block(index, InvalidBlockReason::Truncated));
+    }
+    if blocks_data.len() > expected_size {
+        return Err(InvalidBlock {
+            x: 0,
+            y: blocks_y,
+            reason: InvalidBlockReason::TrailingData,
+        });
+    }
+
+    Ok(())
+}
+
+/// Helper function to decompress block data into RGBA8 data, that rejects invalid blocks.
+///
+/// The data is validated with [`validate_blocks`] before decoding, so nothing is written into
+/// `rgba_data` if a block is invalid.
+///
+/// # Panics
+/// - The `rgba_data` has not the expected size (`width * height * 4`)
+pub fn decompress_blocks_as_rgba8_validated(
+    variant: CompressionVariant,
+    width: u32,
+    height: u32,
+    blocks_data: &[u8],
+    rgba_data: &mut [u8],
+) -> Result<(), InvalidBlock> {
+    validate_blocks(variant, width, height, blocks_data)?;
+    super::decompress_blocks_as_rgba8(variant, width, height, blocks_data, rgba_data);
+    Ok(())
+}
+
+#[allow(unused_variables)]
+fn is_valid_block(variant: CompressionVariant, block: &[u8]) -> bool {
+    match variant {
+        #[cfg(feature = "bc6h")]
+        CompressionVariant::BC6H(..) => parse_block_bc6h(block, false).is_some(),
+        #[cfg(feature = "bc7")]
+        CompressionVariant::BC7(..) => parse_block_bc7(block).is_some(),
+        #[cfg(feature = "astc")]
+        CompressionVariant::ASTC(block_size) => {
+            let block = u128::from_le_bytes(block[..16].try_into().unwrap());
+
+            // Void-extent blocks have no block mode.
+            if read_bits(block, 9, 0) == 0x1FC {
+                return true;
+            }
+
+            // Weight grids, that are larger than the footprint, are illegal too.
+            let (block_width, block_height) = block_size.dimensions();
+            decode_block_mode(read_bits(block, 11, 0)).is_some_and(|mode| {
+                mode.grid_width <= block_width && mode.grid_height <= block_height
+            })
+        }
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_size_errors() {
+        let variant = CompressionVariant::BC4;
+        let blocks_data = [0u8; 8 * 6];
+
+        assert_eq!(validate_blocks(variant, 12, 8, &blocks_data), Ok(()));
+        assert_eq!(
+            validate_blocks(variant, 12, 8, &blocks_data[..8 * 4 + 3]),
+            Err(InvalidBlock {
+                x: 1,
+                y: 1,
+                reason: InvalidBlockReason::Truncated,
+            })
+        );
+        assert_eq!(
+            validate_blocks(variant, 12, 4, &blocks_data),
+            Err(InvalidBlock {
+                x: 0,
+                y: 1,
+                reason: InvalidBlockReason::TrailingData,
+            })
+        );
+    }
+
+    #[cfg(feature = "bc7")]
+    #[test]
+    fn test_bc7_mode_byte_zero() {
+        let variant = CompressionVariant::BC7(crate::BC7Settings::opaque_ultra_fast());
+
+        // Mode 6 blocks followed by a block without any mode bit.
+        let mut blocks_data = [0u8; 16 * 4];
+        for block in blocks_data.chunks_exact_mut(16) {
+            block[0] = 0b0100_0000;
+        }
+        assert_eq!(validate_blocks(variant, 8, 8, &blocks_data), Ok(()));
+
+        blocks_data[48] = 0;
+        let mut rgba_data = [0x11u8; 8 * 8 * 4];
+        let error =
+            decompress_blocks_as_rgba8_validated(variant, 8, 8, &blocks_data, &mut rgba_data);
+
+        assert_eq!(
+            error,
+            Err(InvalidBlock {
+                x: 1,
+                y: 1,
+                reason: InvalidBlockReason::ReservedMode,
+            })
+        );
+        assert_eq!(rgba_data, [0x11; 8 * 8 * 4]);
+    }
+
+    #[cfg(feature = "bc6h")]
+    #[test]
+    fn test_bc6h_reserved_modes() {
+        let variant = CompressionVariant::BC6H(crate::BC6HSettings::basic());
+
+        for mode in 0..32u8 {
+            let mut block = [0u8; 16];
+            block[0] = mode;
+
+            let reserved = matches!(mode, 0b10011 | 0b10111 | 0b11011 | 0b11111);
+            let result = validate_blocks(variant, 4, 4, &block);
+            assert_eq!(result.is_err(), reserved, "mode {mode:#07b}");
+        }
+    }
+
+    #[cfg(feature = "astc")]
+    #[test]
+    fn test_astc_reserved_block_mode() {
+        let variant = CompressionVariant::ASTC(crate::ASTCBlockSize::Block4x4);
+
+        // A void-extent block is valid, an all zero block uses a reserved block mode.
+        let mut blocks_data = [0u8; 32];
+        blocks_data[0] = 0xFC;
+        blocks_data[1] = 0xFD;
+        blocks_data[2..8].fill(0xFF);
+
+        assert_eq!(
+            validate_blocks(variant, 8, 4, &blocks_data),
+            Err(InvalidBlock {
+                x: 1,
+                y: 0,
+                reason: InvalidBlockReason::ReservedMode,
+            })
+        );
+    }
+}
@@ -1,14 +1,29 @@
 //! CPU based encoding.
 
-use crate::CompressionVariant;
+#[cfg(feature = "astc")]
+mod astc;
+#[cfg(feature = "etc2")]
+mod etc;
+mod strict;
+
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+use crate::{AlphaOptions, CompressionVariant};
 
 struct BlockCompressorBC15 {
     block: [f32; 64],
+    weight_color_by_alpha: bool,
 }
 
 impl Default for BlockCompressorBC15 {
     fn default() -> Self {
-        Self { block: [0.0; 64] }
+        Self {
+            block: [0.0; 64],
+            weight_color_by_alpha: false,
+        }
     }
 }
 
@@ -40,6 +55,38 @@ impl BlockCompressorBC15 {
         }
     }
 
+    /// Applies the alpha options to a block, that was loaded with interleaved RGBA.
+    fn apply_alpha_options(&mut self, options: AlphaOptions) {
+        if options.premultiply {
+            for k in 0..16 {
+                let alpha = self.block[48 + k] / 255.0;
+                for p in 0..3 {
+                    self.block[k + p * 16] *= alpha;
+                }
+            }
+        }
+
+        self.weight_color_by_alpha = options.weight_color_by_alpha;
+    }
+
+    /// The weight of the color error of a texel. Fully transparent texels keep a small weight, so
+    /// that the endpoint fit of a fully transparent block stays well defined.
+    fn color_weight(&self, k: usize) -> f32 {
+        if self.weight_color_by_alpha {
+            f32::max(self.block[48 + k], 1.0) / 255.0
+        } else {
+            1.0
+        }
+    }
+
+    /// Returns true, if the texel should be ignored when picking the color endpoints. Only fully
+    /// transparent texels are ignored and only if the block has at least one visible texel.
+    fn is_hidden(&self, k: usize) -> bool {
+        self.weight_color_by_alpha
+            && self.block[48 + k] == 0.0
+            && self.block[48..].iter().any(|&alpha| alpha > 0.0)
+    }
+
     fn load_block_r_8bit(&mut self, rgba_data: &[u8], xx: usize, yy: usize, stride: usize) {
         for y in 0..4 {
             for x in 0..4 {
@@ -120,12 +167,17 @@ impl BlockCompressorBC15 {
     }
 
     fn compute_covar_dc(&self, covar: &mut [f32; 6], dc: &mut [f32; 3]) {
+        let mut weight_sum = 0.0;
+        for k in 0..16 {
+            weight_sum += self.color_weight(k);
+        }
+
         for (p, value) in dc.iter_mut().enumerate() {
             let mut acc = 0.0;
             for k in 0..16 {
-                acc += self.block[k + p * 16];
+                acc += self.color_weight(k) * self.block[k + p * 16];
             }
-            *value = acc / 16.0;
+            *value = acc / weight_sum;
         }
 
         let mut covar0 = 0.0;
@@ -136,16 +188,17 @@ impl BlockCompressorBC15 {
         let mut covar5 = 0.0;
 
         for k in 0..16 {
+            let weight = self.color_weight(k);
             let rgb0 = self.block[k] - dc[0];
             let rgb1 = self.block[k + 16] - dc[1];
             let rgb2 = self.block[k + 32] - dc[2];
 
-            covar0 += rgb0 * rgb0;
-            covar1 += rgb0 * rgb1;
-            covar2 += rgb0 * rgb2;
-            covar3 += rgb1 * rgb1;
-            covar4 += rgb1 * rgb2;
-            covar5 += rgb2 * rgb2;
+            covar0 += weight * rgb0 * rgb0;
+            covar1 += weight * rgb0 * rgb1;
+            covar2 += weight * rgb0 * rgb2;
+            covar3 += weight * rgb1 * rgb1;
+            covar4 += weight * rgb1 * rgb2;
+            covar5 += weight * rgb2 * rgb2;
         }
 
         covar[0] = covar0;
@@ -193,6 +246,10 @@ impl BlockCompressorBC15 {
 
         for y in 0..4 {
             for x in 0..4 {
+                if self.is_hidden(y * 4 + x) {
+                    continue;
+                }
+
                 let mut dot = 0.0;
                 for p in 0..3 {
                     dot += (self.block[p * 16 + y * 4 + x] - dc[p]) * axis[p];
@@ -294,6 +351,7 @@ impl BlockCompressorBC15 {
             c1.copy_from_slice(&dc[..]);
         } else {
             let mut atb1 = [0.0; 3];
+            let mut sum_w = 0.0;
             let mut sum_q = 0.0;
             let mut sum_qq = 0.0;
             let mut shifted_bits = bits;
@@ -302,13 +360,15 @@ impl BlockCompressorBC15 {
                 let q = (shifted_bits & 3) as f32;
                 shifted_bits >>= 2;
 
+                let weight = self.color_weight(k);
                 let x = 3.0 - q;
 
-                sum_q += q;
-                sum_qq += q * q;
+                sum_w += weight;
+                sum_q += weight * q;
+                sum_qq += weight * q * q;
 
                 for (p, value) in atb1.iter_mut().enumerate() {
-                    *value += x * self.block[k + p * 16];
+                    *value += weight * x * self.block[k + p * 16];
                 }
             }
 
@@ -316,11 +376,11 @@ impl BlockCompressorBC15 {
             let mut atb2 = [0.0; 3];
 
             for p in 0..3 {
-                sum[p] = dc[p] * 16.0;
+                sum[p] = dc[p] * sum_w;
                 atb2[p] = 3.0 * sum[p] - atb1[p];
             }
 
-            let cxx = 16.0 * 9.0 - 2.0 * 3.0 * sum_q + sum_qq;
+            let cxx = sum_w * 9.0 - 2.0 * 3.0 * sum_q + sum_qq;
             let cyy = sum_qq;
             let cxy = 3.0 * sum_q - sum_qq;
             let scale = 3.0 * (cxx * cyy - cxy * cxy).recip();
@@ -437,10 +497,11 @@ impl BlockCompressorBC15 {
     }
 }
 
-/// Compresses raw RGBA8 data into BC1-5 block compressed format.
+/// Compresses raw RGBA8 data into BC1-5, ETC2, EAC or ASTC block compressed format.
 ///
 /// This function provides CPU-based texture compression for RGBA8 data. It supports BC1 through BC5
-/// compression formats, with BC6H and BC7 being currently unimplemented.
+/// and the ETC2, EAC and ASTC compression formats, with BC6H and BC7 being currently
+/// unimplemented.
 ///
 /// # Data Layout Requirements
 /// The input data must be in RGBA8 format (8 bits per channel, 32 bits per pixel). The data is
@@ -463,10 +524,10 @@ impl BlockCompressorBC15 {
 /// * `width` - Width of the image in pixels
 /// * `height` - Height of the image in pixels
 /// * `stride` - Number of bytes per row in the source data (for padding).
-///              Must be `width * 4` for tightly packed RGBA data.
+///   Must be `width * 4` for tightly packed RGBA data.
 ///
 /// # Panics
-/// * If `width` or `height` is not a multiple of 4
+/// * If `width` or `height` is not a multiple of 4 (except for ASTC, which supports any size)
 /// * If the destination `blocks_buffer` is too small to hold the compressed data
 /// * If BC6H or BC7 compression is requested (currently unimplemented)
 ///
@@ -499,10 +560,41 @@ pub fn compress_rgba8(
     height: u32,
     stride: u32,
 ) {
-    assert_eq!(height % 4, 0);
-    assert_eq!(width % 4, 0);
+    compress_rgba8_with_alpha(
+        variation,
+        rgba_data,
+        blocks_buffer,
+        width,
+        height,
+        stride,
+        AlphaOptions::default(),
+    );
+}
+
+/// Compresses raw RGBA8 data like [`compress_rgba8()`], with options for how the alpha channel
+/// influences the color encoding.
+///
+/// The [`AlphaOptions`] are used by BC2 and BC3 and ignored by all other variants.
+///
+/// # Panics
+/// * If `width` or `height` is not a multiple of 4 (except for ASTC, which supports any size)
+/// * If the destination `blocks_buffer` is too small to hold the compressed data
+/// * If BC6H or BC7 compression is requested (currently unimplemented)
+pub fn compress_rgba8_with_alpha(
+    variation: CompressionVariant,
+    rgba_data: &[u8],
+    blocks_buffer: &mut [u8],
+    width: u32,
+    height: u32,
+    stride: u32,
+    alpha_options: AlphaOptions,
+) {
+    if !variation.supports_any_size() {
+        assert_eq!(height % 4, 0);
+        assert_eq!(width % 4, 0);
+    }
 
-    let required_size = CompressionVariant::BC1.blocks_byte_size(width, height);
+    let required_size = variation.blocks_byte_size(width, height);
 
     assert!(
         blocks_buffer.len() >= required_size,
@@ -512,18 +604,32 @@ pub fn compress_rgba8(
     );
 
     let stride = stride as usize;
-    let block_width = (width as usize + 3) / 4;
-    let block_height = (height as usize + 3) / 4;
+    let block_width = (width as usize).div_ceil(4);
+    let block_height = (height as usize).div_ceil(4);
 
     match variation {
         CompressionVariant::BC1 => {
             compress_bc1(rgba_data, blocks_buffer, block_width, block_height, stride);
         }
         CompressionVariant::BC2 => {
-            compress_bc2(rgba_data, blocks_buffer, block_width, block_height, stride);
+            compress_bc2(
+                rgba_data,
+                blocks_buffer,
+                block_width,
+                block_height,
+                stride,
+                alpha_options,
+            );
         }
         CompressionVariant::BC3 => {
-            compress_bc3(rgba_data, blocks_buffer, block_width, block_height, stride);
+            compress_bc3(
+                rgba_data,
+                blocks_buffer,
+                block_width,
+                block_height,
+                stride,
+                alpha_options,
+            );
         }
         CompressionVariant::BC4 => {
             compress_bc4(rgba_data, blocks_buffer, block_width, block_height, stride);
@@ -531,13 +637,239 @@ pub fn compress_rgba8(
         CompressionVariant::BC5 => {
             compress_bc5(rgba_data, blocks_buffer, block_width, block_height, stride);
         }
+        #[cfg(feature = "bc6h")]
         CompressionVariant::BC6H(_) => {
             unimplemented!("CPU based BC6H compression not yet implemented yet");
         }
+        #[cfg(feature = "bc7")]
         CompressionVariant::BC7(_) => {
             unimplemented!("CPU based BC7 compression not yet implemented yet");
         }
+        #[cfg(feature = "etc2")]
+        CompressionVariant::ETC2RGB8 => {
+            etc::compress_etc2_rgb8(rgba_data, blocks_buffer, block_width, block_height, stride);
+        }
+        #[cfg(feature = "etc2")]
+        CompressionVariant::ETC2RGB8A1 => {
+            etc::compress_etc2_rgb8a1(rgba_data, blocks_buffer, block_width, block_height, stride);
+        }
+        #[cfg(feature = "etc2")]
+        CompressionVariant::ETC2RGBA8 => {
+            etc::compress_etc2_rgba8(rgba_data, blocks_buffer, block_width, block_height, stride);
+        }
+        #[cfg(feature = "etc2")]
+        CompressionVariant::EACR11 => {
+            etc::compress_eac_r11(rgba_data, blocks_buffer, block_width, block_height, stride);
+        }
+        #[cfg(feature = "etc2")]
+        CompressionVariant::EACRG11 => {
+            etc::compress_eac_rg11(rgba_data, blocks_buffer, block_width, block_height, stride);
+        }
+        #[cfg(feature = "astc")]
+        CompressionVariant::ASTC(block_size) => {
+            astc::compress_astc(
+                rgba_data,
+                blocks_buffer,
+                width as usize,
+                height as usize,
+                stride,
+                block_size,
+            );
+        }
+    }
+}
+
+/// Compresses raw RGBA8 data into BC1 to BC5 like [`compress_rgba8_with_alpha()`], using only
+/// integer arithmetic.
+///
+/// The regular encoders use floating point math, whose rounding differs slightly between CPUs and
+/// GPU backends. The strict mode avoids this and produces exactly the same blocks as the GPU
+/// encoder with [`GpuBlockCompressor::set_strict_mode()`](crate::GpuBlockCompressor::set_strict_mode)
+/// on every backend. This allows blocks compressed on the CPU and on the GPU to be used
+/// interchangeably, for example in a build cache. The quality is close to the regular encoders.
+///
+/// All other variants are compressed like with [`compress_rgba8_with_alpha()`].
+///
+/// # Panics
+/// * If `width` or `height` is not a multiple of 4 (except for ASTC, which supports any size)
+/// * If the destination `blocks_buffer` is too small to hold the compressed data
+/// * If BC6H or BC7 compression is requested (currently unimplemented)
+pub fn compress_rgba8_strict(
+    variation: CompressionVariant,
+    rgba_data: &[u8],
+    blocks_buffer: &mut [u8],
+    width: u32,
+    height: u32,
+    stride: u32,
+    alpha_options: AlphaOptions,
+) {
+    if !variation.supports_strict_mode() {
+        compress_rgba8_with_alpha(
+            variation,
+            rgba_data,
+            blocks_buffer,
+            width,
+            height,
+            stride,
+            alpha_options,
+        );
+        return;
+    }
+
+    assert_eq!(height % 4, 0);
+    assert_eq!(width % 4, 0);
+
+    let required_size = variation.blocks_byte_size(width, height);
+
+    assert!(
+        blocks_buffer.len() >= required_size,
+        "blocks_buffer size ({}) is too small to hold compressed blocks. Required size: {}",
+        blocks_buffer.len(),
+        required_size
+    );
+
+    strict::compress_strict(
+        variation,
+        rgba_data,
+        blocks_buffer,
+        width as usize / 4,
+        height as usize / 4,
+        stride as usize,
+        alpha_options,
+    );
+}
+
+/// Shared flag to cancel a running [`compress_rgba8_with_progress()`] from another thread.
+///
+/// Clones share the same flag.
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    /// Creates a new token, that is not cancelled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Requests the cancellation of every compression, that checks this token.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns `true` once [`CancellationToken::cancel()`] was called.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// How a compression with [`compress_rgba8_with_progress()`] ended.
+#[must_use]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum EncodeStatus {
+    /// All blocks were compressed.
+    Completed,
+    /// The compression was cancelled. Only the block rows before the cancellation were written.
+    Cancelled,
+}
+
+/// Compresses raw RGBA8 data like [`compress_rgba8_with_alpha()`], one row of blocks at a time.
+///
+/// After each row of blocks `progress` is called with the number of compressed blocks and the
+/// total number of blocks. The `cancellation_token` is checked before each row of blocks, so a
+/// cancelled compression returns [`EncodeStatus::Cancelled`] after at most one more row.
+///
+/// # Panics
+/// * If `width` or `height` is not a multiple of 4 (except for ASTC, which supports any size)
+/// * If the destination `blocks_buffer` is too small to hold the compressed data
+/// * If BC6H or BC7 compression is requested (currently unimplemented)
+///
+/// # Example
+/// ```
+/// use block_compression::{
+///     encode::{compress_rgba8_with_progress, CancellationToken, EncodeStatus},
+///     AlphaOptions, CompressionVariant,
+/// };
+///
+/// let rgba_data = vec![0u8; 256 * 256 * 4];
+/// let variant = CompressionVariant::BC1;
+/// let mut blocks_buffer = vec![0u8; variant.blocks_byte_size(256, 256)];
+/// let cancellation_token = CancellationToken::new();
+///
+/// let status = compress_rgba8_with_progress(
+///     variant,
+///     &rgba_data,
+///     &mut blocks_buffer,
+///     256,
+///     256,
+///     256 * 4,
+///     AlphaOptions::default(),
+///     |done, total| println!("{done} of {total} blocks"),
+///     &cancellation_token,
+/// );
+/// assert_eq!(status, EncodeStatus::Completed);
+/// ```
+#[allow(clippy::too_many_arguments)]
+pub fn compress_rgba8_with_progress(
+    variation: CompressionVariant,
+    rgba_data: &[u8],
+    blocks_buffer: &mut [u8],
+    width: u32,
+    height: u32,
+    stride: u32,
+    alpha_options: AlphaOptions,
+    mut progress: impl FnMut(usize, usize),
+    cancellation_token: &CancellationToken,
+) -> EncodeStatus {
+    if !variation.supports_any_size() {
+        assert_eq!(height % 4, 0);
+        assert_eq!(width % 4, 0);
+    }
+
+    let required_size = variation.blocks_byte_size(width, height);
+
+    assert!(
+        blocks_buffer.len() >= required_size,
+        "blocks_buffer size ({}) is too small to hold compressed blocks. Required size: {}",
+        blocks_buffer.len(),
+        required_size
+    );
+
+    let (footprint_width, footprint_height) = variation.block_footprint();
+    let block_width = width.div_ceil(footprint_width) as usize;
+    let block_height = height.div_ceil(footprint_height);
+    let total_blocks = block_width * block_height as usize;
+    let row_size = block_width * variation.block_byte_size() as usize;
+
+    // Like compress_rgba8(), empty images have nothing to compress.
+    if total_blocks == 0 {
+        return EncodeStatus::Completed;
+    }
+
+    // Every row of blocks only reads its own pixel rows, so each row is compressed as an image of
+    // its own. The last ASTC row can be shorter than the block footprint.
+    for (row, row_blocks) in (0..block_height).zip(blocks_buffer.chunks_exact_mut(row_size)) {
+        if cancellation_token.is_cancelled() {
+            return EncodeStatus::Cancelled;
+        }
+
+        let y = row * footprint_height;
+        let row_height = footprint_height.min(height - y);
+        let row_data = &rgba_data[y as usize * stride as usize..];
+
+        compress_rgba8_with_alpha(
+            variation,
+            row_data,
+            row_blocks,
+            width,
+            row_height,
+            stride,
+            alpha_options,
+        );
+
+        progress((row as usize + 1) * block_width, total_blocks);
     }
+
+    EncodeStatus::Completed
 }
 
 fn compress_bc1(
@@ -564,6 +896,7 @@ fn compress_bc2(
     block_width: usize,
     block_height: usize,
     stride: usize,
+    alpha_options: AlphaOptions,
 ) {
     for yy in 0..block_height {
         for xx in 0..block_width {
@@ -576,6 +909,7 @@ fn compress_bc2(
             compressed_data[1] = alpha_result[1];
 
             block_compressor.load_block_interleaved_rgba(rgba_data, xx, yy, stride);
+            block_compressor.apply_alpha_options(alpha_options);
 
             let color_result = block_compressor.compress_block_bc1_core();
             compressed_data[2] = color_result[0];
@@ -592,6 +926,7 @@ fn compress_bc3(
     block_width: usize,
     block_height: usize,
     stride: usize,
+    alpha_options: AlphaOptions,
 ) {
     for yy in 0..block_height {
         for xx in 0..block_width {
@@ -600,6 +935,7 @@ fn compress_bc3(
             let mut compressed_data = [0; 4];
 
             block_compressor.load_block_interleaved_rgba(rgba_data, xx, yy, stride);
+            block_compressor.apply_alpha_options(alpha_options);
 
             let alpha_result = block_compressor.compress_block_bc3_alpha();
             compressed_data[0] = alpha_result[0];
@@ -667,3 +1003,253 @@ fn compress_bc5(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        decode::decompress_blocks_as_rgba8,
+        encode::{
+            compress_rgba8, compress_rgba8_strict, compress_rgba8_with_alpha,
+            compress_rgba8_with_progress, CancellationToken, EncodeStatus,
+        },
+        AlphaOptions, CompressionVariant,
+    };
+
+    /// A single block with a red gradient in the opaque left half and bright green in the fully
+    /// transparent right half.
+    fn test_block() -> Vec<u8> {
+        let mut rgba_data = Vec::with_capacity(16 * 4);
+        for y in 0..4u8 {
+            for x in 0..4u8 {
+                if x < 2 {
+                    rgba_data.extend_from_slice(&[100 + y * 40 + x * 20, 20, 10, 255]);
+                } else {
+                    rgba_data.extend_from_slice(&[0, 255, 200, 0]);
+                }
+            }
+        }
+        rgba_data
+    }
+
+    fn roundtrip(variant: CompressionVariant, rgba_data: &[u8], options: AlphaOptions) -> Vec<u8> {
+        let mut blocks = vec![0; variant.blocks_byte_size(4, 4)];
+        compress_rgba8_with_alpha(variant, rgba_data, &mut blocks, 4, 4, 16, options);
+
+        let mut decoded = vec![0; rgba_data.len()];
+        decompress_blocks_as_rgba8(variant, 4, 4, &blocks, &mut decoded);
+        decoded
+    }
+
+    fn visible_color_error(original: &[u8], decoded: &[u8]) -> u32 {
+        original
+            .chunks_exact(4)
+            .zip(decoded.chunks_exact(4))
+            .filter(|(a, _)| a[3] > 0)
+            .flat_map(|(a, b)| (0..3).map(move |channel| a[channel].abs_diff(b[channel]) as u32))
+            .map(|diff| diff * diff)
+            .sum()
+    }
+
+    #[test]
+    fn alpha_weighting_reduces_visible_error() {
+        let rgba_data = test_block();
+
+        for variant in [CompressionVariant::BC2, CompressionVariant::BC3] {
+            let unweighted = roundtrip(variant, &rgba_data, AlphaOptions::default());
+            let weighted = roundtrip(
+                variant,
+                &rgba_data,
+                AlphaOptions {
+                    weight_color_by_alpha: true,
+                    ..Default::default()
+                },
+            );
+
+            assert!(
+                visible_color_error(&rgba_data, &weighted)
+                    < visible_color_error(&rgba_data, &unweighted),
+                "{variant:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn premultiplied_colors_of_transparent_texels_are_black() {
+        let rgba_data = test_block();
+        let options = AlphaOptions {
+            premultiply: true,
+            ..Default::default()
+        };
+
+        let decoded = roundtrip(CompressionVariant::BC3, &rgba_data, options);
+
+        for (original, decoded) in rgba_data.chunks_exact(4).zip(decoded.chunks_exact(4)) {
+            if original[3] == 0 {
+                assert!(decoded[..3].iter().all(|&value| value < 16), "{decoded:?}");
+            }
+        }
+    }
+
+    fn gradient(width: u32, height: u32) -> Vec<u8> {
+        (0..height)
+            .flat_map(|y| (0..width).flat_map(move |x| [x as u8 * 9, y as u8 * 7, 128, 255]))
+            .collect()
+    }
+
+    #[test]
+    fn progress_matches_compression_without_progress() {
+        let mut variants = vec![CompressionVariant::BC1, CompressionVariant::BC3];
+        #[cfg(feature = "astc")]
+        variants.push(CompressionVariant::ASTC(crate::ASTCBlockSize::Block6x5));
+
+        for variant in variants {
+            let (width, height) = if variant.supports_any_size() {
+                (18, 13)
+            } else {
+                (16, 12)
+            };
+            let rgba_data = gradient(width, height);
+
+            let mut expected = vec![0; variant.blocks_byte_size(width, height)];
+            compress_rgba8(variant, &rgba_data, &mut expected, width, height, width * 4);
+
+            let mut blocks = vec![0; expected.len()];
+            let mut reports = Vec::new();
+            let status = compress_rgba8_with_progress(
+                variant,
+                &rgba_data,
+                &mut blocks,
+                width,
+                height,
+                width * 4,
+                AlphaOptions::default(),
+                |done, total| reports.push((done, total)),
+                &CancellationToken::new(),
+            );
+
+            assert_eq!(status, EncodeStatus::Completed);
+            assert_eq!(blocks, expected, "{variant:?}");
+
+            let (footprint_width, footprint_height) = variant.block_footprint();
+            let block_width = width.div_ceil(footprint_width) as usize;
+            let block_height = height.div_ceil(footprint_height) as usize;
+            let total = block_width * block_height;
+            assert_eq!(reports.len(), block_height);
+            assert_eq!(reports.last(), Some(&(total, total)));
+        }
+    }
+
+    #[test]
+    fn strict_mode_error_is_close_to_regular_encoder() {
+        let (width, height) = (32, 32);
+        let rgba_data: Vec<u8> = (0..height)
+            .flat_map(|y: u32| {
+                (0..width).flat_map(move |x: u32| {
+                    let noise = (x * 7919 + y * 104729).wrapping_mul(2654435761) >> 27;
+                    [
+                        (x * 8 + noise) as u8,
+                        (y * 6 + noise * 2) as u8,
+                        ((x + y) * 3) as u8,
+                        (x * y) as u8,
+                    ]
+                })
+            })
+            .collect();
+
+        let squared_error = |blocks: &[u8], variant: CompressionVariant| -> u64 {
+            let mut decoded = vec![0; rgba_data.len()];
+            decompress_blocks_as_rgba8(variant, width, height, blocks, &mut decoded);
+            let channels = match variant {
+                CompressionVariant::BC1 => 3,
+                CompressionVariant::BC4 => 1,
+                CompressionVariant::BC5 => 2,
+                _ => 4,
+            };
+            rgba_data
+                .chunks_exact(4)
+                .zip(decoded.chunks_exact(4))
+                .flat_map(|(a, b)| (0..channels).map(move |c| a[c].abs_diff(b[c]) as u64))
+                .map(|diff| diff * diff)
+                .sum()
+        };
+
+        for variant in [
+            CompressionVariant::BC1,
+            CompressionVariant::BC2,
+            CompressionVariant::BC3,
+            CompressionVariant::BC4,
+            CompressionVariant::BC5,
+        ] {
+            let mut regular = vec![0; variant.blocks_byte_size(width, height)];
+            compress_rgba8(variant, &rgba_data, &mut regular, width, height, width * 4);
+
+            let mut strict = vec![0; regular.len()];
+            compress_rgba8_strict(
+                variant,
+                &rgba_data,
+                &mut strict,
+                width,
+                height,
+                width * 4,
+                AlphaOptions::default(),
+            );
+
+            let regular_error = squared_error(&regular, variant);
+            let strict_error = squared_error(&strict, variant);
+            assert!(
+                strict_error * 4 <= regular_error * 5,
+                "{variant:?}: strict error {strict_error}, regular error {regular_error}"
+            );
+        }
+    }
+
+    #[test]
+    fn cancellation_stops_between_rows() {
+        let rgba_data = gradient(16, 16);
+        let mut blocks = vec![0; CompressionVariant::BC1.blocks_byte_size(16, 16)];
+        let cancellation_token = CancellationToken::new();
+        let mut reports = Vec::new();
+
+        let status = compress_rgba8_with_progress(
+            CompressionVariant::BC1,
+            &rgba_data,
+            &mut blocks,
+            16,
+            16,
+            16 * 4,
+            AlphaOptions::default(),
+            |done, total| {
+                reports.push((done, total));
+                cancellation_token.cancel();
+            },
+            &cancellation_token,
+        );
+
+        assert_eq!(status, EncodeStatus::Cancelled);
+        assert_eq!(reports, [(4, 16)]);
+        assert!(blocks[4 * 8..].iter().all(|&byte| byte == 0));
+    }
+
+    #[test]
+    fn progress_completes_empty_images() {
+        for (width, height) in [(0, 8), (8, 0), (0, 0)] {
+            let rgba_data = vec![0; (width * height * 4) as usize];
+            let mut reports = Vec::new();
+
+            let status = compress_rgba8_with_progress(
+                CompressionVariant::BC1,
+                &rgba_data,
+                &mut [],
+                width,
+                height,
+                width * 4,
+                AlphaOptions::default(),
+                |done, total| reports.push((done, total)),
+                &CancellationToken::new(),
+            );
+
+            assert_eq!(status, EncodeStatus::Completed, "{width}x{height}");
+            assert!(reports.is_empty());
+        }
+    }
+}
@@ -0,0 +1,530 @@
+//! CPU based encoding of the ASTC LDR block format.
+//!
+//! Every block is encoded as a single partition with one weight plane. Opaque blocks use the
+//! direct RGB endpoint mode, all others the direct RGBA endpoint mode. The endpoints are found by
+//! a principal component fit and refined once with a least squares fit of the decoded weights.
+//!
+//! The blocks are built as 128-bit values and stored in little-endian byte order. The GPU
+//! encoder in `ASTC.wgsl` is a port of this encoder.
+
+use crate::{
+    astc::{
+        infill_contributions, infill_weights, unquantize_color, unquantize_weight, write_ise,
+        EncoderParameters, MAX_BLOCK_TEXELS, QUANT_LEVELS,
+    },
+    ASTCBlockSize,
+};
+
+/// Endpoints and weights of a block and the resulting squared error.
+#[derive(Copy, Clone)]
+struct Candidate {
+    color_values: [u8; 8],
+    grid_weights: [u8; 64],
+    error: f32,
+}
+
+struct BlockCompressorASTC {
+    block: [f32; 4 * MAX_BLOCK_TEXELS],
+    parameters: EncoderParameters,
+    texel_count: usize,
+    color_quant: usize,
+    color_endpoint_mode: u32,
+}
+
+impl BlockCompressorASTC {
+    fn new(parameters: EncoderParameters) -> Self {
+        Self {
+            block: [0.0; 4 * MAX_BLOCK_TEXELS],
+            parameters,
+            texel_count: (parameters.block_width * parameters.block_height) as usize,
+            color_quant: parameters.color_quant_rgba as usize,
+            color_endpoint_mode: 12,
+        }
+    }
+
+    /// Loads the block in planar layout. Pixels outside the image repeat the last row and column.
+    fn load_block_interleaved_rgba(
+        &mut self,
+        rgba_data: &[u8],
+        width: usize,
+        height: usize,
+        xx: usize,
+        yy: usize,
+        stride: usize,
+    ) {
+        let block_width = self.parameters.block_width as usize;
+        let block_height = self.parameters.block_height as usize;
+
+        for y in 0..block_height {
+            for x in 0..block_width {
+                let pixel_x = (xx * block_width + x).min(width - 1);
+                let pixel_y = (yy * block_height + y).min(height - 1);
+
+                let offset = pixel_y * stride + pixel_x * 4;
+                let texel = y * block_width + x;
+
+                for channel in 0..4 {
+                    self.block[channel * MAX_BLOCK_TEXELS + texel] =
+                        rgba_data[offset + channel] as f32;
+                }
+            }
+        }
+
+        let opaque = self.block[3 * MAX_BLOCK_TEXELS..][..self.texel_count]
+            .iter()
+            .all(|&alpha| alpha == 255.0);
+
+        if opaque {
+            self.color_quant = self.parameters.color_quant_rgb as usize;
+            self.color_endpoint_mode = 8;
+        }
+    }
+
+    #[inline(always)]
+    fn texel(&self, texel: usize) -> [f32; 4] {
+        [
+            self.block[texel],
+            self.block[MAX_BLOCK_TEXELS + texel],
+            self.block[2 * MAX_BLOCK_TEXELS + texel],
+            self.block[3 * MAX_BLOCK_TEXELS + texel],
+        ]
+    }
+
+    fn compress_block_astc(&self) -> u128 {
+        let (e0, e1) = self.principal_component_endpoints();
+        let mut best = self.evaluate_endpoints(e0, e1);
+
+        let (e0, e1) = self.refine_endpoints(&best);
+        let refined = self.evaluate_endpoints(e0, e1);
+        if refined.error < best.error {
+            best = refined;
+        }
+
+        self.pack_block(&best)
+    }
+
+    /// Fits a line through the colors of the block and returns its extent.
+    fn principal_component_endpoints(&self) -> ([f32; 4], [f32; 4]) {
+        let count = self.texel_count as f32;
+
+        let mut mean = [0.0; 4];
+        for texel in 0..self.texel_count {
+            let color = self.texel(texel);
+            for channel in 0..4 {
+                mean[channel] += color[channel];
+            }
+        }
+        for value in mean.iter_mut() {
+            *value /= count;
+        }
+
+        let mut covariance = [[0.0f32; 4]; 4];
+        for texel in 0..self.texel_count {
+            let color = self.texel(texel);
+            for i in 0..4 {
+                for j in 0..4 {
+                    covariance[i][j] += (color[i] - mean[i]) * (color[j] - mean[j]);
+                }
+            }
+        }
+
+        // The power iteration starts with the row of the channel with the highest variance.
+        let mut best_row = 0;
+        for row in 1..4 {
+            if covariance[row][row] > covariance[best_row][best_row] {
+                best_row = row;
+            }
+        }
+        let mut axis = covariance[best_row];
+
+        for _ in 0..8 {
+            let mut next = [0.0; 4];
+            for i in 0..4 {
+                for j in 0..4 {
+                    next[i] += covariance[i][j] * axis[j];
+                }
+            }
+
+            let length = dot(next, next).sqrt();
+            if length < 1e-6 {
+                break;
+            }
+            axis = next.map(|value| value / length);
+        }
+
+        let length = dot(axis, axis).sqrt();
+        if length < 1e-6 {
+            return (mean, mean);
+        }
+        let axis = axis.map(|value| value / length);
+
+        let mut min_t = f32::MAX;
+        let mut max_t = f32::MIN;
+        for texel in 0..self.texel_count {
+            let color = self.texel(texel);
+            let t = dot(sub(color, mean), axis);
+            min_t = min_t.min(t);
+            max_t = max_t.max(t);
+        }
+
+        let mut e0 = [0.0; 4];
+        let mut e1 = [0.0; 4];
+        for channel in 0..4 {
+            e0[channel] = (mean[channel] + axis[channel] * min_t).clamp(0.0, 255.0);
+            e1[channel] = (mean[channel] + axis[channel] * max_t).clamp(0.0, 255.0);
+        }
+
+        (e0, e1)
+    }
+
+    /// Least squares fit of the endpoints to the decoded weights of a candidate.
+    fn refine_endpoints(&self, candidate: &Candidate) -> ([f32; 4], [f32; 4]) {
+        let [d0, d1] = self.decoded_endpoints(&candidate.color_values);
+        let texel_weights = self.texel_weights(&candidate.grid_weights);
+
+        let mut a = 0.0;
+        let mut b = 0.0;
+        let mut c = 0.0;
+        let mut r0 = [0.0; 4];
+        let mut r1 = [0.0; 4];
+
+        for (texel, &weight) in texel_weights[..self.texel_count].iter().enumerate() {
+            let color = self.texel(texel);
+            let w = weight as f32 / 64.0;
+            let iw = 1.0 - w;
+
+            a += iw * iw;
+            b += iw * w;
+            c += w * w;
+
+            for channel in 0..4 {
+                r0[channel] += iw * color[channel];
+                r1[channel] += w * color[channel];
+            }
+        }
+
+        let det = a * c - b * b;
+        if det.abs() < 1e-6 {
+            return (d0, d1);
+        }
+
+        let mut e0 = [0.0; 4];
+        let mut e1 = [0.0; 4];
+        for channel in 0..4 {
+            e0[channel] = ((c * r0[channel] - b * r1[channel]) / det).clamp(0.0, 255.0);
+            e1[channel] = ((a * r1[channel] - b * r0[channel]) / det).clamp(0.0, 255.0);
+        }
+
+        (e0, e1)
+    }
+
+    /// Quantizes the endpoints, finds the best weights and calculates the error of the block.
+    fn evaluate_endpoints(&self, e0: [f32; 4], e1: [f32; 4]) -> Candidate {
+        let mut color_values = [0; 8];
+        for channel in 0..4 {
+            color_values[channel * 2] = quantize_color(self.color_quant, e0[channel]);
+            color_values[channel * 2 + 1] = quantize_color(self.color_quant, e1[channel]);
+        }
+
+        // The decoder swaps endpoints and applies blue contraction, if the second endpoint is
+        // darker than the first. We store them in the order that avoids this.
+        let sum = |offset: usize| -> u32 {
+            (0..3)
+                .map(|channel| {
+                    unquantize_color(self.color_quant, color_values[channel * 2 + offset]) as u32
+                })
+                .sum()
+        };
+        if sum(1) < sum(0) {
+            for channel in 0..4 {
+                color_values.swap(channel * 2, channel * 2 + 1);
+            }
+        }
+
+        let [d0, d1] = self.decoded_endpoints(&color_values);
+        let direction = sub(d1, d0);
+        let length_squared = dot(direction, direction);
+
+        // Ideal weight of every texel.
+        let mut ideal_weights = [0.0; MAX_BLOCK_TEXELS];
+        if length_squared > 0.0 {
+            for (texel, ideal_weight) in ideal_weights[..self.texel_count].iter_mut().enumerate() {
+                let t = dot(sub(self.texel(texel), d0), direction) / length_squared;
+                *ideal_weight = t.clamp(0.0, 1.0) * 64.0;
+            }
+        }
+
+        // Each grid weight is the average of the ideal weights of the texels it contributes to.
+        let grid_count = (self.parameters.grid_width * self.parameters.grid_height) as usize;
+        let mut weight_sums = [0.0; 64];
+        let mut factor_sums = [0.0; 64];
+
+        for y in 0..self.parameters.block_height {
+            for x in 0..self.parameters.block_width {
+                let texel = (y * self.parameters.block_width + x) as usize;
+                let contributions = infill_contributions(
+                    self.parameters.block_width,
+                    self.parameters.block_height,
+                    self.parameters.grid_width,
+                    self.parameters.grid_height,
+                    x,
+                    y,
+                );
+
+                for (index, factor) in contributions {
+                    weight_sums[index] += factor as f32 * ideal_weights[texel];
+                    factor_sums[index] += factor as f32;
+                }
+            }
+        }
+
+        let weight_quant = self.parameters.weight_quant as usize;
+        let mut grid_weights = [0; 64];
+        for index in 0..grid_count {
+            let weight = if factor_sums[index] > 0.0 {
+                weight_sums[index] / factor_sums[index]
+            } else {
+                32.0
+            };
+            grid_weights[index] = quantize_weight(weight_quant, weight);
+        }
+
+        let texel_weights = self.texel_weights(&grid_weights);
+
+        let mut error = 0.0;
+        for (texel, &weight) in texel_weights[..self.texel_count].iter().enumerate() {
+            let color = self.texel(texel);
+            for channel in 0..4 {
+                let decoded = interpolate(d0[channel], d1[channel], weight);
+                let diff = decoded - color[channel];
+                error += diff * diff;
+            }
+        }
+
+        Candidate {
+            color_values,
+            grid_weights,
+            error,
+        }
+    }
+
+    fn decoded_endpoints(&self, color_values: &[u8; 8]) -> [[f32; 4]; 2] {
+        let mut endpoints = [[255.0; 4]; 2];
+        let channels = if self.color_endpoint_mode == 12 { 4 } else { 3 };
+
+        for channel in 0..channels {
+            endpoints[0][channel] =
+                unquantize_color(self.color_quant, color_values[channel * 2]) as f32;
+            endpoints[1][channel] =
+                unquantize_color(self.color_quant, color_values[channel * 2 + 1]) as f32;
+        }
+
+        endpoints
+    }
+
+    fn texel_weights(&self, grid_weights: &[u8; 64]) -> [u8; MAX_BLOCK_TEXELS] {
+        let weight_quant = self.parameters.weight_quant as usize;
+        let unquantized = grid_weights.map(|weight| unquantize_weight(weight_quant, weight));
+
+        let mut texel_weights = [0; MAX_BLOCK_TEXELS];
+        infill_weights(
+            self.parameters.block_width,
+            self.parameters.block_height,
+            self.parameters.grid_width,
+            self.parameters.grid_height,
+            &unquantized,
+            &mut texel_weights,
+        );
+        texel_weights
+    }
+
+    fn pack_block(&self, candidate: &Candidate) -> u128 {
+        let value_count = if self.color_endpoint_mode == 12 { 8 } else { 6 };
+        let grid_count = (self.parameters.grid_width * self.parameters.grid_height) as usize;
+
+        // Single partition, so the partition count bits stay zero.
+        let mut block = self.parameters.block_mode as u128;
+        block |= (self.color_endpoint_mode as u128) << 13;
+
+        write_ise(
+            &mut block,
+            &candidate.color_values[..value_count],
+            self.color_quant,
+            17,
+        );
+
+        // Weights are written from the most significant bit downwards.
+        let mut weights = 0;
+        write_ise(
+            &mut weights,
+            &candidate.grid_weights[..grid_count],
+            self.parameters.weight_quant as usize,
+            0,
+        );
+
+        block | weights.reverse_bits()
+    }
+
+    fn store_data(blocks_buffer: &mut [u8], block_width: usize, xx: usize, yy: usize, block: u128) {
+        let offset = (yy * block_width + xx) * 16;
+        blocks_buffer[offset..offset + 16].copy_from_slice(&block.to_le_bytes());
+    }
+}
+
+/// Returns the quantized color value, that unquantizes closest to `value`.
+fn quantize_color(quant: usize, value: f32) -> u8 {
+    let levels = QUANT_LEVELS[quant][0];
+    let mut best = 0;
+    let mut best_error = f32::MAX;
+
+    for level in 0..levels {
+        let error = (unquantize_color(quant, level as u8) as f32 - value).abs();
+        if error < best_error {
+            best = level;
+            best_error = error;
+        }
+    }
+
+    best as u8
+}
+
+/// Returns the quantized weight, that unquantizes closest to `value`.
+fn quantize_weight(quant: usize, value: f32) -> u8 {
+    let levels = QUANT_LEVELS[quant][0];
+    let mut best = 0;
+    let mut best_error = f32::MAX;
+
+    for level in 0..levels {
+        let error = (unquantize_weight(quant, level as u8) as f32 - value).abs();
+        if error < best_error {
+            best = level;
+            best_error = error;
+        }
+    }
+
+    best as u8
+}
+
+/// Interpolates like the decoder, but returns the result in float precision.
+#[inline(always)]
+fn interpolate(e0: f32, e1: f32, weight: u8) -> f32 {
+    let weight = weight as f32;
+    let color = (e0 * 257.0 * (64.0 - weight) + e1 * 257.0 * weight + 32.0) / 64.0;
+    color / 257.0
+}
+
+#[inline(always)]
+fn dot(a: [f32; 4], b: [f32; 4]) -> f32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2] + a[3] * b[3]
+}
+
+#[inline(always)]
+fn sub(a: [f32; 4], b: [f32; 4]) -> [f32; 4] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2], a[3] - b[3]]
+}
+
+pub(super) fn compress_astc(
+    rgba_data: &[u8],
+    blocks_buffer: &mut [u8],
+    width: usize,
+    height: usize,
+    stride: usize,
+    block_size: ASTCBlockSize,
+) {
+    let parameters = block_size.encoder_parameters();
+    let block_width = width.div_ceil(parameters.block_width as usize);
+    let block_height = height.div_ceil(parameters.block_height as usize);
+
+    for yy in 0..block_height {
+        for xx in 0..block_width {
+            let mut block_compressor = BlockCompressorASTC::new(parameters);
+
+            block_compressor.load_block_interleaved_rgba(rgba_data, width, height, xx, yy, stride);
+            let block = block_compressor.compress_block_astc();
+            BlockCompressorASTC::store_data(blocks_buffer, block_width, xx, yy, block);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        decode::decompress_blocks_as_rgba8, encode::compress_rgba8, ASTCBlockSize,
+        CompressionVariant,
+    };
+
+    // Not a multiple of most footprints, so that the edge clamping is tested too.
+    const SIZE: u32 = 30;
+
+    fn test_image() -> Vec<u8> {
+        let mut rgba_data = Vec::with_capacity((SIZE * SIZE * 4) as usize);
+        for y in 0..SIZE {
+            for x in 0..SIZE {
+                // Mostly diagonal ramps with a non-linear blue channel, like a lit surface.
+                rgba_data.extend_from_slice(&[
+                    ((x + y) * 4) as u8,
+                    ((x + y) * 3 + 20) as u8,
+                    (200 - x * y / 5) as u8,
+                    255 - (x * 4) as u8,
+                ]);
+            }
+        }
+        rgba_data
+    }
+
+    fn roundtrip(variant: CompressionVariant, rgba_data: &[u8]) -> Vec<u8> {
+        let mut blocks = vec![0; variant.blocks_byte_size(SIZE, SIZE)];
+        compress_rgba8(variant, rgba_data, &mut blocks, SIZE, SIZE, SIZE * 4);
+
+        let mut decoded = vec![0; rgba_data.len()];
+        decompress_blocks_as_rgba8(variant, SIZE, SIZE, &blocks, &mut decoded);
+        decoded
+    }
+
+    fn psnr(original: &[u8], decoded: &[u8]) -> f64 {
+        let error: f64 = original
+            .iter()
+            .zip(decoded)
+            .map(|(&a, &b)| (a as f64 - b as f64).powi(2))
+            .sum();
+        let mse = error / original.len() as f64;
+        if mse == 0.0 {
+            f64::INFINITY
+        } else {
+            10.0 * (255.0 * 255.0 / mse).log10()
+        }
+    }
+
+    #[test]
+    fn test_astc_roundtrip() {
+        let rgba_data = test_image();
+
+        for (block_size, min_psnr) in [
+            (ASTCBlockSize::Block4x4, 40.0),
+            (ASTCBlockSize::Block6x6, 35.0),
+            (ASTCBlockSize::Block8x8, 32.0),
+            (ASTCBlockSize::Block12x12, 28.0),
+        ] {
+            let decoded = roundtrip(CompressionVariant::ASTC(block_size), &rgba_data);
+            let psnr = psnr(&rgba_data, &decoded);
+            assert!(psnr > min_psnr, "{block_size:?}: PSNR {psnr}");
+        }
+    }
+
+    #[test]
+    fn test_astc_opaque_roundtrip() {
+        let mut rgba_data = test_image();
+        for pixel in rgba_data.chunks_exact_mut(4) {
+            pixel[3] = 255;
+        }
+
+        let decoded = roundtrip(
+            CompressionVariant::ASTC(ASTCBlockSize::Block6x6),
+            &rgba_data,
+        );
+        for pixel in decoded.chunks_exact(4) {
+            assert_eq!(pixel[3], 255);
+        }
+        assert!(psnr(&rgba_data, &decoded) > 35.0);
+    }
+}
@@ -0,0 +1,704 @@
+//! CPU based encoding of the ETC2 and EAC block formats.
+//!
+//! The color encoder tries the individual, differential and planar modes of ETC2 and picks the one
+//! with the smallest error. The T and H modes are not used. The EAC encoder searches all modifier
+//! tables with a small set of multiplier and base value candidates around the min/max fit.
+//!
+//! The blocks are built as 64-bit values and stored in big-endian byte order.
+
+/// Intensity modifiers of the individual and differential mode.
+static ETC1_MODIFIER_TABLE: &[[i32; 2]; 8] = &[
+    [2, 8],
+    [5, 17],
+    [9, 29],
+    [13, 42],
+    [18, 60],
+    [24, 80],
+    [33, 106],
+    [47, 183],
+];
+
+/// Modifiers of the EAC alpha, R11 and RG11 formats.
+static EAC_MODIFIER_TABLE: &[[i32; 8]; 16] = &[
+    [-3, -6, -9, -15, 2, 5, 8, 14],
+    [-3, -7, -10, -13, 2, 6, 9, 12],
+    [-2, -5, -8, -13, 1, 4, 7, 12],
+    [-2, -4, -6, -13, 1, 3, 5, 12],
+    [-3, -6, -8, -12, 2, 5, 7, 11],
+    [-3, -7, -9, -11, 2, 6, 8, 10],
+    [-4, -7, -8, -11, 3, 6, 7, 10],
+    [-3, -5, -8, -11, 2, 4, 7, 10],
+    [-2, -6, -8, -10, 1, 5, 7, 9],
+    [-2, -5, -8, -10, 1, 4, 7, 9],
+    [-2, -4, -8, -10, 1, 3, 7, 9],
+    [-2, -5, -7, -10, 1, 4, 6, 9],
+    [-3, -4, -7, -10, 2, 3, 6, 9],
+    [-1, -2, -3, -10, 0, 1, 2, 9],
+    [-4, -6, -8, -9, 3, 5, 7, 8],
+    [-3, -5, -7, -9, 2, 4, 6, 8],
+];
+
+/// Alpha values below this threshold are encoded as transparent by the punch-through variant.
+const ALPHA_THRESHOLD: f32 = 128.0;
+
+/// Result of encoding a sub-block or a whole block.
+#[derive(Copy, Clone)]
+struct Candidate {
+    block: u64,
+    error: f32,
+}
+
+impl Candidate {
+    const WORST: Self = Self {
+        block: 0,
+        error: f32::MAX,
+    };
+
+    fn pick(self, other: Self) -> Self {
+        if other.error < self.error {
+            other
+        } else {
+            self
+        }
+    }
+}
+
+struct BlockCompressorETC2 {
+    block: [f32; 64],
+}
+
+impl Default for BlockCompressorETC2 {
+    fn default() -> Self {
+        Self { block: [0.0; 64] }
+    }
+}
+
+impl BlockCompressorETC2 {
+    fn load_block_interleaved_rgba(
+        &mut self,
+        rgba_data: &[u8],
+        xx: usize,
+        yy: usize,
+        stride: usize,
+    ) {
+        for y in 0..4 {
+            for x in 0..4 {
+                let pixel_x = xx * 4 + x;
+                let pixel_y = yy * 4 + y;
+
+                let offset = pixel_y * stride + pixel_x * 4;
+
+                for channel in 0..4 {
+                    self.block[channel * 16 + y * 4 + x] = rgba_data[offset + channel] as f32;
+                }
+            }
+        }
+    }
+
+    fn store_data(
+        blocks_buffer: &mut [u8],
+        block_width: usize,
+        xx: usize,
+        yy: usize,
+        data: &[u64],
+    ) {
+        let offset = (yy * block_width + xx) * (data.len() * 8);
+
+        for (index, &value) in data.iter().enumerate() {
+            let byte_offset = offset + index * 8;
+            blocks_buffer[byte_offset..byte_offset + 8].copy_from_slice(&value.to_be_bytes());
+        }
+    }
+
+    #[inline(always)]
+    fn texel(&self, channel: usize, x: usize, y: usize) -> f32 {
+        self.block[channel * 16 + y * 4 + x]
+    }
+
+    #[inline(always)]
+    fn color_error(&self, x: usize, y: usize, color: [i32; 3]) -> f32 {
+        let mut error = 0.0;
+        for (channel, &value) in color.iter().enumerate() {
+            let diff = value.clamp(0, 255) as f32 - self.texel(channel, x, y);
+            error += diff * diff;
+        }
+        error
+    }
+
+    #[inline(always)]
+    fn is_transparent(&self, x: usize, y: usize) -> bool {
+        self.texel(3, x, y) < ALPHA_THRESHOLD
+    }
+
+    /// Returns the average color of a sub-block, ignoring transparent texels if `punch_through`
+    /// is set.
+    fn subblock_average(&self, flip: bool, subblock: usize, punch_through: bool) -> [f32; 3] {
+        let mut sum = [0.0; 3];
+        let mut count = 0.0;
+
+        for (x, y) in subblock_texels(flip, subblock) {
+            if punch_through && self.is_transparent(x, y) {
+                continue;
+            }
+            for (channel, value) in sum.iter_mut().enumerate() {
+                *value += self.texel(channel, x, y);
+            }
+            count += 1.0;
+        }
+
+        if count > 0.0 {
+            sum.map(|value| value / count)
+        } else {
+            sum
+        }
+    }
+
+    /// Finds the best modifier table for a sub-block with the given base color. Returns the table
+    /// in bits 39 to 37 and the texel indices at their final position.
+    fn encode_subblock(
+        &self,
+        flip: bool,
+        subblock: usize,
+        base: [i32; 3],
+        punch_through: bool,
+    ) -> Candidate {
+        let mut best = Candidate::WORST;
+
+        for (table, &[a, b]) in ETC1_MODIFIER_TABLE.iter().enumerate() {
+            // Index 2 marks transparent texels in non-opaque punch-through blocks and index 0
+            // has no modifier.
+            let modifiers = if punch_through {
+                [0, b, 0, -b]
+            } else {
+                [a, b, -a, -b]
+            };
+
+            let mut candidate = Candidate {
+                block: (table as u64) << 37,
+                error: 0.0,
+            };
+
+            for (x, y) in subblock_texels(flip, subblock) {
+                let pixel = x * 4 + y;
+
+                let index = if punch_through && self.is_transparent(x, y) {
+                    2
+                } else {
+                    let mut best_index = 0;
+                    let mut best_error = f32::MAX;
+                    for (index, &modifier) in modifiers.iter().enumerate() {
+                        if punch_through && index == 2 {
+                            continue;
+                        }
+                        let error = self.color_error(x, y, base.map(|c| c + modifier));
+                        if error < best_error {
+                            best_error = error;
+                            best_index = index;
+                        }
+                    }
+                    candidate.error += best_error;
+                    best_index as u64
+                };
+
+                candidate.block |= ((index >> 1) << (16 + pixel)) | ((index & 1) << pixel);
+            }
+
+            best = best.pick(candidate);
+
+            if best.error == 0.0 {
+                break;
+            }
+        }
+
+        best
+    }
+
+    /// Combines the two encoded sub-blocks into a block with the given base color bits.
+    fn combine_subblocks(flip: bool, base_bits: u64, subblocks: [Candidate; 2]) -> Candidate {
+        // The table of the second sub-block is stored three bits after the first one.
+        let table1 = (subblocks[1].block >> 37) & 0x7;
+        let indices1 = subblocks[1].block & 0xFFFF_FFFF;
+
+        Candidate {
+            block: base_bits
+                | subblocks[0].block
+                | (table1 << 34)
+                | indices1
+                | ((flip as u64) << 32),
+            error: subblocks[0].error + subblocks[1].error,
+        }
+    }
+
+    fn encode_individual(&self, flip: bool) -> Candidate {
+        let colors = [0, 1].map(|subblock| {
+            self.subblock_average(flip, subblock, false)
+                .map(|value| ((value * 15.0 / 255.0).round() as i32).clamp(0, 15))
+        });
+
+        let subblocks = [0, 1].map(|subblock| {
+            let base = colors[subblock].map(|value| value * 17);
+            self.encode_subblock(flip, subblock, base, false)
+        });
+
+        let mut base_bits = 0;
+        for (channel, (&color0, &color1)) in colors[0].iter().zip(&colors[1]).enumerate() {
+            let shift = 60 - channel * 8;
+            base_bits |= (color0 as u64) << shift;
+            base_bits |= (color1 as u64) << (shift - 4);
+        }
+
+        Self::combine_subblocks(flip, base_bits, subblocks)
+    }
+
+    fn encode_differential(&self, flip: bool, punch_through: bool) -> Candidate {
+        let averages = [0, 1].map(|subblock| {
+            self.subblock_average(flip, subblock, punch_through)
+                .map(|value| ((value * 31.0 / 255.0).round() as i32).clamp(0, 31))
+        });
+
+        let base0 = averages[0];
+        let delta = [0, 1, 2].map(|channel| (averages[1][channel] - base0[channel]).clamp(-4, 3));
+        let base1 = [0, 1, 2].map(|channel| base0[channel] + delta[channel]);
+
+        let subblocks = [
+            self.encode_subblock(flip, 0, base0.map(extend_5to8), punch_through),
+            self.encode_subblock(flip, 1, base1.map(extend_5to8), punch_through),
+        ];
+
+        // Without punch-through alpha the differential bit is always set. With punch-through
+        // alpha it's the opaque bit.
+        let differential_bit = !punch_through as u64;
+
+        let mut base_bits = differential_bit << 33;
+        for channel in 0..3 {
+            let shift = 59 - channel * 8;
+            base_bits |= (base0[channel] as u64) << shift;
+            base_bits |= ((delta[channel] & 0x7) as u64) << (shift - 3);
+        }
+
+        Self::combine_subblocks(flip, base_bits, subblocks)
+    }
+
+    fn encode_planar(&self) -> Candidate {
+        // Least squares fit of a plane through the texels of each channel.
+        let mut origin = [0; 3];
+        let mut horizontal = [0; 3];
+        let mut vertical = [0; 3];
+
+        for channel in 0..3 {
+            let mut mean = 0.0;
+            let mut slope_x = 0.0;
+            let mut slope_y = 0.0;
+
+            for y in 0..4 {
+                for x in 0..4 {
+                    let value = self.texel(channel, x, y);
+                    mean += value;
+                    slope_x += (x as f32 - 1.5) * value;
+                    slope_y += (y as f32 - 1.5) * value;
+                }
+            }
+
+            mean /= 16.0;
+            slope_x /= 20.0;
+            slope_y /= 20.0;
+
+            let o = mean - 1.5 * slope_x - 1.5 * slope_y;
+            let h = o + 4.0 * slope_x;
+            let v = o + 4.0 * slope_y;
+
+            let max = if channel == 1 { 127.0 } else { 63.0 };
+            let quantize = |value: f32| (value * max / 255.0).round().clamp(0.0, max) as i32;
+
+            origin[channel] = quantize(o);
+            horizontal[channel] = quantize(h);
+            vertical[channel] = quantize(v);
+        }
+
+        let extend = |channel: usize, value: i32| {
+            if channel == 1 {
+                extend_7to8(value)
+            } else {
+                extend_6to8(value)
+            }
+        };
+
+        let mut error = 0.0;
+        for y in 0..4 {
+            for x in 0..4 {
+                let color = [0, 1, 2].map(|channel| {
+                    let o = extend(channel, origin[channel]);
+                    let h = extend(channel, horizontal[channel]);
+                    let v = extend(channel, vertical[channel]);
+                    (x as i32 * (h - o) + y as i32 * (v - o) + 4 * o + 2) >> 2
+                });
+                error += self.color_error(x, y, color);
+            }
+        }
+
+        let [ro, go, bo] = origin.map(|value| value as u64);
+        let [rh, gh, bh] = horizontal.map(|value| value as u64);
+        let [rv, gv, bv] = vertical.map(|value| value as u64);
+
+        let mut block = (ro << 57)
+            | ((go >> 6) << 56)
+            | ((go & 0x3F) << 49)
+            | ((bo >> 5) << 48)
+            | (((bo >> 3) & 0x3) << 43)
+            | ((bo & 0x7) << 39)
+            | ((rh >> 1) << 34)
+            | (1 << 33)
+            | ((rh & 1) << 32)
+            | (gh << 25)
+            | (bh << 19)
+            | (rv << 13)
+            | (gv << 6)
+            | bv;
+
+        // The unused bits must make the red and green differential sums valid, but the blue sum
+        // has to overflow to select the planar mode.
+        let red = ((block >> 59) & 0x1F) as i32 + sign_extend_3(block >> 56);
+        if red < 0 {
+            block |= 1 << 63;
+        }
+
+        let green = ((block >> 51) & 0x1F) as i32 + sign_extend_3(block >> 48);
+        if green < 0 {
+            block |= 1 << 55;
+        }
+
+        if ((block >> 43) & 0x3) + ((block >> 40) & 0x3) < 4 {
+            block |= 1 << 42;
+        } else {
+            block |= 0x7 << 45;
+        }
+
+        Candidate { block, error }
+    }
+
+    fn compress_block_etc2(&self, punch_through: bool) -> u64 {
+        let has_transparency =
+            punch_through && (0..16).any(|index| self.block[48 + index] < ALPHA_THRESHOLD);
+
+        let mut best = Candidate::WORST;
+
+        for flip in [false, true] {
+            if has_transparency {
+                best = best.pick(self.encode_differential(flip, true));
+                continue;
+            }
+
+            // The punch-through variant uses the differential bit as the opaque bit, so it has no
+            // individual mode.
+            if !punch_through {
+                best = best.pick(self.encode_individual(flip));
+            }
+            best = best.pick(self.encode_differential(flip, false));
+        }
+
+        if !has_transparency {
+            best = best.pick(self.encode_planar());
+        }
+
+        best.block
+    }
+
+    /// Encodes the values of a channel, which are in the range of 0 to 255 for alpha and 0 to 2047
+    /// for the 11-bit formats.
+    fn compress_block_eac(&self, channel: usize, eleven_bit: bool) -> u64 {
+        let values = &self.block[channel * 16..channel * 16 + 16];
+
+        let mut min = f32::MAX;
+        let mut max = f32::MIN;
+        for &value in values {
+            min = min.min(value);
+            max = max.max(value);
+        }
+
+        // The 11-bit formats scale the modifiers by 8 and add an offset of 4.
+        let (scale, offset, max_base) = if eleven_bit {
+            (8.0, 4.0, 255)
+        } else {
+            (1.0, 0.0, 255)
+        };
+
+        let mut best = Candidate::WORST;
+
+        for (table, modifiers) in EAC_MODIFIER_TABLE.iter().enumerate() {
+            let span = (modifiers[7] - modifiers[3]) as f32;
+            let center = (modifiers[7] + modifiers[3]) as f32 * 0.5;
+
+            let multiplier = ((max - min) / (span * scale)).round() as i32;
+
+            for multiplier in (multiplier - 1).max(1)..=(multiplier + 1).min(15) {
+                let base = ((((min + max) * 0.5 - offset) / scale) - multiplier as f32 * center)
+                    .round() as i32;
+
+                for base in (base - 1).max(0)..=(base + 1).min(max_base) {
+                    let mut candidate = Candidate {
+                        block: ((base as u64) << 56)
+                            | ((multiplier as u64) << 52)
+                            | ((table as u64) << 48),
+                        error: 0.0,
+                    };
+
+                    for y in 0..4 {
+                        for x in 0..4 {
+                            let value = values[y * 4 + x];
+
+                            let mut best_index = 0;
+                            let mut best_error = f32::MAX;
+                            for (index, &modifier) in modifiers.iter().enumerate() {
+                                let decoded = if eleven_bit {
+                                    (base * 8 + 4 + modifier * multiplier * 8).clamp(0, 2047)
+                                } else {
+                                    (base + modifier * multiplier).clamp(0, 255)
+                                };
+                                let diff = decoded as f32 - value;
+                                if diff * diff < best_error {
+                                    best_error = diff * diff;
+                                    best_index = index;
+                                }
+                            }
+
+                            candidate.error += best_error;
+                            candidate.block |= (best_index as u64) << (45 - (x * 4 + y) * 3);
+                        }
+                    }
+
+                    best = best.pick(candidate);
+                }
+            }
+        }
+
+        best.block
+    }
+
+    fn rescale_to_11bit(&mut self, channel: usize) {
+        for value in self.block[channel * 16..channel * 16 + 16].iter_mut() {
+            *value = *value * 2047.0 / 255.0;
+        }
+    }
+}
+
+/// Returns the texel coordinates of a sub-block.
+#[inline(always)]
+fn subblock_texels(flip: bool, subblock: usize) -> impl Iterator<Item = (usize, usize)> {
+    (0..8).map(move |index| {
+        let (x, y) = (index / 4, index % 4);
+        if flip {
+            (y, x + subblock * 2)
+        } else {
+            (x + subblock * 2, y)
+        }
+    })
+}
+
+#[inline(always)]
+fn extend_5to8(value: i32) -> i32 {
+    (value << 3) | (value >> 2)
+}
+
+#[inline(always)]
+fn extend_6to8(value: i32) -> i32 {
+    (value << 2) | (value >> 4)
+}
+
+#[inline(always)]
+fn extend_7to8(value: i32) -> i32 {
+    (value << 1) | (value >> 6)
+}
+
+#[inline(always)]
+fn sign_extend_3(value: u64) -> i32 {
+    let value = (value & 0x7) as i32;
+    (value << 29) >> 29
+}
+
+pub(super) fn compress_etc2_rgb8(
+    rgba_data: &[u8],
+    blocks_buffer: &mut [u8],
+    block_width: usize,
+    block_height: usize,
+    stride: usize,
+) {
+    for yy in 0..block_height {
+        for xx in 0..block_width {
+            let mut block_compressor = BlockCompressorETC2::default();
+
+            block_compressor.load_block_interleaved_rgba(rgba_data, xx, yy, stride);
+            let color_result = block_compressor.compress_block_etc2(false);
+            BlockCompressorETC2::store_data(blocks_buffer, block_width, xx, yy, &[color_result]);
+        }
+    }
+}
+
+pub(super) fn compress_etc2_rgb8a1(
+    rgba_data: &[u8],
+    blocks_buffer: &mut [u8],
+    block_width: usize,
+    block_height: usize,
+    stride: usize,
+) {
+    for yy in 0..block_height {
+        for xx in 0..block_width {
+            let mut block_compressor = BlockCompressorETC2::default();
+
+            block_compressor.load_block_interleaved_rgba(rgba_data, xx, yy, stride);
+            let color_result = block_compressor.compress_block_etc2(true);
+            BlockCompressorETC2::store_data(blocks_buffer, block_width, xx, yy, &[color_result]);
+        }
+    }
+}
+
+pub(super) fn compress_etc2_rgba8(
+    rgba_data: &[u8],
+    blocks_buffer: &mut [u8],
+    block_width: usize,
+    block_height: usize,
+    stride: usize,
+) {
+    for yy in 0..block_height {
+        for xx in 0..block_width {
+            let mut block_compressor = BlockCompressorETC2::default();
+
+            block_compressor.load_block_interleaved_rgba(rgba_data, xx, yy, stride);
+            let alpha_result = block_compressor.compress_block_eac(3, false);
+            let color_result = block_compressor.compress_block_etc2(false);
+            BlockCompressorETC2::store_data(
+                blocks_buffer,
+                block_width,
+                xx,
+                yy,
+                &[alpha_result, color_result],
+            );
+        }
+    }
+}
+
+pub(super) fn compress_eac_r11(
+    rgba_data: &[u8],
+    blocks_buffer: &mut [u8],
+    block_width: usize,
+    block_height: usize,
+    stride: usize,
+) {
+    for yy in 0..block_height {
+        for xx in 0..block_width {
+            let mut block_compressor = BlockCompressorETC2::default();
+
+            block_compressor.load_block_interleaved_rgba(rgba_data, xx, yy, stride);
+            block_compressor.rescale_to_11bit(0);
+            let red_result = block_compressor.compress_block_eac(0, true);
+            BlockCompressorETC2::store_data(blocks_buffer, block_width, xx, yy, &[red_result]);
+        }
+    }
+}
+
+pub(super) fn compress_eac_rg11(
+    rgba_data: &[u8],
+    blocks_buffer: &mut [u8],
+    block_width: usize,
+    block_height: usize,
+    stride: usize,
+) {
+    for yy in 0..block_height {
+        for xx in 0..block_width {
+            let mut block_compressor = BlockCompressorETC2::default();
+
+            block_compressor.load_block_interleaved_rgba(rgba_data, xx, yy, stride);
+            block_compressor.rescale_to_11bit(0);
+            block_compressor.rescale_to_11bit(1);
+            let red_result = block_compressor.compress_block_eac(0, true);
+            let green_result = block_compressor.compress_block_eac(1, true);
+            BlockCompressorETC2::store_data(
+                blocks_buffer,
+                block_width,
+                xx,
+                yy,
+                &[red_result, green_result],
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{decode::decompress_blocks_as_rgba8, encode::compress_rgba8, CompressionVariant};
+
+    const SIZE: u32 = 16;
+
+    fn test_image() -> Vec<u8> {
+        let mut rgba_data = Vec::with_capacity((SIZE * SIZE * 4) as usize);
+        for y in 0..SIZE {
+            for x in 0..SIZE {
+                rgba_data.extend_from_slice(&[
+                    (x * 16) as u8,
+                    (y * 16) as u8,
+                    ((x + y) * 8) as u8,
+                    if (x / 4 + y / 4) % 2 == 0 { 255 } else { 0 },
+                ]);
+            }
+        }
+        rgba_data
+    }
+
+    fn roundtrip(variant: CompressionVariant, rgba_data: &[u8]) -> Vec<u8> {
+        let mut blocks = vec![0; variant.blocks_byte_size(SIZE, SIZE)];
+        compress_rgba8(variant, rgba_data, &mut blocks, SIZE, SIZE, SIZE * 4);
+
+        let mut decoded = vec![0; rgba_data.len()];
+        decompress_blocks_as_rgba8(variant, SIZE, SIZE, &blocks, &mut decoded);
+        decoded
+    }
+
+    fn psnr(original: &[u8], decoded: &[u8], channels: &[usize]) -> f64 {
+        let mut error = 0.0;
+        let mut count = 0.0;
+        for (a, b) in original.chunks_exact(4).zip(decoded.chunks_exact(4)) {
+            for &channel in channels {
+                let diff = a[channel] as f64 - b[channel] as f64;
+                error += diff * diff;
+                count += 1.0;
+            }
+        }
+        let mse = error / count;
+        if mse == 0.0 {
+            f64::INFINITY
+        } else {
+            10.0 * (255.0 * 255.0 / mse).log10()
+        }
+    }
+
+    #[test]
+    fn test_etc2_rgb8_roundtrip() {
+        let rgba_data = test_image();
+        let decoded = roundtrip(CompressionVariant::ETC2RGB8, &rgba_data);
+        assert!(psnr(&rgba_data, &decoded, &[0, 1, 2]) > 35.0);
+    }
+
+    #[test]
+    fn test_etc2_rgb8a1_roundtrip() {
+        let rgba_data = test_image();
+        let decoded = roundtrip(CompressionVariant::ETC2RGB8A1, &rgba_data);
+
+        for (original, decoded) in rgba_data.chunks_exact(4).zip(decoded.chunks_exact(4)) {
+            assert_eq!(original[3], decoded[3]);
+        }
+    }
+
+    #[test]
+    fn test_etc2_rgba8_roundtrip() {
+        let rgba_data = test_image();
+        let decoded = roundtrip(CompressionVariant::ETC2RGBA8, &rgba_data);
+        assert!(psnr(&rgba_data, &decoded, &[0, 1, 2]) > 35.0);
+        assert!(psnr(&rgba_data, &decoded, &[3]) > 40.0);
+    }
+
+    #[test]
+    fn test_eac_rg11_roundtrip() {
+        let rgba_data = test_image();
+        let decoded = roundtrip(CompressionVariant::EACRG11, &rgba_data);
+        assert!(psnr(&rgba_data, &decoded, &[0, 1]) > 45.0);
+    }
+}
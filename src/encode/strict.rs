@@ -0,0 +1,502 @@
+//! CPU based encoding of BC1 to BC5 in strict mode.
+//!
+//! The float encoder depends on how divisions, square roots and fused multiply-adds are rounded,
+//! which differs between CPUs and GPU backends. The strict encoder only uses 32-bit integer
+//! arithmetic, which Rust and WGSL define identically as long as no value overflows and nothing is
+//! divided by zero. The comments note the bounds of the intermediate values, that keep them far
+//! from overflowing.
+//!
+//! The strict mode of `BC1_to_5.wgsl` is a line by line port of this file, so both produce the
+//! same blocks bit for bit.
+
+use crate::{AlphaOptions, CompressionVariant};
+
+/// Bit length of the normalized principal axis components.
+const AXIS_BITS: u32 = 14;
+
+/// Bit length the covariance matrix is scaled down to before the power iteration.
+const COVAR_BITS: u32 = 15;
+
+struct BlockCompressorStrict {
+    /// The red, green, blue and alpha planes of the 16 texels in `0..=255`.
+    block: [i32; 64],
+    weight_color_by_alpha: bool,
+}
+
+impl Default for BlockCompressorStrict {
+    fn default() -> Self {
+        Self {
+            block: [0; 64],
+            weight_color_by_alpha: false,
+        }
+    }
+}
+
+impl BlockCompressorStrict {
+    fn load_block_interleaved_rgba(
+        &mut self,
+        rgba_data: &[u8],
+        xx: usize,
+        yy: usize,
+        stride: usize,
+    ) {
+        for y in 0..4 {
+            for x in 0..4 {
+                let offset = (yy * 4 + y) * stride + (xx * 4 + x) * 4;
+
+                for p in 0..4 {
+                    self.block[p * 16 + y * 4 + x] = rgba_data[offset + p] as i32;
+                }
+            }
+        }
+    }
+
+    /// Loads a single channel into the alpha plane.
+    fn load_block_channel(
+        &mut self,
+        rgba_data: &[u8],
+        xx: usize,
+        yy: usize,
+        stride: usize,
+        channel: usize,
+    ) {
+        for y in 0..4 {
+            for x in 0..4 {
+                let offset = (yy * 4 + y) * stride + (xx * 4 + x) * 4;
+                self.block[48 + y * 4 + x] = rgba_data[offset + channel] as i32;
+            }
+        }
+    }
+
+    fn apply_alpha_options(&mut self, options: AlphaOptions) {
+        if options.premultiply {
+            for k in 0..16 {
+                let alpha = self.block[48 + k];
+                for p in 0..3 {
+                    self.block[k + p * 16] = (self.block[k + p * 16] * alpha + 127) / 255;
+                }
+            }
+        }
+
+        self.weight_color_by_alpha = options.weight_color_by_alpha;
+    }
+
+    /// The weight of the color error of a texel in `1..=8`. Fully transparent texels keep a
+    /// weight of 1.
+    fn color_weight(&self, k: usize) -> i32 {
+        if self.weight_color_by_alpha {
+            (i32::max(self.block[48 + k], 1) + 31) >> 5
+        } else {
+            1
+        }
+    }
+
+    fn is_hidden(&self, k: usize) -> bool {
+        self.weight_color_by_alpha
+            && self.block[48 + k] == 0
+            && self.block[48..].iter().any(|&alpha| alpha > 0)
+    }
+
+    /// Packs the 4-bit alpha values of BC2.
+    fn alpha_4bit(&self) -> [u32; 2] {
+        let mut alpha_bits = [0; 2];
+
+        for k in 0..16 {
+            let alpha4 = ((self.block[48 + k] * 15 + 127) / 255) as u32;
+            alpha_bits[k / 8] |= alpha4 << ((k % 8) * 4);
+        }
+
+        alpha_bits
+    }
+
+    fn store_data(
+        blocks_buffer: &mut [u8],
+        block_width: usize,
+        xx: usize,
+        yy: usize,
+        data: &[u32],
+    ) {
+        let offset = (yy * block_width + xx) * (data.len() * 4);
+
+        for (index, &value) in data.iter().enumerate() {
+            let byte_offset = offset + index * 4;
+            blocks_buffer[byte_offset..byte_offset + 4].copy_from_slice(&value.to_le_bytes());
+        }
+    }
+
+    /// Computes the weighted mean color and the weighted covariance matrix around it.
+    fn compute_covar_mean(&self, covar: &mut [i32; 6], mean: &mut [i32; 3]) {
+        // sum_w <= 16 * 8, sum_wc <= 16 * 8 * 255
+        let mut sum_w = 0;
+        let mut sum_wc = [0; 3];
+        for k in 0..16 {
+            let weight = self.color_weight(k);
+            sum_w += weight;
+            for (p, value) in sum_wc.iter_mut().enumerate() {
+                *value += weight * self.block[k + p * 16];
+            }
+        }
+
+        for p in 0..3 {
+            mean[p] = div_round(sum_wc[p], sum_w);
+        }
+
+        // |covar| <= 16 * 8 * 255 * 255
+        *covar = [0; 6];
+        for k in 0..16 {
+            let weight = self.color_weight(k);
+            let rgb0 = self.block[k] - mean[0];
+            let rgb1 = self.block[k + 16] - mean[1];
+            let rgb2 = self.block[k + 32] - mean[2];
+
+            covar[0] += weight * rgb0 * rgb0;
+            covar[1] += weight * rgb0 * rgb1;
+            covar[2] += weight * rgb0 * rgb2;
+            covar[3] += weight * rgb1 * rgb1;
+            covar[4] += weight * rgb1 * rgb2;
+            covar[5] += weight * rgb2 * rgb2;
+        }
+    }
+
+    /// Finds the principal axis with a power iteration. The returned axis has a largest component
+    /// magnitude between `2^13` and `2^14`.
+    fn compute_axis3(covar: &[i32; 6], power_iterations: u32) -> [i32; 3] {
+        // The off-diagonal entries are bounded by the diagonal, so after the scaling all entries
+        // are at most 2^15 and a product with the axis fits into 3 * 2^29.
+        let max_diagonal = covar[0].max(covar[3]).max(covar[5]);
+        let shift = bit_length(max_diagonal).max(COVAR_BITS) - COVAR_BITS;
+
+        let mut scaled = [0; 6];
+        for (value, &entry) in scaled.iter_mut().zip(covar) {
+            *value = entry >> shift;
+        }
+        scaled[0] += 1;
+        scaled[3] += 1;
+        scaled[5] += 1;
+
+        let mut axis = [1 << (AXIS_BITS - 1); 3];
+        for _ in 0..power_iterations {
+            let next = [
+                scaled[0] * axis[0] + scaled[1] * axis[1] + scaled[2] * axis[2],
+                scaled[1] * axis[0] + scaled[3] * axis[1] + scaled[4] * axis[2],
+                scaled[2] * axis[0] + scaled[4] * axis[1] + scaled[5] * axis[2],
+            ];
+            axis = normalize_axis(next);
+        }
+
+        axis
+    }
+
+    fn pick_endpoints(
+        &self,
+        c0: &mut [i32; 3],
+        c1: &mut [i32; 3],
+        axis: &[i32; 3],
+        mean: &[i32; 3],
+    ) {
+        // |axis| <= 2^8, |dot| <= 3 * 255 * 2^8 and norm_sq <= 3 * 2^16.
+        let axis = axis.map(|value| value >> (AXIS_BITS - 8));
+
+        let mut min_dot = i32::MAX;
+        let mut max_dot = i32::MIN;
+
+        for k in 0..16 {
+            if self.is_hidden(k) {
+                continue;
+            }
+
+            let mut dot = 0;
+            for p in 0..3 {
+                dot += (self.block[p * 16 + k] - mean[p]) * axis[p];
+            }
+
+            min_dot = i32::min(min_dot, dot);
+            max_dot = i32::max(max_dot, dot);
+        }
+
+        let mut norm_sq = 0;
+        for value in axis.iter() {
+            norm_sq += value * value;
+        }
+
+        for p in 0..3 {
+            c0[p] = i32::clamp(mean[p] + div_round(min_dot * axis[p], norm_sq), 0, 255);
+            c1[p] = i32::clamp(mean[p] + div_round(max_dot * axis[p], norm_sq), 0, 255);
+        }
+    }
+
+    fn dec_rgb565(c: &mut [i32; 3], p: i32) {
+        let b5 = p & 31;
+        let g6 = (p >> 5) & 63;
+        let r5 = (p >> 11) & 31;
+
+        c[0] = (r5 << 3) + (r5 >> 2);
+        c[1] = (g6 << 2) + (g6 >> 4);
+        c[2] = (b5 << 3) + (b5 >> 2);
+    }
+
+    fn enc_rgb565(c: &[i32; 3]) -> i32 {
+        let r5 = (c[0] * 31 + 128 + ((c[0] * 31) >> 8)) >> 8;
+        let g6 = (c[1] * 63 + 128 + ((c[1] * 63) >> 8)) >> 8;
+        let b5 = (c[2] * 31 + 128 + ((c[2] * 31) >> 8)) >> 8;
+
+        (r5 << 11) + (g6 << 5) + b5
+    }
+
+    /// Projects every texel onto the line between the endpoints. Returns the positions on the line
+    /// in `0..=3` as 2-bit values.
+    fn quantize(&self, p0: i32, p1: i32) -> u32 {
+        if p0 == p1 {
+            return 0;
+        }
+
+        let mut c0 = [0; 3];
+        let mut c1 = [0; 3];
+        Self::dec_rgb565(&mut c0, p0);
+        Self::dec_rgb565(&mut c1, p1);
+
+        // 0 < sq_norm <= 3 * 255 * 255, |dot| <= 3 * 255 * 255
+        let mut dir = [0; 3];
+        let mut sq_norm = 0;
+        for p in 0..3 {
+            dir[p] = c1[p] - c0[p];
+            sq_norm += dir[p] * dir[p];
+        }
+
+        let mut bits = 0;
+        for k in 0..16 {
+            let mut dot = 0;
+            for (p, value) in dir.iter().enumerate() {
+                dot += (self.block[k + p * 16] - c0[p]) * value;
+            }
+
+            let q = if dot <= 0 {
+                0
+            } else {
+                i32::min((6 * dot + sq_norm) / (2 * sq_norm), 3)
+            };
+            bits |= (q as u32) << (k * 2);
+        }
+
+        bits
+    }
+
+    /// Fits the endpoints to the texels with a least squares solve for the given positions.
+    fn bc1_refine(&self, pe: &mut [i32; 2], bits: u32, mean: &[i32; 3]) {
+        let mut c0 = *mean;
+        let mut c1 = *mean;
+
+        // cxx, cxy, cyy <= 16 * 8 * 9, |atb| <= 16 * 8 * 3 * 255
+        let mut cxx = 0;
+        let mut cxy = 0;
+        let mut cyy = 0;
+        let mut atb1 = [0; 3];
+        let mut atb2 = [0; 3];
+
+        for k in 0..16 {
+            let q = ((bits >> (k * 2)) & 3) as i32;
+            let x = 3 - q;
+            let weight = self.color_weight(k);
+
+            cxx += weight * x * x;
+            cxy += weight * x * q;
+            cyy += weight * q * q;
+
+            for p in 0..3 {
+                atb1[p] += weight * x * self.block[k + p * 16];
+                atb2[p] += weight * q * self.block[k + p * 16];
+            }
+        }
+
+        // The determinant is only zero, if all texels have the same position.
+        let det = cxx * cyy - cxy * cxy;
+        if det > 0 {
+            for p in 0..3 {
+                c0[p] = div_round(3 * (atb1[p] * cyy - atb2[p] * cxy), det).clamp(0, 255);
+                c1[p] = div_round(3 * (atb2[p] * cxx - atb1[p] * cxy), det).clamp(0, 255);
+            }
+        }
+
+        pe[0] = Self::enc_rgb565(&c0);
+        pe[1] = Self::enc_rgb565(&c1);
+    }
+
+    fn fix_qbits(qbits: u32) -> u32 {
+        const MASK_01B: u32 = 0x55555555;
+        const MASK_10B: u32 = 0xAAAAAAAA;
+
+        let qbits0 = qbits & MASK_01B;
+        let qbits1 = qbits & MASK_10B;
+
+        (qbits1 >> 1) + (qbits1 ^ (qbits0 << 1))
+    }
+
+    fn compress_block_bc1_core(&self) -> [u32; 2] {
+        let power_iterations = 4;
+        let refine_iterations = 1;
+
+        let mut covar = [0; 6];
+        let mut mean = [0; 3];
+        self.compute_covar_mean(&mut covar, &mut mean);
+
+        let axis = Self::compute_axis3(&covar, power_iterations);
+
+        let mut c0 = [0; 3];
+        let mut c1 = [0; 3];
+        self.pick_endpoints(&mut c0, &mut c1, &axis, &mean);
+
+        let mut p = [Self::enc_rgb565(&c0), Self::enc_rgb565(&c1)];
+        if p[0] < p[1] {
+            p.swap(0, 1);
+        }
+
+        let mut data = [0; 2];
+        data[0] = ((p[1] as u32) << 16) | p[0] as u32;
+        data[1] = self.quantize(p[0], p[1]);
+
+        for _ in 0..refine_iterations {
+            self.bc1_refine(&mut p, data[1], &mean);
+            if p[0] < p[1] {
+                p.swap(0, 1);
+            }
+            data[0] = ((p[1] as u32) << 16) | p[0] as u32;
+            data[1] = self.quantize(p[0], p[1]);
+        }
+
+        data[1] = Self::fix_qbits(data[1]);
+
+        data
+    }
+
+    fn compress_block_bc3_alpha(&self) -> [u32; 2] {
+        let mut ep = [255, 0];
+
+        for k in 0..16 {
+            ep[0] = i32::min(ep[0], self.block[48 + k]);
+            ep[1] = i32::max(ep[1], self.block[48 + k]);
+        }
+
+        let range = ep[1] - ep[0];
+
+        let mut qblock = [0; 2];
+        for k in 0..16 {
+            let v = self.block[48 + k];
+            let proj = if range == 0 {
+                0
+            } else {
+                ((v - ep[0]) * 14 + range) / (2 * range)
+            };
+
+            let mut q = 7 - i32::clamp(proj, 0, 7);
+            if q > 0 {
+                q += 1;
+            }
+            if q == 8 {
+                q = 1;
+            }
+
+            qblock[k / 8] |= (q as u32) << ((k % 8) * 3);
+        }
+
+        let mut data = [0; 2];
+        data[0] = ((ep[0] as u32) << 8) | ep[1] as u32;
+        data[0] |= qblock[0] << 16;
+        data[1] = qblock[0] >> 16;
+        data[1] |= qblock[1] << 8;
+
+        data
+    }
+}
+
+/// Number of significant bits of a non-negative value.
+fn bit_length(value: i32) -> u32 {
+    32 - value.leading_zeros()
+}
+
+/// Divides and rounds half away from zero. The denominator must be positive.
+fn div_round(numerator: i32, denominator: i32) -> i32 {
+    if numerator >= 0 {
+        (numerator + denominator / 2) / denominator
+    } else {
+        -((denominator / 2 - numerator) / denominator)
+    }
+}
+
+/// Scales the axis, so that its largest component magnitude is between `2^13` and `2^14`.
+fn normalize_axis(axis: [i32; 3]) -> [i32; 3] {
+    let max = axis[0].abs().max(axis[1].abs()).max(axis[2].abs());
+    if max == 0 {
+        return [1 << (AXIS_BITS - 1); 3];
+    }
+
+    let length = bit_length(max);
+    if length > AXIS_BITS {
+        axis.map(|value| value >> (length - AXIS_BITS))
+    } else {
+        axis.map(|value| value << (AXIS_BITS - length))
+    }
+}
+
+pub(super) fn compress_strict(
+    variation: CompressionVariant,
+    rgba_data: &[u8],
+    blocks_buffer: &mut [u8],
+    block_width: usize,
+    block_height: usize,
+    stride: usize,
+    alpha_options: AlphaOptions,
+) {
+    for yy in 0..block_height {
+        for xx in 0..block_width {
+            let mut block_compressor = BlockCompressorStrict::default();
+            let mut data = [0; 4];
+
+            let len = match variation {
+                CompressionVariant::BC1 => {
+                    block_compressor.load_block_interleaved_rgba(rgba_data, xx, yy, stride);
+                    let color_result = block_compressor.compress_block_bc1_core();
+                    data[..2].copy_from_slice(&color_result);
+                    2
+                }
+                CompressionVariant::BC2 => {
+                    block_compressor.load_block_interleaved_rgba(rgba_data, xx, yy, stride);
+                    let alpha_result = block_compressor.alpha_4bit();
+                    block_compressor.apply_alpha_options(alpha_options);
+                    let color_result = block_compressor.compress_block_bc1_core();
+                    data[..2].copy_from_slice(&alpha_result);
+                    data[2..].copy_from_slice(&color_result);
+                    4
+                }
+                CompressionVariant::BC3 => {
+                    block_compressor.load_block_interleaved_rgba(rgba_data, xx, yy, stride);
+                    block_compressor.apply_alpha_options(alpha_options);
+                    let alpha_result = block_compressor.compress_block_bc3_alpha();
+                    let color_result = block_compressor.compress_block_bc1_core();
+                    data[..2].copy_from_slice(&alpha_result);
+                    data[2..].copy_from_slice(&color_result);
+                    4
+                }
+                CompressionVariant::BC4 => {
+                    block_compressor.load_block_channel(rgba_data, xx, yy, stride, 0);
+                    let red_result = block_compressor.compress_block_bc3_alpha();
+                    data[..2].copy_from_slice(&red_result);
+                    2
+                }
+                CompressionVariant::BC5 => {
+                    block_compressor.load_block_channel(rgba_data, xx, yy, stride, 0);
+                    let red_result = block_compressor.compress_block_bc3_alpha();
+                    block_compressor.load_block_channel(rgba_data, xx, yy, stride, 1);
+                    let green_result = block_compressor.compress_block_bc3_alpha();
+                    data[..2].copy_from_slice(&red_result);
+                    data[2..].copy_from_slice(&green_result);
+                    4
+                }
+                // Only reachable if one of the optional variants is enabled.
+                #[allow(unreachable_patterns)]
+                _ => unreachable!("strict mode only supports BC1 to BC5"),
+            };
+
+            BlockCompressorStrict::store_data(blocks_buffer, block_width, xx, yy, &data[..len]);
+        }
+    }
+}
@@ -0,0 +1,725 @@
+//! Registry of the block compressed texture formats and their identifiers in wgpu, DXGI and
+//! Vulkan.
+//!
+//! ```
+//! use block_compression::{format::BlockFormat, CompressionVariant};
+//! use wgpu::TextureFormat;
+//!
+//! let format = BlockFormat::from_variant(CompressionVariant::BC3, true);
+//! assert_eq!(format.texture_format(), TextureFormat::Bc3RgbaUnormSrgb);
+//! assert_eq!(format.dxgi_format(), Some(78));
+//! assert_eq!(format.vk_format(), 138);
+//! assert_eq!(
+//!     BlockFormat::from_texture_format(TextureFormat::Bc3RgbaUnormSrgb),
+//!     Some(format)
+//! );
+//! ```
+
+use wgpu::TextureFormat;
+#[cfg(feature = "astc")]
+use wgpu::{AstcBlock, AstcChannel};
+
+#[cfg(feature = "astc")]
+use crate::ASTCBlockSize;
+#[cfg(feature = "bc6h")]
+use crate::BC6HSettings;
+#[cfg(feature = "bc7")]
+use crate::BC7Settings;
+use crate::CompressionVariant;
+
+/// A block compressed texture format, which is a block compression with its channel layout and
+/// value type.
+///
+/// The names follow [`TextureFormat`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum BlockFormat {
+    /// BC1 with linear colors.
+    Bc1RgbaUnorm,
+    /// BC1 with sRGB colors.
+    Bc1RgbaUnormSrgb,
+    /// BC2 with linear colors.
+    Bc2RgbaUnorm,
+    /// BC2 with sRGB colors.
+    Bc2RgbaUnormSrgb,
+    /// BC3 with linear colors.
+    Bc3RgbaUnorm,
+    /// BC3 with sRGB colors.
+    Bc3RgbaUnormSrgb,
+    /// BC4 with unsigned values.
+    Bc4RUnorm,
+    /// BC4 with signed values.
+    Bc4RSnorm,
+    /// BC5 with unsigned values.
+    Bc5RgUnorm,
+    /// BC5 with signed values.
+    Bc5RgSnorm,
+    /// BC6H with unsigned half floats (UF16).
+    Bc6hRgbUfloat,
+    /// BC6H with signed half floats (SF16).
+    Bc6hRgbFloat,
+    /// BC7 with linear colors.
+    Bc7RgbaUnorm,
+    /// BC7 with sRGB colors.
+    Bc7RgbaUnormSrgb,
+    /// ETC2 RGB8 with linear colors.
+    Etc2Rgb8Unorm,
+    /// ETC2 RGB8 with sRGB colors.
+    Etc2Rgb8UnormSrgb,
+    /// ETC2 RGB8 with punch-through alpha and linear colors.
+    Etc2Rgb8A1Unorm,
+    /// ETC2 RGB8 with punch-through alpha and sRGB colors.
+    Etc2Rgb8A1UnormSrgb,
+    /// ETC2 RGBA8 with linear colors.
+    Etc2Rgba8Unorm,
+    /// ETC2 RGBA8 with sRGB colors.
+    Etc2Rgba8UnormSrgb,
+    /// EAC R11 with unsigned values.
+    EacR11Unorm,
+    /// EAC R11 with signed values.
+    EacR11Snorm,
+    /// EAC RG11 with unsigned values.
+    EacRg11Unorm,
+    /// EAC RG11 with signed values.
+    EacRg11Snorm,
+    /// ASTC LDR with the given block footprint, with linear or sRGB colors.
+    #[cfg(feature = "astc")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "astc")))]
+    Astc {
+        /// The block footprint.
+        block_size: ASTCBlockSize,
+        /// The colors are stored in sRGB.
+        srgb: bool,
+    },
+}
+
+/// The channels, that a format stores.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum ChannelLayout {
+    /// Red.
+    R,
+    /// Red and green.
+    Rg,
+    /// Red, green and blue.
+    Rgb,
+    /// Red, green, blue and alpha.
+    Rgba,
+}
+
+impl ChannelLayout {
+    /// Returns the number of channels.
+    pub const fn count(self) -> u32 {
+        match self {
+            Self::R => 1,
+            Self::Rg => 2,
+            Self::Rgb => 3,
+            Self::Rgba => 4,
+        }
+    }
+}
+
+/// How the values of the channels are interpreted when a texture is sampled.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum ValueType {
+    /// Unsigned values from `0.0` to `1.0`.
+    Unorm,
+    /// Unsigned values from `0.0` to `1.0`. The color channels are converted from sRGB into
+    /// linear space.
+    UnormSrgb,
+    /// Signed values from `-1.0` to `1.0`.
+    Snorm,
+    /// Unsigned half floats.
+    Ufloat,
+    /// Signed half floats.
+    Sfloat,
+}
+
+/// The identifiers of a format without a block footprint.
+struct Entry {
+    format: BlockFormat,
+    texture_format: TextureFormat,
+    dxgi_format: Option<u32>,
+    vk_format: u32,
+}
+
+const ENTRIES: &[Entry] = &[
+    entry(
+        BlockFormat::Bc1RgbaUnorm,
+        TextureFormat::Bc1RgbaUnorm,
+        Some(71),
+        133,
+    ),
+    entry(
+        BlockFormat::Bc1RgbaUnormSrgb,
+        TextureFormat::Bc1RgbaUnormSrgb,
+        Some(72),
+        134,
+    ),
+    entry(
+        BlockFormat::Bc2RgbaUnorm,
+        TextureFormat::Bc2RgbaUnorm,
+        Some(74),
+        135,
+    ),
+    entry(
+        BlockFormat::Bc2RgbaUnormSrgb,
+        TextureFormat::Bc2RgbaUnormSrgb,
+        Some(75),
+        136,
+    ),
+    entry(
+        BlockFormat::Bc3RgbaUnorm,
+        TextureFormat::Bc3RgbaUnorm,
+        Some(77),
+        137,
+    ),
+    entry(
+        BlockFormat::Bc3RgbaUnormSrgb,
+        TextureFormat::Bc3RgbaUnormSrgb,
+        Some(78),
+        138,
+    ),
+    entry(
+        BlockFormat::Bc4RUnorm,
+        TextureFormat::Bc4RUnorm,
+        Some(80),
+        139,
+    ),
+    entry(
+        BlockFormat::Bc4RSnorm,
+        TextureFormat::Bc4RSnorm,
+        Some(81),
+        140,
+    ),
+    entry(
+        BlockFormat::Bc5RgUnorm,
+        TextureFormat::Bc5RgUnorm,
+        Some(83),
+        141,
+    ),
+    entry(
+        BlockFormat::Bc5RgSnorm,
+        TextureFormat::Bc5RgSnorm,
+        Some(84),
+        142,
+    ),
+    entry(
+        BlockFormat::Bc6hRgbUfloat,
+        TextureFormat::Bc6hRgbUfloat,
+        Some(95),
+        143,
+    ),
+    entry(
+        BlockFormat::Bc6hRgbFloat,
+        TextureFormat::Bc6hRgbFloat,
+        Some(96),
+        144,
+    ),
+    entry(
+        BlockFormat::Bc7RgbaUnorm,
+        TextureFormat::Bc7RgbaUnorm,
+        Some(98),
+        145,
+    ),
+    entry(
+        BlockFormat::Bc7RgbaUnormSrgb,
+        TextureFormat::Bc7RgbaUnormSrgb,
+        Some(99),
+        146,
+    ),
+    entry(
+        BlockFormat::Etc2Rgb8Unorm,
+        TextureFormat::Etc2Rgb8Unorm,
+        None,
+        147,
+    ),
+    entry(
+        BlockFormat::Etc2Rgb8UnormSrgb,
+        TextureFormat::Etc2Rgb8UnormSrgb,
+        None,
+        148,
+    ),
+    entry(
+        BlockFormat::Etc2Rgb8A1Unorm,
+        TextureFormat::Etc2Rgb8A1Unorm,
+        None,
+        149,
+    ),
+    entry(
+        BlockFormat::Etc2Rgb8A1UnormSrgb,
+        TextureFormat::Etc2Rgb8A1UnormSrgb,
+        None,
+        150,
+    ),
+    entry(
+        BlockFormat::Etc2Rgba8Unorm,
+        TextureFormat::Etc2Rgba8Unorm,
+        None,
+        151,
+    ),
+    entry(
+        BlockFormat::Etc2Rgba8UnormSrgb,
+        TextureFormat::Etc2Rgba8UnormSrgb,
+        None,
+        152,
+    ),
+    entry(
+        BlockFormat::EacR11Unorm,
+        TextureFormat::EacR11Unorm,
+        None,
+        153,
+    ),
+    entry(
+        BlockFormat::EacR11Snorm,
+        TextureFormat::EacR11Snorm,
+        None,
+        154,
+    ),
+    entry(
+        BlockFormat::EacRg11Unorm,
+        TextureFormat::EacRg11Unorm,
+        None,
+        155,
+    ),
+    entry(
+        BlockFormat::EacRg11Snorm,
+        TextureFormat::EacRg11Snorm,
+        None,
+        156,
+    ),
+];
+
+const fn entry(
+    format: BlockFormat,
+    texture_format: TextureFormat,
+    dxgi_format: Option<u32>,
+    vk_format: u32,
+) -> Entry {
+    Entry {
+        format,
+        texture_format,
+        dxgi_format,
+        vk_format,
+    }
+}
+
+/// The typeless DXGI formats, which are read as the linear formats.
+const DXGI_TYPELESS: &[(u32, BlockFormat)] = &[
+    (70, BlockFormat::Bc1RgbaUnorm),
+    (73, BlockFormat::Bc2RgbaUnorm),
+    (76, BlockFormat::Bc3RgbaUnorm),
+    (79, BlockFormat::Bc4RUnorm),
+    (82, BlockFormat::Bc5RgUnorm),
+    (94, BlockFormat::Bc6hRgbUfloat),
+    (97, BlockFormat::Bc7RgbaUnorm),
+];
+
+/// `VK_FORMAT_BC1_RGB_UNORM_BLOCK` and `VK_FORMAT_BC1_RGB_SRGB_BLOCK`, which store the same data
+/// as the BC1 RGBA formats, but ignore the alpha.
+const VK_BC1_RGB: &[(u32, BlockFormat)] = &[
+    (131, BlockFormat::Bc1RgbaUnorm),
+    (132, BlockFormat::Bc1RgbaUnormSrgb),
+];
+
+/// `VK_FORMAT_ASTC_4x4_UNORM_BLOCK`, the first of the ASTC LDR formats, which alternate between
+/// UNORM and SRGB.
+#[cfg(feature = "astc")]
+const VK_ASTC_FIRST: u32 = 157;
+
+impl BlockFormat {
+    /// Returns the format, that the blocks of a compression variant are stored in.
+    ///
+    /// `srgb` selects the sRGB format for variants, that store colors. It's ignored by BC4, BC5,
+    /// BC6H and EAC. The encoders of this crate only create unsigned data, so the SNORM and SF16
+    /// formats are never returned.
+    pub const fn from_variant(variant: CompressionVariant, srgb: bool) -> Self {
+        match (variant, srgb) {
+            (CompressionVariant::BC1, false) => Self::Bc1RgbaUnorm,
+            (CompressionVariant::BC1, true) => Self::Bc1RgbaUnormSrgb,
+            (CompressionVariant::BC2, false) => Self::Bc2RgbaUnorm,
+            (CompressionVariant::BC2, true) => Self::Bc2RgbaUnormSrgb,
+            (CompressionVariant::BC3, false) => Self::Bc3RgbaUnorm,
+            (CompressionVariant::BC3, true) => Self::Bc3RgbaUnormSrgb,
+            (CompressionVariant::BC4, _) => Self::Bc4RUnorm,
+            (CompressionVariant::BC5, _) => Self::Bc5RgUnorm,
+            #[cfg(feature = "bc6h")]
+            (CompressionVariant::BC6H(..), _) => Self::Bc6hRgbUfloat,
+            #[cfg(feature = "bc7")]
+            (CompressionVariant::BC7(..), false) => Self::Bc7RgbaUnorm,
+            #[cfg(feature = "bc7")]
+            (CompressionVariant::BC7(..), true) => Self::Bc7RgbaUnormSrgb,
+            #[cfg(feature = "etc2")]
+            (CompressionVariant::ETC2RGB8, false) => Self::Etc2Rgb8Unorm,
+            #[cfg(feature = "etc2")]
+            (CompressionVariant::ETC2RGB8, true) => Self::Etc2Rgb8UnormSrgb,
+            #[cfg(feature = "etc2")]
+            (CompressionVariant::ETC2RGB8A1, false) => Self::Etc2Rgb8A1Unorm,
+            #[cfg(feature = "etc2")]
+            (CompressionVariant::ETC2RGB8A1, true) => Self::Etc2Rgb8A1UnormSrgb,
+            #[cfg(feature = "etc2")]
+            (CompressionVariant::ETC2RGBA8, false) => Self::Etc2Rgba8Unorm,
+            #[cfg(feature = "etc2")]
+            (CompressionVariant::ETC2RGBA8, true) => Self::Etc2Rgba8UnormSrgb,
+            #[cfg(feature = "etc2")]
+            (CompressionVariant::EACR11, _) => Self::EacR11Unorm,
+            #[cfg(feature = "etc2")]
+            (CompressionVariant::EACRG11, _) => Self::EacRg11Unorm,
+            #[cfg(feature = "astc")]
+            (CompressionVariant::ASTC(block_size), srgb) => Self::Astc { block_size, srgb },
+        }
+    }
+
+    /// Returns the compression variant, that encodes and decodes this format.
+    ///
+    /// BC6H uses the `very_slow` and BC7 the `alpha_slow` settings. Returns `None` for the SNORM
+    /// and SF16 formats, which this crate can't encode, and for formats of disabled features.
+    pub const fn variant(self) -> Option<CompressionVariant> {
+        match self {
+            Self::Bc1RgbaUnorm | Self::Bc1RgbaUnormSrgb => Some(CompressionVariant::BC1),
+            Self::Bc2RgbaUnorm | Self::Bc2RgbaUnormSrgb => Some(CompressionVariant::BC2),
+            Self::Bc3RgbaUnorm | Self::Bc3RgbaUnormSrgb => Some(CompressionVariant::BC3),
+            Self::Bc4RUnorm => Some(CompressionVariant::BC4),
+            Self::Bc5RgUnorm => Some(CompressionVariant::BC5),
+            #[cfg(feature = "bc6h")]
+            Self::Bc6hRgbUfloat => Some(CompressionVariant::BC6H(BC6HSettings::very_slow())),
+            #[cfg(feature = "bc7")]
+            Self::Bc7RgbaUnorm | Self::Bc7RgbaUnormSrgb => {
+                Some(CompressionVariant::BC7(BC7Settings::alpha_slow()))
+            }
+            #[cfg(feature = "etc2")]
+            Self::Etc2Rgb8Unorm | Self::Etc2Rgb8UnormSrgb => Some(CompressionVariant::ETC2RGB8),
+            #[cfg(feature = "etc2")]
+            Self::Etc2Rgb8A1Unorm | Self::Etc2Rgb8A1UnormSrgb => {
+                Some(CompressionVariant::ETC2RGB8A1)
+            }
+            #[cfg(feature = "etc2")]
+            Self::Etc2Rgba8Unorm | Self::Etc2Rgba8UnormSrgb => Some(CompressionVariant::ETC2RGBA8),
+            #[cfg(feature = "etc2")]
+            Self::EacR11Unorm => Some(CompressionVariant::EACR11),
+            #[cfg(feature = "etc2")]
+            Self::EacRg11Unorm => Some(CompressionVariant::EACRG11),
+            #[cfg(feature = "astc")]
+            Self::Astc { block_size, .. } => Some(CompressionVariant::ASTC(block_size)),
+            _ => None,
+        }
+    }
+
+    /// Returns the wgpu texture format.
+    pub fn texture_format(self) -> TextureFormat {
+        #[cfg(feature = "astc")]
+        if let Self::Astc { block_size, srgb } = self {
+            return TextureFormat::Astc {
+                block: astc_block(block_size),
+                channel: if srgb {
+                    AstcChannel::UnormSrgb
+                } else {
+                    AstcChannel::Unorm
+                },
+            };
+        }
+
+        self.entry().texture_format
+    }
+
+    /// Returns the format of a wgpu texture format. Returns `None` for formats, that are not
+    /// block compressed, and for ASTC HDR.
+    pub fn from_texture_format(texture_format: TextureFormat) -> Option<Self> {
+        #[cfg(feature = "astc")]
+        if let TextureFormat::Astc { block, channel } = texture_format {
+            let block_size = ASTCBlockSize::ALL
+                .into_iter()
+                .find(|&block_size| astc_block(block_size) == block)?;
+
+            return match channel {
+                AstcChannel::Unorm => Some(Self::Astc {
+                    block_size,
+                    srgb: false,
+                }),
+                AstcChannel::UnormSrgb => Some(Self::Astc {
+                    block_size,
+                    srgb: true,
+                }),
+                AstcChannel::Hdr => None,
+            };
+        }
+
+        ENTRIES
+            .iter()
+            .find(|entry| entry.texture_format == texture_format)
+            .map(|entry| entry.format)
+    }
+
+    /// Returns the value of the `DXGI_FORMAT`. Returns `None` for ETC2, EAC and ASTC, which
+    /// DXGI doesn't support.
+    pub fn dxgi_format(self) -> Option<u32> {
+        #[cfg(feature = "astc")]
+        if let Self::Astc { .. } = self {
+            return None;
+        }
+
+        self.entry().dxgi_format
+    }
+
+    /// Returns the format of a `DXGI_FORMAT` value. The typeless formats are returned as the
+    /// linear formats.
+    pub fn from_dxgi_format(dxgi_format: u32) -> Option<Self> {
+        ENTRIES
+            .iter()
+            .find(|entry| entry.dxgi_format == Some(dxgi_format))
+            .map(|entry| entry.format)
+            .or_else(|| {
+                DXGI_TYPELESS
+                    .iter()
+                    .find(|(typeless, _)| *typeless == dxgi_format)
+                    .map(|&(_, format)| format)
+            })
+    }
+
+    /// Returns the value of the `VkFormat`.
+    pub fn vk_format(self) -> u32 {
+        #[cfg(feature = "astc")]
+        if let Self::Astc { block_size, srgb } = self {
+            let index = ASTCBlockSize::ALL
+                .iter()
+                .position(|&other| other == block_size)
+                .expect("all block sizes are listed") as u32;
+            return VK_ASTC_FIRST + index * 2 + srgb as u32;
+        }
+
+        self.entry().vk_format
+    }
+
+    /// Returns the format of a `VkFormat` value. The BC1 RGB formats are returned as the BC1
+    /// RGBA formats, which store the same data.
+    pub fn from_vk_format(vk_format: u32) -> Option<Self> {
+        #[cfg(feature = "astc")]
+        if let Some(offset) = vk_format.checked_sub(VK_ASTC_FIRST) {
+            if let Some(&block_size) = ASTCBlockSize::ALL.get(offset as usize / 2) {
+                return Some(Self::Astc {
+                    block_size,
+                    srgb: offset % 2 == 1,
+                });
+            }
+        }
+
+        ENTRIES
+            .iter()
+            .find(|entry| entry.vk_format == vk_format)
+            .map(|entry| entry.format)
+            .or_else(|| {
+                VK_BC1_RGB
+                    .iter()
+                    .find(|(bc1_rgb, _)| *bc1_rgb == vk_format)
+                    .map(|&(_, format)| format)
+            })
+    }
+
+    /// Returns the width and height of a block in pixels.
+    pub const fn block_footprint(self) -> (u32, u32) {
+        match self {
+            #[cfg(feature = "astc")]
+            Self::Astc { block_size, .. } => block_size.dimensions(),
+            _ => (4, 4),
+        }
+    }
+
+    /// Returns the size of a block in bytes.
+    pub const fn block_byte_size(self) -> u32 {
+        match self {
+            Self::Bc1RgbaUnorm
+            | Self::Bc1RgbaUnormSrgb
+            | Self::Bc4RUnorm
+            | Self::Bc4RSnorm
+            | Self::Etc2Rgb8Unorm
+            | Self::Etc2Rgb8UnormSrgb
+            | Self::Etc2Rgb8A1Unorm
+            | Self::Etc2Rgb8A1UnormSrgb
+            | Self::EacR11Unorm
+            | Self::EacR11Snorm => 8,
+            _ => 16,
+        }
+    }
+
+    /// Returns the channels, that the format stores. BC1 stores a binary alpha.
+    pub const fn channels(self) -> ChannelLayout {
+        match self {
+            Self::Bc4RUnorm | Self::Bc4RSnorm | Self::EacR11Unorm | Self::EacR11Snorm => {
+                ChannelLayout::R
+            }
+            Self::Bc5RgUnorm | Self::Bc5RgSnorm | Self::EacRg11Unorm | Self::EacRg11Snorm => {
+                ChannelLayout::Rg
+            }
+            Self::Bc6hRgbUfloat
+            | Self::Bc6hRgbFloat
+            | Self::Etc2Rgb8Unorm
+            | Self::Etc2Rgb8UnormSrgb => ChannelLayout::Rgb,
+            _ => ChannelLayout::Rgba,
+        }
+    }
+
+    /// Returns how the values of the channels are interpreted.
+    pub const fn value_type(self) -> ValueType {
+        match self {
+            Self::Bc1RgbaUnormSrgb
+            | Self::Bc2RgbaUnormSrgb
+            | Self::Bc3RgbaUnormSrgb
+            | Self::Bc7RgbaUnormSrgb
+            | Self::Etc2Rgb8UnormSrgb
+            | Self::Etc2Rgb8A1UnormSrgb
+            | Self::Etc2Rgba8UnormSrgb => ValueType::UnormSrgb,
+            #[cfg(feature = "astc")]
+            Self::Astc { srgb: true, .. } => ValueType::UnormSrgb,
+            Self::Bc4RSnorm | Self::Bc5RgSnorm | Self::EacR11Snorm | Self::EacRg11Snorm => {
+                ValueType::Snorm
+            }
+            Self::Bc6hRgbUfloat => ValueType::Ufloat,
+            Self::Bc6hRgbFloat => ValueType::Sfloat,
+            _ => ValueType::Unorm,
+        }
+    }
+
+    /// Returns the sRGB or linear counterpart of the format. Returns `None`, if the format has no
+    /// sRGB counterpart.
+    pub fn with_srgb(self, srgb: bool) -> Option<Self> {
+        #[cfg(feature = "astc")]
+        if let Self::Astc { block_size, .. } = self {
+            return Some(Self::Astc { block_size, srgb });
+        }
+
+        if !matches!(self.value_type(), ValueType::Unorm | ValueType::UnormSrgb) {
+            return None;
+        }
+
+        let format = self.variant_family()?;
+        let with_srgb = ENTRIES.iter().map(|entry| entry.format).find(|&other| {
+            other.variant_family() == Some(format)
+                && (other.value_type() == ValueType::UnormSrgb) == srgb
+        })?;
+
+        Some(with_srgb)
+    }
+
+    /// The linear format of the sRGB and linear pairs.
+    fn variant_family(self) -> Option<Self> {
+        match self {
+            Self::Bc1RgbaUnorm | Self::Bc1RgbaUnormSrgb => Some(Self::Bc1RgbaUnorm),
+            Self::Bc2RgbaUnorm | Self::Bc2RgbaUnormSrgb => Some(Self::Bc2RgbaUnorm),
+            Self::Bc3RgbaUnorm | Self::Bc3RgbaUnormSrgb => Some(Self::Bc3RgbaUnorm),
+            Self::Bc7RgbaUnorm | Self::Bc7RgbaUnormSrgb => Some(Self::Bc7RgbaUnorm),
+            Self::Etc2Rgb8Unorm | Self::Etc2Rgb8UnormSrgb => Some(Self::Etc2Rgb8Unorm),
+            Self::Etc2Rgb8A1Unorm | Self::Etc2Rgb8A1UnormSrgb => Some(Self::Etc2Rgb8A1Unorm),
+            Self::Etc2Rgba8Unorm | Self::Etc2Rgba8UnormSrgb => Some(Self::Etc2Rgba8Unorm),
+            _ => None,
+        }
+    }
+
+    fn entry(self) -> &'static Entry {
+        ENTRIES
+            .iter()
+            .find(|entry| entry.format == self)
+            .expect("all formats without a block footprint are listed")
+    }
+}
+
+#[cfg(feature = "astc")]
+const fn astc_block(block_size: ASTCBlockSize) -> AstcBlock {
+    match block_size {
+        ASTCBlockSize::Block4x4 => AstcBlock::B4x4,
+        ASTCBlockSize::Block5x4 => AstcBlock::B5x4,
+        ASTCBlockSize::Block5x5 => AstcBlock::B5x5,
+        ASTCBlockSize::Block6x5 => AstcBlock::B6x5,
+        ASTCBlockSize::Block6x6 => AstcBlock::B6x6,
+        ASTCBlockSize::Block8x5 => AstcBlock::B8x5,
+        ASTCBlockSize::Block8x6 => AstcBlock::B8x6,
+        ASTCBlockSize::Block8x8 => AstcBlock::B8x8,
+        ASTCBlockSize::Block10x5 => AstcBlock::B10x5,
+        ASTCBlockSize::Block10x6 => AstcBlock::B10x6,
+        ASTCBlockSize::Block10x8 => AstcBlock::B10x8,
+        ASTCBlockSize::Block10x10 => AstcBlock::B10x10,
+        ASTCBlockSize::Block12x10 => AstcBlock::B12x10,
+        ASTCBlockSize::Block12x12 => AstcBlock::B12x12,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn all_formats() -> Vec<BlockFormat> {
+        let mut formats: Vec<BlockFormat> = ENTRIES.iter().map(|entry| entry.format).collect();
+        #[cfg(feature = "astc")]
+        for block_size in ASTCBlockSize::ALL {
+            formats.push(BlockFormat::Astc {
+                block_size,
+                srgb: false,
+            });
+            formats.push(BlockFormat::Astc {
+                block_size,
+                srgb: true,
+            });
+        }
+        formats
+    }
+
+    #[test]
+    fn identifiers_round_trip() {
+        for format in all_formats() {
+            assert_eq!(
+                BlockFormat::from_texture_format(format.texture_format()),
+                Some(format)
+            );
+            assert_eq!(
+                BlockFormat::from_vk_format(format.vk_format()),
+                Some(format)
+            );
+            if let Some(dxgi_format) = format.dxgi_format() {
+                assert_eq!(BlockFormat::from_dxgi_format(dxgi_format), Some(format));
+            }
+        }
+    }
+
+    #[test]
+    fn block_sizes_match_wgpu() {
+        for format in all_formats() {
+            let texture_format = format.texture_format();
+            assert_eq!(
+                Some(format.block_byte_size()),
+                texture_format.block_copy_size(None),
+                "{format:?}"
+            );
+            assert_eq!(
+                format.block_footprint(),
+                texture_format.block_dimensions(),
+                "{format:?}"
+            );
+            assert_eq!(
+                format.value_type() == ValueType::UnormSrgb,
+                texture_format.is_srgb(),
+                "{format:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn variants_round_trip() {
+        for format in all_formats() {
+            let Some(variant) = format.variant() else {
+                continue;
+            };
+            let srgb = format.value_type() == ValueType::UnormSrgb;
+            assert_eq!(BlockFormat::from_variant(variant, srgb), format);
+        }
+    }
+
+    #[test]
+    fn srgb_counterparts() {
+        assert_eq!(
+            BlockFormat::Bc7RgbaUnorm.with_srgb(true),
+            Some(BlockFormat::Bc7RgbaUnormSrgb)
+        );
+        assert_eq!(
+            BlockFormat::Bc1RgbaUnormSrgb.with_srgb(false),
+            Some(BlockFormat::Bc1RgbaUnorm)
+        );
+        assert_eq!(BlockFormat::Bc4RUnorm.with_srgb(true), None);
+    }
+}
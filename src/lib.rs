@@ -13,7 +13,8 @@
 //!
 //! The pipeline creation for BC7 and especially BC6H takes a long time under DX12. The DXC compiler
 //! seems to take a very long time to compile the shader. For this reason we moved them behind
-//! features, which are included in the default features.
+//! features, which are included in the default features. The ETC2 and EAC variants, which are
+//! mostly used on mobile devices, are behind the `etc2` feature, ASTC is behind the `astc` feature.
 //!
 //! ## Supported block compressions
 //!
@@ -26,26 +27,56 @@
 //!  * BC5
 //!  * BC6H
 //!  * BC7
+//!  * ETC2 RGB8, RGB8A1 and RGBA8
+//!  * EAC R11 and RG11
+//!  * ASTC LDR with all 2D block footprints from 4x4 to 12x12
+//!
+//! ## Decompression
+//!
+//! Compressed blocks can be decoded on the CPU with the [`decode`] module, or on the GPU with the
+//! [`GpuBlockDecompressor`], which writes into a RGBA8 or RGBA16F storage texture.
+//!
+//! ## Specifications
+//!
+//! [`CompressionVariant`] prints and parses a stable specification of the form
+//! `variant[:preset][{field=value,...}]`, for example `bc1`, `bc7:alpha_slow` or
+//! `bc6h{refine=2}`. The optional `serde` feature serializes variants and settings as this
+//! specification.
 
 #![cfg_attr(docsrs, feature(doc_cfg))]
 
+pub mod analysis;
+#[cfg(feature = "astc")]
+mod astc;
 mod block_compressor;
+mod block_decompressor;
 pub mod decode;
 pub mod encode;
+pub mod format;
 mod settings;
+mod spec;
+pub mod virtual_texture;
 
 use std::hash::{Hash, Hasher};
 
-pub use block_compressor::GpuBlockCompressor;
+#[cfg(feature = "astc")]
+pub use astc::ASTCBlockSize;
+pub use block_compressor::{GpuBlockCompressor, SourceBufferFormat, TaskTimestamps};
+pub use block_decompressor::{GpuBlockDecompressor, OutputTextureFormat};
 #[cfg(feature = "bc6h")]
 #[cfg_attr(docsrs, doc(cfg(feature = "bc6h")))]
 pub use half;
+pub use settings::AlphaOptions;
+#[cfg(any(feature = "bc6h", feature = "bc7"))]
+#[cfg_attr(docsrs, doc(cfg(any(feature = "bc6h", feature = "bc7"))))]
+pub use settings::SettingsError;
 #[cfg(feature = "bc6h")]
 #[cfg_attr(docsrs, doc(cfg(feature = "bc6h")))]
-pub use settings::BC6HSettings;
+pub use settings::{BC6HSettings, BC6HSettingsBuilder};
 #[cfg(feature = "bc7")]
 #[cfg_attr(docsrs, doc(cfg(feature = "bc7")))]
-pub use settings::BC7Settings;
+pub use settings::{BC7Settings, BC7SettingsBuilder};
+pub use spec::ParseVariantError;
 
 /// Block compression variants supported by this crate.
 #[derive(Copy, Clone, Debug)]
@@ -68,6 +99,30 @@ pub enum CompressionVariant {
     #[cfg_attr(docsrs, doc(cfg(feature = "bc7")))]
     /// BC7 compression with smooth alpha (RGBA)
     BC7(BC7Settings),
+    #[cfg(feature = "etc2")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "etc2")))]
+    /// ETC2 compression (RGB)
+    ETC2RGB8,
+    #[cfg(feature = "etc2")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "etc2")))]
+    /// ETC2 compression with punch-through alpha (RGBA)
+    ETC2RGB8A1,
+    #[cfg(feature = "etc2")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "etc2")))]
+    /// ETC2 compression with EAC alpha (RGBA)
+    ETC2RGBA8,
+    #[cfg(feature = "etc2")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "etc2")))]
+    /// EAC compression with 11-bit precision (R)
+    EACR11,
+    #[cfg(feature = "etc2")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "etc2")))]
+    /// EAC compression with 11-bit precision (RG)
+    EACRG11,
+    #[cfg(feature = "astc")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "astc")))]
+    /// ASTC LDR compression with the given block footprint (RGBA)
+    ASTC(ASTCBlockSize),
 }
 
 impl PartialEq for CompressionVariant {
@@ -85,28 +140,75 @@ impl Hash for CompressionVariant {
 }
 
 impl CompressionVariant {
+    /// Compares the variants including their settings and ASTC block footprints.
+    ///
+    /// `PartialEq` and `Hash` only compare the variants themselves.
+    pub fn eq_with_settings(&self, other: &Self) -> bool {
+        match (self, other) {
+            #[cfg(feature = "bc6h")]
+            (Self::BC6H(settings), Self::BC6H(other_settings)) => settings == other_settings,
+            #[cfg(feature = "bc7")]
+            (Self::BC7(settings), Self::BC7(other_settings)) => settings == other_settings,
+            #[cfg(feature = "astc")]
+            (Self::ASTC(block_size), Self::ASTC(other_block_size)) => {
+                block_size == other_block_size
+            }
+            _ => self == other,
+        }
+    }
+
+    /// Returns the width and height of a block in pixels.
+    ///
+    /// All variants except ASTC use 4x4 blocks.
+    pub const fn block_footprint(self) -> (u32, u32) {
+        match self {
+            #[cfg(feature = "astc")]
+            Self::ASTC(block_size) => block_size.dimensions(),
+            _ => (4, 4),
+        }
+    }
+
     /// Returns the bytes per row for the given width.
     ///
     /// The width is used to calculate how many blocks are needed per row,
     /// which is then multiplied by the block size.
-    /// Width is rounded up to the nearest multiple of 4.
+    /// Width is rounded up to the nearest multiple of the block width.
     pub const fn bytes_per_row(self, width: u32) -> u32 {
-        let blocks_per_row = (width + 3) / 4;
+        let blocks_per_row = width.div_ceil(self.block_footprint().0);
         blocks_per_row * self.block_byte_size()
     }
 
     /// Returns the byte size required for storing compressed blocks for the given dimensions.
     ///
     /// The size is calculated based on the block compression format and rounded up dimensions.
-    /// Width and height are rounded up to the nearest multiple of 4.
+    /// Width and height are rounded up to the nearest multiple of the block footprint.
     pub const fn blocks_byte_size(self, width: u32, height: u32) -> usize {
-        let block_width = (width as usize + 3) / 4;
-        let block_height = (height as usize + 3) / 4;
+        let (footprint_width, footprint_height) = self.block_footprint();
+        let block_width = width.div_ceil(footprint_width) as usize;
+        let block_height = height.div_ceil(footprint_height) as usize;
         let block_count = block_width * block_height;
         let block_size = self.block_byte_size() as usize;
         block_count * block_size
     }
 
+    /// ASTC encoders clamp reads to the image border, so images of any size can be compressed.
+    /// All other variants need dimensions that are a multiple of 4.
+    const fn supports_any_size(self) -> bool {
+        match self {
+            #[cfg(feature = "astc")]
+            Self::ASTC(..) => true,
+            _ => false,
+        }
+    }
+
+    /// BC1 to BC5 have an integer only encoder, that is identical on the CPU and the GPU.
+    const fn supports_strict_mode(self) -> bool {
+        matches!(
+            self,
+            Self::BC1 | Self::BC2 | Self::BC3 | Self::BC4 | Self::BC5
+        )
+    }
+
     const fn block_byte_size(self) -> u32 {
         match self {
             Self::BC1 | Self::BC4 => 8,
@@ -115,6 +217,12 @@ impl CompressionVariant {
             Self::BC6H(..) => 16,
             #[cfg(feature = "bc7")]
             Self::BC7(..) => 16,
+            #[cfg(feature = "etc2")]
+            Self::ETC2RGB8 | Self::ETC2RGB8A1 | Self::EACR11 => 8,
+            #[cfg(feature = "etc2")]
+            Self::ETC2RGBA8 | Self::EACRG11 => 16,
+            #[cfg(feature = "astc")]
+            Self::ASTC(..) => 16,
         }
     }
 
@@ -129,6 +237,18 @@ impl CompressionVariant {
             Self::BC6H(..) => "bc6h",
             #[cfg(feature = "bc7")]
             Self::BC7(..) => "bc7",
+            #[cfg(feature = "etc2")]
+            Self::ETC2RGB8 => "etc2_rgb8",
+            #[cfg(feature = "etc2")]
+            Self::ETC2RGB8A1 => "etc2_rgb8a1",
+            #[cfg(feature = "etc2")]
+            Self::ETC2RGBA8 => "etc2_rgba8",
+            #[cfg(feature = "etc2")]
+            Self::EACR11 => "eac_r11",
+            #[cfg(feature = "etc2")]
+            Self::EACRG11 => "eac_rg11",
+            #[cfg(feature = "astc")]
+            Self::ASTC(..) => "astc",
         }
     }
 
@@ -143,6 +263,18 @@ impl CompressionVariant {
             Self::BC6H(..) => "compress_bc6h",
             #[cfg(feature = "bc7")]
             Self::BC7(..) => "compress_bc7",
+            #[cfg(feature = "etc2")]
+            Self::ETC2RGB8 => "compress_etc2_rgb8",
+            #[cfg(feature = "etc2")]
+            Self::ETC2RGB8A1 => "compress_etc2_rgb8a1",
+            #[cfg(feature = "etc2")]
+            Self::ETC2RGBA8 => "compress_etc2_rgba8",
+            #[cfg(feature = "etc2")]
+            Self::EACR11 => "compress_eac_r11",
+            #[cfg(feature = "etc2")]
+            Self::EACRG11 => "compress_eac_rg11",
+            #[cfg(feature = "astc")]
+            Self::ASTC(..) => "compress_astc",
         }
     }
 }
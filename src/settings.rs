@@ -1,17 +1,39 @@
 #[cfg(any(feature = "bc6h", feature = "bc7"))]
 use bytemuck::{Pod, Zeroable};
 
+/// How the alpha channel influences the color encoding of BC2, BC3 and BC7.
+///
+/// By default the color endpoints are fit with the same weight for all texels, even for fully
+/// transparent ones, whose colors are never visible. Both options are ignored by all other
+/// variants.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct AlphaOptions {
+    /// Multiplies the color channels with the alpha channel before encoding. The compressed data
+    /// then contains premultiplied colors, which must be blended accordingly.
+    pub premultiply: bool,
+    /// Weights the color error of each texel by its alpha value, so that the color endpoints fit
+    /// the visible texels best.
+    pub weight_color_by_alpha: bool,
+}
+
+impl AlphaOptions {
+    /// Packs the options into the flags, that the compute shaders expect.
+    pub(crate) const fn flags(self) -> u32 {
+        (self.premultiply as u32) | ((self.weight_color_by_alpha as u32) << 1)
+    }
+}
+
 /// Encoding settings for BC6H.
 #[cfg(feature = "bc6h")]
 #[cfg_attr(docsrs, doc(cfg(feature = "bc6h")))]
-#[derive(Copy, Clone, Debug, Eq, PartialEq, Pod, Zeroable)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Pod, Zeroable)]
 #[repr(C)]
 pub struct BC6HSettings {
-    slow_mode: u32,
-    fast_mode: u32,
-    refine_iterations_1p: u32,
-    refine_iterations_2p: u32,
-    fast_skip_threshold: u32,
+    pub(crate) slow_mode: u32,
+    pub(crate) fast_mode: u32,
+    pub(crate) refine_iterations_1p: u32,
+    pub(crate) refine_iterations_2p: u32,
+    pub(crate) fast_skip_threshold: u32,
 }
 
 #[cfg(feature = "bc6h")]
@@ -72,21 +94,199 @@ impl BC6HSettings {
     }
 }
 
+/// Builder for custom BC6H settings, that starts from one of the presets.
+///
+/// The setters accept any value, [`BC6HSettingsBuilder::build()`] validates the ranges and
+/// combinations of all fields.
+///
+/// ```
+/// use block_compression::{BC6HSettings, BC6HSettingsBuilder};
+///
+/// let settings = BC6HSettingsBuilder::new(BC6HSettings::basic())
+///     .refine_iterations_2p(3)
+///     .build()
+///     .unwrap();
+/// ```
+#[cfg(feature = "bc6h")]
+#[cfg_attr(docsrs, doc(cfg(feature = "bc6h")))]
+#[derive(Copy, Clone, Debug)]
+pub struct BC6HSettingsBuilder {
+    settings: BC6HSettings,
+}
+
+#[cfg(feature = "bc6h")]
+#[cfg_attr(docsrs, doc(cfg(feature = "bc6h")))]
+impl BC6HSettingsBuilder {
+    /// The maximum number of refinement iterations.
+    pub const MAX_REFINE_ITERATIONS: u32 = 8;
+    /// The maximum fast skip threshold, which is the number of partitions of the two subset modes.
+    pub const MAX_FAST_SKIP_THRESHOLD: u32 = 32;
+
+    /// Creates a new builder with the values of the given settings.
+    pub const fn new(settings: BC6HSettings) -> Self {
+        Self { settings }
+    }
+
+    /// Tests all modes of the format exhaustively. Can't be combined with the fast mode.
+    pub const fn slow_mode(mut self, slow_mode: bool) -> Self {
+        self.settings.slow_mode = slow_mode as _;
+        self
+    }
+
+    /// Only tests a reduced set of modes. Can't be combined with the slow mode.
+    pub const fn fast_mode(mut self, fast_mode: bool) -> Self {
+        self.settings.fast_mode = fast_mode as _;
+        self
+    }
+
+    /// Refinement iterations of the endpoints of the single subset modes, from 0 to
+    /// [`BC6HSettingsBuilder::MAX_REFINE_ITERATIONS`].
+    pub const fn refine_iterations_1p(mut self, iterations: u32) -> Self {
+        self.settings.refine_iterations_1p = iterations;
+        self
+    }
+
+    /// Refinement iterations of the endpoints of the two subset modes, from 0 to
+    /// [`BC6HSettingsBuilder::MAX_REFINE_ITERATIONS`].
+    pub const fn refine_iterations_2p(mut self, iterations: u32) -> Self {
+        self.settings.refine_iterations_2p = iterations;
+        self
+    }
+
+    /// Number of the best partitions of the two subset modes, that are fully evaluated, from 0
+    /// to [`BC6HSettingsBuilder::MAX_FAST_SKIP_THRESHOLD`]. Without the slow mode, 0 skips the
+    /// two subset modes.
+    pub const fn fast_skip_threshold(mut self, threshold: u32) -> Self {
+        self.settings.fast_skip_threshold = threshold;
+        self
+    }
+
+    /// Validates and returns the settings.
+    pub const fn build(self) -> Result<BC6HSettings, SettingsError> {
+        let settings = self.settings;
+
+        if settings.slow_mode != 0 && settings.fast_mode != 0 {
+            return Err(SettingsError::SlowAndFastMode);
+        }
+        if let Err(error) = check_range(
+            "refine_iterations_1p",
+            settings.refine_iterations_1p,
+            0,
+            Self::MAX_REFINE_ITERATIONS,
+        ) {
+            return Err(error);
+        }
+        if let Err(error) = check_range(
+            "refine_iterations_2p",
+            settings.refine_iterations_2p,
+            0,
+            Self::MAX_REFINE_ITERATIONS,
+        ) {
+            return Err(error);
+        }
+        if let Err(error) = check_range(
+            "fast_skip_threshold",
+            settings.fast_skip_threshold,
+            0,
+            Self::MAX_FAST_SKIP_THRESHOLD,
+        ) {
+            return Err(error);
+        }
+
+        Ok(settings)
+    }
+}
+
+/// The reason why a settings builder rejected its values.
+#[cfg(any(feature = "bc6h", feature = "bc7"))]
+#[cfg_attr(docsrs, doc(cfg(any(feature = "bc6h", feature = "bc7"))))]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum SettingsError {
+    /// A field is outside of its documented range.
+    OutOfRange {
+        /// The name of the field.
+        field: &'static str,
+        /// The rejected value.
+        value: u32,
+        /// The smallest valid value.
+        min: u32,
+        /// The biggest valid value.
+        max: u32,
+    },
+    /// The BC6H slow mode and fast mode are both enabled.
+    SlowAndFastMode,
+    /// The BC7 settings don't test a single mode.
+    NoModeSelected,
+    /// The BC7 modes 4 and 5 are selected, but the first channel rotation is not smaller than the
+    /// number of channels, so no rotation would be tested.
+    NoRotationSelected {
+        /// The first channel rotation.
+        mode45_channel0: u32,
+        /// The number of channels.
+        channels: u32,
+    },
+}
+
+#[cfg(any(feature = "bc6h", feature = "bc7"))]
+impl std::fmt::Display for SettingsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::OutOfRange {
+                field,
+                value,
+                min,
+                max,
+            } => write!(f, "{field} is {value}, but must be between {min} and {max}"),
+            Self::SlowAndFastMode => write!(f, "slow mode and fast mode can't both be enabled"),
+            Self::NoModeSelected => write!(f, "the settings don't test any mode"),
+            Self::NoRotationSelected {
+                mode45_channel0,
+                channels,
+            } => write!(
+                f,
+                "mode45_channel0 is {mode45_channel0}, but must be smaller than the {channels} \
+                 channels, if modes 4 and 5 are selected"
+            ),
+        }
+    }
+}
+
+#[cfg(any(feature = "bc6h", feature = "bc7"))]
+impl std::error::Error for SettingsError {}
+
+#[cfg(any(feature = "bc6h", feature = "bc7"))]
+const fn check_range(
+    field: &'static str,
+    value: u32,
+    min: u32,
+    max: u32,
+) -> Result<(), SettingsError> {
+    if value < min || value > max {
+        return Err(SettingsError::OutOfRange {
+            field,
+            value,
+            min,
+            max,
+        });
+    }
+    Ok(())
+}
+
 #[cfg(feature = "bc7")]
 #[cfg_attr(docsrs, doc(cfg(feature = "bc7")))]
 /// Encoding settings for BC7.
-#[derive(Copy, Clone, Debug, Eq, PartialEq, Pod, Zeroable)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Pod, Zeroable)]
 #[repr(C)]
 pub struct BC7Settings {
-    refine_iterations: [u32; 8],
-    mode_selection: [u32; 4],
-    skip_mode2: u32,
-    fast_skip_threshold_mode1: u32,
-    fast_skip_threshold_mode3: u32,
-    fast_skip_threshold_mode7: u32,
-    mode45_channel0: u32,
-    refine_iterations_channel: u32,
-    channels: u32,
+    pub(crate) refine_iterations: [u32; 8],
+    pub(crate) mode_selection: [u32; 4],
+    pub(crate) skip_mode2: u32,
+    pub(crate) fast_skip_threshold_mode1: u32,
+    pub(crate) fast_skip_threshold_mode3: u32,
+    pub(crate) fast_skip_threshold_mode7: u32,
+    pub(crate) mode45_channel0: u32,
+    pub(crate) refine_iterations_channel: u32,
+    pub(crate) channels: u32,
 }
 
 #[cfg(feature = "bc7")]
@@ -242,3 +442,288 @@ impl BC7Settings {
         }
     }
 }
+
+/// Builder for custom BC7 settings, that starts from one of the presets.
+///
+/// The setters accept any value, [`BC7SettingsBuilder::build()`] validates the ranges and
+/// combinations of all fields.
+///
+/// The modes are selected in four groups with [`BC7SettingsBuilder::mode_selection()`]. Inside
+/// the groups, mode 2 is disabled with [`BC7SettingsBuilder::skip_mode2()`] and the modes 1, 3
+/// and 7 are disabled with a fast skip threshold of 0. For example, only the modes 1 and 6 are
+/// tested with:
+///
+/// ```
+/// use block_compression::{BC7Settings, BC7SettingsBuilder};
+///
+/// let settings = BC7SettingsBuilder::new(BC7Settings::alpha_basic())
+///     .mode_selection([false, true, false, true])
+///     .fast_skip_threshold_mode3(0)
+///     .fast_skip_threshold_mode7(0)
+///     .refine_iterations(6, 4)
+///     .build()
+///     .unwrap();
+/// ```
+#[cfg(feature = "bc7")]
+#[cfg_attr(docsrs, doc(cfg(feature = "bc7")))]
+#[derive(Copy, Clone, Debug)]
+pub struct BC7SettingsBuilder {
+    settings: BC7Settings,
+    invalid_mode: Option<usize>,
+}
+
+#[cfg(feature = "bc7")]
+#[cfg_attr(docsrs, doc(cfg(feature = "bc7")))]
+impl BC7SettingsBuilder {
+    /// The maximum number of refinement iterations.
+    pub const MAX_REFINE_ITERATIONS: u32 = 8;
+    /// The maximum fast skip threshold, which is the number of partitions of a mode.
+    pub const MAX_FAST_SKIP_THRESHOLD: u32 = 64;
+
+    /// Creates a new builder with the values of the given settings.
+    pub const fn new(settings: BC7Settings) -> Self {
+        Self {
+            settings,
+            invalid_mode: None,
+        }
+    }
+
+    /// Refinement iterations of the endpoints of the given mode (0 to 7), from 0 to
+    /// [`BC7SettingsBuilder::MAX_REFINE_ITERATIONS`].
+    pub const fn refine_iterations(mut self, mode: usize, iterations: u32) -> Self {
+        if mode < self.settings.refine_iterations.len() {
+            self.settings.refine_iterations[mode] = iterations;
+        } else if self.invalid_mode.is_none() {
+            self.invalid_mode = Some(mode);
+        }
+        self
+    }
+
+    /// Selects the groups of modes, that are tested: modes 0 and 2, modes 1, 3 and 7, modes 4
+    /// and 5 and mode 6. At least one mode must be tested.
+    pub const fn mode_selection(mut self, mode_selection: [bool; 4]) -> Self {
+        self.settings.mode_selection = [
+            mode_selection[0] as _,
+            mode_selection[1] as _,
+            mode_selection[2] as _,
+            mode_selection[3] as _,
+        ];
+        self
+    }
+
+    /// Skips mode 2, when the modes 0 and 2 are selected.
+    pub const fn skip_mode2(mut self, skip_mode2: bool) -> Self {
+        self.settings.skip_mode2 = skip_mode2 as _;
+        self
+    }
+
+    /// Number of the best partitions of mode 1, that are fully evaluated, from 0 to
+    /// [`BC7SettingsBuilder::MAX_FAST_SKIP_THRESHOLD`]. 0 skips mode 1.
+    pub const fn fast_skip_threshold_mode1(mut self, threshold: u32) -> Self {
+        self.settings.fast_skip_threshold_mode1 = threshold;
+        self
+    }
+
+    /// Number of the best partitions of mode 3, that are fully evaluated, from 0 to
+    /// [`BC7SettingsBuilder::MAX_FAST_SKIP_THRESHOLD`]. 0 skips mode 3.
+    pub const fn fast_skip_threshold_mode3(mut self, threshold: u32) -> Self {
+        self.settings.fast_skip_threshold_mode3 = threshold;
+        self
+    }
+
+    /// Number of the best partitions of mode 7, that are fully evaluated, from 0 to
+    /// [`BC7SettingsBuilder::MAX_FAST_SKIP_THRESHOLD`]. 0 skips mode 7.
+    pub const fn fast_skip_threshold_mode7(mut self, threshold: u32) -> Self {
+        self.settings.fast_skip_threshold_mode7 = threshold;
+        self
+    }
+
+    /// First channel rotation, that is tested by the modes 4 and 5, from 0 to 3. The rotations
+    /// up to the number of channels are tested, so it must be smaller than the number of channels.
+    pub const fn mode45_channel0(mut self, channel: u32) -> Self {
+        self.settings.mode45_channel0 = channel;
+        self
+    }
+
+    /// Refinement iterations of the scalar channel of the modes 4 and 5, from 0 to
+    /// [`BC7SettingsBuilder::MAX_REFINE_ITERATIONS`].
+    pub const fn refine_iterations_channel(mut self, iterations: u32) -> Self {
+        self.settings.refine_iterations_channel = iterations;
+        self
+    }
+
+    /// Number of encoded channels: 3 for opaque and 4 for images with alpha.
+    pub const fn channels(mut self, channels: u32) -> Self {
+        self.settings.channels = channels;
+        self
+    }
+
+    /// Validates and returns the settings.
+    pub const fn build(self) -> Result<BC7Settings, SettingsError> {
+        let settings = self.settings;
+
+        if let Some(mode) = self.invalid_mode {
+            return Err(SettingsError::OutOfRange {
+                field: "refine_iterations mode",
+                value: mode as u32,
+                min: 0,
+                max: 7,
+            });
+        }
+
+        let mut mode = 0;
+        while mode < settings.refine_iterations.len() {
+            if let Err(error) = check_range(
+                "refine_iterations",
+                settings.refine_iterations[mode],
+                0,
+                Self::MAX_REFINE_ITERATIONS,
+            ) {
+                return Err(error);
+            }
+            mode += 1;
+        }
+
+        if let Err(error) = check_range(
+            "fast_skip_threshold_mode1",
+            settings.fast_skip_threshold_mode1,
+            0,
+            Self::MAX_FAST_SKIP_THRESHOLD,
+        ) {
+            return Err(error);
+        }
+        if let Err(error) = check_range(
+            "fast_skip_threshold_mode3",
+            settings.fast_skip_threshold_mode3,
+            0,
+            Self::MAX_FAST_SKIP_THRESHOLD,
+        ) {
+            return Err(error);
+        }
+        if let Err(error) = check_range(
+            "fast_skip_threshold_mode7",
+            settings.fast_skip_threshold_mode7,
+            0,
+            Self::MAX_FAST_SKIP_THRESHOLD,
+        ) {
+            return Err(error);
+        }
+        if let Err(error) = check_range("mode45_channel0", settings.mode45_channel0, 0, 3) {
+            return Err(error);
+        }
+        if let Err(error) = check_range(
+            "refine_iterations_channel",
+            settings.refine_iterations_channel,
+            0,
+            Self::MAX_REFINE_ITERATIONS,
+        ) {
+            return Err(error);
+        }
+        if let Err(error) = check_range("channels", settings.channels, 3, 4) {
+            return Err(error);
+        }
+
+        let [modes02, modes137, modes45, mode6] = settings.mode_selection;
+
+        if modes45 != 0 && settings.mode45_channel0 >= settings.channels {
+            return Err(SettingsError::NoRotationSelected {
+                mode45_channel0: settings.mode45_channel0,
+                channels: settings.channels,
+            });
+        }
+
+        let tests_modes137 = modes137 != 0
+            && (settings.fast_skip_threshold_mode1 != 0
+                || settings.fast_skip_threshold_mode3 != 0
+                || settings.fast_skip_threshold_mode7 != 0);
+
+        if modes02 == 0 && !tests_modes137 && modes45 == 0 && mode6 == 0 {
+            return Err(SettingsError::NoModeSelected);
+        }
+
+        Ok(settings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "bc6h")]
+    #[test]
+    fn bc6h_builder_validates() {
+        use super::{BC6HSettings, BC6HSettingsBuilder, SettingsError};
+
+        for preset in [
+            BC6HSettings::very_fast(),
+            BC6HSettings::fast(),
+            BC6HSettings::basic(),
+            BC6HSettings::slow(),
+            BC6HSettings::very_slow(),
+        ] {
+            assert_eq!(BC6HSettingsBuilder::new(preset).build(), Ok(preset));
+        }
+
+        let builder = BC6HSettingsBuilder::new(BC6HSettings::slow());
+        assert_eq!(
+            builder.fast_mode(true).build(),
+            Err(SettingsError::SlowAndFastMode)
+        );
+        assert!(matches!(
+            builder.fast_skip_threshold(33).build(),
+            Err(SettingsError::OutOfRange {
+                field: "fast_skip_threshold",
+                value: 33,
+                ..
+            })
+        ));
+    }
+
+    #[cfg(feature = "bc7")]
+    #[test]
+    fn bc7_builder_validates() {
+        use super::{BC7Settings, BC7SettingsBuilder, SettingsError};
+
+        for preset in [
+            BC7Settings::opaque_ultra_fast(),
+            BC7Settings::opaque_very_fast(),
+            BC7Settings::opaque_fast(),
+            BC7Settings::opaque_basic(),
+            BC7Settings::opaque_slow(),
+            BC7Settings::alpha_ultrafast(),
+            BC7Settings::alpha_very_fast(),
+            BC7Settings::alpha_fast(),
+            BC7Settings::alpha_basic(),
+            BC7Settings::alpha_slow(),
+        ] {
+            assert_eq!(BC7SettingsBuilder::new(preset).build(), Ok(preset));
+        }
+
+        let builder = BC7SettingsBuilder::new(BC7Settings::alpha_basic());
+        assert!(matches!(
+            builder.refine_iterations(8, 1).build(),
+            Err(SettingsError::OutOfRange { value: 8, .. })
+        ));
+        assert!(matches!(
+            builder.channels(2).build(),
+            Err(SettingsError::OutOfRange {
+                field: "channels",
+                ..
+            })
+        ));
+        assert_eq!(
+            builder.channels(3).mode45_channel0(3).build(),
+            Err(SettingsError::NoRotationSelected {
+                mode45_channel0: 3,
+                channels: 3
+            })
+        );
+        assert_eq!(
+            builder
+                .mode_selection([false, true, false, false])
+                .fast_skip_threshold_mode1(0)
+                .fast_skip_threshold_mode3(0)
+                .fast_skip_threshold_mode7(0)
+                .build(),
+            Err(SettingsError::NoModeSelected)
+        );
+    }
+}
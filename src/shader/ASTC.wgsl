@@ -0,0 +1,735 @@
+// Copyright (c) 2025, Nils Hasenbanck
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of this software and associated
+// documentation files (the "Software"), to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all copies or substantial portions of
+// the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+// THE WARRANTIES OF MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT,
+// TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+// ASTC LDR encoder. This is a port of the CPU encoder in `encode/astc.rs`.
+//
+// Every block is encoded as a single partition with one weight plane and the direct RGB or RGBA
+// endpoint mode. The 128-bit blocks are built in four little-endian u32 words.
+
+const SOURCE_TEXTURE: u32 = 0u;
+const SOURCE_BUFFER_RGBA8: u32 = 1u;
+const SOURCE_BUFFER_RGBA16F: u32 = 2u;
+const SOURCE_BUFFER_RGBA32F: u32 = 3u;
+
+const MAX_BLOCK_TEXELS: u32 = 144u;
+const MAX_ERROR: f32 = 3.40282347e+38;
+
+struct Uniforms {
+    width: u32,
+    height: u32,
+    blocks_offset: u32,
+    source_format: u32,
+    source_offset: u32,
+    source_stride: u32,
+    source_row: u32,
+}
+
+struct Parameters {
+    block_width: u32,
+    block_height: u32,
+    grid_width: u32,
+    grid_height: u32,
+    weight_quant: u32,
+    block_mode: u32,
+    color_quant_rgb: u32,
+    color_quant_rgba: u32,
+}
+
+struct Candidate {
+    color_values: array<u32, 8>,
+    grid_weights: array<u32, 64>,
+    error: f32,
+}
+
+struct Contributions {
+    index: vec4<u32>,
+    factor: vec4<u32>,
+}
+
+struct Endpoints {
+    e0: vec4<f32>,
+    e1: vec4<f32>,
+}
+
+@group(0) @binding(0) var source_texture: texture_2d<f32>;
+@group(0) @binding(1) var<storage, read_write> block_buffer: array<u32>;
+@group(0) @binding(2) var<uniform> uniforms: Uniforms;
+@group(0) @binding(3) var<storage, read> parameters: Parameters;
+@group(0) @binding(4) var<storage, read> source_buffer: array<u32>;
+
+var<private> block: array<f32, 576>;
+var<private> texel_count: u32;
+var<private> color_quant: u32;
+var<private> color_endpoint_mode: u32;
+
+// Number of levels, trits, quints and bits of the integer sequence encoding.
+fn get_quant_level(quant: u32) -> vec4<u32> {
+    const quant_levels = array<vec4<u32>, 21>(
+        vec4<u32>(2u, 0u, 0u, 1u),
+        vec4<u32>(3u, 1u, 0u, 0u),
+        vec4<u32>(4u, 0u, 0u, 2u),
+        vec4<u32>(5u, 0u, 1u, 0u),
+        vec4<u32>(6u, 1u, 0u, 1u),
+        vec4<u32>(8u, 0u, 0u, 3u),
+        vec4<u32>(10u, 0u, 1u, 1u),
+        vec4<u32>(12u, 1u, 0u, 2u),
+        vec4<u32>(16u, 0u, 0u, 4u),
+        vec4<u32>(20u, 0u, 1u, 2u),
+        vec4<u32>(24u, 1u, 0u, 3u),
+        vec4<u32>(32u, 0u, 0u, 5u),
+        vec4<u32>(40u, 0u, 1u, 3u),
+        vec4<u32>(48u, 1u, 0u, 4u),
+        vec4<u32>(64u, 0u, 0u, 6u),
+        vec4<u32>(80u, 0u, 1u, 4u),
+        vec4<u32>(96u, 1u, 0u, 5u),
+        vec4<u32>(128u, 0u, 0u, 7u),
+        vec4<u32>(160u, 0u, 1u, 5u),
+        vec4<u32>(192u, 1u, 0u, 6u),
+        vec4<u32>(256u, 0u, 0u, 8u),
+    );
+    return quant_levels[quant];
+}
+
+fn get_trit_encoding(index: u32) -> u32 {
+    const trit_encoding = array<u32, 243>(
+        0u, 1u, 2u, 4u, 5u, 6u, 8u, 9u, 10u, 16u, 17u, 18u, 20u, 21u, 22u, 24u,
+        25u, 26u, 3u, 7u, 11u, 19u, 23u, 27u, 12u, 13u, 14u, 32u, 33u, 34u, 36u, 37u,
+        38u, 40u, 41u, 42u, 48u, 49u, 50u, 52u, 53u, 54u, 56u, 57u, 58u, 35u, 39u, 43u,
+        51u, 55u, 59u, 44u, 45u, 46u, 64u, 65u, 66u, 68u, 69u, 70u, 72u, 73u, 74u, 80u,
+        81u, 82u, 84u, 85u, 86u, 88u, 89u, 90u, 67u, 71u, 75u, 83u, 87u, 91u, 76u, 77u,
+        78u, 128u, 129u, 130u, 132u, 133u, 134u, 136u, 137u, 138u, 144u, 145u, 146u, 148u, 149u, 150u,
+        152u, 153u, 154u, 131u, 135u, 139u, 147u, 151u, 155u, 140u, 141u, 142u, 160u, 161u, 162u, 164u,
+        165u, 166u, 168u, 169u, 170u, 176u, 177u, 178u, 180u, 181u, 182u, 184u, 185u, 186u, 163u, 167u,
+        171u, 179u, 183u, 187u, 172u, 173u, 174u, 192u, 193u, 194u, 196u, 197u, 198u, 200u, 201u, 202u,
+        208u, 209u, 210u, 212u, 213u, 214u, 216u, 217u, 218u, 195u, 199u, 203u, 211u, 215u, 219u, 204u,
+        205u, 206u, 96u, 97u, 98u, 100u, 101u, 102u, 104u, 105u, 106u, 112u, 113u, 114u, 116u, 117u,
+        118u, 120u, 121u, 122u, 99u, 103u, 107u, 115u, 119u, 123u, 108u, 109u, 110u, 224u, 225u, 226u,
+        228u, 229u, 230u, 232u, 233u, 234u, 240u, 241u, 242u, 244u, 245u, 246u, 248u, 249u, 250u, 227u,
+        231u, 235u, 243u, 247u, 251u, 236u, 237u, 238u, 28u, 29u, 30u, 60u, 61u, 62u, 92u, 93u,
+        94u, 156u, 157u, 158u, 188u, 189u, 190u, 220u, 221u, 222u, 31u, 63u, 95u, 159u, 191u, 223u,
+        124u, 125u, 126u,
+    );
+    return trit_encoding[index];
+}
+
+fn get_quint_encoding(index: u32) -> u32 {
+    const quint_encoding = array<u32, 125>(
+        0u, 1u, 2u, 3u, 4u, 8u, 9u, 10u, 11u, 12u, 16u, 17u, 18u, 19u, 20u, 24u,
+        25u, 26u, 27u, 28u, 5u, 13u, 21u, 29u, 6u, 32u, 33u, 34u, 35u, 36u, 40u, 41u,
+        42u, 43u, 44u, 48u, 49u, 50u, 51u, 52u, 56u, 57u, 58u, 59u, 60u, 37u, 45u, 53u,
+        61u, 14u, 64u, 65u, 66u, 67u, 68u, 72u, 73u, 74u, 75u, 76u, 80u, 81u, 82u, 83u,
+        84u, 88u, 89u, 90u, 91u, 92u, 69u, 77u, 85u, 93u, 22u, 96u, 97u, 98u, 99u, 100u,
+        104u, 105u, 106u, 107u, 108u, 112u, 113u, 114u, 115u, 116u, 120u, 121u, 122u, 123u, 124u, 101u,
+        109u, 117u, 125u, 30u, 102u, 103u, 70u, 71u, 38u, 110u, 111u, 78u, 79u, 46u, 118u, 119u,
+        86u, 87u, 54u, 126u, 127u, 94u, 95u, 62u, 39u, 47u, 55u, 63u, 7u,
+    );
+    return quint_encoding[index];
+}
+
+fn ise_bit_count(count: u32, quant: u32) -> u32 {
+    let level = get_quant_level(quant);
+    return count * level.w + (count * 8u * level.y + 4u) / 5u + (count * 7u * level.z + 2u) / 3u;
+}
+
+fn replicate_bits(value: u32, source_bits: u32, target_bits: u32) -> u32 {
+    var result = 0u;
+    var filled = 0u;
+    while (filled < target_bits) {
+        result = (result << source_bits) | value;
+        filled += source_bits;
+    }
+    return result >> (filled - target_bits);
+}
+
+fn unquantize_color(quant: u32, value: u32) -> u32 {
+    let level = get_quant_level(quant);
+    let bits = level.w;
+
+    if (level.y == 0u && level.z == 0u) {
+        return replicate_bits(value, bits, 8u);
+    }
+    if (bits == 0u) {
+        return value * 255u / (level.x - 1u);
+    }
+
+    let d = value >> bits;
+    let a = select(0u, 0x1FFu, (value & 1u) == 1u);
+    let b1 = (value >> 1u) & 1u;
+    let b2 = (value >> 2u) & 1u;
+    let b3 = (value >> 3u) & 1u;
+    let b4 = (value >> 4u) & 1u;
+    let b5 = (value >> 5u) & 1u;
+
+    var b = 0u;
+    var c = 0u;
+    if (level.y == 1u) {
+        switch (bits) {
+            case 1u: { c = 204u; }
+            case 2u: { b = b1 * 0x116u; c = 93u; }
+            case 3u: { b = b2 * 0x10Au + b1 * 0x085u; c = 44u; }
+            case 4u: { b = b3 * 0x104u + b2 * 0x082u + b1 * 0x041u; c = 22u; }
+            case 5u: { b = b4 * 0x102u + b3 * 0x081u + b2 * 0x040u + b1 * 0x020u; c = 11u; }
+            default: { b = b5 * 0x101u + b4 * 0x080u + b3 * 0x040u + b2 * 0x020u + b1 * 0x010u; c = 5u; }
+        }
+    } else {
+        switch (bits) {
+            case 1u: { c = 113u; }
+            case 2u: { b = b1 * 0x10Cu; c = 54u; }
+            case 3u: { b = b2 * 0x105u + b1 * 0x082u; c = 26u; }
+            case 4u: { b = b3 * 0x102u + b2 * 0x081u + b1 * 0x040u; c = 13u; }
+            default: { b = b4 * 0x101u + b3 * 0x080u + b2 * 0x040u + b1 * 0x020u; c = 6u; }
+        }
+    }
+
+    let t = (d * c + b) ^ a;
+    return (a & 0x80u) | (t >> 2u);
+}
+
+fn unquantize_weight(quant: u32, value: u32) -> u32 {
+    let level = get_quant_level(quant);
+    let bits = level.w;
+
+    var t = 0u;
+    if (level.y == 0u && level.z == 0u) {
+        t = replicate_bits(value, bits, 6u);
+    } else if (bits == 0u) {
+        return value * 64u / (level.x - 1u);
+    } else {
+        let d = value >> bits;
+        let a = select(0u, 0x7Fu, (value & 1u) == 1u);
+        let b1 = (value >> 1u) & 1u;
+        let b2 = (value >> 2u) & 1u;
+
+        var b = 0u;
+        var c = 0u;
+        if (level.y == 1u) {
+            switch (bits) {
+                case 1u: { c = 50u; }
+                case 2u: { b = b1 * 0x45u; c = 23u; }
+                default: { b = b2 * 0x42u + b1 * 0x21u; c = 11u; }
+            }
+        } else {
+            switch (bits) {
+                case 1u: { c = 28u; }
+                default: { b = b1 * 0x42u; c = 13u; }
+            }
+        }
+
+        t = (a & 0x20u) | (((d * c + b) ^ a) >> 2u);
+    }
+
+    return select(t, t + 1u, t > 32u);
+}
+
+fn quantize_color(quant: u32, value: f32) -> u32 {
+    let levels = get_quant_level(quant).x;
+    var best = 0u;
+    var best_error = MAX_ERROR;
+
+    for (var level = 0u; level < levels; level++) {
+        let error = abs(f32(unquantize_color(quant, level)) - value);
+        if (error < best_error) {
+            best = level;
+            best_error = error;
+        }
+    }
+
+    return best;
+}
+
+fn quantize_weight(quant: u32, value: f32) -> u32 {
+    let levels = get_quant_level(quant).x;
+    var best = 0u;
+    var best_error = MAX_ERROR;
+
+    for (var level = 0u; level < levels; level++) {
+        let error = abs(f32(unquantize_weight(quant, level)) - value);
+        if (error < best_error) {
+            best = level;
+            best_error = error;
+        }
+    }
+
+    return best;
+}
+
+fn infill_contributions(x: u32, y: u32) -> Contributions {
+    let block_width = parameters.block_width;
+    let block_height = parameters.block_height;
+    let grid_width = parameters.grid_width;
+    let grid_height = parameters.grid_height;
+
+    let ds = (1024u + block_width / 2u) / (block_width - 1u);
+    let dt = (1024u + block_height / 2u) / (block_height - 1u);
+
+    let gs = (ds * x * (grid_width - 1u) + 32u) >> 6u;
+    let gt = (dt * y * (grid_height - 1u) + 32u) >> 6u;
+
+    let js = gs >> 4u;
+    let fs = gs & 0xFu;
+    let jt = gt >> 4u;
+    let ft = gt & 0xFu;
+
+    let w11 = (fs * ft + 8u) >> 4u;
+    let w10 = ft - w11;
+    let w01 = fs - w11;
+    let w00 = 16u + w11 - fs - ft;
+
+    // Contributions outside of the grid always have a factor of zero.
+    let v0 = js + jt * grid_width;
+    let last = grid_width * grid_height - 1u;
+
+    var contributions: Contributions;
+    contributions.index = vec4<u32>(
+        v0,
+        min(v0 + 1u, last),
+        min(v0 + grid_width, last),
+        min(v0 + grid_width + 1u, last),
+    );
+    contributions.factor = vec4<u32>(w00, w01, w10, w11);
+    return contributions;
+}
+
+fn texel_weight(grid_weights: ptr<function, array<u32, 64>>, x: u32, y: u32) -> f32 {
+    let contributions = infill_contributions(x, y);
+
+    var sum = 8u;
+    for (var i = 0u; i < 4u; i++) {
+        let weight = unquantize_weight(parameters.weight_quant, (*grid_weights)[contributions.index[i]]);
+        sum += weight * contributions.factor[i];
+    }
+
+    return f32(sum >> 4u);
+}
+
+fn load_texel(pixel_x: u32, pixel_y: u32) -> vec4<f32> {
+    switch (uniforms.source_format) {
+        case SOURCE_BUFFER_RGBA8: {
+            let index = uniforms.source_offset + pixel_y * uniforms.source_stride + pixel_x;
+            return unpack4x8unorm(source_buffer[index]);
+        }
+        case SOURCE_BUFFER_RGBA16F: {
+            let index = uniforms.source_offset + pixel_y * uniforms.source_stride + pixel_x * 2u;
+            return vec4<f32>(
+                unpack2x16float(source_buffer[index]),
+                unpack2x16float(source_buffer[index + 1u]),
+            );
+        }
+        case SOURCE_BUFFER_RGBA32F: {
+            let index = uniforms.source_offset + pixel_y * uniforms.source_stride + pixel_x * 4u;
+            return vec4<f32>(
+                bitcast<f32>(source_buffer[index]),
+                bitcast<f32>(source_buffer[index + 1u]),
+                bitcast<f32>(source_buffer[index + 2u]),
+                bitcast<f32>(source_buffer[index + 3u]),
+            );
+        }
+        case SOURCE_TEXTURE, default: {
+            return textureLoad(source_texture, vec2<u32>(pixel_x, uniforms.source_row + pixel_y), 0);
+        }
+    }
+}
+
+// Pixels outside the image repeat the last row and column.
+fn load_block_interleaved_rgba(xx: u32, yy: u32) {
+    let block_width = parameters.block_width;
+    let block_height = parameters.block_height;
+
+    texel_count = block_width * block_height;
+
+    var opaque = true;
+    for (var y = 0u; y < block_height; y++) {
+        for (var x = 0u; x < block_width; x++) {
+            let pixel_x = min(xx * block_width + x, uniforms.width - 1u);
+            let pixel_y = min(yy * block_height + y, uniforms.height - 1u);
+            let rgba = load_texel(pixel_x, pixel_y) * 255.0;
+            let texel = y * block_width + x;
+
+            block[MAX_BLOCK_TEXELS * 0u + texel] = rgba.r;
+            block[MAX_BLOCK_TEXELS * 1u + texel] = rgba.g;
+            block[MAX_BLOCK_TEXELS * 2u + texel] = rgba.b;
+            block[MAX_BLOCK_TEXELS * 3u + texel] = rgba.a;
+
+            opaque = opaque && rgba.a == 255.0;
+        }
+    }
+
+    if (opaque) {
+        color_quant = parameters.color_quant_rgb;
+        color_endpoint_mode = 8u;
+    } else {
+        color_quant = parameters.color_quant_rgba;
+        color_endpoint_mode = 12u;
+    }
+}
+
+fn get_texel(texel: u32) -> vec4<f32> {
+    return vec4<f32>(
+        block[texel],
+        block[MAX_BLOCK_TEXELS + texel],
+        block[MAX_BLOCK_TEXELS * 2u + texel],
+        block[MAX_BLOCK_TEXELS * 3u + texel],
+    );
+}
+
+// Fits a line through the colors of the block and returns its extent.
+fn principal_component_endpoints() -> Endpoints {
+    let count = f32(texel_count);
+
+    var mean = vec4<f32>(0.0);
+    for (var texel = 0u; texel < texel_count; texel++) {
+        mean += get_texel(texel);
+    }
+    mean /= count;
+
+    var covariance = mat4x4<f32>();
+    for (var texel = 0u; texel < texel_count; texel++) {
+        let diff = get_texel(texel) - mean;
+        covariance += mat4x4<f32>(diff * diff.x, diff * diff.y, diff * diff.z, diff * diff.w);
+    }
+
+    // The power iteration starts with the row of the channel with the highest variance.
+    var best_row = 0u;
+    for (var row = 1u; row < 4u; row++) {
+        if (covariance[row][row] > covariance[best_row][best_row]) {
+            best_row = row;
+        }
+    }
+    var axis = covariance[best_row];
+
+    for (var i = 0u; i < 8u; i++) {
+        let next = covariance * axis;
+        let len = length(next);
+        if (len < 1e-6) {
+            break;
+        }
+        axis = next / len;
+    }
+
+    var endpoints: Endpoints;
+
+    let len = length(axis);
+    if (len < 1e-6) {
+        endpoints.e0 = mean;
+        endpoints.e1 = mean;
+        return endpoints;
+    }
+    axis /= len;
+
+    var min_t = MAX_ERROR;
+    var max_t = -MAX_ERROR;
+    for (var texel = 0u; texel < texel_count; texel++) {
+        let t = dot(get_texel(texel) - mean, axis);
+        min_t = min(min_t, t);
+        max_t = max(max_t, t);
+    }
+
+    endpoints.e0 = clamp(mean + axis * min_t, vec4<f32>(0.0), vec4<f32>(255.0));
+    endpoints.e1 = clamp(mean + axis * max_t, vec4<f32>(0.0), vec4<f32>(255.0));
+    return endpoints;
+}
+
+fn decoded_endpoints(color_values: ptr<function, array<u32, 8>>) -> Endpoints {
+    var endpoints: Endpoints;
+    endpoints.e0 = vec4<f32>(255.0);
+    endpoints.e1 = vec4<f32>(255.0);
+
+    let channels = select(3u, 4u, color_endpoint_mode == 12u);
+    for (var channel = 0u; channel < channels; channel++) {
+        endpoints.e0[channel] = f32(unquantize_color(color_quant, (*color_values)[channel * 2u]));
+        endpoints.e1[channel] = f32(unquantize_color(color_quant, (*color_values)[channel * 2u + 1u]));
+    }
+
+    return endpoints;
+}
+
+// Interpolates like the decoder, but returns the result in float precision.
+fn interpolate(e0: vec4<f32>, e1: vec4<f32>, weight: f32) -> vec4<f32> {
+    let color = (e0 * 257.0 * (64.0 - weight) + e1 * 257.0 * weight + 32.0) / 64.0;
+    return color / 257.0;
+}
+
+// Least squares fit of the endpoints to the decoded weights of a candidate.
+fn refine_endpoints(candidate: ptr<function, Candidate>) -> Endpoints {
+    var color_values = (*candidate).color_values;
+    var grid_weights = (*candidate).grid_weights;
+    let decoded = decoded_endpoints(&color_values);
+
+    var a = 0.0;
+    var b = 0.0;
+    var c = 0.0;
+    var r0 = vec4<f32>(0.0);
+    var r1 = vec4<f32>(0.0);
+
+    for (var y = 0u; y < parameters.block_height; y++) {
+        for (var x = 0u; x < parameters.block_width; x++) {
+            let color = get_texel(y * parameters.block_width + x);
+            let w = texel_weight(&grid_weights, x, y) / 64.0;
+            let iw = 1.0 - w;
+
+            a += iw * iw;
+            b += iw * w;
+            c += w * w;
+            r0 += iw * color;
+            r1 += w * color;
+        }
+    }
+
+    let det = a * c - b * b;
+    if (abs(det) < 1e-6) {
+        return decoded;
+    }
+
+    var endpoints: Endpoints;
+    endpoints.e0 = clamp((c * r0 - b * r1) / det, vec4<f32>(0.0), vec4<f32>(255.0));
+    endpoints.e1 = clamp((a * r1 - b * r0) / det, vec4<f32>(0.0), vec4<f32>(255.0));
+    return endpoints;
+}
+
+// Quantizes the endpoints, finds the best weights and calculates the error of the block.
+fn evaluate_endpoints(endpoints: Endpoints) -> Candidate {
+    var candidate: Candidate;
+
+    for (var channel = 0u; channel < 4u; channel++) {
+        candidate.color_values[channel * 2u] = quantize_color(color_quant, endpoints.e0[channel]);
+        candidate.color_values[channel * 2u + 1u] = quantize_color(color_quant, endpoints.e1[channel]);
+    }
+
+    // The decoder swaps endpoints and applies blue contraction, if the second endpoint is darker
+    // than the first. We store them in the order that avoids this.
+    var sum0 = 0u;
+    var sum1 = 0u;
+    for (var channel = 0u; channel < 3u; channel++) {
+        sum0 += unquantize_color(color_quant, candidate.color_values[channel * 2u]);
+        sum1 += unquantize_color(color_quant, candidate.color_values[channel * 2u + 1u]);
+    }
+    if (sum1 < sum0) {
+        for (var channel = 0u; channel < 4u; channel++) {
+            let value = candidate.color_values[channel * 2u];
+            candidate.color_values[channel * 2u] = candidate.color_values[channel * 2u + 1u];
+            candidate.color_values[channel * 2u + 1u] = value;
+        }
+    }
+
+    var color_values = candidate.color_values;
+    let decoded = decoded_endpoints(&color_values);
+    let direction = decoded.e1 - decoded.e0;
+    let length_squared = dot(direction, direction);
+
+    // Each grid weight is the average of the ideal weights of the texels it contributes to.
+    var weight_sums: array<f32, 64>;
+    var factor_sums: array<f32, 64>;
+
+    for (var y = 0u; y < parameters.block_height; y++) {
+        for (var x = 0u; x < parameters.block_width; x++) {
+            var ideal_weight = 0.0;
+            if (length_squared > 0.0) {
+                let t = dot(get_texel(y * parameters.block_width + x) - decoded.e0, direction) / length_squared;
+                ideal_weight = clamp(t, 0.0, 1.0) * 64.0;
+            }
+
+            let contributions = infill_contributions(x, y);
+            for (var i = 0u; i < 4u; i++) {
+                let index = contributions.index[i];
+                let factor = f32(contributions.factor[i]);
+                weight_sums[index] += factor * ideal_weight;
+                factor_sums[index] += factor;
+            }
+        }
+    }
+
+    let grid_count = parameters.grid_width * parameters.grid_height;
+    for (var index = 0u; index < grid_count; index++) {
+        var weight = 32.0;
+        if (factor_sums[index] > 0.0) {
+            weight = weight_sums[index] / factor_sums[index];
+        }
+        candidate.grid_weights[index] = quantize_weight(parameters.weight_quant, weight);
+    }
+
+    var grid_weights = candidate.grid_weights;
+    var error = 0.0;
+    for (var y = 0u; y < parameters.block_height; y++) {
+        for (var x = 0u; x < parameters.block_width; x++) {
+            let weight = texel_weight(&grid_weights, x, y);
+            let diff = interpolate(decoded.e0, decoded.e1, weight) - get_texel(y * parameters.block_width + x);
+            error += dot(diff, diff);
+        }
+    }
+    candidate.error = error;
+
+    return candidate;
+}
+
+// Writes `count` bits of `value` at `position` of an integer sequence, that starts at bit `start`
+// of the block. Reversed sequences start at the most significant bit and grow downwards. Bits
+// after the end of the sequence are dropped.
+fn write_sequence_bits(
+    data: ptr<function, array<u32, 4>>,
+    value: u32,
+    count: u32,
+    position: u32,
+    total_bits: u32,
+    start: u32,
+    reversed: bool,
+) {
+    for (var i = 0u; i < count; i++) {
+        let sequence_position = position + i;
+        if (sequence_position >= total_bits) {
+            return;
+        }
+
+        let bit_index = select(start + sequence_position, 127u - sequence_position, reversed);
+        (*data)[bit_index >> 5u] |= ((value >> i) & 1u) << (bit_index & 31u);
+    }
+}
+
+fn write_ise(
+    data: ptr<function, array<u32, 4>>,
+    values: ptr<function, array<u32, 64>>,
+    count: u32,
+    quant: u32,
+    start: u32,
+    reversed: bool,
+) {
+    let level = get_quant_level(quant);
+    let bits = level.w;
+    let mask = (1u << bits) - 1u;
+    let total_bits = ise_bit_count(count, quant);
+
+    var position = 0u;
+
+    if (level.y == 1u) {
+        const trit_shifts = array<u32, 5>(0u, 2u, 4u, 5u, 7u);
+        const trit_counts = array<u32, 5>(2u, 2u, 1u, 2u, 1u);
+
+        for (var i = 0u; i < count; i += 5u) {
+            var index = 0u;
+            var multiplier = 1u;
+            for (var j = 0u; j < 5u; j++) {
+                if (i + j < count) {
+                    index += ((*values)[i + j] >> bits) * multiplier;
+                }
+                multiplier *= 3u;
+            }
+            let packed = get_trit_encoding(index);
+
+            // The packed bits are interleaved with the low bits of the values.
+            for (var j = 0u; j < 5u; j++) {
+                var low = 0u;
+                if (i + j < count) {
+                    low = (*values)[i + j] & mask;
+                }
+                write_sequence_bits(data, low, bits, position, total_bits, start, reversed);
+                position += bits;
+                write_sequence_bits(data, packed >> trit_shifts[j], trit_counts[j], position, total_bits, start, reversed);
+                position += trit_counts[j];
+            }
+        }
+    } else if (level.z == 1u) {
+        const quint_shifts = array<u32, 3>(0u, 3u, 5u);
+        const quint_counts = array<u32, 3>(3u, 2u, 2u);
+
+        for (var i = 0u; i < count; i += 3u) {
+            var index = 0u;
+            var multiplier = 1u;
+            for (var j = 0u; j < 3u; j++) {
+                if (i + j < count) {
+                    index += ((*values)[i + j] >> bits) * multiplier;
+                }
+                multiplier *= 5u;
+            }
+            let packed = get_quint_encoding(index);
+
+            for (var j = 0u; j < 3u; j++) {
+                var low = 0u;
+                if (i + j < count) {
+                    low = (*values)[i + j] & mask;
+                }
+                write_sequence_bits(data, low, bits, position, total_bits, start, reversed);
+                position += bits;
+                write_sequence_bits(data, packed >> quint_shifts[j], quint_counts[j], position, total_bits, start, reversed);
+                position += quint_counts[j];
+            }
+        }
+    } else {
+        for (var i = 0u; i < count; i++) {
+            write_sequence_bits(data, (*values)[i], bits, position, total_bits, start, reversed);
+            position += bits;
+        }
+    }
+}
+
+fn pack_block(candidate: ptr<function, Candidate>) -> array<u32, 4> {
+    let value_count = select(6u, 8u, color_endpoint_mode == 12u);
+    let grid_count = parameters.grid_width * parameters.grid_height;
+
+    // Single partition, so the partition count bits stay zero.
+    var data: array<u32, 4>;
+    data[0] = parameters.block_mode | (color_endpoint_mode << 13u);
+
+    var values: array<u32, 64>;
+    for (var i = 0u; i < value_count; i++) {
+        values[i] = (*candidate).color_values[i];
+    }
+    write_ise(&data, &values, value_count, color_quant, 17u, false);
+
+    // Weights are written from the most significant bit downwards.
+    values = (*candidate).grid_weights;
+    write_ise(&data, &values, grid_count, parameters.weight_quant, 0u, true);
+
+    return data;
+}
+
+fn compress_block_astc() -> array<u32, 4> {
+    var best = evaluate_endpoints(principal_component_endpoints());
+
+    let refined = evaluate_endpoints(refine_endpoints(&best));
+    if (refined.error < best.error) {
+        best = refined;
+    }
+
+    return pack_block(&best);
+}
+
+fn store_data(block_width: u32, xx: u32, yy: u32, data: array<u32, 4>) {
+    let offset = uniforms.blocks_offset + (yy * block_width + xx) * 4u;
+
+    block_buffer[offset + 0u] = data[0];
+    block_buffer[offset + 1u] = data[1];
+    block_buffer[offset + 2u] = data[2];
+    block_buffer[offset + 3u] = data[3];
+}
+
+@compute
+@workgroup_size(8, 8)
+fn compress_astc(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    let xx = global_id.x;
+    let yy = global_id.y;
+
+    let block_width = (uniforms.width + parameters.block_width - 1u) / parameters.block_width;
+    let block_height = (uniforms.height + parameters.block_height - 1u) / parameters.block_height;
+
+    if (xx >= block_width || yy >= block_height) {
+        return;
+    }
+
+    load_block_interleaved_rgba(xx, yy);
+
+    let data = compress_block_astc();
+
+    store_data(block_width, xx, yy, data);
+}
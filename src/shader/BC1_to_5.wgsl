@@ -15,15 +15,30 @@
 // TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
+const SOURCE_TEXTURE: u32 = 0u;
+const SOURCE_BUFFER_RGBA8: u32 = 1u;
+const SOURCE_BUFFER_RGBA16F: u32 = 2u;
+const SOURCE_BUFFER_RGBA32F: u32 = 3u;
+
+const ALPHA_PREMULTIPLY: u32 = 1u;
+const ALPHA_WEIGHT_COLOR: u32 = 2u;
+
 struct Uniforms {
     width: u32,
     height: u32,
     blocks_offset: u32,
+    source_format: u32,
+    source_offset: u32,
+    source_stride: u32,
+    source_row: u32,
+    alpha_flags: u32,
+    strict: u32,
 }
 
 @group(0) @binding(0) var source_texture: texture_2d<f32>;
 @group(0) @binding(1) var<storage, read_write> block_buffer: array<u32>;
 @group(0) @binding(2) var<uniform> uniforms: Uniforms;
+@group(0) @binding(4) var<storage, read> source_buffer: array<u32>;
 
 fn sq(x: f32) -> f32 {
     return x * x;
@@ -37,12 +52,40 @@ fn rcp(x: f32) -> f32 {
     return 1.0 / x;
 }
 
+fn load_texel(pixel_x: u32, pixel_y: u32) -> vec4<f32> {
+    switch (uniforms.source_format) {
+        case SOURCE_BUFFER_RGBA8: {
+            let index = uniforms.source_offset + pixel_y * uniforms.source_stride + pixel_x;
+            return unpack4x8unorm(source_buffer[index]);
+        }
+        case SOURCE_BUFFER_RGBA16F: {
+            let index = uniforms.source_offset + pixel_y * uniforms.source_stride + pixel_x * 2u;
+            return vec4<f32>(
+                unpack2x16float(source_buffer[index]),
+                unpack2x16float(source_buffer[index + 1u]),
+            );
+        }
+        case SOURCE_BUFFER_RGBA32F: {
+            let index = uniforms.source_offset + pixel_y * uniforms.source_stride + pixel_x * 4u;
+            return vec4<f32>(
+                bitcast<f32>(source_buffer[index]),
+                bitcast<f32>(source_buffer[index + 1u]),
+                bitcast<f32>(source_buffer[index + 2u]),
+                bitcast<f32>(source_buffer[index + 3u]),
+            );
+        }
+        case SOURCE_TEXTURE, default: {
+            return textureLoad(source_texture, vec2<u32>(pixel_x, uniforms.source_row + pixel_y), 0);
+        }
+    }
+}
+
 fn load_block_interleaved_rgba(block: ptr<function, array<f32, 64>>, xx: u32, yy: u32) {
     for (var y = 0u; y < 4u; y++) {
         for (var x = 0u; x < 4u; x++) {
             let pixel_x = xx * 4u + x;
             let pixel_y = yy * 4u + y;
-            let rgba = textureLoad(source_texture, vec2<u32>(pixel_x, pixel_y), 0);
+            let rgba = load_texel(pixel_x, pixel_y);
 
             (*block)[16u * 0u + y * 4u + x] = rgba.r * 255.0;
             (*block)[16u * 1u + y * 4u + x] = rgba.g * 255.0;
@@ -52,12 +95,46 @@ fn load_block_interleaved_rgba(block: ptr<function, array<f32, 64>>, xx: u32, yy
     }
 }
 
+fn apply_alpha_options(block: ptr<function, array<f32, 64>>) {
+    if ((uniforms.alpha_flags & ALPHA_PREMULTIPLY) != 0u) {
+        for (var k = 0u; k < 16u; k++) {
+            let alpha = (*block)[48u + k] / 255.0;
+            for (var p = 0u; p < 3u; p++) {
+                (*block)[k + p * 16u] *= alpha;
+            }
+        }
+    }
+}
+
+// Fully transparent texels keep a small weight, so that the endpoint fit of a fully transparent
+// block stays well defined.
+fn color_weight(block: ptr<function, array<f32, 64>>, k: u32) -> f32 {
+    if ((uniforms.alpha_flags & ALPHA_WEIGHT_COLOR) != 0u) {
+        return max((*block)[48u + k], 1.0) / 255.0;
+    }
+    return 1.0;
+}
+
+// Fully transparent texels are ignored when picking the endpoints, if the block has a visible texel.
+fn is_hidden(block: ptr<function, array<f32, 64>>, k: u32) -> bool {
+    if ((uniforms.alpha_flags & ALPHA_WEIGHT_COLOR) == 0u || (*block)[48u + k] != 0.0) {
+        return false;
+    }
+
+    for (var i = 0u; i < 16u; i++) {
+        if ((*block)[48u + i] > 0.0) {
+            return true;
+        }
+    }
+    return false;
+}
+
 fn load_block_r_8bit(block: ptr<function, array<f32, 64>>, xx: u32, yy: u32) {
     for (var y = 0u; y < 4u; y++) {
         for (var x = 0u; x < 4u; x++) {
             let pixel_x = xx * 4u + x;
             let pixel_y = yy * 4u + y;
-            let red = textureLoad(source_texture, vec2<u32>(pixel_x, pixel_y), 0).r;
+            let red = load_texel(pixel_x, pixel_y).r;
 
             (*block)[48u + y * 4u + x] = red * 255.0;
         }
@@ -69,7 +146,7 @@ fn load_block_g_8bit(block: ptr<function, array<f32, 64>>, xx: u32, yy: u32) {
         for (var x = 0u; x < 4u; x++) {
             let pixel_x = xx * 4u + x;
             let pixel_y = yy * 4u + y;
-            let green = textureLoad(source_texture, vec2<u32>(pixel_x, pixel_y), 0).g;
+            let green = load_texel(pixel_x, pixel_y).g;
 
             (*block)[48u + y * 4u + x] = green  * 255.0;
         }
@@ -83,7 +160,7 @@ fn load_block_alpha_4bit(xx: u32, yy: u32) -> vec2<u32> {
         for (var x = 0u; x < 4u; x++) {
             let pixel_x = xx * 4u + x;
             let pixel_y = yy * 4u + y;
-            let alpha = textureLoad(source_texture, vec2<u32>(pixel_x, pixel_y), 0).a;
+            let alpha = load_texel(pixel_x, pixel_y).a;
 
             // Convert alpha to 4 bits (0-15)
             let alpha4 = u32(alpha * 15.0);
@@ -121,12 +198,17 @@ fn compute_covar_dc(
     dc: ptr<function, vec3<f32>>,
     block: ptr<function, array<f32, 64>>,
 ) {
+    var weight_sum = 0.0;
+    for (var k = 0u; k < 16u; k++) {
+        weight_sum += color_weight(block, k);
+    }
+
     for (var p = 0u; p < 3u; p++) {
         var acc = 0.0;
         for (var k = 0u; k < 16u; k++) {
-            acc += (*block)[k + p * 16u];
+            acc += color_weight(block, k) * (*block)[k + p * 16u];
         }
-        (*dc)[p] = acc / 16.0;
+        (*dc)[p] = acc / weight_sum;
     }
 
     var covar0 = 0.0;
@@ -137,16 +219,17 @@ fn compute_covar_dc(
     var covar5 = 0.0;
 
     for (var k = 0u; k < 16u; k++) {
+        let weight = color_weight(block, k);
         let rgb0 = (*block)[k + 0u * 16u] - (*dc)[0];
         let rgb1 = (*block)[k + 1u * 16u] - (*dc)[1];
         let rgb2 = (*block)[k + 2u * 16u] - (*dc)[2];
 
-        covar0 += rgb0 * rgb0;
-        covar1 += rgb0 * rgb1;
-        covar2 += rgb0 * rgb2;
-        covar3 += rgb1 * rgb1;
-        covar4 += rgb1 * rgb2;
-        covar5 += rgb2 * rgb2;
+        covar0 += weight * rgb0 * rgb0;
+        covar1 += weight * rgb0 * rgb1;
+        covar2 += weight * rgb0 * rgb2;
+        covar3 += weight * rgb1 * rgb1;
+        covar4 += weight * rgb1 * rgb2;
+        covar5 += weight * rgb2 * rgb2;
     }
 
     (*covar)[0] = covar0;
@@ -203,6 +286,10 @@ fn pick_endpoints(
 
     for (var y = 0u; y < 4u; y++) {
         for (var x = 0u; x < 4u; x++) {
+            if (is_hidden(block, y * 4u + x)) {
+                continue;
+            }
+
             var dot = 0.0;
             for (var p = 0u; p < 3u; p++) {
                 dot += ((*block)[p * 16u + y * 4u + x] - (*dc)[p]) * (*axis)[p];
@@ -306,6 +393,7 @@ fn bc1_refine(pe: ptr<function, vec2<i32>>, block: ptr<function, array<f32, 64>>
         }
     } else {
         var atb1: vec3<f32>;
+        var sum_w = 0.0;
         var sum_q = 0.0;
         var sum_qq = 0.0;
         var shifted_bits = bits;
@@ -314,13 +402,15 @@ fn bc1_refine(pe: ptr<function, vec2<i32>>, block: ptr<function, array<f32, 64>>
             let q = f32(shifted_bits & 3u);
             shifted_bits = shifted_bits >> 2u;
 
+            let weight = color_weight(block, k);
             let x = 3.0 - q;
 
-            sum_q += q;
-            sum_qq += q * q;
+            sum_w += weight;
+            sum_q += weight * q;
+            sum_qq += weight * q * q;
 
             for (var p = 0u; p < 3u; p++) {
-                atb1[p] += x * (*block)[k + p * 16u];
+                atb1[p] += weight * x * (*block)[k + p * 16u];
             }
         }
 
@@ -328,11 +418,11 @@ fn bc1_refine(pe: ptr<function, vec2<i32>>, block: ptr<function, array<f32, 64>>
         var atb2: vec3<f32>;
 
         for (var p = 0u; p < 3u; p++) {
-            sum[p] = (*dc)[p] * 16.0;
+            sum[p] = (*dc)[p] * sum_w;
             atb2[p] = 3.0 * sum[p] - atb1[p];
         }
 
-        let cxx = 16.0 * sq(3.0) - 2.0 * 3.0 * sum_q + sum_qq;
+        let cxx = sum_w * sq(3.0) - 2.0 * 3.0 * sum_q + sum_qq;
         let cyy = sum_qq;
         let cxy = 3.0 * sum_q - sum_qq;
         let scale = 3.0 * rcp(cxx * cyy - cxy * cxy);
@@ -450,6 +540,383 @@ fn compress_block_bc3_alpha(block: ptr<function, array<f32, 64>>) -> vec2<u32> {
     return data;
 }
 
+// Strict mode: an integer only port of `src/encode/strict.rs`, that produces the same blocks as the
+// CPU encoder on every backend. See there for the bounds of the intermediate values.
+
+const STRICT_AXIS_BITS: u32 = 14u;
+const STRICT_COVAR_BITS: u32 = 15u;
+
+fn load_texel_strict(pixel_x: u32, pixel_y: u32) -> vec4<i32> {
+    if (uniforms.source_format == SOURCE_BUFFER_RGBA8) {
+        let index = uniforms.source_offset + pixel_y * uniforms.source_stride + pixel_x;
+        let rgba = source_buffer[index];
+        return vec4<i32>(
+            i32(rgba & 0xFFu),
+            i32((rgba >> 8u) & 0xFFu),
+            i32((rgba >> 16u) & 0xFFu),
+            i32(rgba >> 24u),
+        );
+    }
+
+    return vec4<i32>(round(saturate(load_texel(pixel_x, pixel_y)) * 255.0));
+}
+
+fn load_block_interleaved_rgba_strict(block: ptr<function, array<i32, 64>>, xx: u32, yy: u32) {
+    for (var y = 0u; y < 4u; y++) {
+        for (var x = 0u; x < 4u; x++) {
+            let rgba = load_texel_strict(xx * 4u + x, yy * 4u + y);
+
+            for (var p = 0u; p < 4u; p++) {
+                (*block)[p * 16u + y * 4u + x] = rgba[p];
+            }
+        }
+    }
+}
+
+// Loads a single channel into the alpha plane.
+fn load_block_channel_strict(block: ptr<function, array<i32, 64>>, xx: u32, yy: u32, channel: u32) {
+    for (var y = 0u; y < 4u; y++) {
+        for (var x = 0u; x < 4u; x++) {
+            (*block)[48u + y * 4u + x] = load_texel_strict(xx * 4u + x, yy * 4u + y)[channel];
+        }
+    }
+}
+
+fn apply_alpha_options_strict(block: ptr<function, array<i32, 64>>) {
+    if ((uniforms.alpha_flags & ALPHA_PREMULTIPLY) != 0u) {
+        for (var k = 0u; k < 16u; k++) {
+            let alpha = (*block)[48u + k];
+            for (var p = 0u; p < 3u; p++) {
+                (*block)[k + p * 16u] = ((*block)[k + p * 16u] * alpha + 127) / 255;
+            }
+        }
+    }
+}
+
+fn color_weight_strict(block: ptr<function, array<i32, 64>>, k: u32) -> i32 {
+    if ((uniforms.alpha_flags & ALPHA_WEIGHT_COLOR) != 0u) {
+        return (max((*block)[48u + k], 1) + 31) >> 5u;
+    }
+    return 1;
+}
+
+fn is_hidden_strict(block: ptr<function, array<i32, 64>>, k: u32) -> bool {
+    if ((uniforms.alpha_flags & ALPHA_WEIGHT_COLOR) == 0u || (*block)[48u + k] != 0) {
+        return false;
+    }
+
+    for (var i = 0u; i < 16u; i++) {
+        if ((*block)[48u + i] > 0) {
+            return true;
+        }
+    }
+    return false;
+}
+
+fn alpha_4bit_strict(block: ptr<function, array<i32, 64>>) -> vec2<u32> {
+    var alpha_bits: vec2<u32>;
+
+    for (var k = 0u; k < 16u; k++) {
+        let alpha4 = u32(((*block)[48u + k] * 15 + 127) / 255);
+        alpha_bits[k / 8u] |= alpha4 << ((k % 8u) * 4u);
+    }
+
+    return alpha_bits;
+}
+
+fn bit_length_strict(value: i32) -> u32 {
+    return 32u - countLeadingZeros(u32(value));
+}
+
+fn div_round_strict(numerator: i32, denominator: i32) -> i32 {
+    if (numerator >= 0) {
+        return (numerator + denominator / 2) / denominator;
+    }
+    return -((denominator / 2 - numerator) / denominator);
+}
+
+fn normalize_axis_strict(axis: vec3<i32>) -> vec3<i32> {
+    let max_value = max(max(abs(axis[0]), abs(axis[1])), abs(axis[2]));
+    if (max_value == 0) {
+        return vec3<i32>(1i << (STRICT_AXIS_BITS - 1u));
+    }
+
+    let length = bit_length_strict(max_value);
+    if (length > STRICT_AXIS_BITS) {
+        return axis >> vec3<u32>(length - STRICT_AXIS_BITS);
+    }
+    return axis << vec3<u32>(STRICT_AXIS_BITS - length);
+}
+
+fn compute_covar_mean_strict(
+    covar: ptr<function, array<i32, 6>>,
+    mean: ptr<function, vec3<i32>>,
+    block: ptr<function, array<i32, 64>>,
+) {
+    var sum_w = 0;
+    var sum_wc = vec3<i32>(0);
+    for (var k = 0u; k < 16u; k++) {
+        let weight = color_weight_strict(block, k);
+        sum_w += weight;
+        for (var p = 0u; p < 3u; p++) {
+            sum_wc[p] += weight * (*block)[k + p * 16u];
+        }
+    }
+
+    for (var p = 0u; p < 3u; p++) {
+        (*mean)[p] = div_round_strict(sum_wc[p], sum_w);
+    }
+
+    var covar0 = 0;
+    var covar1 = 0;
+    var covar2 = 0;
+    var covar3 = 0;
+    var covar4 = 0;
+    var covar5 = 0;
+
+    for (var k = 0u; k < 16u; k++) {
+        let weight = color_weight_strict(block, k);
+        let rgb0 = (*block)[k + 0u * 16u] - (*mean)[0];
+        let rgb1 = (*block)[k + 1u * 16u] - (*mean)[1];
+        let rgb2 = (*block)[k + 2u * 16u] - (*mean)[2];
+
+        covar0 += weight * rgb0 * rgb0;
+        covar1 += weight * rgb0 * rgb1;
+        covar2 += weight * rgb0 * rgb2;
+        covar3 += weight * rgb1 * rgb1;
+        covar4 += weight * rgb1 * rgb2;
+        covar5 += weight * rgb2 * rgb2;
+    }
+
+    (*covar)[0] = covar0;
+    (*covar)[1] = covar1;
+    (*covar)[2] = covar2;
+    (*covar)[3] = covar3;
+    (*covar)[4] = covar4;
+    (*covar)[5] = covar5;
+}
+
+fn compute_axis3_strict(covar: ptr<function, array<i32, 6>>, power_iterations: i32) -> vec3<i32> {
+    let max_diagonal = max(max((*covar)[0], (*covar)[3]), (*covar)[5]);
+    let shift = max(bit_length_strict(max_diagonal), STRICT_COVAR_BITS) - STRICT_COVAR_BITS;
+
+    var scaled: array<i32, 6>;
+    for (var i = 0u; i < 6u; i++) {
+        scaled[i] = (*covar)[i] >> shift;
+    }
+    scaled[0] += 1;
+    scaled[3] += 1;
+    scaled[5] += 1;
+
+    var axis = vec3<i32>(1i << (STRICT_AXIS_BITS - 1u));
+    for (var i = 0; i < power_iterations; i++) {
+        let next = vec3<i32>(
+            scaled[0] * axis[0] + scaled[1] * axis[1] + scaled[2] * axis[2],
+            scaled[1] * axis[0] + scaled[3] * axis[1] + scaled[4] * axis[2],
+            scaled[2] * axis[0] + scaled[4] * axis[1] + scaled[5] * axis[2],
+        );
+        axis = normalize_axis_strict(next);
+    }
+
+    return axis;
+}
+
+fn pick_endpoints_strict(
+    c0: ptr<function, vec3<i32>>,
+    c1: ptr<function, vec3<i32>>,
+    block: ptr<function, array<i32, 64>>,
+    principal_axis: vec3<i32>,
+    mean: vec3<i32>,
+) {
+    let axis = principal_axis >> vec3<u32>(STRICT_AXIS_BITS - 8u);
+
+    var min_dot = 2147483647i;
+    var max_dot = -2147483647i - 1i;
+
+    for (var k = 0u; k < 16u; k++) {
+        if (is_hidden_strict(block, k)) {
+            continue;
+        }
+
+        var dot = 0;
+        for (var p = 0u; p < 3u; p++) {
+            dot += ((*block)[p * 16u + k] - mean[p]) * axis[p];
+        }
+
+        min_dot = min(min_dot, dot);
+        max_dot = max(max_dot, dot);
+    }
+
+    var norm_sq = 0;
+    for (var p = 0u; p < 3u; p++) {
+        norm_sq += axis[p] * axis[p];
+    }
+
+    for (var p = 0u; p < 3u; p++) {
+        (*c0)[p] = clamp(mean[p] + div_round_strict(min_dot * axis[p], norm_sq), 0, 255);
+        (*c1)[p] = clamp(mean[p] + div_round_strict(max_dot * axis[p], norm_sq), 0, 255);
+    }
+}
+
+fn dec_rgb565_strict(p: i32) -> vec3<i32> {
+    let b5 = (p >> 0u) & 31;
+    let g6 = (p >> 5u) & 63;
+    let r5 = (p >> 11u) & 31;
+
+    return vec3<i32>((r5 << 3u) + (r5 >> 2u), (g6 << 2u) + (g6 >> 4u), (b5 << 3u) + (b5 >> 2u));
+}
+
+fn enc_rgb565_strict(c: vec3<i32>) -> i32 {
+    let r5 = (c[0] * 31 + 128 + ((c[0] * 31) >> 8u)) >> 8u;
+    let g6 = (c[1] * 63 + 128 + ((c[1] * 63) >> 8u)) >> 8u;
+    let b5 = (c[2] * 31 + 128 + ((c[2] * 31) >> 8u)) >> 8u;
+
+    return (r5 << 11u) + (g6 << 5u) + b5;
+}
+
+fn quantize_strict(block: ptr<function, array<i32, 64>>, p0: i32, p1: i32) -> u32 {
+    if (p0 == p1) {
+        return 0u;
+    }
+
+    let c0 = dec_rgb565_strict(p0);
+    let c1 = dec_rgb565_strict(p1);
+
+    var dir: vec3<i32>;
+    var sq_norm = 0;
+    for (var p = 0u; p < 3u; p++) {
+        dir[p] = c1[p] - c0[p];
+        sq_norm += dir[p] * dir[p];
+    }
+
+    var bits = 0u;
+    for (var k = 0u; k < 16u; k++) {
+        var dot = 0;
+        for (var p = 0u; p < 3u; p++) {
+            dot += ((*block)[k + p * 16u] - c0[p]) * dir[p];
+        }
+
+        var q = 0;
+        if (dot > 0) {
+            q = min((6 * dot + sq_norm) / (2 * sq_norm), 3);
+        }
+        bits |= u32(q) << (k * 2u);
+    }
+
+    return bits;
+}
+
+fn bc1_refine_strict(pe: ptr<function, vec2<i32>>, block: ptr<function, array<i32, 64>>, bits: u32, mean: vec3<i32>) {
+    var c0 = mean;
+    var c1 = mean;
+
+    var cxx = 0;
+    var cxy = 0;
+    var cyy = 0;
+    var atb1 = vec3<i32>(0);
+    var atb2 = vec3<i32>(0);
+
+    for (var k = 0u; k < 16u; k++) {
+        let q = i32((bits >> (k * 2u)) & 3u);
+        let x = 3 - q;
+        let weight = color_weight_strict(block, k);
+
+        cxx += weight * x * x;
+        cxy += weight * x * q;
+        cyy += weight * q * q;
+
+        for (var p = 0u; p < 3u; p++) {
+            atb1[p] += weight * x * (*block)[k + p * 16u];
+            atb2[p] += weight * q * (*block)[k + p * 16u];
+        }
+    }
+
+    let det = cxx * cyy - cxy * cxy;
+    if (det > 0) {
+        for (var p = 0u; p < 3u; p++) {
+            c0[p] = clamp(div_round_strict(3 * (atb1[p] * cyy - atb2[p] * cxy), det), 0, 255);
+            c1[p] = clamp(div_round_strict(3 * (atb2[p] * cxx - atb1[p] * cxy), det), 0, 255);
+        }
+    }
+
+    (*pe)[0] = enc_rgb565_strict(c0);
+    (*pe)[1] = enc_rgb565_strict(c1);
+}
+
+fn compress_block_bc1_core_strict(block: ptr<function, array<i32, 64>>) -> vec2<u32> {
+    let power_iterations = 4;
+    let refine_iterations = 1;
+
+    var covar: array<i32, 6>;
+    var mean: vec3<i32>;
+    compute_covar_mean_strict(&covar, &mean, block);
+
+    let axis = compute_axis3_strict(&covar, power_iterations);
+
+    var c0: vec3<i32>;
+    var c1: vec3<i32>;
+    pick_endpoints_strict(&c0, &c1, block, axis, mean);
+
+    var p = vec2<i32>(enc_rgb565_strict(c0), enc_rgb565_strict(c1));
+    if (p[0] < p[1]) {
+        p = p.yx;
+    }
+
+    var data: vec2<u32>;
+    data[0] = (u32(p[1]) << 16u) | u32(p[0]);
+    data[1] = quantize_strict(block, p[0], p[1]);
+
+    for (var i = 0; i < refine_iterations; i++) {
+        bc1_refine_strict(&p, block, data[1], mean);
+        if (p[0] < p[1]) {
+            p = p.yx;
+        }
+        data[0] = (u32(p[1]) << 16u) | u32(p[0]);
+        data[1] = quantize_strict(block, p[0], p[1]);
+    }
+
+    data[1] = fix_qbits(data[1]);
+    return data;
+}
+
+fn compress_block_bc3_alpha_strict(block: ptr<function, array<i32, 64>>) -> vec2<u32> {
+    var ep = vec2<i32>(255, 0);
+
+    for (var k = 0u; k < 16u; k++) {
+        ep[0] = min(ep[0], (*block)[48u + k]);
+        ep[1] = max(ep[1], (*block)[48u + k]);
+    }
+
+    let range = ep[1] - ep[0];
+
+    var qblock: vec2<u32>;
+    for (var k = 0u; k < 16u; k++) {
+        let v = (*block)[48u + k];
+        var proj = 0;
+        if (range != 0) {
+            proj = ((v - ep[0]) * 14 + range) / (2 * range);
+        }
+
+        var q = 7 - clamp(proj, 0, 7);
+        if (q > 0) {
+            q += 1;
+        }
+        if (q == 8) {
+            q = 1;
+        }
+
+        qblock[k / 8u] |= u32(q) << ((k % 8u) * 3u);
+    }
+
+    var data: vec2<u32>;
+    data[0] = (u32(ep[0]) << 8u) | u32(ep[1]);
+    data[0] |= qblock[0] << 16u;
+    data[1] = qblock[0] >> 16u;
+    data[1] |= qblock[1] << 8u;
+
+    return data;
+}
+
 @compute
 @workgroup_size(8, 8)
 fn compress_bc1(@builtin(global_invocation_id) global_id: vec3<u32>) {
@@ -463,6 +930,13 @@ fn compress_bc1(@builtin(global_invocation_id) global_id: vec3<u32>) {
         return;
     }
 
+    if (uniforms.strict != 0u) {
+        var block: array<i32, 64>;
+        load_block_interleaved_rgba_strict(&block, xx, yy);
+        store_data_2(block_width, xx, yy, compress_block_bc1_core_strict(&block));
+        return;
+    }
+
     var block: array<f32, 64>;
     var compressed_data: vec2<u32>;
 
@@ -488,6 +962,16 @@ fn compress_bc2(@builtin(global_invocation_id) global_id: vec3<u32>) {
         return;
     }
 
+    if (uniforms.strict != 0u) {
+        var block: array<i32, 64>;
+        load_block_interleaved_rgba_strict(&block, xx, yy);
+        let alpha_result = alpha_4bit_strict(&block);
+        apply_alpha_options_strict(&block);
+        let color_result = compress_block_bc1_core_strict(&block);
+        store_data_4(block_width, xx, yy, vec4<u32>(alpha_result, color_result));
+        return;
+    }
+
     var block: array<f32, 64>;
     var compressed_data: vec4<u32>;
 
@@ -496,6 +980,7 @@ fn compress_bc2(@builtin(global_invocation_id) global_id: vec3<u32>) {
     compressed_data[1] = alpha_result[1];
 
     load_block_interleaved_rgba(&block, xx, yy);
+    apply_alpha_options(&block);
 
     let color_result = compress_block_bc1_core(&block);
     compressed_data[2] = color_result[0];
@@ -517,10 +1002,21 @@ fn compress_bc3(@builtin(global_invocation_id) global_id: vec3<u32>) {
         return;
     }
 
+    if (uniforms.strict != 0u) {
+        var block: array<i32, 64>;
+        load_block_interleaved_rgba_strict(&block, xx, yy);
+        apply_alpha_options_strict(&block);
+        let alpha_result = compress_block_bc3_alpha_strict(&block);
+        let color_result = compress_block_bc1_core_strict(&block);
+        store_data_4(block_width, xx, yy, vec4<u32>(alpha_result, color_result));
+        return;
+    }
+
     var block: array<f32, 64>;
     var compressed_data: vec4<u32>;
 
     load_block_interleaved_rgba(&block, xx, yy);
+    apply_alpha_options(&block);
 
     let alpha_result = compress_block_bc3_alpha(&block);
     compressed_data[0] = alpha_result[0];
@@ -546,6 +1042,13 @@ fn compress_bc4(@builtin(global_invocation_id) global_id: vec3<u32>) {
         return;
     }
 
+    if (uniforms.strict != 0u) {
+        var block: array<i32, 64>;
+        load_block_channel_strict(&block, xx, yy, 0u);
+        store_data_2(block_width, xx, yy, compress_block_bc3_alpha_strict(&block));
+        return;
+    }
+
     var block: array<f32, 64>;
     var compressed_data: vec2<u32>;
 
@@ -571,6 +1074,16 @@ fn compress_bc5(@builtin(global_invocation_id) global_id: vec3<u32>) {
         return;
     }
 
+    if (uniforms.strict != 0u) {
+        var block: array<i32, 64>;
+        load_block_channel_strict(&block, xx, yy, 0u);
+        let red_result = compress_block_bc3_alpha_strict(&block);
+        load_block_channel_strict(&block, xx, yy, 1u);
+        let green_result = compress_block_bc3_alpha_strict(&block);
+        store_data_4(block_width, xx, yy, vec4<u32>(red_result, green_result));
+        return;
+    }
+
     var block: array<f32, 64>;
     var compressed_data: vec4<u32>;
 
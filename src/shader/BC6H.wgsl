@@ -15,10 +15,19 @@
 // TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
+const SOURCE_TEXTURE: u32 = 0u;
+const SOURCE_BUFFER_RGBA8: u32 = 1u;
+const SOURCE_BUFFER_RGBA16F: u32 = 2u;
+const SOURCE_BUFFER_RGBA32F: u32 = 3u;
+
 struct Uniforms {
     width: u32,
     height: u32,
     blocks_offset: u32,
+    source_format: u32,
+    source_offset: u32,
+    source_stride: u32,
+    source_row: u32,
 }
 
 struct Settings {
@@ -46,6 +55,7 @@ struct State {
 @group(0) @binding(1) var<storage, read_write> block_buffer: array<u32>;
 @group(0) @binding(2) var<uniform> uniforms: Uniforms;
 @group(0) @binding(3) var<storage, read> settings: Settings;
+@group(0) @binding(4) var<storage, read> source_buffer: array<u32>;
 
 fn sq(x: f32) -> f32 {
     return x * x;
@@ -63,12 +73,40 @@ fn f32_to_f16_bits(f: f32) -> u32 {
     return (sign << 15) | (exp << 10) | frac;
 }
 
+fn load_texel(pixel_x: u32, pixel_y: u32) -> vec4<f32> {
+    switch (uniforms.source_format) {
+        case SOURCE_BUFFER_RGBA8: {
+            let index = uniforms.source_offset + pixel_y * uniforms.source_stride + pixel_x;
+            return unpack4x8unorm(source_buffer[index]);
+        }
+        case SOURCE_BUFFER_RGBA16F: {
+            let index = uniforms.source_offset + pixel_y * uniforms.source_stride + pixel_x * 2u;
+            return vec4<f32>(
+                unpack2x16float(source_buffer[index]),
+                unpack2x16float(source_buffer[index + 1u]),
+            );
+        }
+        case SOURCE_BUFFER_RGBA32F: {
+            let index = uniforms.source_offset + pixel_y * uniforms.source_stride + pixel_x * 4u;
+            return vec4<f32>(
+                bitcast<f32>(source_buffer[index]),
+                bitcast<f32>(source_buffer[index + 1u]),
+                bitcast<f32>(source_buffer[index + 2u]),
+                bitcast<f32>(source_buffer[index + 3u]),
+            );
+        }
+        case SOURCE_TEXTURE, default: {
+            return textureLoad(source_texture, vec2<u32>(pixel_x, uniforms.source_row + pixel_y), 0);
+        }
+    }
+}
+
 fn load_block_interleaved_16bit(block: ptr<function, array<f32, 64>>, xx: u32, yy: u32) {
     for (var y = 0u; y < 4u; y++) {
         for (var x = 0u; x < 4u; x++) {
             let pixel_x = xx * 4u + x;
             let pixel_y = yy * 4u + y;
-            let rgba = textureLoad(source_texture, vec2<u32>(pixel_x, pixel_y), 0);
+            let rgba = load_texel(pixel_x, pixel_y);
 
             (*block)[16u * 0u + y * 4u + x] = f32(f32_to_f16_bits(rgba.r) & 0xFFFF);
             (*block)[16u * 1u + y * 4u + x] = f32(f32_to_f16_bits(rgba.g) & 0xFFFF);
@@ -15,10 +15,23 @@
 // TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
+const SOURCE_TEXTURE: u32 = 0u;
+const SOURCE_BUFFER_RGBA8: u32 = 1u;
+const SOURCE_BUFFER_RGBA16F: u32 = 2u;
+const SOURCE_BUFFER_RGBA32F: u32 = 3u;
+
+const ALPHA_PREMULTIPLY: u32 = 1u;
+const ALPHA_WEIGHT_COLOR: u32 = 2u;
+
 struct Uniforms {
     width: u32,
     height: u32,
     blocks_offset: u32,
+    source_format: u32,
+    source_offset: u32,
+    source_stride: u32,
+    source_row: u32,
+    alpha_flags: u32,
 }
 
 struct Settings {
@@ -52,6 +65,10 @@ struct Mode45Parameters {
 @group(0) @binding(1) var<storage, read_write> block_buffer: array<u32>;
 @group(0) @binding(2) var<uniform> uniforms: Uniforms;
 @group(0) @binding(3) var<storage, read> settings: Settings;
+@group(0) @binding(4) var<storage, read> source_buffer: array<u32>;
+
+// Weights of the color error of each texel of the current block.
+var<private> color_weights: array<f32, 16>;
 
 fn sq(x: f32) -> f32 {
     return x * x;
@@ -61,12 +78,40 @@ fn rsqrt(x: f32) -> f32 {
     return 1.0 / sqrt(x);
 }
 
+fn load_texel(pixel_x: u32, pixel_y: u32) -> vec4<f32> {
+    switch (uniforms.source_format) {
+        case SOURCE_BUFFER_RGBA8: {
+            let index = uniforms.source_offset + pixel_y * uniforms.source_stride + pixel_x;
+            return unpack4x8unorm(source_buffer[index]);
+        }
+        case SOURCE_BUFFER_RGBA16F: {
+            let index = uniforms.source_offset + pixel_y * uniforms.source_stride + pixel_x * 2u;
+            return vec4<f32>(
+                unpack2x16float(source_buffer[index]),
+                unpack2x16float(source_buffer[index + 1u]),
+            );
+        }
+        case SOURCE_BUFFER_RGBA32F: {
+            let index = uniforms.source_offset + pixel_y * uniforms.source_stride + pixel_x * 4u;
+            return vec4<f32>(
+                bitcast<f32>(source_buffer[index]),
+                bitcast<f32>(source_buffer[index + 1u]),
+                bitcast<f32>(source_buffer[index + 2u]),
+                bitcast<f32>(source_buffer[index + 3u]),
+            );
+        }
+        case SOURCE_TEXTURE, default: {
+            return textureLoad(source_texture, vec2<u32>(pixel_x, uniforms.source_row + pixel_y), 0);
+        }
+    }
+}
+
 fn load_block_interleaved_rgba(block: ptr<function, array<f32, 64>>, xx: u32, yy: u32) {
     for (var y = 0u; y < 4u; y++) {
         for (var x = 0u; x < 4u; x++) {
             let pixel_x = xx * 4u + x;
             let pixel_y = yy * 4u + y;
-            let rgba = textureLoad(source_texture, vec2<u32>(pixel_x, pixel_y), 0);
+            let rgba = load_texel(pixel_x, pixel_y);
 
             (*block)[16u * 0u + y * 4u + x] = rgba.r * 255.0;
             (*block)[16u * 1u + y * 4u + x] = rgba.g * 255.0;
@@ -76,6 +121,34 @@ fn load_block_interleaved_rgba(block: ptr<function, array<f32, 64>>, xx: u32, yy
     }
 }
 
+fn apply_alpha_options(block: ptr<function, array<f32, 64>>) {
+    if ((uniforms.alpha_flags & ALPHA_PREMULTIPLY) != 0u) {
+        for (var k = 0u; k < 16u; k++) {
+            let alpha = (*block)[48u + k] / 255.0;
+            for (var p = 0u; p < 3u; p++) {
+                (*block)[k + p * 16u] *= alpha;
+            }
+        }
+    }
+
+    // Fully transparent texels keep a small weight, so that the endpoint fit of a fully
+    // transparent block stays well defined.
+    for (var k = 0u; k < 16u; k++) {
+        color_weights[k] = 1.0;
+        if ((uniforms.alpha_flags & ALPHA_WEIGHT_COLOR) != 0u) {
+            color_weights[k] = max((*block)[48u + k], 1.0) / 255.0;
+        }
+    }
+}
+
+// The alpha channel is always fit with full weight.
+fn texel_weight(k: u32, p: u32) -> f32 {
+    if (p < 3u) {
+        return color_weights[k];
+    }
+    return 1.0;
+}
+
 fn store_data(state: ptr<function, State>, block_width: u32, xx: u32, yy: u32) {
     let offset = uniforms.blocks_offset + (yy * block_width * 4u + xx * 4u);
 
@@ -540,8 +613,9 @@ fn block_quant(qblock: ptr<function, vec2<u32>>, block: ptr<function, array<f32,
             let ep_b = (*ep)[8u * j + 4u + p];
             let dec_v0 = f32(((64 - w0) * i32(ep_a) + w0 * i32(ep_b) + 32) / 64);
             let dec_v1 = f32(((64 - w1) * i32(ep_a) + w1 * i32(ep_b) + 32) / 64);
-            err0 += sq(dec_v0 - (*block)[k + p * 16u]);
-            err1 += sq(dec_v1 - (*block)[k + p * 16u]);
+            let weight = texel_weight(k, p);
+            err0 += weight * sq(dec_v0 - (*block)[k + p * 16u]);
+            err1 += weight * sq(dec_v1 - (*block)[k + p * 16u]);
         }
 
         var best_err = err1;
@@ -760,10 +834,12 @@ fn data_shl_1bit_from(state: ptr<function, State>, from_bits: u32) {
 fn opt_endpoints(ep: ptr<function, array<f32, 24>>, offset: u32, block: ptr<function, array<f32, 64>>, bits: u32, qblock: vec2<u32>, mask: u32, channels: u32) {
     let levels = i32(1u << bits);
 
+    // The sums are kept per channel, because only the color channels are weighted.
     var Atb1: vec4<f32>;
-    var sum_q = 0.0;
-    var sum_qq = 0.0;
-    var sum: array<f32, 5>;
+    var sum_w: vec4<f32>;
+    var sum_q: vec4<f32>;
+    var sum_qq: vec4<f32>;
+    var sum: vec4<f32>;
 
     var mask_shifted = mask << 1u;
     for (var k1 = 0u; k1 < 2u; k1++) {
@@ -779,38 +855,32 @@ fn opt_endpoints(ep: ptr<function, array<f32, 24>>, offset: u32, block: ptr<func
             }
 
             let x = f32(levels - 1) - q;
-            let y = q;
-
-            sum_q += q;
-            sum_qq += q * q;
 
-            sum[4] += 1.0;
             for (var p = 0u; p < channels; p++) {
-                sum[p] += (*block)[k + p * 16u];
-                Atb1[p] += x * (*block)[k + p * 16u];
+                let weight = texel_weight(k, p);
+                sum_w[p] += weight;
+                sum_q[p] += weight * q;
+                sum_qq[p] += weight * q * q;
+                sum[p] += weight * (*block)[k + p * 16u];
+                Atb1[p] += weight * x * (*block)[k + p * 16u];
             }
         }
     }
 
-    var Atb2: vec4<f32>;
     for (var p = 0u; p < channels; p++) {
-        Atb2[p] = f32(levels - 1) * sum[p] - Atb1[p];
-    }
+        let Atb2 = f32(levels - 1) * sum[p] - Atb1[p];
 
-    let Cxx = sum[4] * sq(f32(levels - 1)) - 2.0 * f32(levels - 1) * sum_q + sum_qq;
-    let Cyy = sum_qq;
-    let Cxy = f32(levels - 1) * sum_q - sum_qq;
-    let scale = f32(levels - 1) / (Cxx * Cyy - Cxy * Cxy);
+        let Cxx = sum_w[p] * sq(f32(levels - 1)) - 2.0 * f32(levels - 1) * sum_q[p] + sum_qq[p];
+        let Cyy = sum_qq[p];
+        let Cxy = f32(levels - 1) * sum_q[p] - sum_qq[p];
+        let scale = f32(levels - 1) / (Cxx * Cyy - Cxy * Cxy);
 
-    for (var p = 0u; p < channels; p++) {
-        (*ep)[offset + 0u + p] = (Atb1[p] * Cyy - Atb2[p] * Cxy) * scale;
-        (*ep)[offset + 4u + p] = (Atb2[p] * Cxx - Atb1[p] * Cxy) * scale;
-    }
+        (*ep)[offset + 0u + p] = (Atb1[p] * Cyy - Atb2 * Cxy) * scale;
+        (*ep)[offset + 4u + p] = (Atb2 * Cxx - Atb1[p] * Cxy) * scale;
 
-    if (abs(Cxx * Cyy - Cxy * Cxy) < 0.001) {
-        // flatten
-        for (var p = 0u; p < channels; p++) {
-            (*ep)[offset + 0u + p] = sum[p] / sum[4];
+        if (abs(Cxx * Cyy - Cxy * Cxy) < 0.001) {
+            // flatten
+            (*ep)[offset + 0u + p] = sum[p] / sum_w[p];
             (*ep)[offset + 4u + p] = (*ep)[offset + 0u + p];
         }
     }
@@ -1376,6 +1446,7 @@ fn compress_bc7(@builtin(global_invocation_id) global_id: vec3<u32>) {
     var block: array<f32, 64>;
 
     load_block_interleaved_rgba(&block, xx, yy);
+    apply_alpha_options(&block);
 
     var state: State;
     state.best_err = 3.40282347e38;
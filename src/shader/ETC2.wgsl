@@ -0,0 +1,691 @@
+// Copyright (c) 2025, Nils Hasenbanck
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of this software and associated
+// documentation files (the "Software"), to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all copies or substantial portions of
+// the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+// THE WARRANTIES OF MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT,
+// TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+// ETC2 and EAC encoder. This is a port of the CPU encoder in `encode/etc.rs`.
+//
+// ETC2 and EAC blocks are big-endian 64-bit values. The encoder works on the high and low 32 bits
+// of a block and swaps the bytes of both words before storing them.
+
+const SOURCE_TEXTURE: u32 = 0u;
+const SOURCE_BUFFER_RGBA8: u32 = 1u;
+const SOURCE_BUFFER_RGBA16F: u32 = 2u;
+const SOURCE_BUFFER_RGBA32F: u32 = 3u;
+
+const ALPHA_THRESHOLD: f32 = 128.0;
+const MAX_ERROR: f32 = 3.40282347e+38;
+
+struct Uniforms {
+    width: u32,
+    height: u32,
+    blocks_offset: u32,
+    source_format: u32,
+    source_offset: u32,
+    source_stride: u32,
+    source_row: u32,
+}
+
+struct Candidate {
+    hi: u32,
+    lo: u32,
+    error: f32,
+}
+
+@group(0) @binding(0) var source_texture: texture_2d<f32>;
+@group(0) @binding(1) var<storage, read_write> block_buffer: array<u32>;
+@group(0) @binding(2) var<uniform> uniforms: Uniforms;
+@group(0) @binding(4) var<storage, read> source_buffer: array<u32>;
+
+fn get_etc1_modifier(table: u32, index: u32) -> i32 {
+    const etc1_modifier_table = array<i32, 16>(
+        2, 8,
+        5, 17,
+        9, 29,
+        13, 42,
+        18, 60,
+        24, 80,
+        33, 106,
+        47, 183,
+    );
+    return etc1_modifier_table[table * 2u + index];
+}
+
+fn get_eac_modifier(table: u32, index: u32) -> i32 {
+    const eac_modifier_table = array<i32, 128>(
+        -3, -6, -9, -15, 2, 5, 8, 14,
+        -3, -7, -10, -13, 2, 6, 9, 12,
+        -2, -5, -8, -13, 1, 4, 7, 12,
+        -2, -4, -6, -13, 1, 3, 5, 12,
+        -3, -6, -8, -12, 2, 5, 7, 11,
+        -3, -7, -9, -11, 2, 6, 8, 10,
+        -4, -7, -8, -11, 3, 6, 7, 10,
+        -3, -5, -8, -11, 2, 4, 7, 10,
+        -2, -6, -8, -10, 1, 5, 7, 9,
+        -2, -5, -8, -10, 1, 4, 7, 9,
+        -2, -4, -8, -10, 1, 3, 7, 9,
+        -2, -5, -7, -10, 1, 4, 6, 9,
+        -3, -4, -7, -10, 2, 3, 6, 9,
+        -1, -2, -3, -10, 0, 1, 2, 9,
+        -4, -6, -8, -9, 3, 5, 7, 8,
+        -3, -5, -7, -9, 2, 4, 6, 8,
+    );
+    return eac_modifier_table[table * 8u + index];
+}
+
+fn load_texel(pixel_x: u32, pixel_y: u32) -> vec4<f32> {
+    switch (uniforms.source_format) {
+        case SOURCE_BUFFER_RGBA8: {
+            let index = uniforms.source_offset + pixel_y * uniforms.source_stride + pixel_x;
+            return unpack4x8unorm(source_buffer[index]);
+        }
+        case SOURCE_BUFFER_RGBA16F: {
+            let index = uniforms.source_offset + pixel_y * uniforms.source_stride + pixel_x * 2u;
+            return vec4<f32>(
+                unpack2x16float(source_buffer[index]),
+                unpack2x16float(source_buffer[index + 1u]),
+            );
+        }
+        case SOURCE_BUFFER_RGBA32F: {
+            let index = uniforms.source_offset + pixel_y * uniforms.source_stride + pixel_x * 4u;
+            return vec4<f32>(
+                bitcast<f32>(source_buffer[index]),
+                bitcast<f32>(source_buffer[index + 1u]),
+                bitcast<f32>(source_buffer[index + 2u]),
+                bitcast<f32>(source_buffer[index + 3u]),
+            );
+        }
+        case SOURCE_TEXTURE, default: {
+            return textureLoad(source_texture, vec2<u32>(pixel_x, uniforms.source_row + pixel_y), 0);
+        }
+    }
+}
+
+fn load_block_interleaved_rgba(block: ptr<function, array<f32, 64>>, xx: u32, yy: u32) {
+    for (var y = 0u; y < 4u; y++) {
+        for (var x = 0u; x < 4u; x++) {
+            let pixel_x = xx * 4u + x;
+            let pixel_y = yy * 4u + y;
+            let rgba = load_texel(pixel_x, pixel_y);
+
+            (*block)[16u * 0u + y * 4u + x] = rgba.r * 255.0;
+            (*block)[16u * 1u + y * 4u + x] = rgba.g * 255.0;
+            (*block)[16u * 2u + y * 4u + x] = rgba.b * 255.0;
+            (*block)[16u * 3u + y * 4u + x] = rgba.a * 255.0;
+        }
+    }
+}
+
+fn rescale_to_11bit(block: ptr<function, array<f32, 64>>, channel: u32) {
+    for (var k = 0u; k < 16u; k++) {
+        (*block)[channel * 16u + k] = (*block)[channel * 16u + k] * 2047.0 / 255.0;
+    }
+}
+
+fn swap_bytes(value: u32) -> u32 {
+    return (value << 24u) | ((value << 8u) & 0x00FF0000u) | ((value >> 8u) & 0x0000FF00u) | (value >> 24u);
+}
+
+fn store_data_2(block_width: u32, xx: u32, yy: u32, data: vec2<u32>) {
+    let offset = uniforms.blocks_offset + (yy * block_width * 2u + xx * 2u);
+
+    block_buffer[offset + 0] = swap_bytes(data[0]);
+    block_buffer[offset + 1] = swap_bytes(data[1]);
+}
+
+fn store_data_4(block_width: u32, xx: u32, yy: u32, data: vec4<u32>) {
+    let offset = uniforms.blocks_offset + (yy * block_width * 4u + xx * 4u);
+
+    block_buffer[offset + 0] = swap_bytes(data[0]);
+    block_buffer[offset + 1] = swap_bytes(data[1]);
+    block_buffer[offset + 2] = swap_bytes(data[2]);
+    block_buffer[offset + 3] = swap_bytes(data[3]);
+}
+
+fn pick(a: Candidate, b: Candidate) -> Candidate {
+    if (b.error < a.error) {
+        return b;
+    }
+    return a;
+}
+
+fn extend_5to8(value: i32) -> i32 {
+    return (value << 3u) | (value >> 2u);
+}
+
+fn extend_6to8(value: i32) -> i32 {
+    return (value << 2u) | (value >> 4u);
+}
+
+fn extend_7to8(value: i32) -> i32 {
+    return (value << 1u) | (value >> 6u);
+}
+
+fn sign_extend_3(value: u32) -> i32 {
+    return (bitcast<i32>(value & 0x7u) << 29u) >> 29u;
+}
+
+fn texel(block: ptr<function, array<f32, 64>>, channel: u32, x: u32, y: u32) -> f32 {
+    return (*block)[channel * 16u + y * 4u + x];
+}
+
+fn color_error(block: ptr<function, array<f32, 64>>, x: u32, y: u32, color: vec3<i32>) -> f32 {
+    let diff = vec3<f32>(clamp(color, vec3<i32>(0), vec3<i32>(255))) - vec3<f32>(
+        texel(block, 0u, x, y),
+        texel(block, 1u, x, y),
+        texel(block, 2u, x, y),
+    );
+    return dot(diff, diff);
+}
+
+fn is_transparent(block: ptr<function, array<f32, 64>>, x: u32, y: u32) -> bool {
+    return texel(block, 3u, x, y) < ALPHA_THRESHOLD;
+}
+
+fn subblock_texel(flip: bool, subblock: u32, index: u32) -> vec2<u32> {
+    let x = index / 4u;
+    let y = index % 4u;
+    if (flip) {
+        return vec2<u32>(y, x + subblock * 2u);
+    }
+    return vec2<u32>(x + subblock * 2u, y);
+}
+
+fn subblock_average(block: ptr<function, array<f32, 64>>, flip: bool, subblock: u32, punch_through: bool) -> vec3<f32> {
+    var sum = vec3<f32>(0.0);
+    var count = 0.0;
+
+    for (var index = 0u; index < 8u; index++) {
+        let texel_xy = subblock_texel(flip, subblock, index);
+        let x = texel_xy.x;
+        let y = texel_xy.y;
+
+        if (punch_through && is_transparent(block, x, y)) {
+            continue;
+        }
+
+        sum += vec3<f32>(texel(block, 0u, x, y), texel(block, 1u, x, y), texel(block, 2u, x, y));
+        count += 1.0;
+    }
+
+    if (count > 0.0) {
+        return sum / count;
+    }
+    return sum;
+}
+
+// Finds the best modifier table for a sub-block. Returns the table in bits 39 to 37 and the
+// texel indices at their final position.
+fn encode_subblock(
+    block: ptr<function, array<f32, 64>>,
+    flip: bool,
+    subblock: u32,
+    base: vec3<i32>,
+    punch_through: bool,
+) -> Candidate {
+    var best = Candidate(0u, 0u, MAX_ERROR);
+
+    for (var table = 0u; table < 8u; table++) {
+        let a = get_etc1_modifier(table, 0u);
+        let b = get_etc1_modifier(table, 1u);
+
+        // Index 2 marks transparent texels in non-opaque punch-through blocks and index 0
+        // has no modifier.
+        var modifiers = array<i32, 4>(a, b, -a, -b);
+        if (punch_through) {
+            modifiers = array<i32, 4>(0, b, 0, -b);
+        }
+
+        var candidate = Candidate(table << 5u, 0u, 0.0);
+
+        for (var texel_index = 0u; texel_index < 8u; texel_index++) {
+            let texel_xy = subblock_texel(flip, subblock, texel_index);
+            let x = texel_xy.x;
+            let y = texel_xy.y;
+            let pixel = x * 4u + y;
+
+            var index = 2u;
+            if (!punch_through || !is_transparent(block, x, y)) {
+                var best_index = 0u;
+                var best_error = MAX_ERROR;
+                for (var i = 0u; i < 4u; i++) {
+                    if (punch_through && i == 2u) {
+                        continue;
+                    }
+                    let error = color_error(block, x, y, base + vec3<i32>(modifiers[i]));
+                    if (error < best_error) {
+                        best_error = error;
+                        best_index = i;
+                    }
+                }
+                candidate.error += best_error;
+                index = best_index;
+            }
+
+            candidate.lo |= ((index >> 1u) << (16u + pixel)) | ((index & 1u) << pixel);
+        }
+
+        best = pick(best, candidate);
+
+        if (best.error == 0.0) {
+            break;
+        }
+    }
+
+    return best;
+}
+
+// Combines the two encoded sub-blocks into a block with the given base color bits.
+fn combine_subblocks(flip: bool, base_bits: u32, subblock0: Candidate, subblock1: Candidate) -> Candidate {
+    // The table of the second sub-block is stored three bits after the first one.
+    let table1 = (subblock1.hi >> 5u) & 0x7u;
+
+    return Candidate(
+        base_bits | subblock0.hi | (table1 << 2u) | u32(flip),
+        subblock0.lo | subblock1.lo,
+        subblock0.error + subblock1.error,
+    );
+}
+
+fn encode_individual(block: ptr<function, array<f32, 64>>, flip: bool) -> Candidate {
+    let color0 = clamp(vec3<i32>(round(subblock_average(block, flip, 0u, false) * 15.0 / 255.0)), vec3<i32>(0), vec3<i32>(15));
+    let color1 = clamp(vec3<i32>(round(subblock_average(block, flip, 1u, false) * 15.0 / 255.0)), vec3<i32>(0), vec3<i32>(15));
+
+    let subblock0 = encode_subblock(block, flip, 0u, color0 * 17, false);
+    let subblock1 = encode_subblock(block, flip, 1u, color1 * 17, false);
+
+    var base_bits = 0u;
+    for (var channel = 0u; channel < 3u; channel++) {
+        let shift = 28u - channel * 8u;
+        base_bits |= u32(color0[channel]) << shift;
+        base_bits |= u32(color1[channel]) << (shift - 4u);
+    }
+
+    return combine_subblocks(flip, base_bits, subblock0, subblock1);
+}
+
+fn encode_differential(block: ptr<function, array<f32, 64>>, flip: bool, punch_through: bool) -> Candidate {
+    let base0 = clamp(vec3<i32>(round(subblock_average(block, flip, 0u, punch_through) * 31.0 / 255.0)), vec3<i32>(0), vec3<i32>(31));
+    let average1 = clamp(vec3<i32>(round(subblock_average(block, flip, 1u, punch_through) * 31.0 / 255.0)), vec3<i32>(0), vec3<i32>(31));
+
+    let delta = clamp(average1 - base0, vec3<i32>(-4), vec3<i32>(3));
+    let base1 = base0 + delta;
+
+    let extended0 = vec3<i32>(extend_5to8(base0.r), extend_5to8(base0.g), extend_5to8(base0.b));
+    let extended1 = vec3<i32>(extend_5to8(base1.r), extend_5to8(base1.g), extend_5to8(base1.b));
+
+    let subblock0 = encode_subblock(block, flip, 0u, extended0, punch_through);
+    let subblock1 = encode_subblock(block, flip, 1u, extended1, punch_through);
+
+    // Without punch-through alpha the differential bit is always set. With punch-through alpha
+    // it's the opaque bit.
+    var base_bits = 0u;
+    if (!punch_through) {
+        base_bits = 1u << 1u;
+    }
+
+    for (var channel = 0u; channel < 3u; channel++) {
+        let shift = 27u - channel * 8u;
+        base_bits |= u32(base0[channel]) << shift;
+        base_bits |= (bitcast<u32>(delta[channel]) & 0x7u) << (shift - 3u);
+    }
+
+    return combine_subblocks(flip, base_bits, subblock0, subblock1);
+}
+
+fn extend_planar(channel: u32, value: i32) -> i32 {
+    if (channel == 1u) {
+        return extend_7to8(value);
+    }
+    return extend_6to8(value);
+}
+
+fn encode_planar(block: ptr<function, array<f32, 64>>) -> Candidate {
+    // Least squares fit of a plane through the texels of each channel.
+    var origin: vec3<i32>;
+    var horizontal: vec3<i32>;
+    var vertical: vec3<i32>;
+
+    for (var channel = 0u; channel < 3u; channel++) {
+        var mean = 0.0;
+        var slope_x = 0.0;
+        var slope_y = 0.0;
+
+        for (var y = 0u; y < 4u; y++) {
+            for (var x = 0u; x < 4u; x++) {
+                let value = texel(block, channel, x, y);
+                mean += value;
+                slope_x += (f32(x) - 1.5) * value;
+                slope_y += (f32(y) - 1.5) * value;
+            }
+        }
+
+        mean /= 16.0;
+        slope_x /= 20.0;
+        slope_y /= 20.0;
+
+        let o = mean - 1.5 * slope_x - 1.5 * slope_y;
+        let h = o + 4.0 * slope_x;
+        let v = o + 4.0 * slope_y;
+
+        var max_value = 63.0;
+        if (channel == 1u) {
+            max_value = 127.0;
+        }
+
+        origin[channel] = i32(clamp(round(o * max_value / 255.0), 0.0, max_value));
+        horizontal[channel] = i32(clamp(round(h * max_value / 255.0), 0.0, max_value));
+        vertical[channel] = i32(clamp(round(v * max_value / 255.0), 0.0, max_value));
+    }
+
+    var error = 0.0;
+    for (var y = 0u; y < 4u; y++) {
+        for (var x = 0u; x < 4u; x++) {
+            var color: vec3<i32>;
+            for (var channel = 0u; channel < 3u; channel++) {
+                let o = extend_planar(channel, origin[channel]);
+                let h = extend_planar(channel, horizontal[channel]);
+                let v = extend_planar(channel, vertical[channel]);
+                color[channel] = (i32(x) * (h - o) + i32(y) * (v - o) + 4 * o + 2) >> 2u;
+            }
+            error += color_error(block, x, y, color);
+        }
+    }
+
+    let ro = u32(origin.r);
+    let go = u32(origin.g);
+    let bo = u32(origin.b);
+    let rh = u32(horizontal.r);
+    let gh = u32(horizontal.g);
+    let bh = u32(horizontal.b);
+    let rv = u32(vertical.r);
+    let gv = u32(vertical.g);
+    let bv = u32(vertical.b);
+
+    var hi = (ro << 25u)
+        | ((go >> 6u) << 24u)
+        | ((go & 0x3Fu) << 17u)
+        | ((bo >> 5u) << 16u)
+        | (((bo >> 3u) & 0x3u) << 11u)
+        | ((bo & 0x7u) << 7u)
+        | ((rh >> 1u) << 2u)
+        | (1u << 1u)
+        | (rh & 1u);
+    let lo = (gh << 25u) | (bh << 19u) | (rv << 13u) | (gv << 6u) | bv;
+
+    // The unused bits must make the red and green differential sums valid, but the blue sum has
+    // to overflow to select the planar mode.
+    let red = i32((hi >> 27u) & 0x1Fu) + sign_extend_3(hi >> 24u);
+    if (red < 0) {
+        hi |= 1u << 31u;
+    }
+
+    let green = i32((hi >> 19u) & 0x1Fu) + sign_extend_3(hi >> 16u);
+    if (green < 0) {
+        hi |= 1u << 23u;
+    }
+
+    if (((hi >> 11u) & 0x3u) + ((hi >> 8u) & 0x3u) < 4u) {
+        hi |= 1u << 10u;
+    } else {
+        hi |= 0x7u << 13u;
+    }
+
+    return Candidate(hi, lo, error);
+}
+
+fn compress_block_etc2(block: ptr<function, array<f32, 64>>, punch_through: bool) -> vec2<u32> {
+    var has_transparency = false;
+    if (punch_through) {
+        for (var k = 0u; k < 16u; k++) {
+            if ((*block)[48u + k] < ALPHA_THRESHOLD) {
+                has_transparency = true;
+            }
+        }
+    }
+
+    var best = Candidate(0u, 0u, MAX_ERROR);
+
+    for (var flip_index = 0u; flip_index < 2u; flip_index++) {
+        let flip = flip_index == 1u;
+
+        if (has_transparency) {
+            best = pick(best, encode_differential(block, flip, true));
+            continue;
+        }
+
+        // The punch-through variant uses the differential bit as the opaque bit, so it has no
+        // individual mode.
+        if (!punch_through) {
+            best = pick(best, encode_individual(block, flip));
+        }
+        best = pick(best, encode_differential(block, flip, false));
+    }
+
+    if (!has_transparency) {
+        best = pick(best, encode_planar(block));
+    }
+
+    return vec2<u32>(best.hi, best.lo);
+}
+
+fn or_bits(candidate: ptr<function, Candidate>, value: u32, shift: u32) {
+    if (shift >= 32u) {
+        (*candidate).hi |= value << (shift - 32u);
+    } else {
+        (*candidate).lo |= value << shift;
+        if (shift > 29u) {
+            (*candidate).hi |= value >> (32u - shift);
+        }
+    }
+}
+
+// Encodes the values of a channel, which are in the range of 0 to 255 for alpha and 0 to 2047
+// for the 11-bit formats.
+fn compress_block_eac(block: ptr<function, array<f32, 64>>, channel: u32, eleven_bit: bool) -> vec2<u32> {
+    var min_value = MAX_ERROR;
+    var max_value = -MAX_ERROR;
+    for (var k = 0u; k < 16u; k++) {
+        min_value = min(min_value, (*block)[channel * 16u + k]);
+        max_value = max(max_value, (*block)[channel * 16u + k]);
+    }
+
+    // The 11-bit formats scale the modifiers by 8 and add an offset of 4.
+    var scale = 1.0;
+    var offset = 0.0;
+    if (eleven_bit) {
+        scale = 8.0;
+        offset = 4.0;
+    }
+
+    var best = Candidate(0u, 0u, MAX_ERROR);
+
+    for (var table = 0u; table < 16u; table++) {
+        let span = f32(get_eac_modifier(table, 7u) - get_eac_modifier(table, 3u));
+        let center = f32(get_eac_modifier(table, 7u) + get_eac_modifier(table, 3u)) * 0.5;
+
+        let multiplier_estimate = i32(round((max_value - min_value) / (span * scale)));
+
+        for (var multiplier = max(multiplier_estimate - 1, 1); multiplier <= min(multiplier_estimate + 1, 15); multiplier++) {
+            let base_estimate = i32(round((((min_value + max_value) * 0.5 - offset) / scale) - f32(multiplier) * center));
+
+            for (var base = max(base_estimate - 1, 0); base <= min(base_estimate + 1, 255); base++) {
+                var candidate = Candidate(
+                    (u32(base) << 24u) | (u32(multiplier) << 20u) | (table << 16u),
+                    0u,
+                    0.0,
+                );
+
+                for (var y = 0u; y < 4u; y++) {
+                    for (var x = 0u; x < 4u; x++) {
+                        let value = (*block)[channel * 16u + y * 4u + x];
+
+                        var best_index = 0u;
+                        var best_error = MAX_ERROR;
+                        for (var index = 0u; index < 8u; index++) {
+                            let modifier = get_eac_modifier(table, index);
+
+                            var decoded: i32;
+                            if (eleven_bit) {
+                                decoded = clamp(base * 8 + 4 + modifier * multiplier * 8, 0, 2047);
+                            } else {
+                                decoded = clamp(base + modifier * multiplier, 0, 255);
+                            }
+
+                            let diff = f32(decoded) - value;
+                            if (diff * diff < best_error) {
+                                best_error = diff * diff;
+                                best_index = index;
+                            }
+                        }
+
+                        candidate.error += best_error;
+                        or_bits(&candidate, best_index, 45u - (x * 4u + y) * 3u);
+                    }
+                }
+
+                best = pick(best, candidate);
+            }
+        }
+    }
+
+    return vec2<u32>(best.hi, best.lo);
+}
+
+@compute
+@workgroup_size(8, 8)
+fn compress_etc2_rgb8(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    let xx = global_id.x;
+    let yy = global_id.y;
+
+    let block_width = (uniforms.width + 3u) / 4u;
+    let block_height = (uniforms.height + 3u) / 4u;
+
+    if (xx >= block_width || yy >= block_height) {
+        return;
+    }
+
+    var block: array<f32, 64>;
+
+    load_block_interleaved_rgba(&block, xx, yy);
+
+    let color_result = compress_block_etc2(&block, false);
+
+    store_data_2(block_width, xx, yy, color_result);
+}
+
+@compute
+@workgroup_size(8, 8)
+fn compress_etc2_rgb8a1(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    let xx = global_id.x;
+    let yy = global_id.y;
+
+    let block_width = (uniforms.width + 3u) / 4u;
+    let block_height = (uniforms.height + 3u) / 4u;
+
+    if (xx >= block_width || yy >= block_height) {
+        return;
+    }
+
+    var block: array<f32, 64>;
+
+    load_block_interleaved_rgba(&block, xx, yy);
+
+    let color_result = compress_block_etc2(&block, true);
+
+    store_data_2(block_width, xx, yy, color_result);
+}
+
+@compute
+@workgroup_size(8, 8)
+fn compress_etc2_rgba8(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    let xx = global_id.x;
+    let yy = global_id.y;
+
+    let block_width = (uniforms.width + 3u) / 4u;
+    let block_height = (uniforms.height + 3u) / 4u;
+
+    if (xx >= block_width || yy >= block_height) {
+        return;
+    }
+
+    var block: array<f32, 64>;
+    var compressed_data: vec4<u32>;
+
+    load_block_interleaved_rgba(&block, xx, yy);
+
+    let alpha_result = compress_block_eac(&block, 3u, false);
+    compressed_data[0] = alpha_result[0];
+    compressed_data[1] = alpha_result[1];
+
+    let color_result = compress_block_etc2(&block, false);
+    compressed_data[2] = color_result[0];
+    compressed_data[3] = color_result[1];
+
+    store_data_4(block_width, xx, yy, compressed_data);
+}
+
+@compute
+@workgroup_size(8, 8)
+fn compress_eac_r11(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    let xx = global_id.x;
+    let yy = global_id.y;
+
+    let block_width = (uniforms.width + 3u) / 4u;
+    let block_height = (uniforms.height + 3u) / 4u;
+
+    if (xx >= block_width || yy >= block_height) {
+        return;
+    }
+
+    var block: array<f32, 64>;
+
+    load_block_interleaved_rgba(&block, xx, yy);
+    rescale_to_11bit(&block, 0u);
+
+    let red_result = compress_block_eac(&block, 0u, true);
+
+    store_data_2(block_width, xx, yy, red_result);
+}
+
+@compute
+@workgroup_size(8, 8)
+fn compress_eac_rg11(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    let xx = global_id.x;
+    let yy = global_id.y;
+
+    let block_width = (uniforms.width + 3u) / 4u;
+    let block_height = (uniforms.height + 3u) / 4u;
+
+    if (xx >= block_width || yy >= block_height) {
+        return;
+    }
+
+    var block: array<f32, 64>;
+    var compressed_data: vec4<u32>;
+
+    load_block_interleaved_rgba(&block, xx, yy);
+    rescale_to_11bit(&block, 0u);
+    rescale_to_11bit(&block, 1u);
+
+    let red_result = compress_block_eac(&block, 0u, true);
+    compressed_data[0] = red_result[0];
+    compressed_data[1] = red_result[1];
+
+    let green_result = compress_block_eac(&block, 1u, true);
+    compressed_data[2] = green_result[0];
+    compressed_data[3] = green_result[1];
+
+    store_data_4(block_width, xx, yy, compressed_data);
+}
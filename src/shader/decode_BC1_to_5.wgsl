@@ -0,0 +1,318 @@
+// WGSL port of the BC1 to BC5 block decoders of "bcdec.h - v0.98" (https://github.com/iOrange/bcdec).
+//
+// bcdec is free and unencumbered software released into the public domain. The CPU decoder in
+// `src/decode/block.rs` is a port of the same code and both must produce the same values.
+
+struct Uniforms {
+    width: u32,
+    height: u32,
+    blocks_offset: u32,
+    padding: u32,
+    fill: vec4<f32>,
+}
+
+@group(0) @binding(0) var<storage, read> block_buffer: array<u32>;
+@group(0) @binding(1) var output_rgba8: texture_storage_2d<rgba8unorm, write>;
+@group(0) @binding(2) var<uniform> uniforms: Uniforms;
+@group(0) @binding(3) var output_rgba16f: texture_storage_2d<rgba16float, write>;
+
+var<private> texels: array<vec4<f32>, 16>;
+
+fn is_outside(xx: u32, yy: u32) -> bool {
+    let block_width = (uniforms.width + 3u) / 4u;
+    let block_height = (uniforms.height + 3u) / 4u;
+    return xx >= block_width || yy >= block_height;
+}
+
+fn block_start(xx: u32, yy: u32, block_words: u32) -> u32 {
+    let block_width = (uniforms.width + 3u) / 4u;
+    return uniforms.blocks_offset + (yy * block_width + xx) * block_words;
+}
+
+fn store_rgba8(xx: u32, yy: u32) {
+    for (var y = 0u; y < 4u; y++) {
+        for (var x = 0u; x < 4u; x++) {
+            let pixel = vec2<u32>(xx * 4u + x, yy * 4u + y);
+            if (pixel.x < uniforms.width && pixel.y < uniforms.height) {
+                textureStore(output_rgba8, pixel, texels[y * 4u + x]);
+            }
+        }
+    }
+}
+
+fn store_rgba16f(xx: u32, yy: u32) {
+    for (var y = 0u; y < 4u; y++) {
+        for (var x = 0u; x < 4u; x++) {
+            let pixel = vec2<u32>(xx * 4u + x, yy * 4u + y);
+            if (pixel.x < uniforms.width && pixel.y < uniforms.height) {
+                textureStore(output_rgba16f, pixel, texels[y * 4u + x]);
+            }
+        }
+    }
+}
+
+fn extract_bits_64(low: u32, high: u32, offset: u32, count: u32) -> u32 {
+    if (offset >= 32u) {
+        return extractBits(high, offset - 32u, count);
+    }
+    if (offset + count <= 32u) {
+        return extractBits(low, offset, count);
+    }
+    let low_count = 32u - offset;
+    return extractBits(low, offset, low_count) | (extractBits(high, 0u, count - low_count) << low_count);
+}
+
+// Decodes a BC1 color block into the RGBA channels of the texels.
+fn decode_color_block(colors: u32, color_indices: u32, opaque_mode: bool) {
+    var ref_colors: array<vec4<u32>, 4>;
+
+    let c0 = colors & 0xFFFFu;
+    let c1 = colors >> 16u;
+
+    // Unpack 565 ref colors
+    let r0 = (c0 >> 11u) & 0x1Fu;
+    let g0 = (c0 >> 5u) & 0x3Fu;
+    let b0 = c0 & 0x1Fu;
+
+    let r1 = (c1 >> 11u) & 0x1Fu;
+    let g1 = (c1 >> 5u) & 0x3Fu;
+    let b1 = c1 & 0x1Fu;
+
+    // Expand 565 ref colors to 888
+    ref_colors[0] = vec4<u32>((r0 * 527u + 23u) >> 6u, (g0 * 259u + 33u) >> 6u, (b0 * 527u + 23u) >> 6u, 255u);
+    ref_colors[1] = vec4<u32>((r1 * 527u + 23u) >> 6u, (g1 * 259u + 33u) >> 6u, (b1 * 527u + 23u) >> 6u, 255u);
+
+    if (c0 > c1 || opaque_mode) {
+        // Standard BC1 mode (also BC3 color block uses ONLY this mode)
+        // color_2 = 2/3*color_0 + 1/3*color_1
+        // color_3 = 1/3*color_0 + 2/3*color_1
+        ref_colors[2] = vec4<u32>(
+            ((2u * r0 + r1) * 351u + 61u) >> 7u,
+            ((2u * g0 + g1) * 2763u + 1039u) >> 11u,
+            ((2u * b0 + b1) * 351u + 61u) >> 7u,
+            255u,
+        );
+        ref_colors[3] = vec4<u32>(
+            ((r0 + r1 * 2u) * 351u + 61u) >> 7u,
+            ((g0 + g1 * 2u) * 2763u + 1039u) >> 11u,
+            ((b0 + b1 * 2u) * 351u + 61u) >> 7u,
+            255u,
+        );
+    } else {
+        // Quite rare BC1A mode
+        // color_2 = 1/2*color_0 + 1/2*color_1
+        // color_3 = 0
+        ref_colors[2] = vec4<u32>(
+            ((r0 + r1) * 1053u + 125u) >> 8u,
+            ((g0 + g1) * 4145u + 1019u) >> 11u,
+            ((b0 + b1) * 1053u + 125u) >> 8u,
+            255u,
+        );
+        ref_colors[3] = vec4<u32>(0u);
+    }
+
+    for (var i = 0u; i < 16u; i++) {
+        let index = (color_indices >> (i * 2u)) & 0x03u;
+        texels[i] = vec4<f32>(ref_colors[index]) / 255.0;
+    }
+}
+
+// Decodes a BC2 alpha block (sharp transitions).
+fn decode_sharp_alpha_block(low: u32, high: u32) -> array<f32, 16> {
+    var alpha: array<f32, 16>;
+
+    for (var i = 0u; i < 16u; i++) {
+        let alpha_value = extract_bits_64(low, high, i * 4u, 4u);
+        alpha[i] = f32(alpha_value * 17u) / 255.0;
+    }
+
+    return alpha;
+}
+
+// Decodes a BC3 alpha block (smooth transitions), which is also used for the channels of BC4 and BC5.
+fn decode_smooth_alpha_block(low: u32, high: u32) -> array<f32, 16> {
+    var alpha: array<u32, 8>;
+    alpha[0] = low & 0xFFu;
+    alpha[1] = (low >> 8u) & 0xFFu;
+
+    if (alpha[0] > alpha[1]) {
+        // 6 interpolated alpha values
+        alpha[2] = (6u * alpha[0] +      alpha[1]) / 7u;   // 6/7*alpha_0 + 1/7*alpha_1
+        alpha[3] = (5u * alpha[0] + 2u * alpha[1]) / 7u;   // 5/7*alpha_0 + 2/7*alpha_1
+        alpha[4] = (4u * alpha[0] + 3u * alpha[1]) / 7u;   // 4/7*alpha_0 + 3/7*alpha_1
+        alpha[5] = (3u * alpha[0] + 4u * alpha[1]) / 7u;   // 3/7*alpha_0 + 4/7*alpha_1
+        alpha[6] = (2u * alpha[0] + 5u * alpha[1]) / 7u;   // 2/7*alpha_0 + 5/7*alpha_1
+        alpha[7] = (     alpha[0] + 6u * alpha[1]) / 7u;   // 1/7*alpha_0 + 6/7*alpha_1
+    } else {
+        // 4 interpolated alpha values
+        alpha[2] = (4u * alpha[0] +      alpha[1]) / 5u;   // 4/5*alpha_0 + 1/5*alpha_1
+        alpha[3] = (3u * alpha[0] + 2u * alpha[1]) / 5u;   // 3/5*alpha_0 + 2/5*alpha_1
+        alpha[4] = (2u * alpha[0] + 3u * alpha[1]) / 5u;   // 2/5*alpha_0 + 3/5*alpha_1
+        alpha[5] = (     alpha[0] + 4u * alpha[1]) / 5u;   // 1/5*alpha_0 + 4/5*alpha_1
+        alpha[6] = 0x00u;
+        alpha[7] = 0xFFu;
+    }
+
+    var values: array<f32, 16>;
+
+    for (var i = 0u; i < 16u; i++) {
+        let index = extract_bits_64(low, high, 16u + i * 3u, 3u);
+        values[i] = f32(alpha[index]) / 255.0;
+    }
+
+    return values;
+}
+
+fn decode_bc1(xx: u32, yy: u32) {
+    let start = block_start(xx, yy, 2u);
+    decode_color_block(block_buffer[start], block_buffer[start + 1u], false);
+}
+
+fn decode_bc2(xx: u32, yy: u32) {
+    let start = block_start(xx, yy, 4u);
+    decode_color_block(block_buffer[start + 2u], block_buffer[start + 3u], true);
+
+    let alpha = decode_sharp_alpha_block(block_buffer[start], block_buffer[start + 1u]);
+    for (var i = 0u; i < 16u; i++) {
+        texels[i].a = alpha[i];
+    }
+}
+
+fn decode_bc3(xx: u32, yy: u32) {
+    let start = block_start(xx, yy, 4u);
+    decode_color_block(block_buffer[start + 2u], block_buffer[start + 3u], true);
+
+    let alpha = decode_smooth_alpha_block(block_buffer[start], block_buffer[start + 1u]);
+    for (var i = 0u; i < 16u; i++) {
+        texels[i].a = alpha[i];
+    }
+}
+
+fn decode_bc4(xx: u32, yy: u32) {
+    let start = block_start(xx, yy, 2u);
+
+    let red = decode_smooth_alpha_block(block_buffer[start], block_buffer[start + 1u]);
+    for (var i = 0u; i < 16u; i++) {
+        texels[i] = vec4<f32>(red[i], uniforms.fill.gba);
+    }
+}
+
+fn decode_bc5(xx: u32, yy: u32) {
+    let start = block_start(xx, yy, 4u);
+
+    let red = decode_smooth_alpha_block(block_buffer[start], block_buffer[start + 1u]);
+    let green = decode_smooth_alpha_block(block_buffer[start + 2u], block_buffer[start + 3u]);
+    for (var i = 0u; i < 16u; i++) {
+        texels[i] = vec4<f32>(red[i], green[i], uniforms.fill.ba);
+    }
+}
+
+@compute
+@workgroup_size(8, 8)
+fn decompress_bc1_rgba8(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    if (is_outside(global_id.x, global_id.y)) {
+        return;
+    }
+
+    decode_bc1(global_id.x, global_id.y);
+    store_rgba8(global_id.x, global_id.y);
+}
+
+@compute
+@workgroup_size(8, 8)
+fn decompress_bc1_rgba16f(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    if (is_outside(global_id.x, global_id.y)) {
+        return;
+    }
+
+    decode_bc1(global_id.x, global_id.y);
+    store_rgba16f(global_id.x, global_id.y);
+}
+
+@compute
+@workgroup_size(8, 8)
+fn decompress_bc2_rgba8(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    if (is_outside(global_id.x, global_id.y)) {
+        return;
+    }
+
+    decode_bc2(global_id.x, global_id.y);
+    store_rgba8(global_id.x, global_id.y);
+}
+
+@compute
+@workgroup_size(8, 8)
+fn decompress_bc2_rgba16f(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    if (is_outside(global_id.x, global_id.y)) {
+        return;
+    }
+
+    decode_bc2(global_id.x, global_id.y);
+    store_rgba16f(global_id.x, global_id.y);
+}
+
+@compute
+@workgroup_size(8, 8)
+fn decompress_bc3_rgba8(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    if (is_outside(global_id.x, global_id.y)) {
+        return;
+    }
+
+    decode_bc3(global_id.x, global_id.y);
+    store_rgba8(global_id.x, global_id.y);
+}
+
+@compute
+@workgroup_size(8, 8)
+fn decompress_bc3_rgba16f(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    if (is_outside(global_id.x, global_id.y)) {
+        return;
+    }
+
+    decode_bc3(global_id.x, global_id.y);
+    store_rgba16f(global_id.x, global_id.y);
+}
+
+@compute
+@workgroup_size(8, 8)
+fn decompress_bc4_rgba8(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    if (is_outside(global_id.x, global_id.y)) {
+        return;
+    }
+
+    decode_bc4(global_id.x, global_id.y);
+    store_rgba8(global_id.x, global_id.y);
+}
+
+@compute
+@workgroup_size(8, 8)
+fn decompress_bc4_rgba16f(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    if (is_outside(global_id.x, global_id.y)) {
+        return;
+    }
+
+    decode_bc4(global_id.x, global_id.y);
+    store_rgba16f(global_id.x, global_id.y);
+}
+
+@compute
+@workgroup_size(8, 8)
+fn decompress_bc5_rgba8(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    if (is_outside(global_id.x, global_id.y)) {
+        return;
+    }
+
+    decode_bc5(global_id.x, global_id.y);
+    store_rgba8(global_id.x, global_id.y);
+}
+
+@compute
+@workgroup_size(8, 8)
+fn decompress_bc5_rgba16f(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    if (is_outside(global_id.x, global_id.y)) {
+        return;
+    }
+
+    decode_bc5(global_id.x, global_id.y);
+    store_rgba16f(global_id.x, global_id.y);
+}
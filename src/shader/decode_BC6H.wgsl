@@ -0,0 +1,606 @@
+// WGSL port of the BC6H block decoder of "bcdec.h - v0.98" (https://github.com/iOrange/bcdec).
+//
+// bcdec is free and unencumbered software released into the public domain. The CPU decoder in
+// `src/decode/block.rs` is a port of the same code and both must produce the same values.
+//
+// Only unsigned BC6H (BC6H_UF16) blocks are supported, since this is what the encoder produces.
+
+struct Uniforms {
+    width: u32,
+    height: u32,
+    blocks_offset: u32,
+    padding: u32,
+    fill: vec4<f32>,
+}
+
+struct BitStream {
+    data: array<u32, 4>,
+    position: u32,
+}
+
+@group(0) @binding(0) var<storage, read> block_buffer: array<u32>;
+@group(0) @binding(1) var output_rgba8: texture_storage_2d<rgba8unorm, write>;
+@group(0) @binding(2) var<uniform> uniforms: Uniforms;
+@group(0) @binding(3) var output_rgba16f: texture_storage_2d<rgba16float, write>;
+
+var<private> texels: array<vec4<f32>, 16>;
+
+fn is_outside(xx: u32, yy: u32) -> bool {
+    let block_width = (uniforms.width + 3u) / 4u;
+    let block_height = (uniforms.height + 3u) / 4u;
+    return xx >= block_width || yy >= block_height;
+}
+
+fn linear_to_srgb(linear: vec3<f32>) -> vec3<f32> {
+    return select(1.055 * pow(linear, vec3<f32>(1.0 / 2.4)) - 0.055, linear * 12.92, linear <= vec3<f32>(0.0031308));
+}
+
+fn store_rgba8(xx: u32, yy: u32) {
+    for (var y = 0u; y < 4u; y++) {
+        for (var x = 0u; x < 4u; x++) {
+            let pixel = vec2<u32>(xx * 4u + x, yy * 4u + y);
+            if (pixel.x < uniforms.width && pixel.y < uniforms.height) {
+                // HDR values are written as sRGB encoded values.
+                let texel = texels[y * 4u + x];
+                textureStore(output_rgba8, pixel, vec4<f32>(linear_to_srgb(texel.rgb), texel.a));
+            }
+        }
+    }
+}
+
+fn store_rgba16f(xx: u32, yy: u32) {
+    for (var y = 0u; y < 4u; y++) {
+        for (var x = 0u; x < 4u; x++) {
+            let pixel = vec2<u32>(xx * 4u + x, yy * 4u + y);
+            if (pixel.x < uniforms.width && pixel.y < uniforms.height) {
+                textureStore(output_rgba16f, pixel, texels[y * 4u + x]);
+            }
+        }
+    }
+}
+
+fn read_bits(stream: ptr<function, BitStream>, count: u32) -> u32 {
+    let word = (*stream).position / 32u;
+    let offset = (*stream).position % 32u;
+
+    var bits = (*stream).data[word] >> offset;
+    if (offset + count > 32u) {
+        bits |= (*stream).data[word + 1u] << (32u - offset);
+    }
+
+    (*stream).position += count;
+
+    return extractBits(bits, 0u, count);
+}
+
+fn read_i32(stream: ptr<function, BitStream>, count: u32) -> i32 {
+    return i32(read_bits(stream, count));
+}
+
+fn read_reversed(stream: ptr<function, BitStream>, count: u32) -> i32 {
+    return i32(reverseBits(read_bits(stream, count)) >> (32u - count));
+}
+
+fn get_partition(part_id: i32) -> u32 {
+    const partition_table = array<u32, 32>(
+        0x50505050u, 0x40404040u, 0x54545454u, 0x54505040u, 0x50404000u, 0x55545450u, 0x55545040u, 0x54504000u,
+        0x50400000u, 0x55555450u, 0x55544000u, 0x54400000u, 0x55555440u, 0x55550000u, 0x55555500u, 0x55000000u,
+        0x55150100u, 0x00004054u, 0x15010000u, 0x00405054u, 0x00004050u, 0x15050100u, 0x05010000u, 0x40505054u,
+        0x00404050u, 0x05010100u, 0x14141414u, 0x05141450u, 0x01155440u, 0x00555500u, 0x15014054u, 0x05414150u,
+    );
+
+    return partition_table[part_id];
+}
+
+fn get_anchor(part_id: i32) -> u32 {
+    const anchor_table = array<u32, 32>(
+        15u, 15u, 15u, 15u, 15u, 15u, 15u, 15u, 15u, 15u, 15u, 15u, 15u, 15u, 15u, 15u,
+        15u, 2u, 8u, 2u, 2u, 8u, 8u, 15u, 2u, 8u, 2u, 2u, 8u, 8u, 2u, 2u,
+    );
+
+    return anchor_table[part_id];
+}
+
+fn get_actual_bits_count(channel: u32, mode: u32) -> i32 {
+    const actual_bits_count = array<array<i32, 14>, 4>(
+        array<i32, 14>(10, 7, 11, 11, 11, 9, 8, 8, 8, 6, 10, 11, 12, 16), // W
+        array<i32, 14>(5, 6, 5, 4, 4, 5, 6, 5, 5, 6, 10, 9, 8, 4),        // dR
+        array<i32, 14>(5, 6, 4, 5, 4, 5, 5, 6, 5, 6, 10, 9, 8, 4),        // dG
+        array<i32, 14>(5, 6, 4, 4, 5, 5, 5, 5, 6, 6, 10, 9, 8, 4),        // dB
+    );
+
+    return actual_bits_count[channel][mode];
+}
+
+fn get_weight(index_bits: u32, index: u32) -> i32 {
+    switch (index_bits) {
+        case 3u: {
+            const table = array<i32, 8>(0, 9, 18, 27, 37, 46, 55, 64);
+            return table[index];
+        }
+        default: {
+            const table = array<i32, 16>(0, 4, 9, 13, 17, 21, 26, 30, 34, 38, 43, 47, 51, 55, 60, 64);
+            return table[index];
+        }
+    }
+}
+
+// http://graphics.stanford.edu/~seander/bithacks.html#VariableSignExtend
+fn extend_sign(val: i32, bits: i32) -> i32 {
+    return (val << u32(32 - bits)) >> u32(32 - bits);
+}
+
+fn transform_inverse(val: i32, a0: i32, bits: i32) -> i32 {
+    // If the precision of A0 is "p" bits, then the transform algorithm is:
+    // B0 = (B0 + A0) & ((1 << p) - 1)
+    return (val + a0) & ((1i << u32(bits)) - 1i);
+}
+
+fn unquantize(val: i32, bits: i32) -> i32 {
+    if (bits >= 15) {
+        return val;
+    } else if (val == 0) {
+        return 0;
+    } else if (val == ((1i << u32(bits)) - 1i)) {
+        return 0xFFFF;
+    }
+    return ((val << 16u) + 0x8000) >> u32(bits);
+}
+
+fn finish_unquantize(val: i32) -> f32 {
+    // Scale the magnitude by 31 / 64
+    let half_bits = u32((val * 31) >> 6u) & 0xFFFFu;
+    return unpack2x16float(half_bits).x;
+}
+
+fn interpolate(a: i32, b: i32, weight: i32) -> i32 {
+    return (a * (64 - weight) + b * weight + 32) >> 6u;
+}
+
+fn decode_bc6h(xx: u32, yy: u32) {
+    let block_width = (uniforms.width + 3u) / 4u;
+    let start = uniforms.blocks_offset + (yy * block_width + xx) * 4u;
+
+    var bstream: BitStream;
+    bstream.data = array<u32, 4>(
+        block_buffer[start],
+        block_buffer[start + 1u],
+        block_buffer[start + 2u],
+        block_buffer[start + 3u],
+    );
+    bstream.position = 0u;
+
+    var r = array<i32, 4>(0, 0, 0, 0);
+    var g = array<i32, 4>(0, 0, 0, 0);
+    var b = array<i32, 4>(0, 0, 0, 0);
+
+    var mode = read_bits(&bstream, 2u);
+    if (mode > 1u) {
+        mode |= read_bits(&bstream, 3u) << 2u;
+    }
+
+    // modes >= 11 (10 in my code) are using 0 one, others will read it from the bitstream
+    var part_id = 0;
+
+    switch (mode) {
+        // Mode 1
+        case 0u: {
+            // Partition indices: 46 bits
+            // Partition: 5 bits
+            // Color Endpoints: 75 bits (10.555, 10.555, 10.555)
+            g[2] |= read_i32(&bstream, 1u) << 4u; // gy[4]
+            b[2] |= read_i32(&bstream, 1u) << 4u; // by[4]
+            b[3] |= read_i32(&bstream, 1u) << 4u; // bz[4]
+            r[0] |= read_i32(&bstream, 10u); // rw[9:0]
+            g[0] |= read_i32(&bstream, 10u); // gw[9:0]
+            b[0] |= read_i32(&bstream, 10u); // bw[9:0]
+            r[1] |= read_i32(&bstream, 5u); // rx[4:0]
+            g[3] |= read_i32(&bstream, 1u) << 4u; // gz[4]
+            g[2] |= read_i32(&bstream, 4u); // gy[3:0]
+            g[1] |= read_i32(&bstream, 5u); // gx[4:0]
+            b[3] |= read_i32(&bstream, 1u); // bz[0]
+            g[3] |= read_i32(&bstream, 4u); // gz[3:0]
+            b[1] |= read_i32(&bstream, 5u); // bx[4:0]
+            b[3] |= read_i32(&bstream, 1u) << 1u; // bz[1]
+            b[2] |= read_i32(&bstream, 4u); // by[3:0]
+            r[2] |= read_i32(&bstream, 5u); // ry[4:0]
+            b[3] |= read_i32(&bstream, 1u) << 2u; // bz[2]
+            r[3] |= read_i32(&bstream, 5u); // rz[4:0]
+            b[3] |= read_i32(&bstream, 1u) << 3u; // bz[3]
+            part_id = read_i32(&bstream, 5u); // d[4:0]
+            mode = 0u;
+        }
+
+        // Mode 2
+        case 1u: {
+            // Partition indices: 46 bits
+            // Partition: 5 bits
+            // Color Endpoints: 75 bits (7666, 7666, 7666)
+            g[2] |= read_i32(&bstream, 1u) << 5u; // gy[5]
+            g[3] |= read_i32(&bstream, 1u) << 4u; // gz[4]
+            g[3] |= read_i32(&bstream, 1u) << 5u; // gz[5]
+            r[0] |= read_i32(&bstream, 7u); // rw[6:0]
+            b[3] |= read_i32(&bstream, 1u); // bz[0]
+            b[3] |= read_i32(&bstream, 1u) << 1u; // bz[1]
+            b[2] |= read_i32(&bstream, 1u) << 4u; // by[4]
+            g[0] |= read_i32(&bstream, 7u); // gw[6:0]
+            b[2] |= read_i32(&bstream, 1u) << 5u; // by[5]
+            b[3] |= read_i32(&bstream, 1u) << 2u; // bz[2]
+            g[2] |= read_i32(&bstream, 1u) << 4u; // gy[4]
+            b[0] |= read_i32(&bstream, 7u); // bw[6:0]
+            b[3] |= read_i32(&bstream, 1u) << 3u; // bz[3]
+            b[3] |= read_i32(&bstream, 1u) << 5u; // bz[5]
+            b[3] |= read_i32(&bstream, 1u) << 4u; // bz[4]
+            r[1] |= read_i32(&bstream, 6u); // rx[5:0]
+            g[2] |= read_i32(&bstream, 4u); // gy[3:0]
+            g[1] |= read_i32(&bstream, 6u); // gx[5:0]
+            g[3] |= read_i32(&bstream, 4u); // gz[3:0]
+            b[1] |= read_i32(&bstream, 6u); // bx[5:0]
+            b[2] |= read_i32(&bstream, 4u); // by[3:0]
+            r[2] |= read_i32(&bstream, 6u); // ry[5:0]
+            r[3] |= read_i32(&bstream, 6u); // rz[5:0]
+            part_id = read_i32(&bstream, 5u); // d[4:0]
+            mode = 1u;
+        }
+
+        // Mode 3
+        case 2u: {
+            // Partition indices: 46 bits
+            // Partition: 5 bits
+            // Color Endpoints: 72 bits (11.555, 11.444, 11.444)
+            r[0] |= read_i32(&bstream, 10u); // rw[9:0]
+            g[0] |= read_i32(&bstream, 10u); // gw[9:0]
+            b[0] |= read_i32(&bstream, 10u); // bw[9:0]
+            r[1] |= read_i32(&bstream, 5u); // rx[4:0]
+            r[0] |= read_i32(&bstream, 1u) << 10u; // rw[10]
+            g[2] |= read_i32(&bstream, 4u); // gy[3:0]
+            g[1] |= read_i32(&bstream, 4u); // gx[3:0]
+            g[0] |= read_i32(&bstream, 1u) << 10u; // gw[10]
+            b[3] |= read_i32(&bstream, 1u); // bz[0]
+            g[3] |= read_i32(&bstream, 4u); // gz[3:0]
+            b[1] |= read_i32(&bstream, 4u); // bx[3:0]
+            b[0] |= read_i32(&bstream, 1u) << 10u; // bw[10]
+            b[3] |= read_i32(&bstream, 1u) << 1u; // bz[1]
+            b[2] |= read_i32(&bstream, 4u); // by[3:0]
+            r[2] |= read_i32(&bstream, 5u); // ry[4:0]
+            b[3] |= read_i32(&bstream, 1u) << 2u; // bz[2]
+            r[3] |= read_i32(&bstream, 5u); // rz[4:0]
+            b[3] |= read_i32(&bstream, 1u) << 3u; // bz[3]
+            part_id = read_i32(&bstream, 5u); // d[4:0]
+            mode = 2u;
+        }
+        // Mode 4
+        case 6u: {
+            // Partition indices: 46 bits
+            // Partition: 5 bits
+            // Color Endpoints: 72 bits (11.444, 11.555, 11.444)
+            r[0] |= read_i32(&bstream, 10u); // rw[9:0]
+            g[0] |= read_i32(&bstream, 10u); // gw[9:0]
+            b[0] |= read_i32(&bstream, 10u); // bw[9:0]
+            r[1] |= read_i32(&bstream, 4u); // rx[3:0]
+            r[0] |= read_i32(&bstream, 1u) << 10u; // rw[10]
+            g[3] |= read_i32(&bstream, 1u) << 4u; // gz[4]
+            g[2] |= read_i32(&bstream, 4u); // gy[3:0]
+            g[1] |= read_i32(&bstream, 5u); // gx[4:0]
+            g[0] |= read_i32(&bstream, 1u) << 10u; // gw[10]
+            g[3] |= read_i32(&bstream, 4u); // gz[3:0]
+            b[1] |= read_i32(&bstream, 4u); // bx[3:0]
+            b[0] |= read_i32(&bstream, 1u) << 10u; // bw[10]
+            b[3] |= read_i32(&bstream, 1u) << 1u; // bz[1]
+            b[2] |= read_i32(&bstream, 4u); // by[3:0]
+            r[2] |= read_i32(&bstream, 4u); // ry[3:0]
+            b[3] |= read_i32(&bstream, 1u); // bz[0]
+            b[3] |= read_i32(&bstream, 1u) << 2u; // bz[2]
+            r[3] |= read_i32(&bstream, 4u); // rz[3:0]
+            g[2] |= read_i32(&bstream, 1u) << 4u; // gy[4]
+            b[3] |= read_i32(&bstream, 1u) << 3u; // bz[3]
+            part_id = read_i32(&bstream, 5u); // d[4:0]
+            mode = 3u;
+        }
+        // Mode 5
+        case 10u: {
+            // Partition indices: 46 bits
+            // Partition: 5 bits
+            // Color Endpoints: 72 bits (11.444, 11.444, 11.555)
+            r[0] |= read_i32(&bstream, 10u); // rw[9:0]
+            g[0] |= read_i32(&bstream, 10u); // gw[9:0]
+            b[0] |= read_i32(&bstream, 10u); // bw[9:0]
+            r[1] |= read_i32(&bstream, 4u); // rx[3:0]
+            r[0] |= read_i32(&bstream, 1u) << 10u; // rw[10]
+            b[2] |= read_i32(&bstream, 1u) << 4u; // by[4]
+            g[2] |= read_i32(&bstream, 4u); // gy[3:0]
+            g[1] |= read_i32(&bstream, 4u); // gx[3:0]
+            g[0] |= read_i32(&bstream, 1u) << 10u; // gw[10]
+            b[3] |= read_i32(&bstream, 1u); // bz[0]
+            g[3] |= read_i32(&bstream, 4u); // gz[3:0]
+            b[1] |= read_i32(&bstream, 5u); // bx[4:0]
+            b[0] |= read_i32(&bstream, 1u) << 10u; // bw[10]
+            b[2] |= read_i32(&bstream, 4u); // by[3:0]
+            r[2] |= read_i32(&bstream, 4u); // ry[3:0]
+            b[3] |= read_i32(&bstream, 1u) << 1u; // bz[1]
+            b[3] |= read_i32(&bstream, 1u) << 2u; // bz[2]
+            r[3] |= read_i32(&bstream, 4u); // rz[3:0]
+            b[3] |= read_i32(&bstream, 1u) << 4u; // bz[4]
+            b[3] |= read_i32(&bstream, 1u) << 3u; // bz[3]
+            part_id = read_i32(&bstream, 5u); // d[4:0]
+            mode = 4u;
+        }
+        // Mode 6
+        case 14u: {
+            // Partition indices: 46 bits
+            // Partition: 5 bits
+            // Color Endpoints: 72 bits (9555, 9555, 9555)
+            r[0] |= read_i32(&bstream, 9u); // rw[8:0]
+            b[2] |= read_i32(&bstream, 1u) << 4u; // by[4]
+            g[0] |= read_i32(&bstream, 9u); // gw[8:0]
+            g[2] |= read_i32(&bstream, 1u) << 4u; // gy[4]
+            b[0] |= read_i32(&bstream, 9u); // bw[8:0]
+            b[3] |= read_i32(&bstream, 1u) << 4u; // bz[4]
+            r[1] |= read_i32(&bstream, 5u); // rx[4:0]
+            g[3] |= read_i32(&bstream, 1u) << 4u; // gz[4]
+            g[2] |= read_i32(&bstream, 4u); // gy[3:0]
+            g[1] |= read_i32(&bstream, 5u); // gx[4:0]
+            b[3] |= read_i32(&bstream, 1u); // bz[0]
+            g[3] |= read_i32(&bstream, 4u); // gx[3:0]
+            b[1] |= read_i32(&bstream, 5u); // bx[4:0]
+            b[3] |= read_i32(&bstream, 1u) << 1u; // bz[1]
+            b[2] |= read_i32(&bstream, 4u); // by[3:0]
+            r[2] |= read_i32(&bstream, 5u); // ry[4:0]
+            b[3] |= read_i32(&bstream, 1u) << 2u; // bz[2]
+            r[3] |= read_i32(&bstream, 5u); // rz[4:0]
+            b[3] |= read_i32(&bstream, 1u) << 3u; // bz[3]
+            part_id = read_i32(&bstream, 5u); // d[4:0]
+            mode = 5u;
+        }
+        // Mode 7
+        case 18u: {
+            // Partition indices: 46 bits
+            // Partition: 5 bits
+            // Color Endpoints: 72 bits (8666, 8555, 8555)
+            r[0] |= read_i32(&bstream, 8u); // rw[7:0]
+            g[3] |= read_i32(&bstream, 1u) << 4u; // gz[4]
+            b[2] |= read_i32(&bstream, 1u) << 4u; // by[4]
+            g[0] |= read_i32(&bstream, 8u); // gw[7:0]
+            b[3] |= read_i32(&bstream, 1u) << 2u; // bz[2]
+            g[2] |= read_i32(&bstream, 1u) << 4u; // gy[4]
+            b[0] |= read_i32(&bstream, 8u); // bw[7:0]
+            b[3] |= read_i32(&bstream, 1u) << 3u; // bz[3]
+            b[3] |= read_i32(&bstream, 1u) << 4u; // bz[4]
+            r[1] |= read_i32(&bstream, 6u); // rx[5:0]
+            g[2] |= read_i32(&bstream, 4u); // gy[3:0]
+            g[1] |= read_i32(&bstream, 5u); // gx[4:0]
+            b[3] |= read_i32(&bstream, 1u); // bz[0]
+            g[3] |= read_i32(&bstream, 4u); // gz[3:0]
+            b[1] |= read_i32(&bstream, 5u); // bx[4:0]
+            b[3] |= read_i32(&bstream, 1u) << 1u; // bz[1]
+            b[2] |= read_i32(&bstream, 4u); // by[3:0]
+            r[2] |= read_i32(&bstream, 6u); // ry[5:0]
+            r[3] |= read_i32(&bstream, 6u); // rz[5:0]
+            part_id = read_i32(&bstream, 5u); // d[4:0]
+            mode = 6u;
+        }
+        // Mode 8
+        case 22u: {
+            // Partition indices: 46 bits
+            // Partition: 5 bits
+            // Color Endpoints: 72 bits (8555, 8666, 8555)
+            r[0] |= read_i32(&bstream, 8u); // rw[7:0]
+            b[3] |= read_i32(&bstream, 1u); // bz[0]
+            b[2] |= read_i32(&bstream, 1u) << 4u; // by[4]
+            g[0] |= read_i32(&bstream, 8u); // gw[7:0]
+            g[2] |= read_i32(&bstream, 1u) << 5u; // gy[5]
+            g[2] |= read_i32(&bstream, 1u) << 4u; // gy[4]
+            b[0] |= read_i32(&bstream, 8u); // bw[7:0]
+            g[3] |= read_i32(&bstream, 1u) << 5u; // gz[5]
+            b[3] |= read_i32(&bstream, 1u) << 4u; // bz[4]
+            r[1] |= read_i32(&bstream, 5u); // rx[4:0]
+            g[3] |= read_i32(&bstream, 1u) << 4u; // gz[4]
+            g[2] |= read_i32(&bstream, 4u); // gy[3:0]
+            g[1] |= read_i32(&bstream, 6u); // gx[5:0]
+            g[3] |= read_i32(&bstream, 4u); // zx[3:0]
+            b[1] |= read_i32(&bstream, 5u); // bx[4:0]
+            b[3] |= read_i32(&bstream, 1u) << 1u; // bz[1]
+            b[2] |= read_i32(&bstream, 4u); // by[3:0]
+            r[2] |= read_i32(&bstream, 5u); // ry[4:0]
+            b[3] |= read_i32(&bstream, 1u) << 2u; // bz[2]
+            r[3] |= read_i32(&bstream, 5u); // rz[4:0]
+            b[3] |= read_i32(&bstream, 1u) << 3u; // bz[3]
+            part_id = read_i32(&bstream, 5u); // d[4:0]
+            mode = 7u;
+        }
+        // Mode 9
+        case 26u: {
+            // Partition indices: 46 bits
+            // Partition: 5 bits
+            // Color Endpoints: 72 bits (8555, 8555, 8666)
+            r[0] |= read_i32(&bstream, 8u); // rw[7:0]
+            b[3] |= read_i32(&bstream, 1u) << 1u; // bz[1]
+            b[2] |= read_i32(&bstream, 1u) << 4u; // by[4]
+            g[0] |= read_i32(&bstream, 8u); // gw[7:0]
+            b[2] |= read_i32(&bstream, 1u) << 5u; // by[5]
+            g[2] |= read_i32(&bstream, 1u) << 4u; // gy[4]
+            b[0] |= read_i32(&bstream, 8u); // bw[7:0]
+            b[3] |= read_i32(&bstream, 1u) << 5u; // bz[5]
+            b[3] |= read_i32(&bstream, 1u) << 4u; // bz[4]
+            r[1] |= read_i32(&bstream, 5u); // bw[4:0]
+            g[3] |= read_i32(&bstream, 1u) << 4u; // gz[4]
+            g[2] |= read_i32(&bstream, 4u); // gy[3:0]
+            g[1] |= read_i32(&bstream, 5u); // gx[4:0]
+            b[3] |= read_i32(&bstream, 1u); // bz[0]
+            g[3] |= read_i32(&bstream, 4u); // gz[3:0]
+            b[1] |= read_i32(&bstream, 6u); // bx[5:0]
+            b[2] |= read_i32(&bstream, 4u); // by[3:0]
+            r[2] |= read_i32(&bstream, 5u); // ry[4:0]
+            b[3] |= read_i32(&bstream, 1u) << 2u; // bz[2]
+            r[3] |= read_i32(&bstream, 5u); // rz[4:0]
+            b[3] |= read_i32(&bstream, 1u) << 3u; // bz[3]
+            part_id = read_i32(&bstream, 5u); // d[4:0]
+            mode = 8u;
+        }
+        // Mode 10
+        case 30u: {
+            // Partition indices: 46 bits
+            // Partition: 5 bits
+            // Color Endpoints: 72 bits (6666, 6666, 6666)
+            r[0] |= read_i32(&bstream, 6u); // rw[5:0]
+            g[3] |= read_i32(&bstream, 1u) << 4u; // gz[4]
+            b[3] |= read_i32(&bstream, 1u); // bz[0]
+            b[3] |= read_i32(&bstream, 1u) << 1u; // bz[1]
+            b[2] |= read_i32(&bstream, 1u) << 4u; // by[4]
+            g[0] |= read_i32(&bstream, 6u); // gw[5:0]
+            g[2] |= read_i32(&bstream, 1u) << 5u; // gy[5]
+            b[2] |= read_i32(&bstream, 1u) << 5u; // by[5]
+            b[3] |= read_i32(&bstream, 1u) << 2u; // bz[2]
+            g[2] |= read_i32(&bstream, 1u) << 4u; // gy[4]
+            b[0] |= read_i32(&bstream, 6u); // bw[5:0]
+            g[3] |= read_i32(&bstream, 1u) << 5u; // gz[5]
+            b[3] |= read_i32(&bstream, 1u) << 3u; // bz[3]
+            b[3] |= read_i32(&bstream, 1u) << 5u; // bz[5]
+            b[3] |= read_i32(&bstream, 1u) << 4u; // bz[4]
+            r[1] |= read_i32(&bstream, 6u); // rx[5:0]
+            g[2] |= read_i32(&bstream, 4u); // gy[3:0]
+            g[1] |= read_i32(&bstream, 6u); // gx[5:0]
+            g[3] |= read_i32(&bstream, 4u); // gz[3:0]
+            b[1] |= read_i32(&bstream, 6u); // bx[5:0]
+            b[2] |= read_i32(&bstream, 4u); // by[3:0]
+            r[2] |= read_i32(&bstream, 6u); // ry[5:0]
+            r[3] |= read_i32(&bstream, 6u); // rz[5:0]
+            part_id = read_i32(&bstream, 5u); // d[4:0]
+            mode = 9u;
+        }
+        // Mode 11
+        case 3u: {
+            // Partition indices: 63 bits
+            // Partition: 0 bits
+            // Color Endpoints: 60 bits (10.10, 10.10, 10.10)
+            r[0] |= read_i32(&bstream, 10u); // rw[9:0]
+            g[0] |= read_i32(&bstream, 10u); // gw[9:0]
+            b[0] |= read_i32(&bstream, 10u); // bw[9:0]
+            r[1] |= read_i32(&bstream, 10u); // rx[9:0]
+            g[1] |= read_i32(&bstream, 10u); // gx[9:0]
+            b[1] |= read_i32(&bstream, 10u); // bx[9:0]
+            mode = 10u;
+        }
+        // Mode 12
+        case 7u: {
+            // Partition indices: 63 bits
+            // Partition: 0 bits
+            // Color Endpoints: 60 bits (11.9, 11.9, 11.9)
+            r[0] |= read_i32(&bstream, 10u); // rw[9:0]
+            g[0] |= read_i32(&bstream, 10u); // gw[9:0]
+            b[0] |= read_i32(&bstream, 10u); // bw[9:0]
+            r[1] |= read_i32(&bstream, 9u); // rx[8:0]
+            r[0] |= read_i32(&bstream, 1u) << 10u; // rw[10]
+            g[1] |= read_i32(&bstream, 9u); // gx[8:0]
+            g[0] |= read_i32(&bstream, 1u) << 10u; // gw[10]
+            b[1] |= read_i32(&bstream, 9u); // bx[8:0]
+            b[0] |= read_i32(&bstream, 1u) << 10u; // bw[10]
+            mode = 11u;
+        }
+        // Mode 13
+        case 11u: {
+            // Partition indices: 63 bits
+            // Partition: 0 bits
+            // Color Endpoints: 60 bits (12.8, 12.8, 12.8)
+            r[0] |= read_i32(&bstream, 10u); // rw[9:0]
+            g[0] |= read_i32(&bstream, 10u); // gw[9:0]
+            b[0] |= read_i32(&bstream, 10u); // bw[9:0]
+            r[1] |= read_i32(&bstream, 8u); // rx[7:0]
+            r[0] |= read_reversed(&bstream, 2u) << 10u; // rx[10:11]
+            g[1] |= read_i32(&bstream, 8u); // gx[7:0]
+            g[0] |= read_reversed(&bstream, 2u) << 10u; // gx[10:11]
+            b[1] |= read_i32(&bstream, 8u); // bx[7:0]
+            b[0] |= read_reversed(&bstream, 2u) << 10u; // bx[10:11]
+            mode = 12u;
+        }
+        // Mode 14
+        case 15u: {
+            // Partition indices: 63 bits
+            // Partition: 0 bits
+            // Color Endpoints: 60 bits (16.4, 16.4, 16.4)
+            r[0] |= read_i32(&bstream, 10u); // rw[9:0]
+            g[0] |= read_i32(&bstream, 10u); // gw[9:0]
+            b[0] |= read_i32(&bstream, 10u); // bw[9:0]
+            r[1] |= read_i32(&bstream, 4u); // rx[3:0]
+            r[0] |= read_reversed(&bstream, 6u) << 10u; // rw[10:15]
+            g[1] |= read_i32(&bstream, 4u); // gx[3:0]
+            g[0] |= read_reversed(&bstream, 6u) << 10u; // gw[10:15]
+            b[1] |= read_i32(&bstream, 4u); // bx[3:0]
+            b[0] |= read_reversed(&bstream, 6u) << 10u; // bw[10:15]
+            mode = 13u;
+        }
+        default: {
+            // Modes 10011, 10111, 11011, and 11111 (not shown) are reserved.
+            // Do not use these in your encoder. If the hardware is passed blocks
+            // with one of these modes specified, the resulting decompressed block
+            // must contain all zeroes in all channels except for the alpha channel.
+            for (var i = 0u; i < 16u; i++) {
+                texels[i] = vec4<f32>(0.0, 0.0, 0.0, uniforms.fill.a);
+            }
+            return;
+        }
+    }
+
+    let num_endpoints = select(4u, 2u, mode >= 10u);
+
+    let actual_bits0_mode = get_actual_bits_count(0u, mode);
+
+    // Mode 11 (like Mode 10) does not use delta compression,
+    // and instead stores both color endpoints explicitly.
+    if (mode != 9u && mode != 10u) {
+        for (var i = 1u; i < num_endpoints; i++) {
+            r[i] = transform_inverse(extend_sign(r[i], get_actual_bits_count(1u, mode)), r[0], actual_bits0_mode);
+            g[i] = transform_inverse(extend_sign(g[i], get_actual_bits_count(2u, mode)), g[0], actual_bits0_mode);
+            b[i] = transform_inverse(extend_sign(b[i], get_actual_bits_count(3u, mode)), b[0], actual_bits0_mode);
+        }
+    }
+
+    for (var i = 0u; i < num_endpoints; i++) {
+        r[i] = unquantize(r[i], actual_bits0_mode);
+        g[i] = unquantize(g[i], actual_bits0_mode);
+        b[i] = unquantize(b[i], actual_bits0_mode);
+    }
+
+    let index_bits = select(3u, 4u, mode >= 10u);
+    let pattern = select(get_partition(part_id), 0u, mode >= 10u);
+    let anchor = select(get_anchor(part_id), 0u, mode >= 10u);
+
+    for (var i = 0u; i < 16u; i++) {
+        // fix-up index is specified with one less bit
+        // The fix-up index for subset 0 is always index 0
+        let is_anchor = i == 0u || (num_endpoints == 4u && i == anchor);
+        let index = read_bits(&bstream, select(index_bits, index_bits - 1u, is_anchor));
+        let weight = get_weight(index_bits, index);
+
+        let ep_i = ((pattern >> (i * 2u)) & 0x01u) * 2u;
+
+        texels[i] = vec4<f32>(
+            finish_unquantize(interpolate(r[ep_i], r[ep_i + 1u], weight)),
+            finish_unquantize(interpolate(g[ep_i], g[ep_i + 1u], weight)),
+            finish_unquantize(interpolate(b[ep_i], b[ep_i + 1u], weight)),
+            uniforms.fill.a,
+        );
+    }
+}
+
+@compute
+@workgroup_size(8, 8)
+fn decompress_bc6h_rgba8(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    if (is_outside(global_id.x, global_id.y)) {
+        return;
+    }
+
+    decode_bc6h(global_id.x, global_id.y);
+    store_rgba8(global_id.x, global_id.y);
+}
+
+@compute
+@workgroup_size(8, 8)
+fn decompress_bc6h_rgba16f(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    if (is_outside(global_id.x, global_id.y)) {
+        return;
+    }
+
+    decode_bc6h(global_id.x, global_id.y);
+    store_rgba16f(global_id.x, global_id.y);
+}
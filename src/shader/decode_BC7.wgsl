@@ -0,0 +1,369 @@
+// WGSL port of the BC7 block decoder of "bcdec.h - v0.98" (https://github.com/iOrange/bcdec).
+//
+// bcdec is free and unencumbered software released into the public domain. The CPU decoder in
+// `src/decode/block.rs` is a port of the same code and both must produce the same values.
+
+struct Uniforms {
+    width: u32,
+    height: u32,
+    blocks_offset: u32,
+    padding: u32,
+    fill: vec4<f32>,
+}
+
+struct BitStream {
+    data: array<u32, 4>,
+    position: u32,
+}
+
+@group(0) @binding(0) var<storage, read> block_buffer: array<u32>;
+@group(0) @binding(1) var output_rgba8: texture_storage_2d<rgba8unorm, write>;
+@group(0) @binding(2) var<uniform> uniforms: Uniforms;
+@group(0) @binding(3) var output_rgba16f: texture_storage_2d<rgba16float, write>;
+
+var<private> texels: array<vec4<f32>, 16>;
+
+fn is_outside(xx: u32, yy: u32) -> bool {
+    let block_width = (uniforms.width + 3u) / 4u;
+    let block_height = (uniforms.height + 3u) / 4u;
+    return xx >= block_width || yy >= block_height;
+}
+
+fn store_rgba8(xx: u32, yy: u32) {
+    for (var y = 0u; y < 4u; y++) {
+        for (var x = 0u; x < 4u; x++) {
+            let pixel = vec2<u32>(xx * 4u + x, yy * 4u + y);
+            if (pixel.x < uniforms.width && pixel.y < uniforms.height) {
+                textureStore(output_rgba8, pixel, texels[y * 4u + x]);
+            }
+        }
+    }
+}
+
+fn store_rgba16f(xx: u32, yy: u32) {
+    for (var y = 0u; y < 4u; y++) {
+        for (var x = 0u; x < 4u; x++) {
+            let pixel = vec2<u32>(xx * 4u + x, yy * 4u + y);
+            if (pixel.x < uniforms.width && pixel.y < uniforms.height) {
+                textureStore(output_rgba16f, pixel, texels[y * 4u + x]);
+            }
+        }
+    }
+}
+
+fn read_bits(stream: ptr<function, BitStream>, count: u32) -> u32 {
+    let word = (*stream).position / 32u;
+    let offset = (*stream).position % 32u;
+
+    var bits = (*stream).data[word] >> offset;
+    if (offset + count > 32u) {
+        bits |= (*stream).data[word + 1u] << (32u - offset);
+    }
+
+    (*stream).position += count;
+
+    return extractBits(bits, 0u, count);
+}
+
+// Returns the subset of every pixel packed into 2 bits.
+fn get_pattern(num_partitions: u32, part_id: u32) -> u32 {
+    const pattern_table_2 = array<u32, 64>(
+        0x50505050u, 0x40404040u, 0x54545454u, 0x54505040u, 0x50404000u, 0x55545450u, 0x55545040u, 0x54504000u,
+        0x50400000u, 0x55555450u, 0x55544000u, 0x54400000u, 0x55555440u, 0x55550000u, 0x55555500u, 0x55000000u,
+        0x55150100u, 0x00004054u, 0x15010000u, 0x00405054u, 0x00004050u, 0x15050100u, 0x05010000u, 0x40505054u,
+        0x00404050u, 0x05010100u, 0x14141414u, 0x05141450u, 0x01155440u, 0x00555500u, 0x15014054u, 0x05414150u,
+        0x44444444u, 0x55005500u, 0x11441144u, 0x05055050u, 0x05500550u, 0x11114444u, 0x41144114u, 0x44111144u,
+        0x15055054u, 0x01055040u, 0x05041050u, 0x05455150u, 0x14414114u, 0x50050550u, 0x41411414u, 0x00141400u,
+        0x00041504u, 0x00105410u, 0x10541000u, 0x04150400u, 0x50410514u, 0x41051450u, 0x05415014u, 0x14054150u,
+        0x41050514u, 0x41505014u, 0x40011554u, 0x54150140u, 0x50505500u, 0x00555050u, 0x15151010u, 0x54540404u,
+    );
+
+    const pattern_table_3 = array<u32, 64>(
+        0xAA685050u, 0x6A5A5040u, 0x5A5A4200u, 0x5450A0A8u, 0xA5A50000u, 0xA0A05050u, 0x5555A0A0u, 0x5A5A5050u,
+        0xAA550000u, 0xAA555500u, 0xAAAA5500u, 0x90909090u, 0x94949494u, 0xA4A4A4A4u, 0xA9A59450u, 0x2A0A4250u,
+        0xA5945040u, 0x0A425054u, 0xA5A5A500u, 0x55A0A0A0u, 0xA8A85454u, 0x6A6A4040u, 0xA4A45000u, 0x1A1A0500u,
+        0x0050A4A4u, 0xAAA59090u, 0x14696914u, 0x69691400u, 0xA08585A0u, 0xAA821414u, 0x50A4A450u, 0x6A5A0200u,
+        0xA9A58000u, 0x5090A0A8u, 0xA8A09050u, 0x24242424u, 0x00AA5500u, 0x24924924u, 0x24499224u, 0x50A50A50u,
+        0x500AA550u, 0xAAAA4444u, 0x66660000u, 0xA5A0A5A0u, 0x50A050A0u, 0x69286928u, 0x44AAAA44u, 0x66666600u,
+        0xAA444444u, 0x54A854A8u, 0x95809580u, 0x96969600u, 0xA85454A8u, 0x80959580u, 0xAA141414u, 0x96960000u,
+        0xAAAA1414u, 0xA05050A0u, 0xA0A5A5A0u, 0x96000000u, 0x40804080u, 0xA9A8A9A8u, 0xAAAAAA44u, 0x2A4A5254u,
+    );
+
+    switch (num_partitions) {
+        case 2u: {
+            return pattern_table_2[part_id];
+        }
+        case 3u: {
+            return pattern_table_3[part_id];
+        }
+        default: {
+            return 0u;
+        }
+    }
+}
+
+// Returns the pixel indices of the fix-up indices of the second and third subset.
+fn get_anchors(num_partitions: u32, part_id: u32) -> vec2<u32> {
+    const anchor_table_2 = array<u32, 64>(
+        15u, 15u, 15u, 15u, 15u, 15u, 15u, 15u, 15u, 15u, 15u, 15u, 15u, 15u, 15u, 15u,
+        15u, 2u, 8u, 2u, 2u, 8u, 8u, 15u, 2u, 8u, 2u, 2u, 8u, 8u, 2u, 2u,
+        15u, 15u, 6u, 8u, 2u, 8u, 15u, 15u, 2u, 8u, 2u, 2u, 2u, 15u, 15u, 6u,
+        6u, 2u, 6u, 8u, 15u, 15u, 2u, 2u, 15u, 15u, 15u, 15u, 15u, 2u, 2u, 15u,
+    );
+
+    const anchor_table_3_second = array<u32, 64>(
+        3u, 3u, 15u, 15u, 8u, 3u, 15u, 15u, 8u, 8u, 6u, 6u, 6u, 5u, 3u, 3u,
+        3u, 3u, 8u, 15u, 3u, 3u, 6u, 10u, 5u, 8u, 8u, 6u, 8u, 5u, 15u, 15u,
+        8u, 15u, 3u, 5u, 6u, 10u, 8u, 15u, 15u, 3u, 15u, 5u, 15u, 15u, 15u, 15u,
+        3u, 15u, 5u, 5u, 5u, 8u, 5u, 10u, 5u, 10u, 8u, 13u, 15u, 12u, 3u, 3u,
+    );
+
+    const anchor_table_3_third = array<u32, 64>(
+        15u, 8u, 8u, 3u, 15u, 15u, 3u, 8u, 15u, 15u, 15u, 15u, 15u, 15u, 15u, 8u,
+        15u, 8u, 15u, 3u, 15u, 8u, 15u, 8u, 3u, 15u, 6u, 10u, 15u, 15u, 10u, 8u,
+        15u, 3u, 15u, 10u, 10u, 8u, 9u, 10u, 6u, 15u, 8u, 15u, 3u, 6u, 6u, 8u,
+        15u, 3u, 15u, 15u, 15u, 15u, 15u, 15u, 15u, 15u, 15u, 15u, 3u, 15u, 15u, 8u,
+    );
+
+    switch (num_partitions) {
+        case 2u: {
+            return vec2<u32>(anchor_table_2[part_id], 0u);
+        }
+        case 3u: {
+            return vec2<u32>(anchor_table_3_second[part_id], anchor_table_3_third[part_id]);
+        }
+        default: {
+            return vec2<u32>(0u);
+        }
+    }
+}
+
+fn get_weight(index_bits: u32, index: u32) -> i32 {
+    switch (index_bits) {
+        case 2u: {
+            const table = array<i32, 4>(0, 21, 43, 64);
+            return table[index];
+        }
+        case 3u: {
+            const table = array<i32, 8>(0, 9, 18, 27, 37, 46, 55, 64);
+            return table[index];
+        }
+        default: {
+            const table = array<i32, 16>(0, 4, 9, 13, 17, 21, 26, 30, 34, 38, 43, 47, 51, 55, 60, 64);
+            return table[index];
+        }
+    }
+}
+
+fn interpolate(a: vec4<i32>, b: vec4<i32>, weight: i32) -> vec4<i32> {
+    return (a * (64 - weight) + b * weight + 32) >> vec4<u32>(6u);
+}
+
+fn decode_bc7(xx: u32, yy: u32) {
+    const actual_bits_count = array<array<u32, 8>, 2>(
+        array<u32, 8>(4u, 6u, 5u, 7u, 5u, 7u, 7u, 5u), // RGBA
+        array<u32, 8>(0u, 0u, 0u, 0u, 6u, 8u, 7u, 5u), // Alpha
+    );
+
+    const MODE_HAS_P_BITS: u32 = 0xCBu;
+
+    let block_width = (uniforms.width + 3u) / 4u;
+    let start = uniforms.blocks_offset + (yy * block_width + xx) * 4u;
+
+    var bstream: BitStream;
+    bstream.data = array<u32, 4>(
+        block_buffer[start],
+        block_buffer[start + 1u],
+        block_buffer[start + 2u],
+        block_buffer[start + 3u],
+    );
+    bstream.position = 0u;
+
+    // Find mode
+    var mode = 0u;
+    while (mode < 8u && read_bits(&bstream, 1u) == 0u) {
+        mode += 1u;
+    }
+
+    // Unexpected mode, clear the block (transparent black)
+    if (mode >= 8u) {
+        for (var i = 0u; i < 16u; i++) {
+            texels[i] = vec4<f32>(0.0);
+        }
+        return;
+    }
+
+    var part_id = 0u;
+    var num_partitions = 1u;
+    var rotation = 0u;
+    var index_selection_bit = 0u;
+
+    if (mode == 0u || mode == 1u || mode == 2u || mode == 3u || mode == 7u) {
+        num_partitions = select(2u, 3u, mode == 0u || mode == 2u);
+        part_id = read_bits(&bstream, select(6u, 4u, mode == 0u));
+    }
+
+    let num_endpoints = num_partitions * 2u;
+
+    if (mode == 4u || mode == 5u) {
+        rotation = read_bits(&bstream, 2u);
+        if (mode == 4u) {
+            index_selection_bit = read_bits(&bstream, 1u);
+        }
+    }
+
+    // Extract endpoints
+    var endpoints: array<vec4<i32>, 6>;
+
+    // RGB
+    for (var i = 0u; i < 3u; i++) {
+        for (var j = 0u; j < num_endpoints; j++) {
+            endpoints[j][i] = i32(read_bits(&bstream, actual_bits_count[0][mode]));
+        }
+    }
+
+    // Alpha (if any)
+    if (actual_bits_count[1][mode] > 0u) {
+        for (var j = 0u; j < num_endpoints; j++) {
+            endpoints[j][3] = i32(read_bits(&bstream, actual_bits_count[1][mode]));
+        }
+    }
+
+    // Fully decode endpoints
+    // Handle modes that have P-bits
+    if (mode == 0u || mode == 1u || mode == 3u || mode == 6u || mode == 7u) {
+        // Component-wise left-shift
+        for (var j = 0u; j < num_endpoints; j++) {
+            endpoints[j] <<= vec4<u32>(1u);
+        }
+
+        // If P-bit is shared
+        if (mode == 1u) {
+            let i = i32(read_bits(&bstream, 1u));
+            let j = i32(read_bits(&bstream, 1u));
+
+            // RGB component-wise insert pbits
+            endpoints[0] |= vec4<i32>(i, i, i, 0);
+            endpoints[1] |= vec4<i32>(i, i, i, 0);
+            endpoints[2] |= vec4<i32>(j, j, j, 0);
+            endpoints[3] |= vec4<i32>(j, j, j, 0);
+        } else if ((MODE_HAS_P_BITS & (1u << mode)) != 0u) {
+            // Unique P-bit per endpoint
+            for (var j = 0u; j < num_endpoints; j++) {
+                endpoints[j] |= vec4<i32>(i32(read_bits(&bstream, 1u)));
+            }
+        }
+    }
+
+    // Fully decode endpoints
+    // Component-wise precision adjustment
+    let p_bit = (MODE_HAS_P_BITS >> mode) & 1u;
+    let color_bits = actual_bits_count[0][mode] + p_bit;
+    let alpha_bits = actual_bits_count[1][mode] + p_bit;
+
+    for (var i = 0u; i < num_endpoints; i++) {
+        // Left shift endpoint components so that their MSB lies in bit 7
+        // and replicate each component's MSB into the LSBs revealed by the left-shift operation
+        let bits = vec4<u32>(color_bits, color_bits, color_bits, alpha_bits);
+        endpoints[i] <<= vec4<u32>(8u) - bits;
+        endpoints[i] |= endpoints[i] >> bits;
+    }
+
+    // If this mode does not explicitly define the alpha component, set alpha to 255 (1.0)
+    if (actual_bits_count[1][mode] == 0u) {
+        for (var j = 0u; j < num_endpoints; j++) {
+            endpoints[j][3] = 0xFF;
+        }
+    }
+
+    // Determine weights tables
+    var index_bits = 2u;
+    if (mode == 0u || mode == 1u) {
+        index_bits = 3u;
+    } else if (mode == 6u) {
+        index_bits = 4u;
+    }
+
+    var index_bits2 = 0u;
+    if (mode == 4u) {
+        index_bits2 = 3u;
+    } else if (mode == 5u) {
+        index_bits2 = 2u;
+    }
+
+    let pattern = get_pattern(num_partitions, part_id);
+    let anchors = get_anchors(num_partitions, part_id);
+
+    // Collect indices in two passes
+    // Pass #1: collecting color indices
+    var indices: array<u32, 16>;
+    for (var i = 0u; i < 16u; i++) {
+        // Fix-up index is specified with one less bit
+        // The fix-up index for subset 0 is always index 0
+        let is_anchor = i == 0u || (num_partitions > 1u && i == anchors.x) || (num_partitions > 2u && i == anchors.y);
+        indices[i] = read_bits(&bstream, select(index_bits, index_bits - 1u, is_anchor));
+    }
+
+    // Pass #2: reading alpha indices (if any) and interpolating & rotating
+    for (var i = 0u; i < 16u; i++) {
+        let partition_set = (pattern >> (i * 2u)) & 0x03u;
+        let endpoint0 = endpoints[partition_set * 2u];
+        let endpoint1 = endpoints[partition_set * 2u + 1u];
+
+        let weight = get_weight(index_bits, indices[i]);
+        var color = interpolate(endpoint0, endpoint1, weight);
+
+        if (index_bits2 != 0u) {
+            let index2 = read_bits(&bstream, select(index_bits2, index_bits2 - 1u, i == 0u));
+            let weight2 = get_weight(index_bits2, index2);
+            let color2 = interpolate(endpoint0, endpoint1, weight2);
+
+            if (index_selection_bit == 0u) {
+                color.a = color2.a;
+            } else {
+                color = vec4<i32>(color2.rgb, color.a);
+            }
+        }
+
+        // Handle rotation
+        switch (rotation) {
+            case 1u: {
+                // 01 – Block format is Scalar(R) Vector(AGB) - swap A and R
+                color = color.agbr;
+            }
+            case 2u: {
+                // 10 – Block format is Scalar(G) Vector(RAB) - swap A and G
+                color = color.rabg;
+            }
+            case 3u: {
+                // 11 - Block format is Scalar(B) Vector(RGA) - swap A and B
+                color = color.rgab;
+            }
+            default: {}
+        }
+
+        texels[i] = vec4<f32>(color) / 255.0;
+    }
+}
+
+@compute
+@workgroup_size(8, 8)
+fn decompress_bc7_rgba8(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    if (is_outside(global_id.x, global_id.y)) {
+        return;
+    }
+
+    decode_bc7(global_id.x, global_id.y);
+    store_rgba8(global_id.x, global_id.y);
+}
+
+@compute
+@workgroup_size(8, 8)
+fn decompress_bc7_rgba16f(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    if (is_outside(global_id.x, global_id.y)) {
+        return;
+    }
+
+    decode_bc7(global_id.x, global_id.y);
+    store_rgba16f(global_id.x, global_id.y);
+}
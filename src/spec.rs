@@ -0,0 +1,582 @@
+//! Textual specification of compression variants and their settings.
+//!
+//! A specification has the form `variant[:preset][{field=value,...}]`, for example `bc1`,
+//! `bc7:alpha_slow`, `bc6h{refine=2}` or `bc7:opaque_basic{channels=4,mode45_channel0=3}`.
+
+use std::{fmt, str::FromStr};
+
+#[cfg(feature = "astc")]
+use crate::ASTCBlockSize;
+use crate::CompressionVariant;
+#[cfg(feature = "bc6h")]
+use crate::{BC6HSettings, BC6HSettingsBuilder};
+#[cfg(feature = "bc7")]
+use crate::{BC7Settings, BC7SettingsBuilder};
+
+/// The error of parsing a compression variant or its settings from a specification.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParseVariantError {
+    message: String,
+}
+
+impl ParseVariantError {
+    fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for ParseVariantError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for ParseVariantError {}
+
+/// A named settings preset.
+#[cfg(any(feature = "bc6h", feature = "bc7"))]
+type Preset<T> = (&'static str, fn() -> T);
+
+/// The presets of BC6H, from fastest to slowest. `very_slow` is used without a preset.
+#[cfg(feature = "bc6h")]
+const BC6H_PRESETS: &[Preset<BC6HSettings>] = &[
+    ("very_fast", BC6HSettings::very_fast),
+    ("fast", BC6HSettings::fast),
+    ("basic", BC6HSettings::basic),
+    ("slow", BC6HSettings::slow),
+    ("very_slow", BC6HSettings::very_slow),
+];
+
+#[cfg(feature = "bc6h")]
+const BC6H_DEFAULT_PRESET: &str = "very_slow";
+
+/// The presets of BC7, from fastest to slowest. `alpha_slow` is used without a preset.
+#[cfg(feature = "bc7")]
+const BC7_PRESETS: &[Preset<BC7Settings>] = &[
+    ("opaque_ultra_fast", BC7Settings::opaque_ultra_fast),
+    ("opaque_very_fast", BC7Settings::opaque_very_fast),
+    ("opaque_fast", BC7Settings::opaque_fast),
+    ("opaque_basic", BC7Settings::opaque_basic),
+    ("opaque_slow", BC7Settings::opaque_slow),
+    ("alpha_ultra_fast", BC7Settings::alpha_ultrafast),
+    ("alpha_very_fast", BC7Settings::alpha_very_fast),
+    ("alpha_fast", BC7Settings::alpha_fast),
+    ("alpha_basic", BC7Settings::alpha_basic),
+    ("alpha_slow", BC7Settings::alpha_slow),
+];
+
+#[cfg(feature = "bc7")]
+const BC7_DEFAULT_PRESET: &str = "alpha_slow";
+
+/// Prints the variant as a specification, that parses back into the same variant and settings.
+///
+/// Settings are printed as the preset with the fewest differing fields, followed by the
+/// differing fields.
+impl fmt::Display for CompressionVariant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            #[cfg(feature = "bc6h")]
+            Self::BC6H(settings) => write!(f, "{}:{settings}", self.name()),
+            #[cfg(feature = "bc7")]
+            Self::BC7(settings) => write!(f, "{}:{settings}", self.name()),
+            #[cfg(feature = "astc")]
+            Self::ASTC(block_size) => {
+                let (width, height) = block_size.dimensions();
+                write!(f, "{}_{width}x{height}", self.name())
+            }
+            _ => f.write_str(self.name()),
+        }
+    }
+}
+
+/// Parses a specification of the form `variant[:preset][{field=value,...}]`.
+///
+/// BC6H uses the `very_slow` preset and BC7 the `alpha_slow` preset, if none is given. The
+/// settings are validated like the settings builders do.
+impl FromStr for CompressionVariant {
+    type Err = ParseVariantError;
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        let spec = spec.trim();
+        let (name, settings) = match spec.find([':', '{']) {
+            Some(index) if spec[index..].starts_with(':') => (&spec[..index], &spec[index + 1..]),
+            Some(index) => (&spec[..index], &spec[index..]),
+            None => (spec, ""),
+        };
+
+        let variant = match name {
+            "bc1" => Self::BC1,
+            "bc2" => Self::BC2,
+            "bc3" => Self::BC3,
+            "bc4" => Self::BC4,
+            "bc5" => Self::BC5,
+            #[cfg(feature = "bc6h")]
+            "bc6h" => return Ok(Self::BC6H(settings.parse()?)),
+            #[cfg(feature = "bc7")]
+            "bc7" => return Ok(Self::BC7(settings.parse()?)),
+            #[cfg(feature = "etc2")]
+            "etc2_rgb8" => Self::ETC2RGB8,
+            #[cfg(feature = "etc2")]
+            "etc2_rgb8a1" => Self::ETC2RGB8A1,
+            #[cfg(feature = "etc2")]
+            "etc2_rgba8" => Self::ETC2RGBA8,
+            #[cfg(feature = "etc2")]
+            "eac_r11" => Self::EACR11,
+            #[cfg(feature = "etc2")]
+            "eac_rg11" => Self::EACRG11,
+            #[cfg(feature = "astc")]
+            _ if name.starts_with("astc_") => ASTCBlockSize::ALL
+                .iter()
+                .map(|&block_size| Self::ASTC(block_size))
+                .find(|variant| variant.to_string() == name)
+                .ok_or_else(|| {
+                    ParseVariantError::new(format!("unknown ASTC block footprint '{name}'"))
+                })?,
+            _ => {
+                return Err(ParseVariantError::new(format!(
+                    "unknown compression variant '{name}'"
+                )))
+            }
+        };
+
+        if !settings.is_empty() {
+            return Err(ParseVariantError::new(format!(
+                "compression variant '{name}' has no settings"
+            )));
+        }
+
+        Ok(variant)
+    }
+}
+
+/// Prints the settings as `preset[{field=value,...}]`.
+#[cfg(feature = "bc6h")]
+impl fmt::Display for BC6HSettings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_settings(f, self, BC6H_PRESETS, bc6h_fields)
+    }
+}
+
+/// Parses settings of the form `[preset][{field=value,...}]`.
+///
+/// The fields are named like the methods of [`BC6HSettingsBuilder`]. `refine` sets the
+/// refinement iterations of all modes.
+#[cfg(feature = "bc6h")]
+impl FromStr for BC6HSettings {
+    type Err = ParseVariantError;
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        let (preset, fields) = split_settings(spec)?;
+        let mut builder =
+            BC6HSettingsBuilder::new(find_preset(BC6H_PRESETS, preset, BC6H_DEFAULT_PRESET)?);
+
+        for (field, value) in fields {
+            builder = match field {
+                "slow_mode" => builder.slow_mode(parse_bool(field, value)?),
+                "fast_mode" => builder.fast_mode(parse_bool(field, value)?),
+                "refine_iterations_1p" => builder.refine_iterations_1p(parse_u32(field, value)?),
+                "refine_iterations_2p" => builder.refine_iterations_2p(parse_u32(field, value)?),
+                "fast_skip_threshold" => builder.fast_skip_threshold(parse_u32(field, value)?),
+                "refine" => {
+                    let iterations = parse_u32(field, value)?;
+                    builder
+                        .refine_iterations_1p(iterations)
+                        .refine_iterations_2p(iterations)
+                }
+                _ => return Err(unknown_field("BC6H", field)),
+            };
+        }
+
+        builder
+            .build()
+            .map_err(|error| ParseVariantError::new(error.to_string()))
+    }
+}
+
+#[cfg(feature = "bc6h")]
+fn bc6h_fields(settings: &BC6HSettings) -> Vec<(&'static str, String)> {
+    vec![
+        ("slow_mode", (settings.slow_mode != 0).to_string()),
+        ("fast_mode", (settings.fast_mode != 0).to_string()),
+        (
+            "refine_iterations_1p",
+            settings.refine_iterations_1p.to_string(),
+        ),
+        (
+            "refine_iterations_2p",
+            settings.refine_iterations_2p.to_string(),
+        ),
+        (
+            "fast_skip_threshold",
+            settings.fast_skip_threshold.to_string(),
+        ),
+    ]
+}
+
+/// Prints the settings as `preset[{field=value,...}]`.
+#[cfg(feature = "bc7")]
+impl fmt::Display for BC7Settings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_settings(f, self, BC7_PRESETS, bc7_fields)
+    }
+}
+
+/// Parses settings of the form `[preset][{field=value,...}]`.
+///
+/// The fields are named like the methods of [`BC7SettingsBuilder`]. `refine_iterations` and
+/// `mode_selection` take lists like `[2,2,2,2,2,2,2,2]` and `[true,false,true,true]`. `refine`
+/// sets the refinement iterations of all modes.
+#[cfg(feature = "bc7")]
+impl FromStr for BC7Settings {
+    type Err = ParseVariantError;
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        let (preset, fields) = split_settings(spec)?;
+        let mut builder =
+            BC7SettingsBuilder::new(find_preset(BC7_PRESETS, preset, BC7_DEFAULT_PRESET)?);
+
+        for (field, value) in fields {
+            builder = match field {
+                "refine_iterations" => {
+                    let iterations: [u32; 8] = parse_list(field, value, parse_u32)?;
+                    (0..8).fold(builder, |builder, mode| {
+                        builder.refine_iterations(mode, iterations[mode])
+                    })
+                }
+                "mode_selection" => builder.mode_selection(parse_list(field, value, parse_bool)?),
+                "skip_mode2" => builder.skip_mode2(parse_bool(field, value)?),
+                "fast_skip_threshold_mode1" => {
+                    builder.fast_skip_threshold_mode1(parse_u32(field, value)?)
+                }
+                "fast_skip_threshold_mode3" => {
+                    builder.fast_skip_threshold_mode3(parse_u32(field, value)?)
+                }
+                "fast_skip_threshold_mode7" => {
+                    builder.fast_skip_threshold_mode7(parse_u32(field, value)?)
+                }
+                "mode45_channel0" => builder.mode45_channel0(parse_u32(field, value)?),
+                "refine_iterations_channel" => {
+                    builder.refine_iterations_channel(parse_u32(field, value)?)
+                }
+                "channels" => builder.channels(parse_u32(field, value)?),
+                "refine" => {
+                    let iterations = parse_u32(field, value)?;
+                    (0..8).fold(builder, |builder, mode| {
+                        builder.refine_iterations(mode, iterations)
+                    })
+                }
+                _ => return Err(unknown_field("BC7", field)),
+            };
+        }
+
+        builder
+            .build()
+            .map_err(|error| ParseVariantError::new(error.to_string()))
+    }
+}
+
+#[cfg(feature = "bc7")]
+fn bc7_fields(settings: &BC7Settings) -> Vec<(&'static str, String)> {
+    let list = |values: &[String]| format!("[{}]", values.join(","));
+
+    vec![
+        (
+            "refine_iterations",
+            list(&settings.refine_iterations.map(|value| value.to_string())),
+        ),
+        (
+            "mode_selection",
+            list(
+                &settings
+                    .mode_selection
+                    .map(|value| (value != 0).to_string()),
+            ),
+        ),
+        ("skip_mode2", (settings.skip_mode2 != 0).to_string()),
+        (
+            "fast_skip_threshold_mode1",
+            settings.fast_skip_threshold_mode1.to_string(),
+        ),
+        (
+            "fast_skip_threshold_mode3",
+            settings.fast_skip_threshold_mode3.to_string(),
+        ),
+        (
+            "fast_skip_threshold_mode7",
+            settings.fast_skip_threshold_mode7.to_string(),
+        ),
+        ("mode45_channel0", settings.mode45_channel0.to_string()),
+        (
+            "refine_iterations_channel",
+            settings.refine_iterations_channel.to_string(),
+        ),
+        ("channels", settings.channels.to_string()),
+    ]
+}
+
+/// Writes the name of the closest preset and the fields, that differ from it.
+#[cfg(any(feature = "bc6h", feature = "bc7"))]
+fn write_settings<T>(
+    f: &mut fmt::Formatter<'_>,
+    settings: &T,
+    presets: &[Preset<T>],
+    fields: fn(&T) -> Vec<(&'static str, String)>,
+) -> fmt::Result {
+    let settings_fields = fields(settings);
+
+    let (name, differences) = presets
+        .iter()
+        .map(|(name, preset)| {
+            let differences: Vec<String> = settings_fields
+                .iter()
+                .zip(fields(&preset()))
+                .filter(|((_, value), (_, preset_value))| value != preset_value)
+                .map(|((field, value), _)| format!("{field}={value}"))
+                .collect();
+            (name, differences)
+        })
+        .min_by_key(|(_, differences)| differences.len())
+        .expect("presets are not empty");
+
+    f.write_str(name)?;
+    if !differences.is_empty() {
+        write!(f, "{{{}}}", differences.join(","))?;
+    }
+
+    Ok(())
+}
+
+/// The field and value pairs of a settings specification.
+#[cfg(any(feature = "bc6h", feature = "bc7"))]
+type Fields<'a> = Vec<(&'a str, &'a str)>;
+
+/// Splits settings of the form `[preset][{field=value,...}]` into the preset and the fields.
+#[cfg(any(feature = "bc6h", feature = "bc7"))]
+fn split_settings(spec: &str) -> Result<(&str, Fields<'_>), ParseVariantError> {
+    let spec = spec.trim();
+
+    let Some(start) = spec.find('{') else {
+        return Ok((spec, Vec::new()));
+    };
+    let body = spec[start + 1..]
+        .strip_suffix('}')
+        .ok_or_else(|| ParseVariantError::new(format!("settings '{spec}' miss a closing '}}'")))?;
+
+    let mut fields = Vec::new();
+    for assignment in split_list(body) {
+        let (field, value) = assignment.split_once('=').ok_or_else(|| {
+            ParseVariantError::new(format!("'{assignment}' must have the form field=value"))
+        })?;
+        fields.push((field.trim(), value.trim()));
+    }
+
+    Ok((spec[..start].trim(), fields))
+}
+
+/// Splits at the commas, that are not inside of brackets. Returns nothing for an empty list.
+#[cfg(any(feature = "bc6h", feature = "bc7"))]
+fn split_list(list: &str) -> Vec<&str> {
+    if list.trim().is_empty() {
+        return Vec::new();
+    }
+
+    let mut items = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+
+    for (index, char) in list.char_indices() {
+        match char {
+            '[' => depth += 1,
+            ']' => depth -= 1,
+            ',' if depth == 0 => {
+                items.push(list[start..index].trim());
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    items.push(list[start..].trim());
+
+    items
+}
+
+#[cfg(any(feature = "bc6h", feature = "bc7"))]
+fn find_preset<T>(
+    presets: &[Preset<T>],
+    name: &str,
+    default: &str,
+) -> Result<T, ParseVariantError> {
+    let name = if name.is_empty() { default } else { name };
+
+    presets
+        .iter()
+        .find(|(preset, _)| *preset == name)
+        .map(|(_, settings)| settings())
+        .ok_or_else(|| ParseVariantError::new(format!("unknown settings preset '{name}'")))
+}
+
+#[cfg(any(feature = "bc6h", feature = "bc7"))]
+fn unknown_field(variant: &str, field: &str) -> ParseVariantError {
+    ParseVariantError::new(format!("unknown {variant} settings field '{field}'"))
+}
+
+#[cfg(any(feature = "bc6h", feature = "bc7"))]
+fn parse_u32(field: &str, value: &str) -> Result<u32, ParseVariantError> {
+    value.parse().map_err(|_| {
+        ParseVariantError::new(format!("{field} needs a positive integer, not '{value}'"))
+    })
+}
+
+#[cfg(any(feature = "bc6h", feature = "bc7"))]
+fn parse_bool(field: &str, value: &str) -> Result<bool, ParseVariantError> {
+    match value {
+        "true" => Ok(true),
+        "false" => Ok(false),
+        _ => Err(ParseVariantError::new(format!(
+            "{field} needs true or false, not '{value}'"
+        ))),
+    }
+}
+
+#[cfg(feature = "bc7")]
+fn parse_list<T: Copy + Default, const N: usize>(
+    field: &str,
+    value: &str,
+    parse: fn(&str, &str) -> Result<T, ParseVariantError>,
+) -> Result<[T; N], ParseVariantError> {
+    let items = value
+        .strip_prefix('[')
+        .and_then(|value| value.strip_suffix(']'))
+        .map(split_list)
+        .filter(|items| items.len() == N)
+        .ok_or_else(|| {
+            ParseVariantError::new(format!("{field} needs a list of {N} values, not '{value}'"))
+        })?;
+
+    let mut list = [T::default(); N];
+    for (entry, item) in list.iter_mut().zip(items) {
+        *entry = parse(field, item)?;
+    }
+
+    Ok(list)
+}
+
+/// Serializes the types as their specification string.
+#[cfg(feature = "serde")]
+macro_rules! impl_serde_with_spec {
+    ($type:ty) => {
+        #[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+        impl serde::Serialize for $type {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.collect_str(self)
+            }
+        }
+
+        #[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+        impl<'de> serde::Deserialize<'de> for $type {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let spec = String::deserialize(deserializer)?;
+                spec.parse().map_err(serde::de::Error::custom)
+            }
+        }
+    };
+}
+
+#[cfg(feature = "serde")]
+impl_serde_with_spec!(CompressionVariant);
+#[cfg(all(feature = "serde", feature = "bc6h"))]
+impl_serde_with_spec!(BC6HSettings);
+#[cfg(all(feature = "serde", feature = "bc7"))]
+impl_serde_with_spec!(BC7Settings);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn variants_round_trip() {
+        let mut variants = vec![
+            CompressionVariant::BC1,
+            CompressionVariant::BC2,
+            CompressionVariant::BC3,
+            CompressionVariant::BC4,
+            CompressionVariant::BC5,
+        ];
+        #[cfg(feature = "bc6h")]
+        variants.extend(
+            BC6H_PRESETS
+                .iter()
+                .map(|(_, preset)| CompressionVariant::BC6H(preset())),
+        );
+        #[cfg(feature = "bc7")]
+        variants.extend(
+            BC7_PRESETS
+                .iter()
+                .map(|(_, preset)| CompressionVariant::BC7(preset())),
+        );
+        #[cfg(feature = "etc2")]
+        variants.extend([
+            CompressionVariant::ETC2RGB8,
+            CompressionVariant::ETC2RGB8A1,
+            CompressionVariant::ETC2RGBA8,
+            CompressionVariant::EACR11,
+            CompressionVariant::EACRG11,
+        ]);
+        #[cfg(feature = "astc")]
+        variants.extend(ASTCBlockSize::ALL.map(CompressionVariant::ASTC));
+
+        for variant in variants {
+            let spec = variant.to_string();
+            let parsed: CompressionVariant = spec.parse().unwrap();
+            assert!(parsed.eq_with_settings(&variant), "{spec}");
+        }
+    }
+
+    #[cfg(feature = "bc7")]
+    #[test]
+    fn bc7_spec() {
+        assert_eq!(
+            CompressionVariant::BC7(BC7Settings::alpha_slow()).to_string(),
+            "bc7:alpha_slow"
+        );
+
+        let variant: CompressionVariant = "bc7:alpha_basic{refine_iterations_channel=4,refine=3}"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            variant.to_string(),
+            "bc7:alpha_basic{refine_iterations=[3,3,3,3,3,3,3,3],refine_iterations_channel=4}"
+        );
+
+        assert!("bc7:alpha_basic{channels=5}"
+            .parse::<CompressionVariant>()
+            .is_err());
+        assert!("bc7:alpha_basic{unknown=1}"
+            .parse::<CompressionVariant>()
+            .is_err());
+        assert!("bc7:unknown".parse::<CompressionVariant>().is_err());
+    }
+
+    #[cfg(feature = "bc6h")]
+    #[test]
+    fn bc6h_spec() {
+        let variant: CompressionVariant = "bc6h{refine=3}".parse().unwrap();
+        assert_eq!(
+            variant.to_string(),
+            "bc6h:very_slow{refine_iterations_1p=3,refine_iterations_2p=3}"
+        );
+        assert!(!variant.eq_with_settings(&CompressionVariant::BC6H(BC6HSettings::very_slow())));
+    }
+
+    #[test]
+    fn variants_without_settings() {
+        assert_eq!(
+            "bc1".parse::<CompressionVariant>(),
+            Ok(CompressionVariant::BC1)
+        );
+        assert!("bc1:basic".parse::<CompressionVariant>().is_err());
+        assert!("bc8".parse::<CompressionVariant>().is_err());
+    }
+}
@@ -0,0 +1,449 @@
+//! Builds the compressed pages of virtual textures.
+//!
+//! A virtual texture is split into square pages of a fixed size for every mip level. Each page
+//! has a border of texels around it, so it can be filtered without sampling the neighbouring
+//! pages, and is compressed independently into its own blocks.
+//!
+//! ```no_run
+//! # fn example(device: std::sync::Arc<wgpu::Device>, queue: std::sync::Arc<wgpu::Queue>) {
+//! use block_compression::{
+//!     virtual_texture::{BorderMode, PageBuilder},
+//!     CompressionVariant,
+//! };
+//!
+//! let rgba_data = vec![0u8; 1024 * 1024 * 4];
+//!
+//! let mut builder = PageBuilder::new(device, queue, CompressionVariant::BC1, 128, 4)
+//!     .border_mode(BorderMode::Neighbour);
+//! let levels = builder.build_rgba8(&rgba_data, 1024, 1024, 1024 * 4);
+//!
+//! for level in &levels {
+//!     for y in 0..level.pages_y {
+//!         for x in 0..level.pages_x {
+//!             let blocks = level.page(x, y);
+//!             // Write the page into the page file.
+//!         }
+//!     }
+//! }
+//! # }
+//! ```
+
+use std::sync::Arc;
+
+use wgpu::{
+    util::{BufferInitDescriptor, DeviceExt},
+    BufferDescriptor, BufferUsages, CommandEncoderDescriptor, ComputePassDescriptor, Device,
+    Maintain, MapMode, Queue,
+};
+
+use crate::{CompressionVariant, GpuBlockCompressor, SourceBufferFormat};
+
+/// Where the border texels of a page come from.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub enum BorderMode {
+    /// The border repeats the outermost texels of the page itself.
+    Replicate,
+    /// The border contains the texels of the neighbouring pages. Borders at the edges of the
+    /// image repeat the outermost texels of the image.
+    #[default]
+    Neighbour,
+}
+
+/// The compressed pages of a single mip level.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PageLevel {
+    /// Width of the mip level in pixels.
+    pub width: u32,
+    /// Height of the mip level in pixels.
+    pub height: u32,
+    /// Number of pages in a row.
+    pub pages_x: u32,
+    /// Number of rows of pages.
+    pub pages_y: u32,
+    /// Size of the blocks of a single page in bytes.
+    pub page_byte_size: usize,
+    /// The blocks of all pages in row-major page order.
+    pub data: Vec<u8>,
+}
+
+impl PageLevel {
+    /// Returns the blocks of the page at the given page coordinates.
+    ///
+    /// # Panics
+    /// - If the page coordinates are outside the mip level
+    pub fn page(&self, x: u32, y: u32) -> &[u8] {
+        assert!(
+            x < self.pages_x && y < self.pages_y,
+            "page is out of bounds"
+        );
+
+        let index = (y * self.pages_x + x) as usize;
+        &self.data[index * self.page_byte_size..][..self.page_byte_size]
+    }
+}
+
+/// Cuts images into pages with borders and compresses them in batches with a
+/// [`GpuBlockCompressor`].
+pub struct PageBuilder {
+    compressor: GpuBlockCompressor,
+    device: Arc<Device>,
+    queue: Arc<Queue>,
+    variant: CompressionVariant,
+    page_size: u32,
+    border: u32,
+    border_mode: BorderMode,
+    batch_size: u32,
+}
+
+impl PageBuilder {
+    /// Creates a page builder for pages with `page_size` texels and a border of `border` texels
+    /// on each side, so every compressed page covers `page_size + 2 * border` texels.
+    ///
+    /// # Panics
+    /// - If `page_size` is zero
+    /// - If `page_size + 2 * border` is not a multiple of 4 (except for ASTC, which supports any
+    ///   size)
+    pub fn new(
+        device: Arc<Device>,
+        queue: Arc<Queue>,
+        variant: CompressionVariant,
+        page_size: u32,
+        border: u32,
+    ) -> Self {
+        assert!(page_size > 0, "page size must not be zero");
+
+        let size = page_size + 2 * border;
+        if !variant.supports_any_size() {
+            assert_eq!(
+                size % 4,
+                0,
+                "page size with borders ({size}) must be a multiple of 4"
+            );
+        }
+
+        Self {
+            compressor: GpuBlockCompressor::new(device.clone(), queue.clone()),
+            device,
+            queue,
+            variant,
+            page_size,
+            border,
+            border_mode: BorderMode::default(),
+            batch_size: 64,
+        }
+    }
+
+    /// Sets where the border texels come from. Defaults to [`BorderMode::Neighbour`].
+    pub fn border_mode(mut self, border_mode: BorderMode) -> Self {
+        self.border_mode = border_mode;
+        self
+    }
+
+    /// Sets how many pages are compressed in a single compute pass. Defaults to 64.
+    ///
+    /// # Panics
+    /// - If `batch_size` is zero
+    pub fn batch_size(mut self, batch_size: u32) -> Self {
+        assert!(batch_size > 0, "batch size must not be zero");
+        self.batch_size = batch_size;
+        self
+    }
+
+    /// Returns the size of a page including its borders in texels.
+    pub fn page_size_with_border(&self) -> u32 {
+        self.page_size + 2 * self.border
+    }
+
+    /// Cuts RGBA8 data with the given row stride in bytes into pages and compresses them.
+    ///
+    /// Returns the pages of every mip level. The mip levels are created with a 2x2 box filter on
+    /// the stored values, until a mip level fits into a single page. This blocks until the GPU
+    /// finished compressing all pages.
+    ///
+    /// # Panics
+    /// - The `rgba_data` is too small for the given dimensions and stride
+    pub fn build_rgba8(
+        &mut self,
+        rgba_data: &[u8],
+        width: u32,
+        height: u32,
+        stride: u32,
+    ) -> Vec<PageLevel> {
+        assert!(width > 0 && height > 0, "image must not be empty");
+
+        let mut level = Image::from_rgba8(rgba_data, width, height, stride);
+        let mut levels = Vec::new();
+
+        loop {
+            levels.push(self.build_level(&level));
+
+            if level.width <= self.page_size && level.height <= self.page_size {
+                break;
+            }
+            level = level.downsample();
+        }
+
+        levels
+    }
+
+    fn build_level(&mut self, image: &Image) -> PageLevel {
+        let pages_x = image.width.div_ceil(self.page_size);
+        let pages_y = image.height.div_ceil(self.page_size);
+        let size = self.page_size_with_border();
+        let page_byte_size = self.variant.blocks_byte_size(size, size);
+        let page_pixels_size = size as usize * size as usize * 4;
+
+        let pages: Vec<(u32, u32)> = (0..pages_y)
+            .flat_map(|y| (0..pages_x).map(move |x| (x, y)))
+            .collect();
+
+        let mut data = Vec::with_capacity(pages.len() * page_byte_size);
+        let mut source_data = Vec::new();
+
+        for batch in pages.chunks(self.batch_size as usize) {
+            source_data.clear();
+            for &page in batch {
+                cut_page(
+                    image,
+                    page,
+                    self.page_size,
+                    self.border,
+                    self.border_mode,
+                    &mut source_data,
+                );
+            }
+
+            let source_buffer = self.device.create_buffer_init(&BufferInitDescriptor {
+                label: Some("page source buffer"),
+                contents: &source_data,
+                usage: BufferUsages::STORAGE,
+            });
+
+            let blocks_size = (batch.len() * page_byte_size) as u64;
+            let blocks_buffer = self.device.create_buffer(&BufferDescriptor {
+                label: Some("page blocks buffer"),
+                size: blocks_size,
+                usage: BufferUsages::COPY_SRC | BufferUsages::STORAGE,
+                mapped_at_creation: false,
+            });
+            let staging_buffer = self.device.create_buffer(&BufferDescriptor {
+                label: Some("page staging buffer"),
+                size: blocks_size,
+                usage: BufferUsages::COPY_DST | BufferUsages::MAP_READ,
+                mapped_at_creation: false,
+            });
+
+            for index in 0..batch.len() {
+                self.compressor.add_buffer_compression_task(
+                    self.variant,
+                    &source_buffer,
+                    SourceBufferFormat::Rgba8Unorm,
+                    Some((index * page_pixels_size) as u32),
+                    size * 4,
+                    size,
+                    size,
+                    &blocks_buffer,
+                    Some((index * page_byte_size) as u32),
+                );
+            }
+
+            let mut encoder = self
+                .device
+                .create_command_encoder(&CommandEncoderDescriptor {
+                    label: Some("page command encoder"),
+                });
+
+            {
+                let mut pass = encoder.begin_compute_pass(&ComputePassDescriptor {
+                    label: Some("page compute pass"),
+                    timestamp_writes: None,
+                });
+
+                self.compressor.compress(&mut pass);
+            }
+
+            encoder.copy_buffer_to_buffer(&blocks_buffer, 0, &staging_buffer, 0, blocks_size);
+
+            self.queue.submit([encoder.finish()]);
+
+            let buffer_slice = staging_buffer.slice(..);
+            let (tx, rx) = std::sync::mpsc::channel();
+            buffer_slice.map_async(MapMode::Read, move |result| tx.send(result).unwrap());
+
+            self.device.poll(Maintain::Wait);
+
+            rx.recv()
+                .expect("map callback was not called")
+                .expect("couldn't read the page blocks");
+            data.extend_from_slice(&buffer_slice.get_mapped_range());
+            staging_buffer.unmap();
+        }
+
+        PageLevel {
+            width: image.width,
+            height: image.height,
+            pages_x,
+            pages_y,
+            page_byte_size,
+            data,
+        }
+    }
+}
+
+/// Appends the RGBA8 texels of a page including its borders.
+fn cut_page(
+    image: &Image,
+    (page_x, page_y): (u32, u32),
+    page_size: u32,
+    border: u32,
+    border_mode: BorderMode,
+    output: &mut Vec<u8>,
+) {
+    let size = (page_size + 2 * border) as i64;
+    let border = border as i64;
+    let start_x = (page_x * page_size) as i64;
+    let start_y = (page_y * page_size) as i64;
+
+    // Texels outside the image always repeat the outermost texels of the image. Replicated
+    // borders additionally repeat the outermost texels of the page.
+    let (min_x, max_x, min_y, max_y) = match border_mode {
+        BorderMode::Replicate => (
+            start_x,
+            (start_x + page_size as i64).min(image.width as i64) - 1,
+            start_y,
+            (start_y + page_size as i64).min(image.height as i64) - 1,
+        ),
+        BorderMode::Neighbour => (0, image.width as i64 - 1, 0, image.height as i64 - 1),
+    };
+
+    for y in 0..size {
+        let source_y = (start_y - border + y).clamp(min_y, max_y) as u32;
+        for x in 0..size {
+            let source_x = (start_x - border + x).clamp(min_x, max_x) as u32;
+            output.extend_from_slice(&image.texel(source_x, source_y));
+        }
+    }
+}
+
+/// A tightly packed RGBA8 image.
+struct Image {
+    width: u32,
+    height: u32,
+    data: Vec<u8>,
+}
+
+impl Image {
+    fn from_rgba8(rgba_data: &[u8], width: u32, height: u32, stride: u32) -> Self {
+        let row_size = width as usize * 4;
+        let data = (0..height as usize)
+            .flat_map(|y| &rgba_data[y * stride as usize..][..row_size])
+            .copied()
+            .collect();
+
+        Self {
+            width,
+            height,
+            data,
+        }
+    }
+
+    fn texel(&self, x: u32, y: u32) -> [u8; 4] {
+        let offset = (y as usize * self.width as usize + x as usize) * 4;
+        self.data[offset..offset + 4].try_into().unwrap()
+    }
+
+    /// Halves the size with a 2x2 box filter. Odd sizes repeat the last row or column.
+    fn downsample(&self) -> Self {
+        let width = self.width.div_ceil(2);
+        let height = self.height.div_ceil(2);
+        let mut data = Vec::with_capacity(width as usize * height as usize * 4);
+
+        for y in 0..height {
+            let y0 = 2 * y;
+            let y1 = (2 * y + 1).min(self.height - 1);
+            for x in 0..width {
+                let x0 = 2 * x;
+                let x1 = (2 * x + 1).min(self.width - 1);
+
+                let texels = [
+                    self.texel(x0, y0),
+                    self.texel(x1, y0),
+                    self.texel(x0, y1),
+                    self.texel(x1, y1),
+                ];
+                for channel in 0..4 {
+                    let sum: u32 = texels.iter().map(|texel| texel[channel] as u32).sum();
+                    data.push(((sum + 2) / 4) as u8);
+                }
+            }
+        }
+
+        Self {
+            width,
+            height,
+            data,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A 4x2 image, whose red channel is the x and green channel the y coordinate.
+    fn coordinate_image() -> Image {
+        let data = (0..2u8)
+            .flat_map(|y| (0..4u8).flat_map(move |x| [x, y, 0, 255]))
+            .collect();
+        Image {
+            width: 4,
+            height: 2,
+            data,
+        }
+    }
+
+    /// Cuts the second page of a layout with pages of 2x2 texels and a border of 1 and returns
+    /// the coordinates of its texels.
+    fn cut(border_mode: BorderMode) -> Vec<(u8, u8)> {
+        let mut output = Vec::new();
+        cut_page(&coordinate_image(), (1, 0), 2, 1, border_mode, &mut output);
+
+        output
+            .chunks_exact(4)
+            .map(|texel| (texel[0], texel[1]))
+            .collect()
+    }
+
+    #[test]
+    fn downsample_averages_and_repeats_odd_edges() {
+        let image = Image {
+            width: 3,
+            height: 1,
+            data: vec![0, 10, 20, 255, 100, 30, 40, 255, 200, 50, 60, 255],
+        };
+
+        let half = image.downsample();
+        assert_eq!((half.width, half.height), (2, 1));
+        assert_eq!(half.texel(0, 0), [50, 20, 30, 255]);
+        assert_eq!(half.texel(1, 0), [200, 50, 60, 255]);
+    }
+
+    #[test]
+    fn borders() {
+        let row = |y: u8, xs: [u8; 4]| xs.map(|x| (x, y));
+
+        let replicate: Vec<_> = [row(0, [2, 2, 3, 3]), row(0, [2, 2, 3, 3])]
+            .into_iter()
+            .chain([row(1, [2, 2, 3, 3]), row(1, [2, 2, 3, 3])])
+            .flatten()
+            .collect();
+        assert_eq!(cut(BorderMode::Replicate), replicate);
+
+        let neighbour: Vec<_> = [row(0, [1, 2, 3, 3]), row(0, [1, 2, 3, 3])]
+            .into_iter()
+            .chain([row(1, [1, 2, 3, 3]), row(1, [1, 2, 3, 3])])
+            .flatten()
+            .collect();
+        assert_eq!(cut(BorderMode::Neighbour), neighbour);
+    }
+}
@@ -0,0 +1,193 @@
+use block_compression::*;
+use half::f16;
+use wgpu::{
+    util::{BufferInitDescriptor, DeviceExt},
+    BufferUsages, CommandEncoderDescriptor, ComputePassDescriptor, TextureViewDescriptor,
+};
+
+use crate::common::{
+    create_blocks_buffer, create_wgpu_resources, download_blocks_data,
+    read_image_and_create_texture, srgb_to_linear, BRICK_FILE_PATH, MARBLE_FILE_PATH,
+};
+
+mod common;
+
+/// Compresses the same image once from a texture and once from a storage buffer with padded rows
+/// and checks, that both tasks produce blocks of the same quality.
+///
+/// Backends don't have to convert `Rgba8Unorm` texels exactly, so the float encoders can choose
+/// different indices for a few blocks of 8-bit sources. Float sources and the strict mode (see
+/// `strict_parity.rs`) produce the same blocks.
+fn test_buffer_source_compression(variant: CompressionVariant, source_format: SourceBufferFormat) {
+    compress_and_compare(BRICK_FILE_PATH, variant, source_format);
+    compress_and_compare(MARBLE_FILE_PATH, variant, source_format);
+}
+
+fn compress_and_compare(
+    file_path: &str,
+    variant: CompressionVariant,
+    source_format: SourceBufferFormat,
+) {
+    let (device, queue) = create_wgpu_resources();
+    let mut block_compressor = GpuBlockCompressor::new(device.clone(), queue.clone());
+
+    let (texture, rgba_data) = read_image_and_create_texture(&device, &queue, file_path, variant);
+    let width = texture.width();
+    let height = texture.height();
+
+    let pixels: Vec<u8> = match source_format {
+        SourceBufferFormat::Rgba8Unorm => rgba_data.clone(),
+        SourceBufferFormat::Rgba16Float => rgba_data
+            .iter()
+            .flat_map(|color| f16::from_f64(srgb_to_linear(*color)).to_le_bytes())
+            .collect(),
+        SourceBufferFormat::Rgba32Float => rgba_data
+            .iter()
+            .flat_map(|color| f16::from_f64(srgb_to_linear(*color)).to_f32().to_le_bytes())
+            .collect(),
+    };
+
+    // Pad every row to test the row stride handling.
+    let row_size = (width * source_format.bytes_per_pixel()) as usize;
+    let row_stride = row_size + 256;
+    let mut source_data = vec![0xFF; 64];
+    for row in pixels.chunks_exact(row_size) {
+        source_data.extend_from_slice(row);
+        source_data.resize(source_data.len() + row_stride - row_size, 0xFF);
+    }
+
+    let source_buffer = device.create_buffer_init(&BufferInitDescriptor {
+        label: Some("source buffer"),
+        contents: &source_data,
+        usage: BufferUsages::STORAGE,
+    });
+
+    let blocks_size = variant.blocks_byte_size(width, height);
+    let blocks = create_blocks_buffer(&device, (blocks_size * 2) as u64);
+
+    block_compressor.add_compression_task(
+        variant,
+        &texture.create_view(&TextureViewDescriptor::default()),
+        width,
+        height,
+        &blocks,
+        None,
+    );
+    block_compressor.add_buffer_compression_task(
+        variant,
+        &source_buffer,
+        source_format,
+        Some(64),
+        row_stride as u32,
+        width,
+        height,
+        &blocks,
+        Some(blocks_size as _),
+    );
+
+    let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor {
+        label: Some("command encoder"),
+    });
+
+    {
+        let mut pass = encoder.begin_compute_pass(&ComputePassDescriptor {
+            label: Some("compute pass"),
+            timestamp_writes: None,
+        });
+
+        block_compressor.compress(&mut pass);
+    }
+
+    queue.submit([encoder.finish()]);
+
+    let blocks_data = download_blocks_data(&device, &queue, blocks);
+
+    let (texture_blocks, buffer_blocks) = blocks_data.split_at(blocks_size);
+    assert!(!texture_blocks.iter().all(|&data| data == 0));
+
+    if source_format != SourceBufferFormat::Rgba8Unorm {
+        assert_eq!(
+            texture_blocks, buffer_blocks,
+            "texture and buffer source produced different blocks for `{file_path}`"
+        );
+        return;
+    }
+
+    let texture_psnr = calculate_psnr(variant, width, height, texture_blocks, &rgba_data);
+    let buffer_psnr = calculate_psnr(variant, width, height, buffer_blocks, &rgba_data);
+    assert!(
+        (texture_psnr - buffer_psnr).abs() < PSNR_TOLERANCE,
+        "texture source ({texture_psnr:.3} dB) and buffer source ({buffer_psnr:.3} dB) differ for `{file_path}`"
+    );
+}
+
+/// The largest PSNR difference in dB between blocks of a texture and a buffer source.
+const PSNR_TOLERANCE: f64 = 0.1;
+
+/// The PSNR of the decoded blocks over the channels, that the variant stores.
+fn calculate_psnr(
+    variant: CompressionVariant,
+    width: u32,
+    height: u32,
+    blocks_data: &[u8],
+    rgba_data: &[u8],
+) -> f64 {
+    let channels = match variant {
+        CompressionVariant::BC4 => 1,
+        CompressionVariant::BC5 => 2,
+        CompressionVariant::BC1 => 3,
+        _ => 4,
+    };
+
+    let mut decoded = vec![0; rgba_data.len()];
+    decode::decompress_blocks_as_rgba8(variant, width, height, blocks_data, &mut decoded);
+
+    let squared_error: f64 = decoded
+        .chunks_exact(4)
+        .zip(rgba_data.chunks_exact(4))
+        .flat_map(|(decoded, source)| decoded[..channels].iter().zip(&source[..channels]))
+        .map(|(&decoded, &source)| (decoded as f64 - source as f64).powi(2))
+        .sum();
+    let mse = squared_error / (width as usize * height as usize * channels) as f64;
+
+    10.0 * (255.0 * 255.0 / mse).log10()
+}
+
+#[test]
+fn buffer_source_compression_bc1() {
+    test_buffer_source_compression(CompressionVariant::BC1, SourceBufferFormat::Rgba8Unorm);
+}
+
+#[test]
+fn buffer_source_compression_bc3() {
+    test_buffer_source_compression(CompressionVariant::BC3, SourceBufferFormat::Rgba8Unorm);
+}
+
+#[test]
+fn buffer_source_compression_bc5() {
+    test_buffer_source_compression(CompressionVariant::BC5, SourceBufferFormat::Rgba8Unorm);
+}
+
+#[test]
+fn buffer_source_compression_bc6h_rgba16f() {
+    test_buffer_source_compression(
+        CompressionVariant::BC6H(BC6HSettings::very_fast()),
+        SourceBufferFormat::Rgba16Float,
+    );
+}
+
+#[test]
+fn buffer_source_compression_bc6h_rgba32f() {
+    test_buffer_source_compression(
+        CompressionVariant::BC6H(BC6HSettings::very_fast()),
+        SourceBufferFormat::Rgba32Float,
+    );
+}
+
+#[test]
+fn buffer_source_compression_bc7() {
+    test_buffer_source_compression(
+        CompressionVariant::BC7(BC7Settings::opaque_ultra_fast()),
+        SourceBufferFormat::Rgba8Unorm,
+    );
+}
@@ -9,8 +9,9 @@ use wgpu::{
     BackendOptions, Backends, Buffer, BufferDescriptor, BufferUsages, CommandEncoderDescriptor,
     Device, DeviceDescriptor, Dx12BackendOptions, Dx12Compiler, Error, Extent3d, Features,
     GlBackendOptions, Gles3MinorVersion, Instance, InstanceDescriptor, InstanceFlags, Limits,
-    Maintain, MapMode, MemoryHints, PowerPreference, Queue, Texture, TextureDescriptor,
-    TextureDimension, TextureFormat, TextureUsages,
+    Maintain, MapMode, MemoryHints, PowerPreference, Queue, TexelCopyBufferInfo,
+    TexelCopyBufferLayout, Texture, TextureDescriptor, TextureDimension, TextureFormat,
+    TextureUsages, COPY_BYTES_PER_ROW_ALIGNMENT,
 };
 
 #[inline]
@@ -83,6 +84,7 @@ pub fn error_handler(error: Error) {
     panic!("wgpu [{message_type}] [error]: {message}");
 }
 
+#[allow(dead_code)]
 pub fn read_image_and_create_texture(
     device: &Device,
     queue: &Queue,
@@ -148,6 +150,7 @@ pub fn read_image_and_create_texture(
     (texture, rgba_image.to_vec())
 }
 
+#[allow(dead_code)]
 pub fn create_blocks_buffer(device: &Device, size: u64) -> Buffer {
     device.create_buffer(&BufferDescriptor {
         label: Some("blocks buffer"),
@@ -157,6 +160,7 @@ pub fn create_blocks_buffer(device: &Device, size: u64) -> Buffer {
     })
 }
 
+#[allow(dead_code)]
 pub fn download_blocks_data(device: &Device, queue: &Queue, block_buffer: Buffer) -> Vec<u8> {
     let size = block_buffer.size();
 
@@ -197,3 +201,67 @@ pub fn download_blocks_data(device: &Device, queue: &Queue, block_buffer: Buffer
 
     result
 }
+
+#[allow(dead_code)]
+pub fn create_storage_texture(
+    device: &Device,
+    width: u32,
+    height: u32,
+    format: TextureFormat,
+) -> Texture {
+    device.create_texture(&TextureDescriptor {
+        label: Some("storage texture"),
+        size: Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: TextureDimension::D2,
+        format,
+        usage: TextureUsages::COPY_SRC | TextureUsages::STORAGE_BINDING,
+        view_formats: &[],
+    })
+}
+
+#[allow(dead_code)]
+pub fn download_texture_data(device: &Device, queue: &Queue, texture: &Texture) -> Vec<u8> {
+    let width = texture.width();
+    let height = texture.height();
+    let row_size = width * texture.format().block_copy_size(None).unwrap();
+    let padded_row_size =
+        row_size.div_ceil(COPY_BYTES_PER_ROW_ALIGNMENT) * COPY_BYTES_PER_ROW_ALIGNMENT;
+
+    let buffer = device.create_buffer(&BufferDescriptor {
+        label: Some("texture download buffer"),
+        size: (padded_row_size * height) as u64,
+        usage: BufferUsages::COPY_DST | BufferUsages::COPY_SRC,
+        mapped_at_creation: false,
+    });
+
+    let mut copy_encoder = device.create_command_encoder(&CommandEncoderDescriptor {
+        label: Some("copy encoder"),
+    });
+
+    copy_encoder.copy_texture_to_buffer(
+        texture.as_image_copy(),
+        TexelCopyBufferInfo {
+            buffer: &buffer,
+            layout: TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(padded_row_size),
+                rows_per_image: None,
+            },
+        },
+        texture.size(),
+    );
+
+    queue.submit([copy_encoder.finish()]);
+
+    download_blocks_data(device, queue, buffer)
+        .chunks_exact(padded_row_size as usize)
+        .flat_map(|row| &row[..row_size as usize])
+        .copied()
+        .collect()
+}
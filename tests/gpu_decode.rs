@@ -0,0 +1,309 @@
+use block_compression::{decode::*, half::f16, *};
+use wgpu::{CommandEncoderDescriptor, ComputePassDescriptor, TextureViewDescriptor};
+
+use crate::common::{
+    create_blocks_buffer, create_storage_texture, create_wgpu_resources, download_blocks_data,
+    download_texture_data, read_image_and_create_texture, BRICK_FILE_PATH, MARBLE_FILE_PATH,
+};
+
+mod common;
+
+/// Compresses an image, decodes the blocks on the GPU and checks, that the result matches the
+/// CPU decoder.
+fn test_gpu_decompression(variant: CompressionVariant) {
+    decompress_and_compare(BRICK_FILE_PATH, variant);
+    decompress_and_compare(MARBLE_FILE_PATH, variant);
+}
+
+fn decompress_and_compare(file_path: &str, variant: CompressionVariant) {
+    let (device, queue) = create_wgpu_resources();
+    let mut block_compressor = GpuBlockCompressor::new(device.clone(), queue.clone());
+    let mut block_decompressor = GpuBlockDecompressor::new(device.clone(), queue.clone());
+
+    let (texture, _) = read_image_and_create_texture(&device, &queue, file_path, variant);
+    let width = texture.width();
+    let height = texture.height();
+
+    let blocks_size = variant.blocks_byte_size(width, height);
+    let blocks = create_blocks_buffer(&device, blocks_size as u64);
+
+    // Also decode into a smaller image, to test the handling of partial blocks.
+    let partial_width = width - 2;
+    let partial_height = height - 3;
+
+    let fill = FillValues {
+        red: 0.25,
+        green: 0.5,
+        blue: 0.75,
+        alpha: 1.0,
+    };
+
+    let rgba8_texture =
+        create_storage_texture(&device, width, height, wgpu::TextureFormat::Rgba8Unorm);
+    let rgba16f_texture =
+        create_storage_texture(&device, width, height, wgpu::TextureFormat::Rgba16Float);
+    let partial_texture = create_storage_texture(
+        &device,
+        partial_width,
+        partial_height,
+        wgpu::TextureFormat::Rgba8Unorm,
+    );
+
+    block_compressor.add_compression_task(
+        variant,
+        &texture.create_view(&TextureViewDescriptor::default()),
+        width,
+        height,
+        &blocks,
+        None,
+    );
+    block_decompressor.add_decompression_task(
+        variant,
+        &blocks,
+        None,
+        width,
+        height,
+        &rgba8_texture.create_view(&TextureViewDescriptor::default()),
+        OutputTextureFormat::Rgba8Unorm,
+        fill,
+    );
+    block_decompressor.add_decompression_task(
+        variant,
+        &blocks,
+        None,
+        width,
+        height,
+        &rgba16f_texture.create_view(&TextureViewDescriptor::default()),
+        OutputTextureFormat::Rgba16Float,
+        fill,
+    );
+    block_decompressor.add_decompression_task(
+        variant,
+        &blocks,
+        None,
+        partial_width,
+        partial_height,
+        &partial_texture.create_view(&TextureViewDescriptor::default()),
+        OutputTextureFormat::Rgba8Unorm,
+        fill,
+    );
+
+    let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor {
+        label: Some("command encoder"),
+    });
+
+    {
+        let mut pass = encoder.begin_compute_pass(&ComputePassDescriptor {
+            label: Some("compute pass"),
+            timestamp_writes: None,
+        });
+
+        block_compressor.compress(&mut pass);
+    }
+
+    {
+        let mut pass = encoder.begin_compute_pass(&ComputePassDescriptor {
+            label: Some("compute pass"),
+            timestamp_writes: None,
+        });
+
+        block_decompressor.decompress(&mut pass);
+    }
+
+    queue.submit([encoder.finish()]);
+
+    let rgba8_data = download_texture_data(&device, &queue, &rgba8_texture);
+    let rgba16f_data = download_texture_data(&device, &queue, &rgba16f_texture);
+    let partial_data = download_texture_data(&device, &queue, &partial_texture);
+    let blocks_data = download_blocks_data(&device, &queue, blocks);
+
+    // The sRGB conversion of BC6H is done with a different pow() implementation.
+    let tolerance = if matches!(variant, CompressionVariant::BC6H(..)) {
+        1
+    } else {
+        0
+    };
+
+    let mut expected_rgba8 = vec![0; (width * height * 4) as usize];
+    decompress_blocks_as_rgba8_with_fill(
+        variant,
+        width,
+        height,
+        &blocks_data,
+        &mut expected_rgba8,
+        fill,
+    );
+    assert_rgba8_eq(&rgba8_data, &expected_rgba8, tolerance, file_path);
+
+    let mut expected_partial = vec![0; (partial_width * partial_height * 4) as usize];
+    decompress_blocks_as_rgba8_with_fill(
+        variant,
+        partial_width,
+        partial_height,
+        &blocks_data,
+        &mut expected_partial,
+        fill,
+    );
+    assert_rgba8_eq(&partial_data, &expected_partial, tolerance, file_path);
+
+    let mut expected_rgba16f = vec![f16::ZERO; (width * height * 4) as usize];
+    decompress_blocks_as_rgba16f_with_fill(
+        variant,
+        width,
+        height,
+        &blocks_data,
+        &mut expected_rgba16f,
+        fill,
+    );
+    for (index, (actual, expected)) in rgba16f_data
+        .chunks_exact(2)
+        .map(|bytes| f16::from_le_bytes([bytes[0], bytes[1]]))
+        .zip(expected_rgba16f)
+        .enumerate()
+    {
+        assert!(
+            (actual.to_f32() - expected.to_f32()).abs() <= expected.to_f32().abs() * 2e-3,
+            "RGBA16F value {index} of `{file_path}` differs: {actual} != {expected}"
+        );
+    }
+}
+
+/// Queues more tasks than the initial uniforms buffer can hold, so that it's recreated on
+/// dispatch. Every task uses different fill values, which are read from its uniforms.
+#[test]
+fn gpu_decompression_many_tasks() {
+    const TASK_COUNT: usize = 20;
+
+    let variant = CompressionVariant::BC4;
+    let (device, queue) = create_wgpu_resources();
+    let mut block_compressor = GpuBlockCompressor::new(device.clone(), queue.clone());
+    let mut block_decompressor = GpuBlockDecompressor::new(device.clone(), queue.clone());
+
+    let (texture, _) = read_image_and_create_texture(&device, &queue, BRICK_FILE_PATH, variant);
+    let width = texture.width();
+    let height = texture.height();
+
+    let blocks = create_blocks_buffer(&device, variant.blocks_byte_size(width, height) as u64);
+    block_compressor.add_compression_task(
+        variant,
+        &texture.create_view(&TextureViewDescriptor::default()),
+        width,
+        height,
+        &blocks,
+        None,
+    );
+
+    let tasks: Vec<_> = (0..TASK_COUNT)
+        .map(|index| {
+            // Multiples of 1/255 are stored exactly in the RGBA8 texture.
+            let fill = FillValues {
+                red: 0.0,
+                green: (index * 12) as f32 / 255.0,
+                blue: (255 - index * 12) as f32 / 255.0,
+                alpha: 1.0,
+            };
+            let output_texture =
+                create_storage_texture(&device, width, height, wgpu::TextureFormat::Rgba8Unorm);
+
+            block_decompressor.add_decompression_task(
+                variant,
+                &blocks,
+                None,
+                width,
+                height,
+                &output_texture.create_view(&TextureViewDescriptor::default()),
+                OutputTextureFormat::Rgba8Unorm,
+                fill,
+            );
+
+            (fill, output_texture)
+        })
+        .collect();
+
+    let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor {
+        label: Some("command encoder"),
+    });
+
+    {
+        let mut pass = encoder.begin_compute_pass(&ComputePassDescriptor {
+            label: Some("compute pass"),
+            timestamp_writes: None,
+        });
+
+        block_compressor.compress(&mut pass);
+    }
+
+    {
+        let mut pass = encoder.begin_compute_pass(&ComputePassDescriptor {
+            label: Some("compute pass"),
+            timestamp_writes: None,
+        });
+
+        block_decompressor.decompress(&mut pass);
+    }
+
+    queue.submit([encoder.finish()]);
+
+    let blocks_data = download_blocks_data(&device, &queue, blocks);
+
+    for (fill, output_texture) in tasks {
+        let mut expected = vec![0; (width * height * 4) as usize];
+        decompress_blocks_as_rgba8_with_fill(
+            variant,
+            width,
+            height,
+            &blocks_data,
+            &mut expected,
+            fill,
+        );
+
+        let actual = download_texture_data(&device, &queue, &output_texture);
+        assert_rgba8_eq(&actual, &expected, 0, BRICK_FILE_PATH);
+    }
+}
+
+fn assert_rgba8_eq(actual: &[u8], expected: &[u8], tolerance: u8, file_path: &str) {
+    assert_eq!(actual.len(), expected.len());
+
+    for (index, (actual, expected)) in actual.iter().zip(expected).enumerate() {
+        assert!(
+            actual.abs_diff(*expected) <= tolerance,
+            "RGBA8 value {index} of `{file_path}` differs: {actual} != {expected}"
+        );
+    }
+}
+
+#[test]
+fn gpu_decompression_bc1() {
+    test_gpu_decompression(CompressionVariant::BC1);
+}
+
+#[test]
+fn gpu_decompression_bc2() {
+    test_gpu_decompression(CompressionVariant::BC2);
+}
+
+#[test]
+fn gpu_decompression_bc3() {
+    test_gpu_decompression(CompressionVariant::BC3);
+}
+
+#[test]
+fn gpu_decompression_bc4() {
+    test_gpu_decompression(CompressionVariant::BC4);
+}
+
+#[test]
+fn gpu_decompression_bc5() {
+    test_gpu_decompression(CompressionVariant::BC5);
+}
+
+#[test]
+fn gpu_decompression_bc6h() {
+    test_gpu_decompression(CompressionVariant::BC6H(BC6HSettings::very_fast()));
+}
+
+#[test]
+fn gpu_decompression_bc7() {
+    test_gpu_decompression(CompressionVariant::BC7(BC7Settings::alpha_basic()));
+}
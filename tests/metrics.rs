@@ -1,6 +1,6 @@
 use block_compression::{
-    decode::decompress_blocks_as_rgba8, encode::compress_rgba8, BC6HSettings, BC7Settings,
-    CompressionVariant, GpuBlockCompressor,
+    decode::decompress_blocks_as_rgba8, encode::compress_rgba8, ASTCBlockSize, BC6HSettings,
+    BC7Settings, CompressionVariant, GpuBlockCompressor,
 };
 use half::f16;
 use intel_tex_2::{bc6h, bc7, RgbaSurface};
@@ -324,6 +324,36 @@ fn compare_psnr(image_path: &str, variant: CompressionVariant, channels: u32) {
     }
 }
 
+/// Checks the PSNR against a fixed minimum, for variants without an independent reference encoder.
+/// The minimums were measured on the test images and leave a margin of about 0.1 dB.
+fn check_psnr(image_path: &str, variant: CompressionVariant, channels: u32, minimum_psnr: f64) {
+    let image_name = std::path::Path::new(image_path)
+        .file_name()
+        .unwrap()
+        .to_str()
+        .unwrap();
+
+    let (width, height, original_data, blocks_data) = compress_image(image_path, variant);
+
+    let psnr = calculate_psnr(
+        variant,
+        channels,
+        width,
+        height,
+        &original_data,
+        &blocks_data,
+    );
+
+    print_metrics(image_name, &psnr);
+
+    if psnr.overall_psnr < minimum_psnr {
+        panic!(
+            "Overall PSNR for image `{image_name}` is below the minimum: {:.3} < {minimum_psnr:.3}",
+            psnr.overall_psnr
+        );
+    }
+}
+
 #[test]
 fn psnr_bc1() {
     compare_psnr(BRICK_FILE_PATH, CompressionVariant::BC1, 3);
@@ -545,3 +575,52 @@ fn psnr_bc7_opaque_slow() {
         3,
     );
 }
+
+#[test]
+fn psnr_etc2_rgb8() {
+    check_psnr(BRICK_FILE_PATH, CompressionVariant::ETC2RGB8, 3, 37.9);
+    check_psnr(MARBLE_FILE_PATH, CompressionVariant::ETC2RGB8, 3, 43.2);
+}
+
+#[test]
+fn psnr_etc2_rgba8() {
+    check_psnr(
+        BRICK_ALPHA_FILE_PATH,
+        CompressionVariant::ETC2RGBA8,
+        4,
+        40.3,
+    );
+    check_psnr(
+        MARBLE_ALPHA_FILE_PATH,
+        CompressionVariant::ETC2RGBA8,
+        4,
+        45.6,
+    );
+}
+
+#[test]
+fn psnr_eac_rg11() {
+    check_psnr(BRICK_FILE_PATH, CompressionVariant::EACRG11, 2, 50.6);
+    check_psnr(MARBLE_FILE_PATH, CompressionVariant::EACRG11, 2, 50.6);
+}
+
+#[test]
+fn psnr_astc_4x4() {
+    let variant = CompressionVariant::ASTC(ASTCBlockSize::Block4x4);
+    check_psnr(BRICK_FILE_PATH, variant, 3, 48.2);
+    check_psnr(MARBLE_FILE_PATH, variant, 3, 50.4);
+}
+
+#[test]
+fn psnr_astc_6x6() {
+    let variant = CompressionVariant::ASTC(ASTCBlockSize::Block6x6);
+    check_psnr(BRICK_ALPHA_FILE_PATH, variant, 4, 40.8);
+    check_psnr(MARBLE_ALPHA_FILE_PATH, variant, 4, 41.3);
+}
+
+#[test]
+fn psnr_astc_8x8() {
+    let variant = CompressionVariant::ASTC(ASTCBlockSize::Block8x8);
+    check_psnr(BRICK_FILE_PATH, variant, 3, 36.7);
+    check_psnr(MARBLE_FILE_PATH, variant, 3, 36.4);
+}
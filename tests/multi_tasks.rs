@@ -98,3 +98,43 @@ fn multi_task_compression_bc6h() {
 fn multi_task_compression_bc7() {
     test_multi_task_compression(CompressionVariant::BC7(BC7Settings::opaque_ultra_fast()));
 }
+
+#[test]
+fn multi_task_compression_etc2_rgb8() {
+    test_multi_task_compression(CompressionVariant::ETC2RGB8);
+}
+
+#[test]
+fn multi_task_compression_etc2_rgb8a1() {
+    test_multi_task_compression(CompressionVariant::ETC2RGB8A1);
+}
+
+#[test]
+fn multi_task_compression_etc2_rgba8() {
+    test_multi_task_compression(CompressionVariant::ETC2RGBA8);
+}
+
+#[test]
+fn multi_task_compression_eac_r11() {
+    test_multi_task_compression(CompressionVariant::EACR11);
+}
+
+#[test]
+fn multi_task_compression_eac_rg11() {
+    test_multi_task_compression(CompressionVariant::EACRG11);
+}
+
+#[test]
+fn multi_task_compression_astc_4x4() {
+    test_multi_task_compression(CompressionVariant::ASTC(ASTCBlockSize::Block4x4));
+}
+
+#[test]
+fn multi_task_compression_astc_6x6() {
+    test_multi_task_compression(CompressionVariant::ASTC(ASTCBlockSize::Block6x6));
+}
+
+#[test]
+fn multi_task_compression_astc_8x8() {
+    test_multi_task_compression(CompressionVariant::ASTC(ASTCBlockSize::Block8x8));
+}
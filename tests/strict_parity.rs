@@ -0,0 +1,200 @@
+use block_compression::{encode::compress_rgba8_strict, *};
+use image::ImageReader;
+use wgpu::{
+    util::{BufferInitDescriptor, DeviceExt, TextureDataOrder},
+    BufferUsages, CommandEncoderDescriptor, ComputePassDescriptor, Extent3d, TextureDescriptor,
+    TextureDimension, TextureFormat, TextureUsages, TextureViewDescriptor,
+};
+
+use crate::common::{
+    create_blocks_buffer, create_wgpu_resources, download_blocks_data, BRICK_FILE_PATH,
+    MARBLE_FILE_PATH,
+};
+
+mod common;
+
+const SOURCE_FORMATS: [SourceBufferFormat; 2] = [
+    SourceBufferFormat::Rgba8Unorm,
+    SourceBufferFormat::Rgba32Float,
+];
+
+/// Loads an image and replaces its alpha channel with a pattern, that contains fully transparent,
+/// translucent and opaque texels.
+fn read_image_with_alpha(file_path: &str) -> (u32, u32, Vec<u8>) {
+    let mut image = ImageReader::open(file_path)
+        .expect("can't open input image")
+        .decode()
+        .expect("can't decode image")
+        .to_rgba8();
+
+    for (x, y, pixel) in image.enumerate_pixels_mut() {
+        pixel.0[3] = match (x / 16 + y / 16) % 4 {
+            0 => 0,
+            1 => 255,
+            _ => ((x * 13) ^ (y * 7)) as u8,
+        };
+    }
+
+    (image.width(), image.height(), image.into_raw())
+}
+
+/// Compresses an image in strict mode from a texture and from storage buffers and checks, that
+/// every task produces exactly the same blocks as the CPU encoder in strict mode.
+fn test_strict_parity(variant: CompressionVariant, alpha_options: AlphaOptions) {
+    compress_and_compare(BRICK_FILE_PATH, variant, alpha_options);
+    compress_and_compare(MARBLE_FILE_PATH, variant, alpha_options);
+}
+
+fn compress_and_compare(file_path: &str, variant: CompressionVariant, alpha_options: AlphaOptions) {
+    let (device, queue) = create_wgpu_resources();
+    let mut block_compressor = GpuBlockCompressor::new(device.clone(), queue.clone());
+    block_compressor.set_strict_mode(true);
+
+    let (width, height, rgba_data) = read_image_with_alpha(file_path);
+
+    let mut expected = vec![0; variant.blocks_byte_size(width, height)];
+    compress_rgba8_strict(
+        variant,
+        &rgba_data,
+        &mut expected,
+        width,
+        height,
+        width * 4,
+        alpha_options,
+    );
+
+    let texture = device.create_texture_with_data(
+        &queue,
+        &TextureDescriptor {
+            label: Some(file_path),
+            size: Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format: TextureFormat::Rgba8Unorm,
+            usage: TextureUsages::COPY_DST | TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        },
+        TextureDataOrder::LayerMajor,
+        &rgba_data,
+    );
+
+    let blocks_size = expected.len();
+    let blocks = create_blocks_buffer(&device, (blocks_size * 3) as u64);
+
+    block_compressor.add_compression_task_with_alpha(
+        variant,
+        &texture.create_view(&TextureViewDescriptor::default()),
+        width,
+        height,
+        &blocks,
+        None,
+        alpha_options,
+    );
+
+    let source_buffers: Vec<_> = SOURCE_FORMATS
+        .iter()
+        .map(|source_format| {
+            let pixels: Vec<u8> = match source_format {
+                SourceBufferFormat::Rgba32Float => rgba_data
+                    .iter()
+                    .flat_map(|&color| (color as f32 / 255.0).to_le_bytes())
+                    .collect(),
+                _ => rgba_data.clone(),
+            };
+
+            device.create_buffer_init(&BufferInitDescriptor {
+                label: Some("source buffer"),
+                contents: &pixels,
+                usage: BufferUsages::STORAGE,
+            })
+        })
+        .collect();
+
+    for (index, (source_format, source_buffer)) in
+        SOURCE_FORMATS.iter().zip(&source_buffers).enumerate()
+    {
+        block_compressor.add_buffer_compression_task_with_alpha(
+            variant,
+            source_buffer,
+            *source_format,
+            None,
+            width * source_format.bytes_per_pixel(),
+            width,
+            height,
+            &blocks,
+            Some(((index + 1) * blocks_size) as u32),
+            alpha_options,
+        );
+    }
+
+    let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor {
+        label: Some("command encoder"),
+    });
+
+    {
+        let mut pass = encoder.begin_compute_pass(&ComputePassDescriptor {
+            label: Some("compute pass"),
+            timestamp_writes: None,
+        });
+
+        block_compressor.compress(&mut pass);
+    }
+
+    queue.submit([encoder.finish()]);
+
+    let blocks_data = download_blocks_data(&device, &queue, blocks);
+
+    let sources = ["texture", "Rgba8Unorm buffer", "Rgba32Float buffer"];
+    for (source, blocks) in sources.iter().zip(blocks_data.chunks_exact(blocks_size)) {
+        let mismatch = blocks
+            .chunks_exact(variant.blocks_byte_size(4, 4))
+            .zip(expected.chunks_exact(variant.blocks_byte_size(4, 4)))
+            .position(|(gpu, cpu)| gpu != cpu);
+
+        assert_eq!(
+            mismatch, None,
+            "strict mode blocks of the {source} source differ from the CPU for `{file_path}` and {variant:?}"
+        );
+    }
+}
+
+#[test]
+fn strict_parity_bc1() {
+    test_strict_parity(CompressionVariant::BC1, AlphaOptions::default());
+}
+
+#[test]
+fn strict_parity_bc2() {
+    test_strict_parity(CompressionVariant::BC2, AlphaOptions::default());
+}
+
+#[test]
+fn strict_parity_bc3() {
+    test_strict_parity(CompressionVariant::BC3, AlphaOptions::default());
+}
+
+#[test]
+fn strict_parity_bc4() {
+    test_strict_parity(CompressionVariant::BC4, AlphaOptions::default());
+}
+
+#[test]
+fn strict_parity_bc5() {
+    test_strict_parity(CompressionVariant::BC5, AlphaOptions::default());
+}
+
+#[test]
+fn strict_parity_alpha_options() {
+    let alpha_options = AlphaOptions {
+        premultiply: true,
+        weight_color_by_alpha: true,
+    };
+
+    test_strict_parity(CompressionVariant::BC2, alpha_options);
+    test_strict_parity(CompressionVariant::BC3, alpha_options);
+}
@@ -0,0 +1,162 @@
+use std::sync::Arc;
+
+use block_compression::*;
+use pollster::block_on;
+use wgpu::{
+    util::{BufferInitDescriptor, DeviceExt},
+    BackendOptions, Backends, BufferUsages, CommandEncoderDescriptor, ComputePassDescriptor,
+    Device, DeviceDescriptor, Dx12BackendOptions, Dx12Compiler, Features, GlBackendOptions,
+    Gles3MinorVersion, Instance, InstanceDescriptor, InstanceFlags, Limits, MemoryHints,
+    PowerPreference, Queue, TextureViewDescriptor,
+};
+
+use crate::common::{
+    create_blocks_buffer, create_wgpu_resources, download_blocks_data, error_handler,
+    read_image_and_create_texture, BRICK_FILE_PATH, MARBLE_FILE_PATH,
+};
+
+mod common;
+
+/// Small enough to split the blocks and source buffers of the test images into several strips.
+const MAX_BINDING_SIZE: u32 = 64 * 1024;
+
+/// Creates a device, that can only bind small storage buffer ranges.
+fn create_limited_wgpu_resources() -> (Arc<Device>, Arc<Queue>) {
+    let instance = Instance::new(&InstanceDescriptor {
+        backends: Backends::from_env().unwrap_or_default(),
+        flags: InstanceFlags::from_build_config().with_env(),
+        backend_options: BackendOptions {
+            gl: GlBackendOptions {
+                gles_minor_version: Gles3MinorVersion::Version1,
+            },
+            dx12: Dx12BackendOptions {
+                shader_compiler: Dx12Compiler::StaticDxc,
+            }
+            .with_env(),
+        },
+    });
+
+    let adapter = block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+        power_preference: PowerPreference::HighPerformance,
+        compatible_surface: None,
+        force_fallback_adapter: false,
+    }))
+    .expect("Failed to find an appropriate adapter");
+
+    let (device, queue) = block_on(adapter.request_device(
+        &DeviceDescriptor {
+            label: Some("limited device"),
+            required_features: Features::default(),
+            required_limits: Limits {
+                max_storage_buffer_binding_size: MAX_BINDING_SIZE,
+                ..Limits::default()
+            },
+            memory_hints: MemoryHints::Performance,
+        },
+        None,
+    ))
+    .expect("Failed to create device");
+    device.on_uncaptured_error(Box::new(error_handler));
+
+    (Arc::new(device), Arc::new(queue))
+}
+
+fn compress(
+    device: &Arc<Device>,
+    queue: &Arc<Queue>,
+    file_path: &str,
+    variant: CompressionVariant,
+    from_buffer: bool,
+) -> Vec<u8> {
+    let mut block_compressor = GpuBlockCompressor::new(device.clone(), queue.clone());
+
+    let (texture, rgba_data) = read_image_and_create_texture(device, queue, file_path, variant);
+    let width = texture.width();
+    let height = texture.height();
+
+    let blocks = create_blocks_buffer(device, variant.blocks_byte_size(width, height) as u64);
+
+    if from_buffer {
+        let source_buffer = device.create_buffer_init(&BufferInitDescriptor {
+            label: Some("source buffer"),
+            contents: &rgba_data,
+            usage: BufferUsages::STORAGE,
+        });
+
+        block_compressor.add_buffer_compression_task(
+            variant,
+            &source_buffer,
+            SourceBufferFormat::Rgba8Unorm,
+            None,
+            width * 4,
+            width,
+            height,
+            &blocks,
+            None,
+        );
+    } else {
+        block_compressor.add_compression_task(
+            variant,
+            &texture.create_view(&TextureViewDescriptor::default()),
+            width,
+            height,
+            &blocks,
+            None,
+        );
+    }
+
+    let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor {
+        label: Some("command encoder"),
+    });
+
+    {
+        let mut pass = encoder.begin_compute_pass(&ComputePassDescriptor {
+            label: Some("compute pass"),
+            timestamp_writes: None,
+        });
+
+        block_compressor.compress(&mut pass);
+    }
+
+    queue.submit([encoder.finish()]);
+
+    download_blocks_data(device, queue, blocks)
+}
+
+/// Compresses the test images on a device with a small storage buffer binding size, which splits
+/// the tasks into strips, and checks that the blocks match an unsplit compression.
+fn test_tiled_compression(variant: CompressionVariant) {
+    compress_and_compare(BRICK_FILE_PATH, variant);
+    compress_and_compare(MARBLE_FILE_PATH, variant);
+}
+
+fn compress_and_compare(file_path: &str, variant: CompressionVariant) {
+    for from_buffer in [false, true] {
+        let (device, queue) = create_wgpu_resources();
+        let expected = compress(&device, &queue, file_path, variant, from_buffer);
+        assert!(expected.len() > MAX_BINDING_SIZE as usize);
+
+        let (device, queue) = create_limited_wgpu_resources();
+        let tiled = compress(&device, &queue, file_path, variant, from_buffer);
+
+        assert_eq!(
+            tiled, expected,
+            "tiled compression produced different blocks for `{file_path}` (buffer source: {from_buffer})"
+        );
+    }
+}
+
+#[test]
+fn tiled_compression_bc1() {
+    test_tiled_compression(CompressionVariant::BC1);
+}
+
+#[test]
+fn tiled_compression_bc3() {
+    test_tiled_compression(CompressionVariant::BC3);
+}
+
+#[test]
+fn tiled_compression_astc_6x6() {
+    test_tiled_compression(CompressionVariant::ASTC(ASTCBlockSize::Block6x6));
+}
@@ -0,0 +1,110 @@
+use block_compression::{
+    decode::decompress_blocks_as_rgba8,
+    virtual_texture::{BorderMode, PageBuilder},
+    CompressionVariant,
+};
+use image::ImageReader;
+
+use crate::common::{create_wgpu_resources, BRICK_FILE_PATH, MARBLE_FILE_PATH};
+
+mod common;
+
+const PAGE_SIZE: u32 = 256;
+const BORDER: u32 = 8;
+
+/// Builds the pages of an image and checks the page layout of every mip level and that the
+/// decoded pages of the first mip level contain the texels of the image and its neighbours.
+fn test_page_builder(file_path: &str, border_mode: BorderMode) {
+    let (device, queue) = create_wgpu_resources();
+    let variant = CompressionVariant::BC1;
+
+    let image = ImageReader::open(file_path)
+        .expect("can't open input image")
+        .decode()
+        .expect("can't decode image")
+        .to_rgba8();
+    let width = image.width();
+    let height = image.height();
+
+    // A small batch size compresses every mip level in several batches.
+    let mut builder = PageBuilder::new(device, queue, variant, PAGE_SIZE, BORDER)
+        .border_mode(border_mode)
+        .batch_size(5);
+    let levels = builder.build_rgba8(&image, width, height, width * 4);
+
+    let size = builder.page_size_with_border();
+    assert_eq!(levels.len(), 3);
+    for (mip, level) in levels.iter().enumerate() {
+        assert_eq!(level.width, width >> mip);
+        assert_eq!(level.height, height >> mip);
+        assert_eq!(level.pages_x, (width >> mip).div_ceil(PAGE_SIZE));
+        assert_eq!(level.pages_y, (height >> mip).div_ceil(PAGE_SIZE));
+        assert_eq!(level.page_byte_size, variant.blocks_byte_size(size, size));
+        assert_eq!(
+            level.data.len(),
+            (level.pages_x * level.pages_y) as usize * level.page_byte_size
+        );
+    }
+
+    let level = &levels[0];
+    let mut decoded = vec![0; (size * size * 4) as usize];
+
+    for page_y in 0..level.pages_y {
+        for page_x in 0..level.pages_x {
+            decompress_blocks_as_rgba8(
+                variant,
+                size,
+                size,
+                level.page(page_x, page_y),
+                &mut decoded,
+            );
+
+            let mut error = 0;
+            for y in 0..size {
+                for x in 0..size {
+                    let (min_x, max_x, min_y, max_y) = match border_mode {
+                        BorderMode::Replicate => (
+                            page_x * PAGE_SIZE,
+                            (page_x + 1) * PAGE_SIZE - 1,
+                            page_y * PAGE_SIZE,
+                            (page_y + 1) * PAGE_SIZE - 1,
+                        ),
+                        BorderMode::Neighbour => (0, width - 1, 0, height - 1),
+                    };
+                    let source_x = (page_x * PAGE_SIZE + x)
+                        .saturating_sub(BORDER)
+                        .clamp(min_x, max_x);
+                    let source_y = (page_y * PAGE_SIZE + y)
+                        .saturating_sub(BORDER)
+                        .clamp(min_y, max_y);
+
+                    let expected = image.get_pixel(source_x, source_y).0;
+                    let offset = ((y * size + x) * 4) as usize;
+                    error += expected[..3]
+                        .iter()
+                        .zip(&decoded[offset..offset + 3])
+                        .map(|(&a, &b)| a.abs_diff(b) as u32)
+                        .sum::<u32>();
+                }
+            }
+
+            let mean_error = error as f64 / (size * size * 3) as f64;
+            assert!(
+                mean_error < 8.0,
+                "page ({page_x}, {page_y}) of `{file_path}` has a mean error of {mean_error}"
+            );
+        }
+    }
+}
+
+#[test]
+fn page_builder_neighbour_borders() {
+    test_page_builder(BRICK_FILE_PATH, BorderMode::Neighbour);
+    test_page_builder(MARBLE_FILE_PATH, BorderMode::Neighbour);
+}
+
+#[test]
+fn page_builder_replicated_borders() {
+    test_page_builder(BRICK_FILE_PATH, BorderMode::Replicate);
+    test_page_builder(MARBLE_FILE_PATH, BorderMode::Replicate);
+}